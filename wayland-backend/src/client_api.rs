@@ -1,21 +1,30 @@
 use std::{
     any::Any,
-    fmt,
+    env, fmt,
     os::unix::{
-        io::{BorrowedFd, OwnedFd, RawFd},
+        io::{BorrowedFd, FromRawFd, OwnedFd, RawFd},
         net::UnixStream,
     },
+    path::PathBuf,
     sync::Arc,
+    time::{Duration, Instant},
 };
 
 #[cfg(doc)]
 use std::io::ErrorKind::WouldBlock;
 
+use rustix::{
+    event::{PollFd, PollFlags},
+    io::retry_on_intr,
+};
+
 use crate::protocol::{Interface, Message, ObjectInfo};
 
 use super::client_impl;
 
-pub use crate::types::client::{InvalidId, NoWaylandLib, WaylandError};
+pub use crate::types::client::{
+    ConnectError, InvalidId, NoWaylandLib, UnknownObjectPolicy, WaylandError,
+};
 
 /// A trait representing your data associated to an object
 ///
@@ -54,6 +63,14 @@ pub trait ObjectData: downcast_rs::DowncastSync {
     fn data_as_any(&self) -> &dyn Any {
         self.as_any()
     }
+
+    /// Helper for accessing the event queue of the object
+    ///
+    /// This function is used to back the `Proxy::queue_handle()` function in `wayland_client`, which
+    /// downcasts the returned value to a `QueueHandle`. By default it returns `None`.
+    fn queue_as_any(&self) -> Option<&dyn Any> {
+        None
+    }
 }
 
 impl std::fmt::Debug for dyn ObjectData {
@@ -120,6 +137,15 @@ impl ObjectId {
         self.id.interface()
     }
 
+    /// Check if this object has the given interface
+    ///
+    /// The interfaces are compared with [`same_interface()`][crate::protocol::same_interface()]. With
+    /// `wayland-client`, `Proxy::is_interface_of()` does this check for the interface of a proxy type.
+    #[inline]
+    pub fn has_interface(&self, interface: &'static Interface) -> bool {
+        crate::protocol::same_interface(self.interface(), interface)
+    }
+
     /// Return the protocol-level numerical ID of this object
     ///
     /// Protocol IDs are reused after object destruction, so this should not be used as a unique identifier,
@@ -155,6 +181,15 @@ impl WeakBackend {
     pub fn upgrade(&self) -> Option<Backend> {
         self.inner.upgrade().map(|backend| Backend { backend })
     }
+
+    /// Check whether an object is still alive, without upgrading this handle
+    ///
+    /// This returns `false` if the object was destroyed or if the associated backend was
+    /// already dropped. It is cheaper than upgrading the handle and calling
+    /// [`Backend::info()`], and never locks the backend with the system library.
+    pub fn object_is_alive(&self, id: &ObjectId) -> bool {
+        self.inner.object_is_alive(&id.id)
+    }
 }
 
 impl Backend {
@@ -169,6 +204,58 @@ impl Backend {
         client_impl::InnerBackend::connect(stream).map(|backend| Self { backend })
     }
 
+    /// Try to connect to the Wayland server following the environment
+    ///
+    /// If `WAYLAND_SOCKET` is set, it is parsed as the file descriptor of an already connected socket,
+    /// which is taken over, and the variable is removed so that child processes don't see it. Otherwise
+    /// the socket named by `WAYLAND_DISPLAY` is connected, relative to `XDG_RUNTIME_DIR` unless it is an
+    /// absolute path.
+    pub fn connect_to_env() -> Result<Self, ConnectError> {
+        let stream = if let Ok(txt) = env::var("WAYLAND_SOCKET") {
+            // We should connect to the provided WAYLAND_SOCKET
+            let fd = txt.parse::<i32>().map_err(|_| ConnectError::InvalidFd)?;
+            let fd = unsafe { OwnedFd::from_raw_fd(fd) };
+            // remove the variable so any child processes don't see it
+            env::remove_var("WAYLAND_SOCKET");
+            // set the CLOEXEC flag on this FD
+            let flags = rustix::io::fcntl_getfd(&fd);
+            let result = flags
+                .map(|f| f | rustix::io::FdFlags::CLOEXEC)
+                .and_then(|f| rustix::io::fcntl_setfd(&fd, f));
+            match result {
+                Ok(_) => {
+                    // setting the O_CLOEXEC worked
+                    UnixStream::from(fd)
+                }
+                Err(_) => {
+                    // something went wrong in F_GETFD or F_SETFD
+                    return Err(ConnectError::InvalidFd);
+                }
+            }
+        } else {
+            let socket_name = env::var_os("WAYLAND_DISPLAY")
+                .map(Into::<PathBuf>::into)
+                .ok_or(ConnectError::NoCompositor)?;
+
+            let socket_path = if socket_name.is_absolute() {
+                socket_name
+            } else {
+                let mut socket_path = env::var_os("XDG_RUNTIME_DIR")
+                    .map(Into::<PathBuf>::into)
+                    .ok_or(ConnectError::NoCompositor)?;
+                if !socket_path.is_absolute() {
+                    return Err(ConnectError::NoCompositor);
+                }
+                socket_path.push(socket_name);
+                socket_path
+            };
+
+            UnixStream::connect(socket_path).map_err(|_| ConnectError::NoCompositor)?
+        };
+
+        Ok(Self::connect(stream)?)
+    }
+
     /// Get a [`WeakBackend`] from this backend
     pub fn downgrade(&self) -> WeakBackend {
         WeakBackend { inner: self.backend.downgrade() }
@@ -182,10 +269,72 @@ impl Backend {
     ///
     /// You can however expect this method returning [`WouldBlock`] to be very rare: it can only occur if
     /// either your client sent a lot of big messages at once, or the server is very laggy.
+    ///
+    /// The data that could not be written is kept, and the next call to this method resumes from where
+    /// the previous one stopped, so you only need to call it again once the socket is writable. With the
+    /// rust backend, the requests sent in the meantime are queued after it rather than failing when the
+    /// outgoing buffer is full.
     pub fn flush(&self) -> Result<(), WaylandError> {
         self.backend.flush()
     }
 
+    /// Send all pending requests and close the connection
+    ///
+    /// Dropping the backend closes its socket right away, and the server may notice the disconnection before
+    /// reading the last requests. This method instead blocks until all pending requests are written to the
+    /// socket, shuts the socket down for writing, and waits for the server to close the connection in turn,
+    /// which it does once it has read everything. The events received in the meantime are discarded.
+    ///
+    /// The socket is only drained while holding a prepared read, like a [`ReadEventsGuard`] would, so the
+    /// other threads reading events wait for the shutdown to complete. With the system backend, the events
+    /// `libwayland-client` already read from the socket are dispatched first, as
+    /// [`dispatch_inner_queue()`][Self::dispatch_inner_queue()] would.
+    ///
+    /// If a `timeout` is given and the server has not closed the connection once it expires, this returns
+    /// an error of kind [`TimedOut`][std::io::ErrorKind::TimedOut]. The socket may then already be shut
+    /// down for writing.
+    ///
+    /// As the backend can be cloned, the other handles to it remain valid, but can no longer send requests
+    /// nor receive events.
+    pub fn shutdown(self, timeout: Option<Duration>) -> Result<(), WaylandError> {
+        let deadline = timeout.map(|timeout| Instant::now() + timeout);
+        let fd = self.poll_fd();
+        loop {
+            match self.backend.flush() {
+                Ok(()) => break,
+                Err(WaylandError::Io(e)) if e.kind() == std::io::ErrorKind::WouldBlock => {
+                    wait_for(fd, PollFlags::OUT, deadline)?
+                }
+                Err(e) => return Err(e),
+            }
+        }
+        rustix::net::shutdown(fd, rustix::net::Shutdown::Write)
+            .map_err(|e| WaylandError::Io(e.into()))?;
+        // keep a read prepared while draining the socket, so that no other thread reads it concurrently
+        let _guard = loop {
+            match self.prepare_read() {
+                Some(guard) => break guard,
+                None => {
+                    self.dispatch_inner_queue()?;
+                }
+            }
+        };
+        let mut buffer = [0; 4096];
+        loop {
+            // the socket may have been given to us in blocking mode
+            match retry_on_intr(|| {
+                rustix::net::recv(fd, &mut buffer, rustix::net::RecvFlags::DONTWAIT)
+            }) {
+                Ok(0) => return Ok(()),
+                Ok(_) => {}
+                Err(rustix::io::Errno::AGAIN) => wait_for(fd, PollFlags::IN, deadline)?,
+                // the server may reset the connection rather than close it
+                Err(rustix::io::Errno::CONNRESET) => return Ok(()),
+                Err(e) => return Err(WaylandError::Io(e.into())),
+            }
+        }
+    }
+
     /// Access the Wayland socket FD for polling
     #[inline]
     pub fn poll_fd(&self) -> BorrowedFd {
@@ -206,6 +355,19 @@ impl Backend {
         self.backend.last_error()
     }
 
+    /// Get the interface description of the object designated by the last protocol error
+    ///
+    /// This allows mapping a [`ProtocolError`][crate::protocol::ProtocolError] back to a known
+    /// interface, for example to interpret its `code` as a value of the `Error` enum of that
+    /// interface.
+    ///
+    /// Returns [`None`] if no protocol error occurred or if the object is unknown. This is always
+    /// [`None`] with the system backend, as `libwayland-client` only reports the name of the interface.
+    #[inline]
+    pub fn protocol_error_interface(&self) -> Option<&'static Interface> {
+        self.backend.protocol_error_interface()
+    }
+
     /// Get the detailed protocol information about a wayland object
     ///
     /// Returns an error if the provided object ID is no longer valid.
@@ -286,6 +448,157 @@ impl Backend {
     pub fn dispatch_inner_queue(&self) -> Result<usize, WaylandError> {
         self.backend.dispatch_inner_queue()
     }
+
+    /// Set how events addressed to an object unknown to this backend are handled
+    ///
+    /// Such events are not expected from a well-behaved server, but can occur in some teardown races.
+    /// By default, they are treated as a protocol error and close the connection. Discarded events are
+    /// skipped entirely, but the file descriptors they may carry cannot be accounted for, as their
+    /// signature is unknown.
+    ///
+    /// This setting is only effective with the Rust backend: `libwayland-client` always silently discards
+    /// these events, and the system backend ignores it.
+    #[inline]
+    pub fn set_unknown_object_policy(&self, policy: UnknownObjectPolicy) {
+        self.backend.set_unknown_object_policy(policy)
+    }
+
+    /// Set a hook invoked with the raw bytes of every message received on this connection
+    ///
+    /// The hook is given the bytes of each message as they were read from the socket, header included,
+    /// along with the file descriptors it carries, before the message is dispatched. This is lower level
+    /// than the `WAYLAND_DEBUG` output, and meant to diagnose framing or alignment issues. A message that
+    /// cannot be parsed is given with no file descriptors, as they cannot be attributed without its
+    /// signature. Setting a new hook replaces the previous one, `None` removes it.
+    ///
+    /// The hook is invoked while internal locks of the backend are held, and thus must not call into the
+    /// backend itself.
+    ///
+    /// This setting is only effective with the Rust backend, the system backend ignores it.
+    #[inline]
+    pub fn set_wire_trace(&self, trace: Option<WireTrace>) {
+        self.backend.set_wire_trace(trace)
+    }
+
+    /// Set a hook invoked every time an object is created on this connection
+    ///
+    /// This includes both objects created by requests sent by this client and objects created
+    /// by the server through events. Setting a new hook replaces the previous one, `None` removes it.
+    ///
+    /// The hook may be invoked while internal locks of the backend are held, and thus must not call
+    /// into the backend itself.
+    #[cfg(feature = "object_hooks")]
+    #[inline]
+    pub fn set_object_created_hook(&self, hook: Option<ObjectHook>) {
+        self.backend.set_object_hooks(|hooks| hooks.created = hook)
+    }
+
+    /// Set a hook invoked every time an object of this connection is destroyed
+    ///
+    /// It is invoked right after [`ObjectData::destroyed()`] for the object. Setting a new hook
+    /// replaces the previous one, `None` removes it.
+    ///
+    /// The hook may be invoked while internal locks of the backend are held, and thus must not call
+    /// into the backend itself.
+    #[cfg(feature = "object_hooks")]
+    #[inline]
+    pub fn set_object_destroyed_hook(&self, hook: Option<ObjectHook>) {
+        self.backend.set_object_hooks(|hooks| hooks.destroyed = hook)
+    }
+
+    /// Set an observer invoked for every event received on this connection
+    ///
+    /// It is given each event right before it is handed to the [`ObjectData`] of its target object, in the
+    /// order they were received, and cannot alter its dispatching. Events sent to objects that were
+    /// already destroyed by this client are discarded without being observed. Setting a new observer
+    /// replaces the previous one, `None` removes it.
+    ///
+    /// With the system backend, only the events of objects created through this crate are observed.
+    #[cfg(feature = "object_hooks")]
+    #[inline]
+    pub fn set_event_observer(&self, observer: Option<EventObserver>) {
+        self.backend.set_object_hooks(|hooks| hooks.events = observer)
+    }
+
+    /// Remove the event observer if it is the given one
+    ///
+    /// This does nothing if the observer was already replaced by another one through
+    /// [`set_event_observer()`][Self::set_event_observer()], allowing several users of the connection to
+    /// install and remove their observer without clobbering each other's.
+    #[cfg(feature = "object_hooks")]
+    #[inline]
+    pub fn remove_event_observer(&self, observer: &EventObserver) {
+        self.backend.set_object_hooks(|hooks| {
+            // compare the data pointers only, vtables may be duplicated across codegen units
+            let installed = hooks.events.as_ref().map(|events| Arc::as_ptr(events) as *const ());
+            if installed == Some(Arc::as_ptr(observer) as *const ()) {
+                hooks.events = None;
+            }
+        })
+    }
+}
+
+/// A hook given the raw bytes and file descriptors of the received messages
+///
+/// See [`Backend::set_wire_trace()`].
+pub type WireTrace = Arc<dyn Fn(&[u8], &[RawFd]) + Send + Sync>;
+
+/// A hook notified of the creation or destruction of objects
+///
+/// See [`Backend::set_object_created_hook()`] and [`Backend::set_object_destroyed_hook()`].
+#[cfg(feature = "object_hooks")]
+pub type ObjectHook = Arc<dyn Fn(ObjectId) + Send + Sync>;
+
+/// An observer of the events received on a connection
+///
+/// See [`Backend::set_event_observer()`].
+#[cfg(feature = "object_hooks")]
+pub type EventObserver = Arc<dyn Fn(&Message<ObjectId, OwnedFd>) + Send + Sync>;
+
+/// Storage for the object hooks of a connection
+///
+/// Without the `object_hooks` feature this is an empty struct whose methods do nothing.
+#[derive(Clone, Default)]
+pub(crate) struct ObjectHooks {
+    #[cfg(feature = "object_hooks")]
+    pub(crate) created: Option<ObjectHook>,
+    #[cfg(feature = "object_hooks")]
+    pub(crate) destroyed: Option<ObjectHook>,
+    #[cfg(feature = "object_hooks")]
+    pub(crate) events: Option<EventObserver>,
+}
+
+impl ObjectHooks {
+    #[inline]
+    pub(crate) fn notify_created(&self, _id: &ObjectId) {
+        #[cfg(feature = "object_hooks")]
+        if let Some(ref hook) = self.created {
+            hook(_id.clone());
+        }
+    }
+
+    #[inline]
+    pub(crate) fn notify_destroyed(&self, _id: &ObjectId) {
+        #[cfg(feature = "object_hooks")]
+        if let Some(ref hook) = self.destroyed {
+            hook(_id.clone());
+        }
+    }
+
+    #[inline]
+    pub(crate) fn notify_event(&self, _msg: &Message<ObjectId, OwnedFd>) {
+        #[cfg(feature = "object_hooks")]
+        if let Some(ref observer) = self.events {
+            observer(_msg);
+        }
+    }
+}
+
+impl fmt::Debug for ObjectHooks {
+    #[cfg_attr(coverage, coverage(off))]
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("ObjectHooks").finish_non_exhaustive()
+    }
 }
 
 /// Guard for synchronizing event reading across multiple threads
@@ -368,3 +681,26 @@ impl ObjectData for UninitObjectData {
         f.debug_struct("UninitObjectData").finish()
     }
 }
+
+/// Block until `fd` is ready for the given events, or until the deadline
+fn wait_for(
+    fd: BorrowedFd<'_>,
+    flags: PollFlags,
+    deadline: Option<Instant>,
+) -> Result<(), WaylandError> {
+    let timeout = match deadline {
+        Some(deadline) => {
+            // round up, to not wake up right before the deadline
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            ((remaining.as_micros() + 999) / 1000).try_into().unwrap_or(i32::MAX)
+        }
+        None => -1,
+    };
+    let mut fds = [PollFd::new(&fd, flags)];
+    let ready = retry_on_intr(|| rustix::event::poll(&mut fds, timeout))
+        .map_err(|e| WaylandError::Io(e.into()))?;
+    if ready == 0 {
+        return Err(WaylandError::Io(std::io::ErrorKind::TimedOut.into()));
+    }
+    Ok(())
+}
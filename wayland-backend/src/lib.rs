@@ -98,6 +98,8 @@ mod core_interfaces;
 mod debug;
 pub mod protocol;
 mod types;
+#[cfg(feature = "xml")]
+mod xml;
 
 /*
  * These trampoline functions need to always be here because the build script cannot
@@ -1,9 +1,16 @@
 //! Types and utilities for manipulating the Wayland protocol
 
-use std::{ffi::CString, os::unix::io::AsRawFd};
+use std::{
+    ffi::CString,
+    hash::{Hash, Hasher},
+    os::unix::io::AsRawFd,
+};
 
 pub use wayland_sys::common::{wl_argument, wl_interface, wl_message};
 
+#[cfg(feature = "xml")]
+pub use crate::xml::XmlError;
+
 /// Describes whether an argument may have a null value.
 #[derive(Clone, Copy, PartialEq, Eq, Debug)]
 pub enum AllowNull {
@@ -52,6 +59,8 @@ pub enum Argument<Id, Fd> {
     /// An unsigned integer argument. Represented by a [`u32`].
     Uint(u32),
     /// A signed fixed point number with 1/256 precision
+    ///
+    /// See [`Fixed`] for conversions from and to other numeric types.
     Fixed(i32),
     /// CString
     ///
@@ -120,13 +129,33 @@ impl<Id: PartialEq, Fd: AsRawFd> PartialEq for Argument<Id, Fd> {
 
 impl<Id: Eq, Fd: AsRawFd> Eq for Argument<Id, Fd> {}
 
+/// Hashes the content of the argument.
+///
+/// File descriptors are not hashable by content, so all `Fd` arguments hash to the same
+/// fixed value regardless of the file descriptor they contain.
+impl<Id: Hash, Fd> Hash for Argument<Id, Fd> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        std::mem::discriminant(self).hash(state);
+        match self {
+            Self::Int(value) => value.hash(state),
+            Self::Uint(value) => value.hash(state),
+            Self::Fixed(value) => value.hash(state),
+            Self::Str(value) => value.hash(state),
+            Self::Object(value) => value.hash(state),
+            Self::NewId(value) => value.hash(state),
+            Self::Array(value) => value.hash(state),
+            Self::Fd(_) => {}
+        }
+    }
+}
+
 impl<Id: std::fmt::Display, Fd: AsRawFd> std::fmt::Display for Argument<Id, Fd> {
     #[cfg_attr(coverage, coverage(off))]
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Self::Int(value) => write!(f, "{}", value),
             Self::Uint(value) => write!(f, "{}", value),
-            Self::Fixed(value) => write!(f, "{:.4}", *value as f64 / 256.0),
+            Self::Fixed(value) => write!(f, "{:.4}", Fixed::from_raw(*value).to_f64()),
             Self::Str(value) => write!(f, "{:?}", value),
             Self::Object(value) => write!(f, "{}", value),
             Self::NewId(value) => write!(f, "{}", value),
@@ -136,6 +165,106 @@ impl<Id: std::fmt::Display, Fd: AsRawFd> std::fmt::Display for Argument<Id, Fd>
     }
 }
 
+/// A signed 24.8 fixed point number, as used by the `fixed` protocol type
+///
+/// The raw representation is the one carried by [`Argument::Fixed`].
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Fixed(i32);
+
+impl Fixed {
+    /// The fixed point representation of `0`
+    pub const ZERO: Fixed = Fixed(0);
+
+    /// Create a fixed point number from its raw wire representation
+    pub const fn from_raw(raw: i32) -> Self {
+        Self(raw)
+    }
+
+    /// Get the raw wire representation of this number
+    pub const fn into_raw(self) -> i32 {
+        self.0
+    }
+
+    /// Create a fixed point number from an integer
+    ///
+    /// Integers outside of the range representable with 24 bits wrap around.
+    pub const fn from_int(value: i32) -> Self {
+        Self(value << 8)
+    }
+
+    /// Convert this number to an integer, rounding towards zero
+    pub const fn to_int(self) -> i32 {
+        self.0 / 256
+    }
+
+    /// Create a fixed point number from a floating point value, rounding to the nearest
+    /// representable value
+    ///
+    /// Values outside of the representable range saturate.
+    pub fn from_f64(value: f64) -> Self {
+        Self((value * 256.0).round() as i32)
+    }
+
+    /// Convert this number to a floating point value
+    ///
+    /// This conversion is exact.
+    pub fn to_f64(self) -> f64 {
+        self.0 as f64 / 256.0
+    }
+}
+
+impl From<Fixed> for f64 {
+    fn from(value: Fixed) -> f64 {
+        value.to_f64()
+    }
+}
+
+impl std::fmt::Display for Fixed {
+    #[cfg_attr(coverage, coverage(off))]
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        std::fmt::Display::fmt(&self.to_f64(), f)
+    }
+}
+
+/// Typed views of the contents of an `array` argument
+///
+/// Many protocol arrays actually contain a list of 32-bit integers in native byte order (for example
+/// the pressed keys of `wl_keyboard.enter`). This trait, implemented for the byte buffer carried by
+/// [`Argument::Array`], allows reading them without copying or transmuting the buffer manually.
+pub trait ArrayView {
+    /// View the array as a slice of `u32`
+    ///
+    /// Returns `None` if the length of the array is not a multiple of 4 or if its buffer is not
+    /// suitably aligned.
+    fn as_u32_slice(&self) -> Option<&[u32]>;
+
+    /// View the array as a slice of `i32`
+    ///
+    /// Returns `None` if the length of the array is not a multiple of 4 or if its buffer is not
+    /// suitably aligned.
+    fn as_i32_slice(&self) -> Option<&[i32]>;
+}
+
+impl ArrayView for [u8] {
+    fn as_u32_slice(&self) -> Option<&[u32]> {
+        if self.len() % 4 != 0 || self.as_ptr().align_offset(std::mem::align_of::<u32>()) != 0 {
+            return None;
+        }
+        // SAFETY: the pointer is aligned for u32, the length is a multiple of 4 and any bit pattern
+        // is a valid u32
+        Some(unsafe { std::slice::from_raw_parts(self.as_ptr() as *const u32, self.len() / 4) })
+    }
+
+    fn as_i32_slice(&self) -> Option<&[i32]> {
+        if self.len() % 4 != 0 || self.as_ptr().align_offset(std::mem::align_of::<i32>()) != 0 {
+            return None;
+        }
+        // SAFETY: the pointer is aligned for i32, the length is a multiple of 4 and any bit pattern
+        // is a valid i32
+        Some(unsafe { std::slice::from_raw_parts(self.as_ptr() as *const i32, self.len() / 4) })
+    }
+}
+
 /// Description of wayland interface.
 ///
 /// An interface describes the possible requests and events that a wayland client and compositor use to
@@ -251,6 +380,18 @@ impl<Id: PartialEq, Fd: AsRawFd> PartialEq for Message<Id, Fd> {
 
 impl<Id: Eq, Fd: AsRawFd> Eq for Message<Id, Fd> {}
 
+/// Hashes the sender id, opcode and arguments of the message.
+///
+/// As for [`Argument`], file descriptors are ignored and all hash to the same fixed value, which
+/// makes it possible to use messages as keys for deduplication or caching.
+impl<Id: Hash, Fd> Hash for Message<Id, Fd> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.sender_id.hash(state);
+        self.opcode.hash(state);
+        self.args.hash(state);
+    }
+}
+
 impl std::error::Error for ProtocolError {}
 
 impl std::fmt::Display for ProtocolError {
@@ -270,14 +411,41 @@ pub fn same_interface(a: &'static Interface, b: &'static Interface) -> bool {
     std::ptr::eq(a, b) || a.name == b.name
 }
 
-pub(crate) fn check_for_signature<Id, Fd>(
-    signature: &[ArgumentType],
-    args: &[Argument<Id, Fd>],
-) -> bool {
-    if signature.len() != args.len() {
+/// Render a message the way libwayland prints it with `WAYLAND_DEBUG`
+///
+/// The result has the form `sender.message_name(args)`, where the sender and object arguments are
+/// formatted with the [`Display`][std::fmt::Display] implementation of `Id`, which for the backend
+/// `ObjectId` types is `interface@id`. The name of the message is looked up in the events of `interface`
+/// if `is_event` is `true`, and in its requests otherwise. Unknown opcodes are rendered as `#opcode`.
+///
+/// Unlike the output triggered by `WAYLAND_DEBUG`, this does not include a timestamp nor a direction
+/// marker, and does not depend on the `log` feature.
+pub fn format_message<Id: std::fmt::Display, Fd: AsRawFd>(
+    interface: &Interface,
+    msg: &Message<Id, Fd>,
+    is_event: bool,
+) -> String {
+    let descs = if is_event { interface.events } else { interface.requests };
+    let args = crate::debug::DisplaySlice(&msg.args);
+    match descs.get(msg.opcode as usize) {
+        Some(desc) => format!("{}.{}({})", msg.sender_id, desc.name, args),
+        None => format!("{}.#{}({})", msg.sender_id, msg.opcode, args),
+    }
+}
+
+/// Check that a list of arguments matches a message signature
+///
+/// Returns `true` if `args` has as many arguments as `expected`, each of the type given by the
+/// signature. This is the check done by the backends before sending a message, so it can be used to
+/// validate a message built from untrusted input before passing it to them.
+///
+/// Only the types of the arguments are checked: whether an object argument can be null, and the
+/// interfaces of the objects, are still checked when the message is sent.
+pub fn check_signature<Id, Fd>(expected: &[ArgumentType], args: &[Argument<Id, Fd>]) -> bool {
+    if expected.len() != args.len() {
         return false;
     }
-    for (typ, arg) in signature.iter().copied().zip(args.iter()) {
+    for (typ, arg) in expected.iter().copied().zip(args.iter()) {
         if !arg.get_type().same_type(typ) {
             return false;
         }
@@ -292,7 +460,10 @@ pub(crate) fn same_interface_or_anonymous(a: &'static Interface, b: &'static Int
 }
 
 /// An enum value in the protocol.
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+///
+/// Its `Debug` representation is the one of the interpreted value if it is known, and the raw value
+/// in hexadecimal otherwise, for example `Unknown(0x1234)`.
+#[derive(Clone, Copy, PartialEq, Eq)]
 pub enum WEnum<T> {
     /// The interpreted value
     Value(T),
@@ -300,6 +471,15 @@ pub enum WEnum<T> {
     Unknown(u32),
 }
 
+impl<T: std::fmt::Debug> std::fmt::Debug for WEnum<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Value(v) => std::fmt::Debug::fmt(v, f),
+            Self::Unknown(value) => write!(f, "Unknown({:#x})", value),
+        }
+    }
+}
+
 /// Error representing an unknown numeric variant for a [`WEnum`]
 #[derive(Debug, Copy, Clone)]
 pub struct WEnumError {
@@ -356,3 +536,193 @@ impl<T: Into<u32>> From<WEnum<T>> for u32 {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{ArrayView, WEnum};
+
+    #[test]
+    fn array_typed_views() {
+        // a u32 buffer is aligned, so its bytes can always be viewed
+        let values: [u32; 3] = [1, 42, u32::MAX];
+        let bytes = unsafe { std::slice::from_raw_parts(values.as_ptr() as *const u8, 12) };
+        assert_eq!(bytes.as_u32_slice(), Some(&values[..]));
+        assert_eq!(bytes.as_i32_slice(), Some(&[1, 42, -1][..]));
+
+        // the length must be a multiple of 4
+        assert_eq!(bytes[..7].as_u32_slice(), None);
+        assert_eq!(bytes[..7].as_i32_slice(), None);
+
+        // the buffer must be aligned, even with a valid length
+        let misaligned = &bytes[1..9];
+        assert_eq!(misaligned.len(), 8);
+        assert_eq!(misaligned.as_u32_slice(), None);
+        assert_eq!(misaligned.as_i32_slice(), None);
+    }
+
+    #[test]
+    fn fixed_conversions() {
+        use super::Fixed;
+
+        // integers round trip, including negative values
+        for value in [0, 1, -1, 42, -42, 8_388_607, -8_388_608] {
+            assert_eq!(Fixed::from_int(value).to_int(), value);
+            assert_eq!(Fixed::from_int(value).to_f64(), value as f64);
+            assert_eq!(Fixed::from_f64(value as f64), Fixed::from_int(value));
+        }
+        assert_eq!(Fixed::from_int(1).into_raw(), 256);
+        assert_eq!(Fixed::from_int(-1).into_raw(), -256);
+        assert_eq!(Fixed::from_int(0), Fixed::ZERO);
+
+        // the raw representation round trips, and its conversion to f64 is exact
+        for raw in [0, 1, -1, 255, -255, i32::MIN, i32::MAX] {
+            let fixed = Fixed::from_raw(raw);
+            assert_eq!(fixed.into_raw(), raw);
+            assert_eq!(fixed.to_f64(), raw as f64 / 256.0);
+            assert_eq!(Fixed::from_f64(fixed.to_f64()), fixed);
+            assert_eq!(f64::from(fixed), fixed.to_f64());
+        }
+        assert_eq!(Fixed::from_raw(i32::MAX).to_int(), 8_388_607);
+        assert_eq!(Fixed::from_raw(i32::MIN).to_int(), -8_388_608);
+
+        // conversion to an integer rounds towards zero
+        assert_eq!(Fixed::from_f64(1.75).to_int(), 1);
+        assert_eq!(Fixed::from_f64(-1.75).to_int(), -1);
+
+        // conversion from a float rounds to the nearest value
+        assert_eq!(Fixed::from_f64(0.5 / 256.0 + 0.0001).into_raw(), 1);
+        assert_eq!(Fixed::from_f64(-0.5 / 256.0 - 0.0001).into_raw(), -1);
+        assert_eq!(Fixed::from_f64(-2.5).into_raw(), -640);
+
+        // out of range values saturate for floats and wrap for integers
+        assert_eq!(Fixed::from_f64(1e12).into_raw(), i32::MAX);
+        assert_eq!(Fixed::from_f64(-1e12).into_raw(), i32::MIN);
+        assert_eq!(Fixed::from_int(8_388_608), Fixed::from_int(-8_388_608));
+
+        assert!(Fixed::from_f64(-0.25) < Fixed::ZERO);
+        assert_eq!(Fixed::from_f64(-2.5).to_string(), "-2.5");
+    }
+
+    #[test]
+    fn format_messages() {
+        use super::{format_message, Argument, Fixed, Message};
+        use crate::core_interfaces::WL_REGISTRY_INTERFACE;
+        use std::os::unix::io::OwnedFd;
+
+        let msg: Message<&str, OwnedFd> = Message {
+            sender_id: "wl_registry@2",
+            opcode: 0,
+            args: smallvec::smallvec![
+                Argument::Uint(1),
+                Argument::Fixed(Fixed::from_f64(-2.0).into_raw()),
+                Argument::NewId("wl_output@7"),
+            ],
+        };
+        assert_eq!(
+            format_message(&WL_REGISTRY_INTERFACE, &msg, false),
+            "wl_registry@2.bind(1, -2.0000, wl_output@7)"
+        );
+        assert_eq!(
+            format_message(&WL_REGISTRY_INTERFACE, &msg, true),
+            "wl_registry@2.global(1, -2.0000, wl_output@7)"
+        );
+
+        let unknown: Message<&str, OwnedFd> =
+            Message { sender_id: "wl_registry@2", opcode: 42, args: Default::default() };
+        assert_eq!(format_message(&WL_REGISTRY_INTERFACE, &unknown, true), "wl_registry@2.#42()");
+    }
+
+    #[test]
+    fn signature_check() {
+        use super::{check_signature, AllowNull, Argument, ArgumentType};
+        use std::os::unix::io::OwnedFd;
+
+        let signature = [ArgumentType::Uint, ArgumentType::Object(AllowNull::Yes)];
+        assert!(check_signature::<u32, OwnedFd>(
+            &signature,
+            &[Argument::Uint(1), Argument::Object(2)]
+        ));
+        // the nullability of objects is not part of the type
+        assert!(check_signature::<u32, OwnedFd>(
+            &[ArgumentType::Uint, ArgumentType::Object(AllowNull::No)],
+            &[Argument::Uint(1), Argument::Object(2)]
+        ));
+        assert!(!check_signature::<u32, OwnedFd>(
+            &signature,
+            &[Argument::Int(1), Argument::Object(2)]
+        ));
+        assert!(!check_signature::<u32, OwnedFd>(&signature, &[Argument::Uint(1)]));
+    }
+
+    #[test]
+    fn wenum_debug() {
+        #[derive(Debug)]
+        enum Format {
+            Argb8888,
+        }
+
+        assert_eq!(format!("{:?}", WEnum::Value(Format::Argb8888)), "Argb8888");
+        assert_eq!(format!("{:?}", WEnum::<Format>::Unknown(0x1234)), "Unknown(0x1234)");
+    }
+
+    #[test]
+    fn message_hash_agrees_with_eq() {
+        use super::{Argument, Message};
+        use std::collections::hash_map::DefaultHasher;
+        use std::ffi::CString;
+        use std::hash::{Hash, Hasher};
+        use std::os::unix::io::OwnedFd;
+
+        fn hash<T: Hash>(value: &T) -> u64 {
+            let mut hasher = DefaultHasher::new();
+            value.hash(&mut hasher);
+            hasher.finish()
+        }
+
+        let msg = |sender_id: u32, opcode: u16, fd: Option<OwnedFd>| {
+            let mut msg: Message<u32, OwnedFd> = Message {
+                sender_id,
+                opcode,
+                args: smallvec::smallvec![
+                    Argument::Int(-1),
+                    Argument::Str(Some(Box::new(CString::new("foo").unwrap()))),
+                    Argument::Array(Box::new(vec![1, 2, 3])),
+                    Argument::NewId(7),
+                ],
+            };
+            if let Some(fd) = fd {
+                msg.args.push(Argument::Fd(fd));
+            }
+            msg
+        };
+
+        // equal messages hash equally
+        let a = msg(3, 1, None);
+        let b = msg(3, 1, None);
+        assert_eq!(a, b);
+        assert_eq!(hash(&a), hash(&b));
+        for (x, y) in a.args.iter().zip(b.args.iter()) {
+            assert_eq!(x, y);
+            assert_eq!(hash(x), hash(y));
+        }
+
+        // file descriptors all hash to the same value, so messages equal up to their fds
+        // still hash equally
+        let fd1 = OwnedFd::from(std::fs::File::open("/dev/null").unwrap());
+        let fd2 = OwnedFd::from(std::fs::File::open("/dev/null").unwrap());
+        let c = msg(3, 1, Some(fd1));
+        let d = msg(3, 1, Some(fd2));
+        assert_ne!(c, d);
+        assert_eq!(hash(&c), hash(&d));
+
+        // the sender, opcode and arguments are all part of the hash
+        assert_ne!(a, msg(4, 1, None));
+        assert_ne!(hash(&a), hash(&msg(4, 1, None)));
+        assert_ne!(hash(&a), hash(&msg(3, 2, None)));
+        assert_ne!(hash(&a), hash(&c));
+        assert_ne!(
+            hash(&Argument::<u32, OwnedFd>::Int(1)),
+            hash(&Argument::<u32, OwnedFd>::Uint(1))
+        );
+    }
+}
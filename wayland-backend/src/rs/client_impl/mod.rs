@@ -13,7 +13,7 @@ use crate::{
     core_interfaces::WL_DISPLAY_INTERFACE,
     debug,
     protocol::{
-        check_for_signature, same_interface, same_interface_or_anonymous, AllowNull, Argument,
+        check_signature, same_interface, same_interface_or_anonymous, AllowNull, Argument,
         ArgumentType, Interface, Message, ObjectInfo, ProtocolError, ANONYMOUS_INTERFACE,
         INLINE_ARGS,
     },
@@ -93,8 +93,23 @@ struct ProtocolState {
     socket: BufferedSocket,
     map: ObjectMap<Data>,
     last_error: Option<WaylandError>,
+    last_error_interface: Option<&'static Interface>,
     last_serial: u32,
     debug: bool,
+    unknown_object_policy: UnknownObjectPolicy,
+    object_hooks: ObjectHooks,
+    wire_trace: WireTracer,
+}
+
+/// The hook set with `Backend::set_wire_trace()`
+#[derive(Default)]
+struct WireTracer(Option<WireTrace>);
+
+impl fmt::Debug for WireTracer {
+    #[cfg_attr(coverage, coverage(off))]
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("WireTracer").field(&self.0.is_some()).finish()
+    }
 }
 
 #[derive(Debug)]
@@ -134,6 +149,17 @@ impl WeakInnerBackend {
     pub fn upgrade(&self) -> Option<InnerBackend> {
         Weak::upgrade(&self.state).map(|state| InnerBackend { state })
     }
+
+    pub fn object_is_alive(&self, id: &InnerObjectId) -> bool {
+        let Some(state) = Weak::upgrade(&self.state) else {
+            return false;
+        };
+        let alive = state
+            .lock_protocol()
+            .get_object(id.clone())
+            .map_or(false, |object| !object.data.client_destroyed);
+        alive
+    }
 }
 
 impl PartialEq for InnerBackend {
@@ -150,7 +176,9 @@ impl InnerBackend {
     }
 
     pub fn connect(stream: UnixStream) -> Result<Self, NoWaylandLib> {
-        let socket = BufferedSocket::new(Socket::from(stream));
+        let mut socket = BufferedSocket::new(Socket::from(stream));
+        // requests sent while the server is not reading are kept until the next flush
+        socket.enable_overflow();
         let mut map = ObjectMap::new();
         map.insert_at(
             1,
@@ -175,8 +203,12 @@ impl InnerBackend {
                     socket,
                     map,
                     last_error: None,
+                    last_error_interface: None,
                     last_serial: 0,
                     debug,
+                    unknown_object_policy: UnknownObjectPolicy::default(),
+                    object_hooks: ObjectHooks::default(),
+                    wire_trace: WireTracer::default(),
                 }),
                 read: Mutex::new(ReadingState {
                     prepared_reads: 0,
@@ -285,6 +317,23 @@ impl InnerBackend {
         self.state.lock_protocol().last_error.clone()
     }
 
+    pub fn protocol_error_interface(&self) -> Option<&'static Interface> {
+        self.state.lock_protocol().last_error_interface
+    }
+
+    pub fn set_unknown_object_policy(&self, policy: UnknownObjectPolicy) {
+        self.state.lock_protocol().unknown_object_policy = policy;
+    }
+
+    pub fn set_wire_trace(&self, trace: Option<WireTrace>) {
+        self.state.lock_protocol().wire_trace = WireTracer(trace);
+    }
+
+    #[cfg(feature = "object_hooks")]
+    pub(crate) fn set_object_hooks(&self, f: impl FnOnce(&mut ObjectHooks)) {
+        f(&mut self.state.lock_protocol().object_hooks);
+    }
+
     pub fn info(&self, id: ObjectId) -> Result<ObjectInfo, InvalidId> {
         let object = self.state.lock_protocol().get_object(id.id.clone())?;
         if object.data.client_destroyed {
@@ -321,7 +370,7 @@ impl InnerBackend {
             return Err(InvalidId);
         }
 
-        if !check_for_signature(message_desc.signature, &args) {
+        if !check_signature(message_desc.signature, &args) {
             panic!(
                 "Unexpected signature for request {}@{}.{}: expected {:?}, got {:?}.",
                 object.interface.name, id.id, message_desc.name, message_desc.signature, args
@@ -471,16 +520,20 @@ impl InnerBackend {
                     obj.data.client_destroyed = true;
                 })
                 .unwrap();
-            object.data.user_data.destroyed(ObjectId { id });
+            let id = ObjectId { id };
+            object.data.user_data.destroyed(id.clone());
+            guard.object_hooks.notify_destroyed(&id);
         }
         if let Some((child_id, child_serial, child_interface)) = child {
-            Ok(ObjectId {
+            let child_id = ObjectId {
                 id: InnerObjectId {
                     id: child_id,
                     serial: child_serial,
                     interface: child_interface,
                 },
-            })
+            };
+            guard.object_hooks.notify_created(&child_id);
+            Ok(child_id)
         } else {
             Ok(Self::null_id())
         }
@@ -567,12 +620,13 @@ impl ProtocolState {
                 if let [Argument::Object(obj), Argument::Uint(code), Argument::Str(Some(ref message))] =
                     message.args[..]
                 {
-                    let object = self.map.find(obj);
+                    let interface = self.map.find(obj).map(|obj| obj.interface);
+                    self.last_error_interface = interface;
                     let err = WaylandError::Protocol(ProtocolError {
                         code,
                         object_id: obj,
-                        object_interface: object
-                            .map(|obj| obj.interface.name)
+                        object_interface: interface
+                            .map(|iface| iface.name)
                             .unwrap_or("<unknown>")
                             .into(),
                         message: message.to_string_lossy().into(),
@@ -612,13 +666,51 @@ fn dispatch_events(state: Arc<ConnectionState>) -> Result<usize, WaylandError> {
     let mut dispatched = 0;
     loop {
         // Attempt to read a message
-        let ProtocolState { ref mut socket, ref map, .. } = *guard;
+        let ProtocolState {
+            ref mut socket, ref map, unknown_object_policy, ref wire_trace, ..
+        } = *guard;
+        // the bytes are copied beforehand, as reading the message consumes them
+        let raw = wire_trace.0.as_ref().and_then(|_| socket.peek_message().map(<[u8]>::to_vec));
         let message = match socket.read_one_message(|id, opcode| {
             map.find(id)
                 .and_then(|o| o.interface.events.get(opcode as usize))
                 .map(|desc| desc.signature)
         }) {
-            Ok(msg) => msg,
+            Err(MessageParseError::Malformed)
+                if unknown_object_policy != UnknownObjectPolicy::Fatal
+                    && socket.peek_sender_id().map_or(false, |id| map.find(id).is_none()) =>
+            {
+                // the message is addressed to an unknown object, discard it
+                socket.skip_one_message().map(|id| {
+                    if unknown_object_policy == UnknownObjectPolicy::Log {
+                        crate::log_warn!("Discarding an event for unknown object {}.", id);
+                    }
+                    None
+                })
+            }
+            ret => ret.map(Some),
+        };
+        if let (Some(trace), Some(raw)) = (&wire_trace.0, raw) {
+            match message {
+                Ok(Some(ref msg)) => {
+                    let fds = msg
+                        .args
+                        .iter()
+                        .filter_map(|arg| match arg {
+                            Argument::Fd(fd) => Some(fd.as_raw_fd()),
+                            _ => None,
+                        })
+                        .collect::<SmallVec<[RawFd; 4]>>();
+                    trace(&raw, &fds);
+                }
+                Ok(None) | Err(MessageParseError::Malformed) => trace(&raw, &[]),
+                // the message is traced once its file descriptors are received
+                Err(_) => {}
+            }
+        }
+        let message = match message {
+            Ok(Some(msg)) => msg,
+            Ok(None) => continue,
             Err(MessageParseError::MissingData) | Err(MessageParseError::MissingFD) => {
                 // need to read more data
                 if let Err(e) = guard.socket.fill_incoming_buffers() {
@@ -747,7 +839,9 @@ fn dispatch_events(state: Arc<ConnectionState>) -> Result<usize, WaylandError> {
                         return Err(guard.store_and_return_error(err));
                     }
 
-                    Argument::NewId(ObjectId { id: child_id })
+                    let child_id = ObjectId { id: child_id };
+                    guard.object_hooks.notify_created(&child_id);
+                    Argument::NewId(child_id)
                 }
             });
         }
@@ -774,6 +868,7 @@ fn dispatch_events(state: Arc<ConnectionState>) -> Result<usize, WaylandError> {
         };
 
         // unlock the mutex while we invoke the user callback
+        let object_hooks = guard.object_hooks.clone();
         std::mem::drop(guard);
         #[cfg(feature = "log")]
         crate::log_debug!(
@@ -782,11 +877,9 @@ fn dispatch_events(state: Arc<ConnectionState>) -> Result<usize, WaylandError> {
             receiver.version,
             debug::DisplaySlice(&args)
         );
-        let ret = receiver
-            .data
-            .user_data
-            .clone()
-            .event(&backend, Message { sender_id: ObjectId { id }, opcode: message.opcode, args });
+        let msg = Message { sender_id: ObjectId { id }, opcode: message.opcode, args };
+        object_hooks.notify_event(&msg);
+        let ret = receiver.data.user_data.clone().event(&backend, msg);
         // lock it again to resume dispatching
         guard = backend.backend.state.lock_protocol();
 
@@ -799,13 +892,15 @@ fn dispatch_events(state: Arc<ConnectionState>) -> Result<usize, WaylandError> {
                     obj.data.client_destroyed = true;
                 })
                 .unwrap();
-            receiver.data.user_data.destroyed(ObjectId {
+            let id = ObjectId {
                 id: InnerObjectId {
                     id: message.sender_id,
                     serial: receiver.data.serial,
                     interface: receiver.interface,
                 },
-            });
+            };
+            receiver.data.user_data.destroyed(id.clone());
+            guard.object_hooks.notify_destroyed(&id);
         }
 
         match (created_id, ret) {
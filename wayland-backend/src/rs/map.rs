@@ -26,12 +26,14 @@ pub struct Object<Data> {
 pub struct ObjectMap<Data> {
     client_objects: Vec<Option<Object<Data>>>,
     server_objects: Vec<Option<Object<Data>>>,
+    // when set, server ids are allocated monotonically from this index rather than reusing free ones
+    server_next: Option<usize>,
 }
 
 impl<Data: Clone> ObjectMap<Data> {
     /// Create a new empty object map
     pub fn new() -> Self {
-        Self { client_objects: Vec::new(), server_objects: Vec::new() }
+        Self { client_objects: Vec::new(), server_objects: Vec::new(), server_next: None }
     }
 
     /// Find an object in the store
@@ -81,9 +83,33 @@ impl<Data: Clone> ObjectMap<Data> {
 
     /// Allocate a new id for an object in the server namespace
     pub fn server_insert_new(&mut self, object: Object<Data>) -> u32 {
+        if let Some(mut idx) = self.server_next {
+            // skip the ids still in use, if the base was set below them
+            while matches!(self.server_objects.get(idx), Some(Some(_))) {
+                idx += 1;
+            }
+            if self.server_objects.len() <= idx {
+                self.server_objects.resize_with(idx + 1, || None);
+            }
+            self.server_objects[idx] = Some(object);
+            self.server_next = Some(idx + 1);
+            return idx as u32 + SERVER_ID_LIMIT;
+        }
         insert_in(&mut self.server_objects, object) + SERVER_ID_LIMIT
     }
 
+    /// Allocate the next ids of the server namespace monotonically, starting from `base`
+    ///
+    /// The ids of destroyed objects are then never reused. Fails if `base` is not in the server namespace.
+    #[cfg(feature = "test-util")]
+    pub fn set_server_id_base(&mut self, base: u32) -> Result<(), ()> {
+        if base < SERVER_ID_LIMIT {
+            return Err(());
+        }
+        self.server_next = Some((base - SERVER_ID_LIMIT) as usize);
+        Ok(())
+    }
+
     /// Mutably access an object of the map
     pub fn with<T, F: FnOnce(&mut Object<Data>) -> T>(&mut self, id: u32, f: F) -> Result<T, ()> {
         if id == 0 {
@@ -11,12 +11,12 @@ use crate::{
     core_interfaces::{WL_CALLBACK_INTERFACE, WL_DISPLAY_INTERFACE, WL_REGISTRY_INTERFACE},
     debug,
     protocol::{
-        check_for_signature, same_interface, same_interface_or_anonymous, AllowNull, Argument,
+        check_signature, same_interface, same_interface_or_anonymous, AllowNull, Argument,
         ArgumentType, Interface, Message, ObjectInfo, ProtocolError, ANONYMOUS_INTERFACE,
         INLINE_ARGS,
     },
     rs::map::SERVER_ID_LIMIT,
-    types::server::{DisconnectReason, InvalidId},
+    types::server::{DisconnectReason, InvalidId, SendEventError},
 };
 
 use smallvec::SmallVec;
@@ -28,9 +28,9 @@ use crate::rs::{
 };
 
 use super::{
-    handle::PendingDestructor, registry::Registry, ClientData, ClientId, Credentials, Data,
-    DumbObjectData, GlobalHandler, InnerClientId, InnerGlobalId, InnerObjectId, ObjectData,
-    ObjectId, UninitObjectData,
+    handle::PendingDestructor, registry::Registry, BindInterceptor, ClientData, ClientId,
+    Credentials, Data, DumbObjectData, GlobalHandler, GlobalId, InnerClientId, InnerGlobalId,
+    InnerObjectId, ObjectData, ObjectId, UninitObjectData,
 };
 
 type ArgSmallVec<Fd> = SmallVec<[Argument<ObjectId, Fd>; INLINE_ARGS]>;
@@ -52,6 +52,7 @@ pub(crate) struct Client<D: 'static> {
     last_serial: u32,
     pub(crate) id: InnerClientId,
     pub(crate) killed: bool,
+    pub(crate) paused: bool,
     pub(crate) data: Arc<dyn ClientData>,
 }
 
@@ -83,7 +84,7 @@ impl<D> Client<D> {
 
         data.initialized(ClientId { id: id.clone() });
 
-        Self { socket, map, debug, id, killed: false, last_serial: 0, data }
+        Self { socket, map, debug, id, killed: false, paused: false, last_serial: 0, data }
     }
 
     pub(crate) fn create_object(
@@ -110,7 +111,7 @@ impl<D> Client<D> {
         &mut self,
         Message { sender_id: object_id, opcode, args }: Message<ObjectId, RawFd>,
         pending_destructors: Option<&mut Vec<super::handle::PendingDestructor<D>>>,
-    ) -> Result<(), InvalidId> {
+    ) -> Result<(), SendEventError> {
         if self.killed {
             return Ok(());
         }
@@ -119,22 +120,19 @@ impl<D> Client<D> {
         let message_desc = match object.interface.events.get(opcode as usize) {
             Some(msg) => msg,
             None => {
-                panic!(
-                    "Unknown opcode {} for object {}@{}.",
-                    opcode, object.interface.name, object_id.id
-                );
+                return Err(SendEventError::UnknownOpcode { interface: object.interface, opcode });
             }
         };
 
-        if !check_for_signature(message_desc.signature, &args) {
-            panic!(
+        if !check_signature(message_desc.signature, &args) {
+            return Err(SendEventError::InvalidArguments(format!(
                 "Unexpected signature for event {}@{}.{}: expected {:?}, got {:?}.",
                 object.interface.name,
                 object_id.id,
                 message_desc.name,
                 message_desc.signature,
                 args
-            );
+            )));
         }
 
         if self.debug {
@@ -160,18 +158,18 @@ impl<D> Client<D> {
                 Argument::NewId(o) => {
                     if o.id.id != 0 {
                         if o.id.client_id != self.id {
-                            panic!("Attempting to send an event with objects from wrong client.")
+                            return Err(SendEventError::WrongClient);
                         }
                         let object = self.get_object(o.id.clone())?;
                         let child_interface = match message_desc.child_interface {
                             Some(iface) => iface,
-                            None => panic!("Trying to send event {}@{}.{} which creates an object without specifying its interface, this is unsupported.", object_id.id.interface.name, object_id.id, message_desc.name),
+                            None => return Err(SendEventError::InvalidArguments(format!("Trying to send event {}@{}.{} which creates an object without specifying its interface, this is unsupported.", object_id.id.interface.name, object_id.id, message_desc.name))),
                         };
                         if !same_interface(child_interface, object.interface) {
-                            panic!("Event {}@{}.{} expects a newid argument of interface {} but {} was provided instead.", object.interface.name, object_id.id, message_desc.name, child_interface.name, object.interface.name);
+                            return Err(SendEventError::InvalidArguments(format!("Event {}@{}.{} expects a newid argument of interface {} but {} was provided instead.", object_id.id.interface.name, object_id.id, message_desc.name, child_interface.name, object.interface.name)));
                         }
                     } else if !matches!(message_desc.signature[i], ArgumentType::NewId) {
-                        panic!("Request {}@{}.{} expects an non-null newid argument.", object.interface.name, object_id.id, message_desc.name);
+                        return Err(SendEventError::InvalidArguments(format!("Event {}@{}.{} expects an non-null newid argument.", object.interface.name, object_id.id, message_desc.name)));
                     }
                     Argument::Object(o.id.id)
                 },
@@ -179,14 +177,14 @@ impl<D> Client<D> {
                     let next_interface = arg_interfaces.next().unwrap();
                     if o.id.id != 0 {
                         if o.id.client_id != self.id {
-                            panic!("Attempting to send an event with objects from wrong client.")
+                            return Err(SendEventError::WrongClient);
                         }
                         let arg_object = self.get_object(o.id.clone())?;
                         if !same_interface_or_anonymous(next_interface, arg_object.interface) {
-                            panic!("Event {}@{}.{} expects an object argument of interface {} but {} was provided instead.", object.interface.name, object_id.id, message_desc.name, next_interface.name, arg_object.interface.name);
+                            return Err(SendEventError::InvalidArguments(format!("Event {}@{}.{} expects an object argument of interface {} but {} was provided instead.", object.interface.name, object_id.id, message_desc.name, next_interface.name, arg_object.interface.name)));
                         }
                     } else if !matches!(message_desc.signature[i], ArgumentType::Object(AllowNull::Yes)) {
-                            panic!("Request {}@{}.{} expects an non-null object argument.", object.interface.name, object_id.id, message_desc.name);
+                        return Err(SendEventError::InvalidArguments(format!("Event {}@{}.{} expects an non-null object argument.", object.interface.name, object_id.id, message_desc.name)));
                     }
                     Argument::Object(o.id.id)
                 }
@@ -201,7 +199,6 @@ impl<D> Client<D> {
 
         // Handle destruction if relevant
         if message_desc.is_destructor {
-            self.map.remove(object_id.id.id);
             if let Some(vec) = pending_destructors {
                 vec.push((object.data.user_data.clone(), self.id.clone(), object_id.id.clone()));
             }
@@ -212,7 +209,16 @@ impl<D> Client<D> {
     }
 
     pub(crate) fn send_delete_id(&mut self, object_id: InnerObjectId) {
-        // We should only send delete_id for objects in the client ID space
+        // Once delete_id is sent the client may reuse the ID, so destroying the object a second time
+        // could free an unrelated object of the client
+        debug_assert!(
+            self.map.find(object_id.id).map_or(false, |obj| obj.data.serial == object_id.serial),
+            "Object {}@{} was destroyed twice.",
+            object_id.interface.name,
+            object_id.id
+        );
+        // We should only send delete_id for objects in the client ID space, the server-allocated
+        // IDs are released without the client acknowledging it
         if object_id.id < SERVER_ID_LIMIT {
             let msg = message!(1, 1, [Argument::Uint(object_id.id)]);
             if self.socket.write_message(&msg).is_err() {
@@ -311,6 +317,11 @@ impl<D> Client<D> {
         Credentials { pid: 0, uid: 0, gid: 0 }
     }
 
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    pub(crate) fn get_security_context(&self) -> std::io::Result<Option<Vec<u8>>> {
+        crate::types::server::get_peer_security_context(self.socket.as_fd())
+    }
+
     pub(crate) fn kill(&mut self, reason: DisconnectReason) {
         self.killed = true;
         self.data.disconnected(ClientId { id: self.id.clone() }, reason);
@@ -493,6 +504,7 @@ impl<D> Client<D> {
         &mut self,
         message: Message<u32, OwnedFd>,
         registry: &mut Registry<D>,
+        bind_interceptor: Option<&BindInterceptor>,
     ) -> Option<(InnerClientId, InnerGlobalId, InnerObjectId, Arc<dyn GlobalHandler<D>>)> {
         match message.opcode {
             // wl_registry.bind(uint name, str interface, uint version, new id)
@@ -503,6 +515,14 @@ impl<D> Client<D> {
                     if let Some((interface, global_id, handler)) =
                         registry.check_bind(self, name, interface_name, version)
                     {
+                        let version = match bind_interceptor {
+                            Some(interceptor) => interceptor.intercept(
+                                ClientId { id: self.id.clone() },
+                                GlobalId { id: global_id.clone() },
+                                version,
+                            ),
+                            None => version,
+                        };
                         let serial = self.next_serial();
                         let object = Object {
                             interface,
@@ -1,11 +1,14 @@
 use std::{
-    os::unix::io::{AsRawFd, BorrowedFd, OwnedFd},
+    ffi::CString,
+    os::unix::io::{AsFd, AsRawFd, BorrowedFd, OwnedFd},
     sync::{Arc, Mutex},
 };
 
 use super::{
-    handle::State, ClientId, Data, GlobalHandler, GlobalId, Handle, InnerClientId, InnerGlobalId,
-    InnerHandle, InnerObjectId, ObjectId,
+    client::DisplayError,
+    handle::{poll_remove, State},
+    BindInterceptor, ClientId, Data, FdValidator, GlobalHandler, GlobalId, Handle, InnerClientId,
+    InnerGlobalId, InnerHandle, InnerObjectId, ObjectId,
 };
 use crate::{
     core_interfaces::{WL_DISPLAY_INTERFACE, WL_REGISTRY_INTERFACE},
@@ -55,6 +58,22 @@ impl<D> InnerBackend<D> {
         self.state.lock().unwrap().flush(client)
     }
 
+    pub fn flush_all(&self) -> std::io::Result<Vec<ClientId>> {
+        self.state.lock().unwrap().flush_all()
+    }
+
+    pub fn set_fd_validator(&mut self, validator: FdValidator) {
+        self.state.lock().unwrap().fd_validator = Some(validator);
+    }
+
+    pub fn set_bind_interceptor(&mut self, interceptor: BindInterceptor) {
+        self.state.lock().unwrap().bind_interceptor = Some(interceptor);
+    }
+
+    pub fn set_destructor_wakeup(&mut self, _enabled: bool) {
+        // the pending destructors are only run by the next dispatch, no wakeup is forced
+    }
+
     pub fn handle(&self) -> Handle {
         Handle { handle: InnerHandle { state: self.state.clone() as Arc<_> } }
     }
@@ -71,6 +90,7 @@ impl<D> InnerBackend<D> {
         data: &mut D,
         client_id: InnerClientId,
     ) -> std::io::Result<usize> {
+        self.remove_expired_globals();
         let ret = self.dispatch_events_for(data, client_id);
         let cleanup = self.state.lock().unwrap().cleanup();
         cleanup(&self.handle(), data);
@@ -81,8 +101,11 @@ impl<D> InnerBackend<D> {
     pub fn dispatch_all_clients(&self, data: &mut D) -> std::io::Result<usize> {
         use std::os::unix::io::AsFd;
 
+        self.remove_expired_globals();
+
+        let mut dispatched = self.dispatch_resumed_clients(data);
+
         let poll_fd = self.poll_fd();
-        let mut dispatched = 0;
         loop {
             let mut events = epoll::EventVec::with_capacity(32);
             epoll::wait(poll_fd.as_fd(), &mut events, 0)?;
@@ -115,8 +138,11 @@ impl<D> InnerBackend<D> {
     pub fn dispatch_all_clients(&self, data: &mut D) -> std::io::Result<usize> {
         use std::time::Duration;
 
+        self.remove_expired_globals();
+
+        let mut dispatched = self.dispatch_resumed_clients(data);
+
         let poll_fd = self.poll_fd();
-        let mut dispatched = 0;
         loop {
             let mut events = Vec::with_capacity(32);
             let nevents = unsafe { kevent(&poll_fd, &[], &mut events, Some(Duration::ZERO))? };
@@ -139,6 +165,28 @@ impl<D> InnerBackend<D> {
         Ok(dispatched)
     }
 
+    /// Dispatch the requests that were read from clients before they were paused
+    fn dispatch_resumed_clients(&self, data: &mut D) -> usize {
+        let resumed = std::mem::take(&mut self.state.lock().unwrap().resumed_clients);
+        let mut dispatched = 0;
+        for id in resumed {
+            if let Ok(count) = self.dispatch_events_for(data, id) {
+                dispatched += count;
+            }
+        }
+        if dispatched > 0 {
+            let cleanup = self.state.lock().unwrap().cleanup();
+            cleanup(&self.handle(), data);
+        }
+        dispatched
+    }
+
+    fn remove_expired_globals(&self) {
+        let mut state = self.state.lock().unwrap();
+        let state = &mut *state;
+        state.registry.remove_expired_globals(&mut state.clients);
+    }
+
     pub(crate) fn dispatch_events_for(
         &self,
         data: &mut D,
@@ -151,6 +199,9 @@ impl<D> InnerBackend<D> {
             let action = {
                 let state = &mut *state;
                 if let Ok(client) = state.clients.get_client_mut(client_id.clone()) {
+                    if client.paused {
+                        break;
+                    }
                     let (message, object) = match client.next_request() {
                         Ok(v) => v,
                         Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => {
@@ -161,44 +212,40 @@ impl<D> InnerBackend<D> {
                             }
                         }
                         Err(e) => {
-                            #[cfg(any(target_os = "linux", target_os = "android"))]
-                            {
-                                epoll::delete(&state.poll_fd, client)?;
-                            }
-
-                            #[cfg(any(
-                                target_os = "dragonfly",
-                                target_os = "freebsd",
-                                target_os = "netbsd",
-                                target_os = "openbsd",
-                                target_os = "macos"
-                            ))]
-                            {
-                                use rustix::event::kqueue::*;
-                                use std::os::unix::io::{AsFd, AsRawFd};
-
-                                let evt = Event::new(
-                                    EventFilter::Read(client.as_fd().as_raw_fd()),
-                                    EventFlags::DELETE,
-                                    client_id.as_u64() as isize,
-                                );
-
-                                let mut events = Vec::new();
-                                unsafe {
-                                    kevent(&state.poll_fd, &[evt], &mut events, None)
-                                        .map(|_| ())?;
-                                }
-                            }
+                            poll_remove(&state.poll_fd, client.as_fd(), &client_id)?;
                             return Err(e);
                         }
                     };
                     dispatched += 1;
+                    if let Some(ref validator) = state.fd_validator {
+                        let rejected = message.args.iter().any(|arg| {
+                            matches!(arg, Argument::Fd(fd) if !validator.validate(object.interface, message.opcode, fd.as_fd()))
+                        });
+                        if rejected {
+                            // the fds are closed when the message is dropped
+                            client.post_display_error(
+                                DisplayError::InvalidMethod,
+                                CString::new(format!(
+                                    "invalid file descriptor for {}@{}.{}",
+                                    object.interface.name,
+                                    message.sender_id,
+                                    object.interface.requests[message.opcode as usize].name
+                                ))
+                                .unwrap(),
+                            );
+                            continue;
+                        }
+                    }
                     if same_interface(object.interface, &WL_DISPLAY_INTERFACE) {
                         client.handle_display_request(message, &mut state.registry);
                         continue;
                     } else if same_interface(object.interface, &WL_REGISTRY_INTERFACE) {
-                        if let Some((client, global, object, handler)) =
-                            client.handle_registry_request(message, &mut state.registry)
+                        if let Some((client, global, object, handler)) = client
+                            .handle_registry_request(
+                                message,
+                                &mut state.registry,
+                                state.bind_interceptor.as_ref(),
+                            )
                         {
                             DispatchAction::Bind { client, global, object, handler }
                         } else {
@@ -1,30 +1,104 @@
 use std::{
     ffi::CString,
     os::unix::{
-        io::{OwnedFd, RawFd},
+        io::{AsFd, BorrowedFd, OwnedFd, RawFd},
         net::UnixStream,
     },
     sync::{Arc, Mutex, Weak},
+    time::Duration,
 };
 
 use crate::{
     protocol::{same_interface, Interface, Message, ObjectInfo, ANONYMOUS_INTERFACE},
-    types::server::{DisconnectReason, GlobalInfo, InvalidId},
+    types::server::{
+        ClientControlError, DisconnectReason, GlobalError, GlobalInfo, InvalidId, SendEventError,
+    },
 };
 
 use super::{
-    client::ClientStore, registry::Registry, ClientData, ClientId, Credentials, GlobalHandler,
-    InnerClientId, InnerGlobalId, InnerObjectId, ObjectData, ObjectId,
+    client::ClientStore, registry::Registry, BindInterceptor, ClientData, ClientId, Credentials,
+    FdValidator, GlobalHandler, InnerClientId, InnerGlobalId, InnerObjectId, ObjectData, ObjectId,
 };
 
 pub(crate) type PendingDestructor<D> = (Arc<dyn ObjectData<D>>, InnerClientId, InnerObjectId);
 
+/// Register the socket of a client to the internal poll fd
+pub(crate) fn poll_add(
+    poll_fd: &OwnedFd,
+    socket: BorrowedFd<'_>,
+    id: &InnerClientId,
+) -> rustix::io::Result<()> {
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    {
+        use rustix::event::epoll;
+        epoll::add(poll_fd, socket, epoll::EventData::new_u64(id.as_u64()), epoll::EventFlags::IN)
+    }
+
+    #[cfg(any(
+        target_os = "dragonfly",
+        target_os = "freebsd",
+        target_os = "netbsd",
+        target_os = "openbsd",
+        target_os = "macos"
+    ))]
+    {
+        use rustix::event::kqueue::*;
+        use std::os::unix::io::AsRawFd;
+
+        let evt = Event::new(
+            EventFilter::Read(socket.as_raw_fd()),
+            EventFlags::ADD | EventFlags::RECEIPT,
+            id.as_u64() as isize,
+        );
+
+        let mut events = Vec::new();
+        unsafe { kevent(poll_fd, &[evt], &mut events, None).map(|_| ()) }
+    }
+}
+
+/// Unregister the socket of a client from the internal poll fd
+pub(crate) fn poll_remove(
+    poll_fd: &OwnedFd,
+    socket: BorrowedFd<'_>,
+    id: &InnerClientId,
+) -> rustix::io::Result<()> {
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    {
+        let _ = id;
+        rustix::event::epoll::delete(poll_fd, socket)
+    }
+
+    #[cfg(any(
+        target_os = "dragonfly",
+        target_os = "freebsd",
+        target_os = "netbsd",
+        target_os = "openbsd",
+        target_os = "macos"
+    ))]
+    {
+        use rustix::event::kqueue::*;
+        use std::os::unix::io::AsRawFd;
+
+        let evt = Event::new(
+            EventFilter::Read(socket.as_raw_fd()),
+            EventFlags::DELETE,
+            id.as_u64() as isize,
+        );
+
+        let mut events = Vec::new();
+        unsafe { kevent(poll_fd, &[evt], &mut events, None).map(|_| ()) }
+    }
+}
+
 #[derive(Debug)]
 pub struct State<D: 'static> {
     pub(crate) clients: ClientStore<D>,
     pub(crate) registry: Registry<D>,
     pub(crate) pending_destructors: Vec<PendingDestructor<D>>,
     pub(crate) poll_fd: OwnedFd,
+    pub(crate) fd_validator: Option<FdValidator>,
+    pub(crate) bind_interceptor: Option<BindInterceptor>,
+    pub(crate) resumed_clients: Vec<InnerClientId>,
 }
 
 impl<D> State<D> {
@@ -36,6 +110,9 @@ impl<D> State<D> {
             registry: Registry::new(),
             pending_destructors: Vec::new(),
             poll_fd,
+            fd_validator: None,
+            bind_interceptor: None,
+            resumed_clients: Vec::new(),
         }
     }
 
@@ -70,6 +147,18 @@ impl<D> State<D> {
             Ok(())
         }
     }
+
+    pub(crate) fn flush_all(&mut self) -> std::io::Result<Vec<ClientId>> {
+        let mut would_block = Vec::new();
+        for client in self.clients.clients_mut() {
+            if let Err(e) = client.flush() {
+                if e.kind() == std::io::ErrorKind::WouldBlock {
+                    would_block.push(ClientId { id: client.id.clone() });
+                }
+            }
+        }
+        Ok(would_block)
+    }
 }
 
 #[derive(Clone)]
@@ -98,6 +187,14 @@ impl WeakInnerHandle {
     pub fn upgrade(&self) -> Option<InnerHandle> {
         self.state.upgrade().map(|state| InnerHandle { state })
     }
+
+    pub fn object_is_alive(&self, id: &InnerObjectId) -> bool {
+        let Some(state) = self.state.upgrade() else {
+            return false;
+        };
+        let alive = state.lock().unwrap().object_info(id.clone()).is_ok();
+        alive
+    }
 }
 
 impl InnerHandle {
@@ -129,6 +226,14 @@ impl InnerHandle {
         self.state.lock().unwrap().get_client_credentials(id)
     }
 
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    pub fn get_client_security_context(
+        &self,
+        id: InnerClientId,
+    ) -> std::io::Result<Option<Vec<u8>>> {
+        self.state.lock().unwrap().get_client_security_context(id)
+    }
+
     pub fn with_all_clients(&self, mut f: impl FnMut(ClientId)) {
         self.state.lock().unwrap().with_all_clients(&mut f)
     }
@@ -176,7 +281,7 @@ impl InnerHandle {
         }
     }
 
-    pub fn send_event(&self, msg: Message<ObjectId, RawFd>) -> Result<(), InvalidId> {
+    pub fn send_event(&self, msg: Message<ObjectId, RawFd>) -> Result<(), SendEventError> {
         self.state.lock().unwrap().send_event(msg)
     }
 
@@ -218,12 +323,38 @@ impl InnerHandle {
         self.state.lock().unwrap().kill_client(client_id, reason)
     }
 
+    pub fn set_client_paused(
+        &self,
+        client_id: InnerClientId,
+        paused: bool,
+    ) -> Result<(), ClientControlError> {
+        Ok(self.state.lock().unwrap().set_client_paused(client_id, paused)?)
+    }
+
+    #[cfg(feature = "test-util")]
+    pub fn set_deterministic_server_ids(
+        &self,
+        client_id: InnerClientId,
+        base: u32,
+    ) -> Result<(), ClientControlError> {
+        self.state.lock().unwrap().set_deterministic_server_ids(client_id, base)
+    }
+
     pub fn create_global<D: 'static>(
         &self,
         interface: &'static Interface,
         version: u32,
         handler: Arc<dyn GlobalHandler<D>>,
     ) -> InnerGlobalId {
+        self.try_create_global(interface, version, handler).unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    pub fn try_create_global<D: 'static>(
+        &self,
+        interface: &'static Interface,
+        version: u32,
+        handler: Arc<dyn GlobalHandler<D>>,
+    ) -> Result<InnerGlobalId, GlobalError> {
         let mut state = self.state.lock().unwrap();
         let state = (&mut *state as &mut dyn ErasedState)
             .downcast_mut::<State<D>>()
@@ -231,6 +362,25 @@ impl InnerHandle {
         state.registry.create_global(interface, version, handler, &mut state.clients)
     }
 
+    pub fn create_globals<D: 'static>(
+        &self,
+        globals: impl IntoIterator<Item = (&'static Interface, u32, Arc<dyn GlobalHandler<D>>)>,
+    ) -> Vec<InnerGlobalId> {
+        let mut state = self.state.lock().unwrap();
+        let state = (&mut *state as &mut dyn ErasedState)
+            .downcast_mut::<State<D>>()
+            .expect("Wrong type parameter passed to Handle::create_globals().");
+        globals
+            .into_iter()
+            .map(|(interface, version, handler)| {
+                state
+                    .registry
+                    .create_global(interface, version, handler, &mut state.clients)
+                    .unwrap_or_else(|err| panic!("{}", err))
+            })
+            .collect()
+    }
+
     pub fn disable_global<D: 'static>(&self, id: InnerGlobalId) {
         let mut state = self.state.lock().unwrap();
         let state = (&mut *state as &mut dyn ErasedState)
@@ -249,6 +399,15 @@ impl InnerHandle {
         state.registry.remove_global(id, &mut state.clients)
     }
 
+    pub fn retire_global<D: 'static>(&self, id: InnerGlobalId, delay: Duration) {
+        let mut state = self.state.lock().unwrap();
+        let state = (&mut *state as &mut dyn ErasedState)
+            .downcast_mut::<State<D>>()
+            .expect("Wrong type parameter passed to Handle::retire_global().");
+
+        state.registry.retire_global(id, delay, &mut state.clients)
+    }
+
     pub fn global_info(&self, id: InnerGlobalId) -> Result<GlobalInfo, InvalidId> {
         self.state.lock().unwrap().global_info(id)
     }
@@ -279,6 +438,8 @@ pub(crate) trait ErasedState: downcast_rs::Downcast {
     fn get_client(&self, id: InnerObjectId) -> Result<ClientId, InvalidId>;
     fn get_client_data(&self, id: InnerClientId) -> Result<Arc<dyn ClientData>, InvalidId>;
     fn get_client_credentials(&self, id: InnerClientId) -> Result<Credentials, InvalidId>;
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    fn get_client_security_context(&self, id: InnerClientId) -> std::io::Result<Option<Vec<u8>>>;
     fn with_all_clients(&self, f: &mut dyn FnMut(ClientId));
     fn with_all_objects_for(
         &self,
@@ -295,9 +456,20 @@ pub(crate) trait ErasedState: downcast_rs::Downcast {
         &self,
         id: InnerObjectId,
     ) -> Result<Arc<dyn std::any::Any + Send + Sync>, InvalidId>;
-    fn send_event(&mut self, msg: Message<ObjectId, RawFd>) -> Result<(), InvalidId>;
+    fn send_event(&mut self, msg: Message<ObjectId, RawFd>) -> Result<(), SendEventError>;
     fn post_error(&mut self, object_id: InnerObjectId, error_code: u32, message: CString);
     fn kill_client(&mut self, client_id: InnerClientId, reason: DisconnectReason);
+    fn set_client_paused(
+        &mut self,
+        client_id: InnerClientId,
+        paused: bool,
+    ) -> Result<(), InvalidId>;
+    #[cfg(feature = "test-util")]
+    fn set_deterministic_server_ids(
+        &mut self,
+        client_id: InnerClientId,
+        base: u32,
+    ) -> Result<(), ClientControlError>;
     fn global_info(&self, id: InnerGlobalId) -> Result<GlobalInfo, InvalidId>;
     fn flush(&mut self, client: Option<ClientId>) -> std::io::Result<()>;
 }
@@ -318,37 +490,7 @@ impl<D> ErasedState for State<D> {
         let client = self.clients.get_client(id.clone()).unwrap();
 
         // register the client to the internal epoll
-        #[cfg(any(target_os = "linux", target_os = "android"))]
-        let ret = {
-            use rustix::event::epoll;
-            epoll::add(
-                &self.poll_fd,
-                client,
-                epoll::EventData::new_u64(id.as_u64()),
-                epoll::EventFlags::IN,
-            )
-        };
-
-        #[cfg(any(
-            target_os = "dragonfly",
-            target_os = "freebsd",
-            target_os = "netbsd",
-            target_os = "openbsd",
-            target_os = "macos"
-        ))]
-        let ret = {
-            use rustix::event::kqueue::*;
-            use std::os::unix::io::{AsFd, AsRawFd};
-
-            let evt = Event::new(
-                EventFilter::Read(client.as_fd().as_raw_fd()),
-                EventFlags::ADD | EventFlags::RECEIPT,
-                id.as_u64() as isize,
-            );
-
-            let mut events = Vec::new();
-            unsafe { kevent(&self.poll_fd, &[evt], &mut events, None).map(|_| ()) }
-        };
+        let ret = poll_add(&self.poll_fd, client.as_fd(), &id);
 
         match ret {
             Ok(()) => Ok(id),
@@ -377,6 +519,15 @@ impl<D> ErasedState for State<D> {
         Ok(client.get_credentials())
     }
 
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    fn get_client_security_context(&self, id: InnerClientId) -> std::io::Result<Option<Vec<u8>>> {
+        let client = self
+            .clients
+            .get_client(id)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::NotFound, e))?;
+        client.get_security_context()
+    }
+
     fn with_all_clients(&self, f: &mut dyn FnMut(ClientId)) {
         for client in self.clients.all_clients_id() {
             f(client)
@@ -420,7 +571,7 @@ impl<D> ErasedState for State<D> {
             .map(|arc| arc.into_any_arc())
     }
 
-    fn send_event(&mut self, msg: Message<ObjectId, RawFd>) -> Result<(), InvalidId> {
+    fn send_event(&mut self, msg: Message<ObjectId, RawFd>) -> Result<(), SendEventError> {
         self.clients
             .get_client_mut(msg.sender_id.id.client_id.clone())?
             .send_event(msg, Some(&mut self.pending_destructors))
@@ -437,6 +588,45 @@ impl<D> ErasedState for State<D> {
             client.kill(reason)
         }
     }
+
+    fn set_client_paused(
+        &mut self,
+        client_id: InnerClientId,
+        paused: bool,
+    ) -> Result<(), InvalidId> {
+        let client = self.clients.get_client_mut(client_id.clone())?;
+        if client.paused == paused {
+            return Ok(());
+        }
+        client.paused = paused;
+        // Stop polling the socket of a paused client, its requests are left in the socket until it
+        // is resumed
+        let ret = if paused {
+            poll_remove(&self.poll_fd, client.as_fd(), &client_id)
+        } else {
+            // some requests may already have been read from the socket, make sure they are
+            // dispatched on the next dispatch_all_clients()
+            self.resumed_clients.push(client_id.clone());
+            poll_add(&self.poll_fd, client.as_fd(), &client_id)
+        };
+        if ret.is_err() {
+            self.kill_client(client_id, DisconnectReason::ConnectionClosed);
+        }
+        Ok(())
+    }
+
+    #[cfg(feature = "test-util")]
+    fn set_deterministic_server_ids(
+        &mut self,
+        client_id: InnerClientId,
+        base: u32,
+    ) -> Result<(), ClientControlError> {
+        self.clients
+            .get_client_mut(client_id)?
+            .map
+            .set_server_id_base(base)
+            .map_err(|()| ClientControlError::InvalidServerIdBase(base))
+    }
     fn global_info(&self, id: InnerGlobalId) -> Result<GlobalInfo, InvalidId> {
         self.registry.get_info(id)
     }
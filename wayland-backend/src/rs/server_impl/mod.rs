@@ -37,6 +37,14 @@ impl InnerObjectId {
         self.client_id == other.client_id
     }
 
+    pub fn client_id(&self) -> Option<InnerClientId> {
+        if self.is_null() {
+            None
+        } else {
+            Some(self.client_id.clone())
+        }
+    }
+
     pub fn protocol_id(&self) -> u32 {
         self.id
     }
@@ -1,10 +1,11 @@
 use std::{
     ffi::{CStr, CString},
     sync::Arc,
+    time::{Duration, Instant},
 };
 
 use crate::protocol::{Argument, Interface};
-use crate::types::server::{GlobalInfo, InvalidId};
+use crate::types::server::{GlobalError, GlobalInfo, InvalidId};
 
 use super::{
     client::{Client, ClientStore},
@@ -30,12 +31,18 @@ struct Global<D: 'static> {
 pub struct Registry<D: 'static> {
     globals: Vec<Option<Global<D>>>,
     known_registries: Vec<InnerObjectId>,
+    retired_globals: Vec<(Instant, InnerGlobalId)>,
     last_serial: u32,
 }
 
 impl<D> Registry<D> {
     pub(crate) fn new() -> Self {
-        Self { globals: Vec::new(), known_registries: Vec::new(), last_serial: 0 }
+        Self {
+            globals: Vec::new(),
+            known_registries: Vec::new(),
+            retired_globals: Vec::new(),
+            last_serial: 0,
+        }
     }
 
     fn next_serial(&mut self) -> u32 {
@@ -49,12 +56,9 @@ impl<D> Registry<D> {
         version: u32,
         handler: Arc<dyn GlobalHandler<D>>,
         clients: &mut ClientStore<D>,
-    ) -> InnerGlobalId {
+    ) -> Result<InnerGlobalId, GlobalError> {
         if version > interface.version {
-            panic!(
-                "Cannot create global {} version {}: maximum supported version is {}",
-                interface.name, version, interface.version
-            );
+            return Err(GlobalError::UnsupportedVersion { interface, version });
         }
         let serial = self.next_serial();
         let (id, place) = match self.globals.iter_mut().enumerate().find(|(_, g)| g.is_none()) {
@@ -71,7 +75,7 @@ impl<D> Registry<D> {
 
         self.send_global_to_all(id.clone(), clients).unwrap();
 
-        id
+        Ok(id)
     }
 
     fn get_global(&self, id: InnerGlobalId) -> Result<&Global<D>, InvalidId> {
@@ -160,6 +164,32 @@ impl<D> Registry<D> {
                 *place = None;
             }
         }
+        self.retired_globals.retain(|(_, g)| g != &id);
+    }
+
+    pub(crate) fn retire_global(
+        &mut self,
+        id: InnerGlobalId,
+        delay: Duration,
+        clients: &mut ClientStore<D>,
+    ) {
+        match self.globals.get(id.id as usize - 1) {
+            Some(Some(g)) if g.id == id => {}
+            _ => return,
+        }
+        self.disable_global(id.clone(), clients);
+        self.retired_globals.retain(|(_, g)| g != &id);
+        self.retired_globals.push((Instant::now() + delay, id));
+    }
+
+    pub(crate) fn remove_expired_globals(&mut self, clients: &mut ClientStore<D>) {
+        let now = Instant::now();
+        let (expired, pending) =
+            std::mem::take(&mut self.retired_globals).into_iter().partition(|&(t, _)| t <= now);
+        self.retired_globals = pending;
+        for (_, id) in expired {
+            self.remove_global(id, clients);
+        }
     }
 
     pub(crate) fn new_registry(
@@ -225,19 +255,21 @@ fn send_global_to<D>(
     global: &Global<D>,
     registry: ObjectId,
 ) -> Result<(), InvalidId> {
-    client.send_event(
-        message!(
-            registry,
-            0, // wl_registry.global
-            [
-                Argument::Uint(global.id.id),
-                Argument::Str(Some(Box::new(CString::new(global.interface.name).unwrap()))),
-                Argument::Uint(global.version),
-            ],
-        ),
-        // This is not a destructor event
-        None,
-    )
+    client
+        .send_event(
+            message!(
+                registry,
+                0, // wl_registry.global
+                [
+                    Argument::Uint(global.id.id),
+                    Argument::Str(Some(Box::new(CString::new(global.interface.name).unwrap()))),
+                    Argument::Uint(global.version),
+                ],
+            ),
+            // This is not a destructor event
+            None,
+        )
+        .map_err(|_| InvalidId)
 }
 
 #[inline]
@@ -246,13 +278,15 @@ fn send_global_remove_to<D>(
     global: &Global<D>,
     registry: ObjectId,
 ) -> Result<(), InvalidId> {
-    client.send_event(
-        message!(
-            registry,
-            1, // wl_registry.global_remove
-            [Argument::Uint(global.id.id)],
-        ),
-        // This is not a destructor event
-        None,
-    )
+    client
+        .send_event(
+            message!(
+                registry,
+                1, // wl_registry.global_remove
+                [Argument::Uint(global.id.id)],
+            ),
+            // This is not a destructor event
+            None,
+        )
+        .map_err(|_| InvalidId)
 }
@@ -12,7 +12,7 @@ use rustix::net::{
     SendAncillaryBuffer, SendAncillaryMessage, SendFlags,
 };
 
-use crate::protocol::{ArgumentType, Message};
+use crate::protocol::{Argument, ArgumentType, Message};
 
 use super::wire::{parse_message, write_to_buffers, MessageParseError, MessageWriteError};
 
@@ -20,6 +20,8 @@ use super::wire::{parse_message, write_to_buffers, MessageParseError, MessageWri
 pub const MAX_FDS_OUT: usize = 28;
 /// Maximum number of bytes that can be sent in a single socket message
 pub const MAX_BYTES_OUT: usize = 4096;
+/// Maximum number of bytes of messages kept aside while the socket is full
+pub const MAX_OVERFLOW_BYTES: usize = 256 * MAX_BYTES_OUT;
 
 /*
  * Socket
@@ -39,7 +41,7 @@ impl Socket {
     /// The `fds` slice should not be longer than `MAX_FDS_OUT`, and the `bytes`
     /// slice should not be longer than `MAX_BYTES_OUT` otherwise the receiving
     /// end may lose some data.
-    pub fn send_msg(&self, bytes: &[u8], fds: &[OwnedFd]) -> IoResult<usize> {
+    pub fn send_msg(&self, bytes: &[u8], fds: &[BorrowedFd<'_>]) -> IoResult<usize> {
         #[cfg(not(target_os = "macos"))]
         let flags = SendFlags::DONTWAIT | SendFlags::NOSIGNAL;
         #[cfg(target_os = "macos")]
@@ -49,8 +51,6 @@ impl Socket {
             let iov = [IoSlice::new(bytes)];
             let mut cmsg_space = vec![0; rustix::cmsg_space!(ScmRights(fds.len()))];
             let mut cmsg_buffer = SendAncillaryBuffer::new(&mut cmsg_space);
-            let fds =
-                unsafe { slice::from_raw_parts(fds.as_ptr() as *const BorrowedFd, fds.len()) };
             cmsg_buffer.push(SendAncillaryMessage::ScmRights(fds));
             Ok(retry_on_intr(|| sendmsg(self, &iov, &mut cmsg_buffer, flags))?)
         } else {
@@ -132,6 +132,24 @@ pub struct BufferedSocket {
     in_fds: VecDeque<OwnedFd>,
     out_data: Buffer<u8>,
     out_fds: Vec<OwnedFd>,
+    overflow: Option<Overflow>,
+}
+
+/// The messages waiting for room in the outgoing buffer
+#[derive(Debug)]
+struct Overflow {
+    messages: VecDeque<PendingMessage>,
+    /// Total size of the queued messages, at most `MAX_OVERFLOW_BYTES`
+    size: usize,
+    /// Buffer the messages are serialized into before being queued
+    scratch: Vec<u8>,
+}
+
+/// A serialized message waiting for room in the outgoing buffer
+#[derive(Debug)]
+struct PendingMessage {
+    bytes: Vec<u8>,
+    fds: Vec<OwnedFd>,
 }
 
 impl BufferedSocket {
@@ -143,21 +161,106 @@ impl BufferedSocket {
             in_fds: VecDeque::new(),                 // able to store leftover data if needed
             out_data: Buffer::new(MAX_BYTES_OUT),
             out_fds: Vec::new(),
+            overflow: None,
+        }
+    }
+
+    /// Keep the messages that do not fit in the outgoing buffer while the socket is full
+    ///
+    /// By default, [`write_message()`][Self::write_message()] fails if the outgoing buffer is full and
+    /// cannot be flushed because the socket would block. Once this is enabled, such messages are instead
+    /// kept aside and sent in order by the following calls to [`flush()`][Self::flush()].
+    ///
+    /// At most [`MAX_OVERFLOW_BYTES`] are kept aside, beyond which writing a message fails with `ENOBUFS`.
+    pub fn enable_overflow(&mut self) {
+        if self.overflow.is_none() {
+            self.overflow = Some(Overflow {
+                messages: VecDeque::new(),
+                size: 0,
+                scratch: vec![0; MAX_BYTES_OUT],
+            });
         }
     }
 
     /// Flush the contents of the outgoing buffer into the socket
+    ///
+    /// This writes until all buffered messages are sent, or until the socket cannot accept more data, in
+    /// which case `WouldBlock` is returned. The unsent data stays buffered, and the next flush resumes
+    /// from where this one stopped.
     pub fn flush(&mut self) -> IoResult<()> {
-        let written = {
-            let bytes = self.out_data.get_contents();
-            if bytes.is_empty() {
-                return Ok(());
+        loop {
+            self.refill_from_overflow();
+            let written = {
+                let bytes = self.out_data.get_contents();
+                if bytes.is_empty() {
+                    return Ok(());
+                }
+                // Safety: OwnedFd and BorrowedFd have the same representation
+                let fds = unsafe {
+                    slice::from_raw_parts(
+                        self.out_fds.as_ptr() as *const BorrowedFd,
+                        self.out_fds.len(),
+                    )
+                };
+                self.socket.send_msg(bytes, fds)?
+            };
+            self.out_data.offset(written);
+            self.out_data.move_to_front();
+            self.out_fds.clear();
+        }
+    }
+
+    // internal method
+    //
+    // moves the messages of the overflow queue into the outgoing buffer, as long as
+    // they fit in it
+    fn refill_from_overflow(&mut self) {
+        let overflow = match self.overflow.as_mut() {
+            Some(overflow) => overflow,
+            None => return,
+        };
+        while let Some(pending) = overflow.messages.front() {
+            let storage = self.out_data.get_writable_storage();
+            if pending.bytes.len() > storage.len()
+                || self.out_fds.len() + pending.fds.len() > MAX_FDS_OUT
+            {
+                break;
             }
-            self.socket.send_msg(bytes, &self.out_fds)?
+            let pending = overflow.messages.pop_front().unwrap();
+            overflow.size -= pending.bytes.len();
+            storage[..pending.bytes.len()].copy_from_slice(&pending.bytes);
+            self.out_data.advance(pending.bytes.len());
+            self.out_fds.extend(pending.fds);
+        }
+    }
+
+    // internal method
+    //
+    // stores a message in the overflow queue, to be sent by the next flushes
+    //
+    // the queue must have been enabled with enable_overflow()
+    fn push_overflow(&mut self, msg: &Message<u32, RawFd>) -> IoResult<()> {
+        let overflow = self.overflow.as_mut().expect("The overflow queue is not enabled");
+        let mut fds = Vec::new();
+        let len = match write_to_buffers(msg, &mut overflow.scratch, &mut fds) {
+            Ok(len) => len,
+            Err(MessageWriteError::BufferTooSmall) => return Err(rustix::io::Errno::TOOBIG.into()),
         };
-        self.out_data.offset(written);
-        self.out_data.move_to_front();
-        self.out_fds.clear();
+        if fds.len() > MAX_FDS_OUT {
+            return Err(rustix::io::Errno::TOOBIG.into());
+        }
+        if overflow.size + len > MAX_OVERFLOW_BYTES {
+            return Err(rustix::io::Errno::NOBUFS.into());
+        }
+        // like in the outgoing buffer, the fds are dup()-ed to outlive the caller's
+        let fds = fds
+            .into_iter()
+            .map(|fd| unsafe { BorrowedFd::borrow_raw(fd) }.try_clone_to_owned())
+            .collect::<IoResult<Vec<_>>>()?;
+        overflow.size += len;
+        overflow
+            .messages
+            .push_back(PendingMessage { bytes: overflow.scratch[..len].to_vec(), fds });
         Ok(())
     }
 
@@ -169,32 +272,113 @@ impl BufferedSocket {
     // if false is returned, it means there is not enough space
     // in the buffer
     fn attempt_write_message(&mut self, msg: &Message<u32, RawFd>) -> IoResult<bool> {
-        match write_to_buffers(msg, self.out_data.get_writable_storage(), &mut self.out_fds) {
+        // the fds of the buffered messages are all sent along the first sendmsg, so they must not
+        // exceed what the other end can receive at once
+        let fd_count = msg.args.iter().filter(|arg| matches!(arg, Argument::Fd(_))).count();
+        if self.out_fds.len() + fd_count > MAX_FDS_OUT {
+            return Ok(false);
+        }
+        let mut fds = Vec::new();
+        match write_to_buffers(msg, self.out_data.get_writable_storage(), &mut fds) {
             Ok(bytes_out) => {
+                // the message stays buffered, so its fds are dup()-ed to outlive the caller's
+                let fds = fds
+                    .into_iter()
+                    .map(|fd| unsafe { BorrowedFd::borrow_raw(fd) }.try_clone_to_owned())
+                    .collect::<IoResult<Vec<_>>>()?;
+                self.out_fds.extend(fds);
                 self.out_data.advance(bytes_out);
                 Ok(true)
             }
             Err(MessageWriteError::BufferTooSmall) => Ok(false),
-            Err(MessageWriteError::DupFdFailed(e)) => Err(e),
+        }
+    }
+
+    // internal method
+    //
+    // attempts to send a message carrying fds right away, so that its fds can be given
+    // to sendmsg() as-is instead of being dup()-ed to be kept in the buffer, returns
+    // true if the message was handled
+    //
+    // this must only be invoked when the outgoing buffer is empty, to preserve the
+    // ordering of the messages
+    fn attempt_send_message(&mut self, msg: &Message<u32, RawFd>) -> IoResult<bool> {
+        let mut fds = Vec::new();
+        let bytes_out = match write_to_buffers(msg, self.out_data.get_writable_storage(), &mut fds)
+        {
+            Ok(bytes_out) => bytes_out,
+            Err(MessageWriteError::BufferTooSmall) => return Ok(false),
+        };
+        if fds.len() > MAX_FDS_OUT {
+            return Ok(false);
+        }
+        self.out_data.advance(bytes_out);
+        // Safety: the fds of the message are valid for the duration of this call
+        let borrowed_fds =
+            fds.iter().map(|&fd| unsafe { BorrowedFd::borrow_raw(fd) }).collect::<Vec<_>>();
+        match self.socket.send_msg(self.out_data.get_contents(), &borrowed_fds) {
+            Ok(written) => {
+                // the fds are sent along the first byte, any unsent bytes can stay buffered on their own
+                self.out_data.offset(written);
+                self.out_data.move_to_front();
+                Ok(true)
+            }
+            Err(e) if e.kind() == ErrorKind::WouldBlock => {
+                // nothing was sent, the message stays buffered along with copies of its fds
+                match borrowed_fds.iter().map(|fd| fd.try_clone_to_owned()).collect() {
+                    Ok(owned_fds) => {
+                        self.out_fds = owned_fds;
+                        Ok(true)
+                    }
+                    Err(e) => {
+                        self.out_data.clear();
+                        Err(e)
+                    }
+                }
+            }
+            Err(e) => {
+                self.out_data.clear();
+                Err(e)
+            }
         }
     }
 
     /// Write a message to the outgoing buffer
     ///
-    /// This method may flush the internal buffer if necessary (if it is full).
+    /// Messages are accumulated in the buffer until [`flush()`][Self::flush()] is invoked, so that
+    /// they are sent with as few `sendmsg` calls as possible. This method may flush the internal buffer
+    /// if necessary (if it is full, or if it already holds as many fds as can be sent at once).
+    ///
+    /// The fds of a buffered message are `dup()`-ed, as the caller may close them before the buffer is
+    /// flushed. To avoid this, a message carrying fds is sent right away if the buffer is empty.
     ///
     /// If the message is too big to fit in the buffer, the error `Error::Sys(E2BIG)`
-    /// will be returned.
+    /// will be returned. This error is also returned if the buffer is full and cannot be flushed
+    /// because the socket would block, unless [`enable_overflow()`][Self::enable_overflow()] was invoked.
+    /// In that case, `Error::Sys(ENOBUFS)` is returned once too many messages are waiting for the socket.
     pub fn write_message(&mut self, msg: &Message<u32, RawFd>) -> IoResult<()> {
+        if self.overflow.as_ref().map_or(false, |overflow| !overflow.messages.is_empty()) {
+            // messages are already waiting for the socket, this one must be sent after them
+            return self.push_overflow(msg);
+        }
+        if self.out_data.get_contents().is_empty()
+            && msg.args.iter().any(|arg| matches!(arg, Argument::Fd(_)))
+            && self.attempt_send_message(msg)?
+        {
+            return Ok(());
+        }
         if !self.attempt_write_message(msg)? {
             // the attempt failed, there is not enough space in the buffer
             // we need to flush it
-            if let Err(e) = self.flush() {
-                if e.kind() != ErrorKind::WouldBlock {
-                    return Err(e);
-                }
-            }
+            let blocked = match self.flush() {
+                Ok(()) => false,
+                Err(e) if e.kind() == ErrorKind::WouldBlock => true,
+                Err(e) => return Err(e),
+            };
             if !self.attempt_write_message(msg)? {
+                if blocked && self.overflow.is_some() {
+                    return self.push_overflow(msg);
+                }
                 // If this fails again, this means the message is too big
                 // to be transmitted at all
                 return Err(rustix::io::Errno::TOOBIG.into());
@@ -222,6 +406,47 @@ impl BufferedSocket {
         Ok(())
     }
 
+    /// The id of the object the next message of the incoming buffers is addressed to, if available
+    pub fn peek_sender_id(&self) -> Option<u32> {
+        let data = self.in_data.get_contents();
+        if data.len() < 4 {
+            return None;
+        }
+        Some(u32::from_ne_bytes([data[0], data[1], data[2], data[3]]))
+    }
+
+    /// The bytes of the next message of the incoming buffers, if it was entirely received
+    pub fn peek_message(&self) -> Option<&[u8]> {
+        let data = self.in_data.get_contents();
+        if data.len() < 2 * 4 {
+            return None;
+        }
+        let word_2 = u32::from_ne_bytes([data[4], data[5], data[6], data[7]]);
+        let len = (word_2 >> 16) as usize;
+        data.get(..len)
+    }
+
+    /// Discard the next message of the incoming buffers without parsing it
+    ///
+    /// Returns the id of the object the message was addressed to. The file descriptors it may carry
+    /// are not discarded, as this cannot be known without its signature.
+    pub fn skip_one_message(&mut self) -> Result<u32, MessageParseError> {
+        let data = self.in_data.get_contents();
+        if data.len() < 2 * 4 {
+            return Err(MessageParseError::MissingData);
+        }
+        let object_id = u32::from_ne_bytes([data[0], data[1], data[2], data[3]]);
+        let word_2 = u32::from_ne_bytes([data[4], data[5], data[6], data[7]]);
+        let len = (word_2 >> 16) as usize;
+        if len < 2 * 4 {
+            return Err(MessageParseError::Malformed);
+        } else if len > data.len() {
+            return Err(MessageParseError::MissingData);
+        }
+        self.in_data.offset(len);
+        Ok(object_id)
+    }
+
     /// Read and deserialize a single message from the incoming buffers socket
     ///
     /// This method requires one closure that given an object id and an opcode,
@@ -300,7 +525,6 @@ impl<T: Copy + Default> Buffer<T> {
     ///
     /// This only sets the counter of occupied space back to zero,
     /// allowing previous content to be overwritten.
-    #[allow(unused)]
     fn clear(&mut self) {
         self.occupied = 0;
         self.offset = 0;
@@ -330,7 +554,7 @@ impl<T: Copy + Default> Buffer<T> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::protocol::{AllowNull, Argument, ArgumentType, Message};
+    use crate::protocol::AllowNull;
 
     use std::ffi::CString;
     use std::os::unix::io::IntoRawFd;
@@ -414,6 +638,32 @@ mod tests {
         assert_eq_msgs(&msg.map_fd(|fd| fd.as_raw_fd()), &ret_msg.map_fd(IntoRawFd::into_raw_fd));
     }
 
+    #[test]
+    fn write_read_cycle_many_fds() {
+        let msg = Message { sender_id: 42, opcode: 7, args: smallvec![Argument::Fd(1)] };
+
+        let (client, server) = ::std::os::unix::net::UnixStream::pair().unwrap();
+        let mut client = BufferedSocket::new(Socket::from(client));
+        let mut server = BufferedSocket::new(Socket::from(server));
+
+        // more fds than can be sent at once, the buffer must be flushed in between
+        for _ in 0..(2 * MAX_FDS_OUT + 1) {
+            client.write_message(&msg).unwrap();
+        }
+        client.flush().unwrap();
+
+        static SIGNATURE: &[ArgumentType] = &[ArgumentType::Fd];
+
+        let mut received = 0;
+        while received < 2 * MAX_FDS_OUT + 1 {
+            server.fill_incoming_buffers().unwrap();
+            while let Ok(ret_msg) = server.read_one_message(|_, _| Some(SIGNATURE)) {
+                assert_eq_msgs(&msg, &ret_msg.map_fd(IntoRawFd::into_raw_fd));
+                received += 1;
+            }
+        }
+    }
+
     #[test]
     fn write_read_cycle_fd() {
         let msg = Message {
@@ -449,6 +699,131 @@ mod tests {
         assert_eq_msgs(&msg.map_fd(|fd| fd.as_raw_fd()), &ret_msg.map_fd(IntoRawFd::into_raw_fd));
     }
 
+    #[test]
+    fn fd_message_sent_without_flush() {
+        let (sent_fd, _other_end) = ::std::os::unix::net::UnixStream::pair().unwrap();
+        let msg = Message {
+            sender_id: 42,
+            opcode: 7,
+            args: smallvec![Argument::Fd(sent_fd.as_raw_fd())],
+        };
+
+        let (client, server) = ::std::os::unix::net::UnixStream::pair().unwrap();
+        let mut client = BufferedSocket::new(Socket::from(client));
+        let mut server = BufferedSocket::new(Socket::from(server));
+
+        // the buffer is empty, so the message is sent right away
+        client.write_message(&msg).unwrap();
+        assert!(client.out_data.get_contents().is_empty());
+        assert!(client.out_fds.is_empty());
+
+        static SIGNATURE: &[ArgumentType] = &[ArgumentType::Fd];
+
+        server.fill_incoming_buffers().unwrap();
+        let ret_msg = server.read_one_message(|_, _| Some(SIGNATURE)).unwrap();
+        assert_eq_msgs(&msg, &ret_msg.map_fd(IntoRawFd::into_raw_fd));
+    }
+
+    #[test]
+    fn buffered_fd_outlives_caller() {
+        let (sent_fd, _other_end) = ::std::os::unix::net::UnixStream::pair().unwrap();
+        let messages = [
+            Message { sender_id: 42, opcode: 0, args: smallvec![Argument::Uint(3)] },
+            Message {
+                sender_id: 42,
+                opcode: 1,
+                args: smallvec![Argument::Fd(sent_fd.as_raw_fd())],
+            },
+        ];
+
+        let (client, server) = ::std::os::unix::net::UnixStream::pair().unwrap();
+        let mut client = BufferedSocket::new(Socket::from(client));
+        let mut server = BufferedSocket::new(Socket::from(server));
+
+        // the buffer is not empty, so the second message is buffered with a copy of its fd
+        for msg in &messages {
+            client.write_message(msg).unwrap();
+        }
+        assert_eq!(client.out_fds.len(), 1);
+        let sent_inode = rustix::fs::fstat(&sent_fd).unwrap().st_ino;
+        ::std::mem::drop(sent_fd);
+        client.flush().unwrap();
+
+        static SIGNATURES: &[&[ArgumentType]] = &[&[ArgumentType::Uint], &[ArgumentType::Fd]];
+
+        server.fill_incoming_buffers().unwrap();
+        server.read_one_message(|_, opcode| Some(SIGNATURES[opcode as usize])).unwrap();
+        let ret_msg =
+            server.read_one_message(|_, opcode| Some(SIGNATURES[opcode as usize])).unwrap();
+        match ret_msg.args[0] {
+            Argument::Fd(ref fd) => assert_eq!(rustix::fs::fstat(fd).unwrap().st_ino, sent_inode),
+            ref arg => panic!("Unexpected argument: {:?}", arg),
+        }
+    }
+
+    #[test]
+    fn overflow_while_blocked() {
+        let (client, server) = ::std::os::unix::net::UnixStream::pair().unwrap();
+        let mut client = BufferedSocket::new(Socket::from(client));
+        let mut server = BufferedSocket::new(Socket::from(server));
+        client.enable_overflow();
+
+        // write more than the socket can hold while the other end does not read
+        let mut count = 0;
+        loop {
+            let msg = Message { sender_id: 42, opcode: 0, args: smallvec![Argument::Uint(count)] };
+            client.write_message(&msg).unwrap();
+            count += 1;
+            if matches!(client.flush(), Err(e) if e.kind() == ErrorKind::WouldBlock) {
+                break;
+            }
+        }
+        for _ in 0..1000 {
+            let msg = Message { sender_id: 42, opcode: 0, args: smallvec![Argument::Uint(count)] };
+            client.write_message(&msg).unwrap();
+            count += 1;
+        }
+        let overflow = client.overflow.as_ref().unwrap();
+        assert!(!overflow.messages.is_empty());
+        // the queued messages are stored with their exact size
+        assert!(overflow.messages.iter().all(|pending| pending.bytes.capacity() == 12));
+        assert_eq!(overflow.size, 12 * overflow.messages.len());
+
+        static SIGNATURE: &[ArgumentType] = &[ArgumentType::Uint];
+
+        // all messages are received in order as the flushes resume
+        let mut received = 0;
+        while received < count {
+            let _ = client.flush();
+            server.fill_incoming_buffers().unwrap();
+            while let Ok(ret_msg) = server.read_one_message(|_, _| Some(SIGNATURE)) {
+                assert_eq!(ret_msg.args[0], Argument::Uint(received));
+                received += 1;
+            }
+        }
+        assert!(client.overflow.as_ref().unwrap().messages.is_empty());
+        assert_eq!(client.overflow.as_ref().unwrap().size, 0);
+    }
+
+    #[test]
+    fn overflow_is_bounded() {
+        let (client, _server) = ::std::os::unix::net::UnixStream::pair().unwrap();
+        let mut client = BufferedSocket::new(Socket::from(client));
+        client.enable_overflow();
+
+        // the other end never reads, so the messages accumulate until the queue is full
+        let msg = Message { sender_id: 42, opcode: 0, args: smallvec![Argument::Uint(0)] };
+        let err = loop {
+            if let Err(e) = client.write_message(&msg) {
+                break e;
+            }
+        };
+        assert_eq!(err.raw_os_error(), Some(rustix::io::Errno::NOBUFS.raw_os_error()));
+        let overflow = client.overflow.as_ref().unwrap();
+        assert!(overflow.size <= MAX_OVERFLOW_BYTES);
+        assert!(overflow.size + 12 > MAX_OVERFLOW_BYTES);
+    }
+
     #[test]
     fn write_read_cycle_multiple() {
         let messages = vec![
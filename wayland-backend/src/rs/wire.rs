@@ -2,7 +2,7 @@
 
 use std::collections::VecDeque;
 use std::ffi::CStr;
-use std::os::unix::io::{BorrowedFd, OwnedFd, RawFd};
+use std::os::unix::io::{OwnedFd, RawFd};
 
 use crate::protocol::{Argument, ArgumentType, Message};
 
@@ -13,8 +13,6 @@ use smallvec::SmallVec;
 pub enum MessageWriteError {
     /// The buffer is too small to hold the message contents
     BufferTooSmall,
-    /// The message contains a FD that could not be dup-ed
-    DupFdFailed(std::io::Error),
 }
 
 impl std::error::Error for MessageWriteError {}
@@ -26,13 +24,6 @@ impl std::fmt::Display for MessageWriteError {
             Self::BufferTooSmall => {
                 f.write_str("The provided buffer is too small to hold message content.")
             }
-            Self::DupFdFailed(e) => {
-                write!(
-                    f,
-                    "The message contains a file descriptor that could not be dup()-ed ({}).",
-                    e
-                )
-            }
         }
     }
 }
@@ -67,11 +58,12 @@ impl std::fmt::Display for MessageParseError {
 ///
 /// Returns the number of elements written in each buffer
 ///
-/// Any serialized Fd will be `dup()`-ed in the process
+/// The Fds of the message are pushed to `fds` as-is, it is up to the caller to ensure they remain valid
+/// until they are sent.
 pub fn write_to_buffers(
     msg: &Message<u32, RawFd>,
     payload: &mut [u8],
-    fds: &mut Vec<OwnedFd>,
+    fds: &mut Vec<RawFd>,
 ) -> Result<usize, MessageWriteError> {
     let orig_payload_len = payload.len();
     // Helper function to write a u32 or a RawFd to its buffer
@@ -124,10 +116,7 @@ pub fn write_to_buffers(
             Argument::NewId(n) => write_buf(n, payload)?,
             Argument::Array(ref a) => write_array_to_payload(a, payload)?,
             Argument::Fd(fd) => {
-                let dup_fd = unsafe { BorrowedFd::borrow_raw(fd) }
-                    .try_clone_to_owned()
-                    .map_err(MessageWriteError::DupFdFailed)?;
-                fds.push(dup_fd);
+                fds.push(fd);
                 payload
             }
         };
@@ -275,8 +264,9 @@ mod tests {
         };
         // write the message to the buffers
         write_to_buffers(&msg, &mut bytes_buffer[..], &mut fd_buffer).unwrap();
+        assert!(fd_buffer.is_empty());
         // read them back
-        let mut fd_buffer = VecDeque::from(fd_buffer);
+        let mut fd_buffer = VecDeque::new();
         let (rebuilt, _) = parse_message(
             &bytes_buffer[..],
             &[
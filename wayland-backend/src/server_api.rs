@@ -6,13 +6,60 @@ use std::{
         net::UnixStream,
     },
     sync::Arc,
+    time::Duration,
 };
 
 use crate::protocol::{Interface, Message, ObjectInfo};
-pub use crate::types::server::{Credentials, DisconnectReason, GlobalInfo, InitError, InvalidId};
+pub use crate::types::server::{
+    ClientControlError, Credentials, DisconnectReason, GlobalError, GlobalInfo, InitError,
+    InvalidId, SendEventError,
+};
 
 use super::server_impl;
 
+/// Validator for the file descriptors received from clients, see [`Backend::set_fd_validator()`]
+#[derive(Clone)]
+#[allow(clippy::type_complexity)]
+pub(crate) struct FdValidator(
+    Arc<dyn Fn(&'static Interface, u16, BorrowedFd<'_>) -> bool + Send + Sync>,
+);
+
+impl FdValidator {
+    pub(crate) fn validate(
+        &self,
+        interface: &'static Interface,
+        opcode: u16,
+        fd: BorrowedFd,
+    ) -> bool {
+        (self.0)(interface, opcode, fd)
+    }
+}
+
+impl fmt::Debug for FdValidator {
+    #[cfg_attr(coverage, coverage(off))]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("FdValidator")
+    }
+}
+
+/// Interceptor of the versions of the globals bound by clients, see [`Backend::set_bind_interceptor()`]
+#[derive(Clone)]
+#[allow(clippy::type_complexity)]
+pub(crate) struct BindInterceptor(Arc<dyn Fn(ClientId, GlobalId, u32) -> u32 + Send + Sync>);
+
+impl BindInterceptor {
+    pub(crate) fn intercept(&self, client: ClientId, global: GlobalId, version: u32) -> u32 {
+        (self.0)(client, global, version).clamp(1, version)
+    }
+}
+
+impl fmt::Debug for BindInterceptor {
+    #[cfg_attr(coverage, coverage(off))]
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("BindInterceptor")
+    }
+}
+
 /// A trait representing your data associated to an object
 ///
 /// You will only be given access to it as a `&` reference, so you
@@ -166,6 +213,15 @@ impl ObjectId {
         self.id.interface()
     }
 
+    /// Check if this object has the given interface.
+    ///
+    /// The interfaces are compared with [`same_interface()`][crate::protocol::same_interface()]. With
+    /// `wayland-server`, `Resource::is_interface_of()` does this check for the interface of a resource type.
+    #[inline]
+    pub fn has_interface(&self, interface: &'static Interface) -> bool {
+        crate::protocol::same_interface(self.interface(), interface)
+    }
+
     /// Check if two object IDs are associated with the same client
     ///
     /// *Note:* This may spuriously return `false` if one (or both) of the objects to compare
@@ -174,6 +230,17 @@ impl ObjectId {
         self.id.same_client_as(&other.id)
     }
 
+    /// Returns the id of the client owning this object
+    ///
+    /// Unlike [`Handle::get_client()`], this does not lock the state of the backend, which makes it
+    /// suitable for hot paths. It returns [`None`] for the null object, and may return [`None`] if the
+    /// object is no longer valid. It does not check that the client is still connected: comparing
+    /// the returned id with a live [`ClientId`] is always correct, but operations on it may fail.
+    #[inline]
+    pub fn client_id(&self) -> Option<ClientId> {
+        self.id.client_id().map(|id| ClientId { id })
+    }
+
     /// Return the protocol-level numerical ID of this object
     ///
     /// Protocol IDs are reused after object destruction and each client has its own ID space, so this should
@@ -255,6 +322,16 @@ impl WeakHandle {
     pub fn upgrade(&self) -> Option<Handle> {
         self.handle.upgrade().map(|handle| Handle { handle })
     }
+
+    /// Check whether an object is still alive, without upgrading this handle
+    ///
+    /// This returns `false` if the object was destroyed or if the associated backend was
+    /// already dropped. It is cheaper than upgrading the handle and calling
+    /// [`Handle::object_info()`], and never locks the backend with the system library.
+    #[inline]
+    pub fn object_is_alive(&self, id: &ObjectId) -> bool {
+        self.handle.object_is_alive(&id.id)
+    }
 }
 
 impl Handle {
@@ -290,6 +367,16 @@ impl Handle {
         self.handle.get_client(id.id)
     }
 
+    /// Returns the id of the client which owns the object, without checking that it is still connected
+    ///
+    /// This is a thin wrapper around [`ObjectId::client_id()`], and is cheaper than
+    /// [`get_client()`][Self::get_client()] as it does not need to lock the state of the backend. This
+    /// makes it a good fit for hot paths like per-request authorization.
+    #[inline]
+    pub fn owning_client(&self, id: &ObjectId) -> Option<ClientId> {
+        id.client_id()
+    }
+
     /// Returns the data associated with a client.
     #[inline]
     pub fn get_client_data(&self, id: ClientId) -> Result<Arc<dyn ClientData>, InvalidId> {
@@ -302,6 +389,18 @@ impl Handle {
         self.handle.get_client_credentials(id.id)
     }
 
+    /// Retrieve the security context of a client
+    ///
+    /// This is the label given to the client process by the Linux Security Module in use (like SELinux or
+    /// AppArmor), as reported by the `SO_PEERSEC` option of its socket. It is `None` if no such module is
+    /// active. An error of kind [`NotFound`][std::io::ErrorKind::NotFound] is returned if the client is no
+    /// longer valid.
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    #[inline]
+    pub fn get_client_security_context(&self, id: ClientId) -> std::io::Result<Option<Vec<u8>>> {
+        self.handle.get_client_security_context(id.id)
+    }
+
     /// Invokes a closure for all clients connected to this server
     ///
     /// Note that while this method is running, an internal lock of the backend is held,
@@ -361,17 +460,17 @@ impl Handle {
 
     /// Send an event to the client
     ///
-    /// Returns an error if the sender ID of the provided message is no longer valid.
-    ///
-    /// # Panics
+    /// Checks against the protocol specification are done, and this method returns an error without
+    /// sending anything if they do not pass:
     ///
-    /// Checks against the protocol specification are done, and this method will panic if they do
-    /// not pass:
-    ///
-    /// - the message opcode must be valid for the sender interface
+    /// - the sender ID and all object arguments must be valid ([`SendEventError::InvalidId`])
+    /// - the message opcode must be valid for the sender interface ([`SendEventError::UnknownOpcode`])
+    /// - all object arguments must belong to the same client as the sender
+    ///   ([`SendEventError::WrongClient`])
     /// - the argument list must match the prototype for the message associated with this opcode
+    ///   ([`SendEventError::InvalidArguments`])
     #[inline]
-    pub fn send_event(&self, msg: Message<ObjectId, RawFd>) -> Result<(), InvalidId> {
+    pub fn send_event(&self, msg: Message<ObjectId, RawFd>) -> Result<(), SendEventError> {
         self.handle.send_event(msg)
     }
 
@@ -423,12 +522,59 @@ impl Handle {
         self.handle.kill_client(client_id.id, reason)
     }
 
+    /// Pauses or resumes the dispatching of the requests of a client.
+    ///
+    /// The requests of a paused client are not read from its socket by
+    /// [`Backend::dispatch_all_clients()`] or [`Backend::dispatch_client()`], and stay buffered until the
+    /// client is resumed. Events can still be sent to it. Requests that were already read before the client
+    /// was paused are dispatched by the next call to [`Backend::dispatch_all_clients()`] after it is resumed.
+    ///
+    /// Returns [`ClientControlError::InvalidId`] if the client is no longer alive.
+    ///
+    /// This is only supported by the Rust backend, as `libwayland-server` has no way to stop reading from a
+    /// client. With the system backend, this returns [`ClientControlError::Unsupported`].
+    #[inline]
+    pub fn set_client_paused(
+        &self,
+        client_id: ClientId,
+        paused: bool,
+    ) -> Result<(), ClientControlError> {
+        self.handle.set_client_paused(client_id.id, paused)
+    }
+
+    /// Makes the IDs of the objects created by the server for a client predictable.
+    ///
+    /// The next objects created by the server for this client get the IDs `base`, `base + 1`, and so on,
+    /// skipping the ones still in use, and the IDs of destroyed objects are no longer reused. This makes
+    /// the messages sent to the client reproducible, for tests comparing them to a reference.
+    ///
+    /// The IDs created by the server start at `0xff000000`, which is thus the smallest valid `base`. The
+    /// IDs below `base` are reserved in the object map of the client, so it should not be much higher.
+    ///
+    /// Requires the `test-util` cargo feature.
+    ///
+    /// # Errors
+    ///
+    /// - [`ClientControlError::InvalidId`] if the client is no longer alive.
+    /// - [`ClientControlError::InvalidServerIdBase`] if `base` is lower than `0xff000000`.
+    /// - [`ClientControlError::Unsupported`] with the system backend, as `libwayland-server` allocates
+    ///   the IDs itself.
+    #[cfg(feature = "test-util")]
+    pub fn set_deterministic_server_ids(
+        &self,
+        client_id: ClientId,
+        base: u32,
+    ) -> Result<(), ClientControlError> {
+        self.handle.set_deterministic_server_ids(client_id.id, base)
+    }
+
     /// Creates a global of the specified interface and version and then advertises it to clients.
     ///
     /// The clients which the global is advertised to is determined by the implementation of the [`GlobalHandler`].
     ///
     /// **Panic:** This method will panic if the type parameter `D` is not same to the same type as the
-    /// one the backend was initialized with.
+    /// one the backend was initialized with, or if the global cannot be created. See
+    /// [`try_create_global()`][Self::try_create_global()] for a non-panicking version.
     #[inline]
     pub fn create_global<D: 'static>(
         &self,
@@ -439,6 +585,39 @@ impl Handle {
         GlobalId { id: self.handle.create_global(interface, version, handler) }
     }
 
+    /// Creates a global of the specified interface and version and then advertises it to clients.
+    ///
+    /// This behaves like [`create_global()`][Self::create_global()], but returns an error if the version
+    /// is not supported by the interface, or if the system library fails to create the global.
+    ///
+    /// **Panic:** This method will panic if the type parameter `D` is not same to the same type as the
+    /// one the backend was initialized with.
+    #[inline]
+    pub fn try_create_global<D: 'static>(
+        &self,
+        interface: &'static Interface,
+        version: u32,
+        handler: Arc<dyn GlobalHandler<D>>,
+    ) -> Result<GlobalId, GlobalError> {
+        self.handle.try_create_global(interface, version, handler).map(|id| GlobalId { id })
+    }
+
+    /// Creates several globals at once and then advertises them to clients.
+    ///
+    /// This behaves like calling [`create_global()`][Self::create_global()] for each item of the iterator,
+    /// but with the Rust backend the internal state is only locked once. The ids of the globals are returned
+    /// in the same order as the iterator.
+    ///
+    /// **Panic:** This method will panic if the type parameter `D` is not same to the same type as the
+    /// one the backend was initialized with.
+    #[inline]
+    pub fn create_globals<D: 'static>(
+        &self,
+        globals: impl IntoIterator<Item = (&'static Interface, u32, Arc<dyn GlobalHandler<D>>)>,
+    ) -> Vec<GlobalId> {
+        self.handle.create_globals(globals).into_iter().map(|id| GlobalId { id }).collect()
+    }
+
     /// Disables a global object that is currently active.
     ///
     /// The global removal will be signaled to all currently connected clients. New clients will not know of
@@ -475,6 +654,28 @@ impl Handle {
         self.handle.remove_global::<D>(id.id)
     }
 
+    /// Disables a global object and schedules its removal after the given delay.
+    ///
+    /// This combines [`disable_global()`][Self::disable_global()] and
+    /// [`remove_global()`][Self::remove_global()]: the global removal is signaled to clients immediately,
+    /// and its state is freed once `delay` has elapsed, giving clients time to notice the removal before
+    /// binding it becomes a protocol error.
+    ///
+    /// The actual removal is performed during [`Backend::dispatch_all_clients()`] or
+    /// [`Backend::dispatch_client()`] once the delay has expired. With the system backend, the poll fd of
+    /// the backend is woken up when the delay expires. The Rust backend does not have such a timer, and the
+    /// removal will happen on the next dispatch after the delay.
+    ///
+    /// Retiring an already removed global does nothing, and retiring a global that is already scheduled for
+    /// removal replaces its deadline.
+    ///
+    /// **Panic:** This method will panic if the type parameter `D` is not same to the same type as the
+    /// one the backend was initialized with.
+    #[inline]
+    pub fn retire_global<D: 'static>(&self, id: GlobalId, delay: Duration) {
+        self.handle.retire_global::<D>(id.id, delay)
+    }
+
     /// Returns information about a global.
     #[inline]
     pub fn global_info(&self, id: GlobalId) -> Result<GlobalInfo, InvalidId> {
@@ -498,6 +699,18 @@ impl Handle {
     }
 }
 
+/// The result of flushing the outgoing buffers of all clients
+///
+/// See [`Backend::flush_all()`].
+#[derive(Debug, Default, Clone)]
+pub struct FlushOutcome {
+    /// Clients whose socket was full, and whose buffer could not be entirely flushed
+    ///
+    /// The remaining data will be sent on the next flush, which should be attempted once the
+    /// socket of these clients becomes writable again.
+    pub would_block: Vec<ClientId>,
+}
+
 /// A backend object that represents the state of a wayland server.
 ///
 /// A backend is used to drive a wayland server by receiving requests, dispatching messages to the appropriate
@@ -522,6 +735,75 @@ impl<D> Backend<D> {
         self.backend.flush(client)
     }
 
+    /// Flushes pending events to all clients, reporting which ones would block.
+    ///
+    /// This is similar to [`flush(None)`][Self::flush()], but rather than ignoring the clients whose
+    /// socket is full, it lists them in the returned [`FlushOutcome`], so that you can retry flushing
+    /// once their socket becomes writable.
+    ///
+    /// **Note:** With the system backend, libwayland tracks the writability of client sockets itself and
+    /// retries the flush when the [`poll_fd()`][Self::poll_fd()] is dispatched, so the returned list is
+    /// always empty.
+    #[inline]
+    pub fn flush_all(&mut self) -> std::io::Result<FlushOutcome> {
+        Ok(FlushOutcome { would_block: self.backend.flush_all()? })
+    }
+
+    /// Set a validator for the file descriptors received from clients
+    ///
+    /// Before a request is dispatched, the validator is invoked with the interface of the object, the
+    /// opcode of the request and each of its file descriptor arguments. If it returns `false` for any of
+    /// them, the file descriptors of the request are closed, the request is not dispatched and the client
+    /// is disconnected with a `wl_display.invalid_method` protocol error.
+    ///
+    /// This can for example be used to check that the file descriptor of `wl_shm.create_pool` refers to a
+    /// regular file or a memfd.
+    ///
+    /// Received file descriptors always have the `CLOEXEC` flag set, regardless of the validator.
+    pub fn set_fd_validator<F>(&mut self, validator: F)
+    where
+        F: Fn(&'static Interface, u16, BorrowedFd<'_>) -> bool + Send + Sync + 'static,
+    {
+        self.backend.set_fd_validator(FdValidator(Arc::new(validator)))
+    }
+
+    /// Set an interceptor for the versions of the globals bound by clients
+    ///
+    /// When a client binds a global, the interceptor is invoked with the client, the global and the
+    /// version requested by the client, before the [`GlobalHandler::bind()`] method of the global. The
+    /// object is then created with the version it returns instead of the requested one. This can be used
+    /// to roll out the support of a new version of a protocol progressively, by clamping the version
+    /// given to some clients.
+    ///
+    /// The returned version is clamped between 1 and the requested version.
+    ///
+    /// **Warning:** The client is never told about the rewritten version: its proxy keeps the version it
+    /// requested, so the client and the server disagree on which requests and events are valid for this
+    /// object. The client may then send requests the server rejects with a protocol error, or wait for
+    /// events that are never sent.
+    ///
+    /// The interceptor must not call into the backend: with the Rust backend it is invoked while the
+    /// internal state of the backend is locked, which would deadlock.
+    pub fn set_bind_interceptor<F>(&mut self, interceptor: F)
+    where
+        F: Fn(ClientId, GlobalId, u32) -> u32 + Send + Sync + 'static,
+    {
+        self.backend.set_bind_interceptor(BindInterceptor(Arc::new(interceptor)))
+    }
+
+    /// Set whether the backend forces a wakeup to run the pending destructors
+    ///
+    /// When objects are destroyed while flushing the clients, their [`ObjectData::destroyed()`] method can
+    /// only be invoked by the next dispatch. By default, the system backend then arms a 1ms timer on its
+    /// [`poll_fd()`][Self::poll_fd()] to make sure this dispatch happens soon. Compositors that already
+    /// dispatch the clients regularly, for example after each flush, can disable it to avoid these
+    /// wakeups.
+    ///
+    /// **Note:** The rust backend never arms such a timer, so this has no effect with it.
+    pub fn set_destructor_wakeup(&mut self, enabled: bool) {
+        self.backend.set_destructor_wakeup(enabled)
+    }
+
     /// Returns a handle which represents the server side state of the backend.
     ///
     /// The handle provides a variety of functionality, such as querying information about wayland objects,
@@ -19,7 +19,7 @@ use crate::{
     debug,
     debug::has_debug_client_env,
     protocol::{
-        check_for_signature, same_interface, AllowNull, Argument, ArgumentType, Interface, Message,
+        check_signature, same_interface, AllowNull, Argument, ArgumentType, Interface, Message,
         ObjectInfo, ProtocolError, ANONYMOUS_INTERFACE,
     },
 };
@@ -173,6 +173,7 @@ struct ConnectionState {
     display_id: InnerObjectId,
     last_error: Option<WaylandError>,
     known_proxies: HashSet<*mut wl_proxy>,
+    object_hooks: ObjectHooks,
 }
 
 unsafe impl Send for ConnectionState {}
@@ -215,6 +216,13 @@ impl WeakInnerBackend {
     pub fn upgrade(&self) -> Option<InnerBackend> {
         Weak::upgrade(&self.inner).map(|inner| InnerBackend { inner })
     }
+
+    pub fn object_is_alive(&self, id: &InnerObjectId) -> bool {
+        // no need to access the backend, the id carries its liveness flag
+        self.inner.strong_count() > 0
+            && !id.ptr.is_null()
+            && id.alive.as_ref().map(|a| a.load(Ordering::Acquire)).unwrap_or(true)
+    }
 }
 
 impl PartialEq for InnerBackend {
@@ -273,6 +281,7 @@ impl InnerBackend {
                     owns_display: owned,
                     last_error: None,
                     known_proxies: HashSet::new(),
+                    object_hooks: ObjectHooks::default(),
                 }),
                 debug: has_debug_client_env(),
                 dispatch_lock: Mutex::new(Dispatcher),
@@ -306,6 +315,19 @@ impl InnerBackend {
     pub fn dispatch_inner_queue(&self) -> Result<usize, WaylandError> {
         self.inner.dispatch_lock.lock().unwrap().dispatch_pending(self.inner.clone())
     }
+
+    pub fn set_unknown_object_policy(&self, _policy: UnknownObjectPolicy) {
+        // libwayland-client always discards these events
+    }
+
+    pub fn set_wire_trace(&self, _trace: Option<WireTrace>) {
+        // libwayland-client does not give access to the raw messages
+    }
+
+    #[cfg(feature = "object_hooks")]
+    pub(crate) fn set_object_hooks(&self, f: impl FnOnce(&mut ObjectHooks)) {
+        f(&mut self.lock_state().object_hooks);
+    }
 }
 
 impl ConnectionState {
@@ -464,6 +486,11 @@ impl InnerBackend {
         self.lock_state().last_error.clone()
     }
 
+    pub fn protocol_error_interface(&self) -> Option<&'static Interface> {
+        // libwayland only gives us the name of the interface
+        None
+    }
+
     pub fn info(&self, ObjectId { id }: ObjectId) -> Result<ObjectInfo, InvalidId> {
         if !id.alive.as_ref().map(|a| a.load(Ordering::Acquire)).unwrap_or(true) || id.ptr.is_null()
         {
@@ -520,7 +547,7 @@ impl InnerBackend {
             unsafe { ffi_dispatch!(wayland_client_handle(), wl_proxy_get_version, id.ptr) }
         };
 
-        if !check_for_signature(message_desc.signature, &args) {
+        if !check_signature(message_desc.signature, &args) {
             panic!(
                 "Unexpected signature for request {}@{}.{}: expected {:?}, got {:?}.",
                 id.interface.name, id.id, message_desc.name, message_desc.signature, args
@@ -702,7 +729,9 @@ impl InnerBackend {
                     );
                 }
                 alive.store(false, Ordering::Release);
-                udata.data.destroyed(ObjectId { id: id.clone() });
+                let id = ObjectId { id: id.clone() };
+                udata.data.destroyed(id.clone());
+                guard.object_hooks.notify_destroyed(&id);
             }
 
             guard.known_proxies.remove(&id.ptr);
@@ -716,15 +745,19 @@ impl InnerBackend {
     }
 
     pub fn get_data(&self, ObjectId { id }: ObjectId) -> Result<Arc<dyn ObjectData>, InvalidId> {
-        if !id.alive.as_ref().map(|a| a.load(Ordering::Acquire)).unwrap_or(false) {
-            return Err(InvalidId);
-        }
-
         if id.id == 1 {
-            // special case the display whose object data is not accessible
+            // special case the display whose object data is not accessible, it has no alive flag if it
+            // is a foreign display
+            if !id.alive.as_ref().map(|a| a.load(Ordering::Acquire)).unwrap_or(true) {
+                return Err(InvalidId);
+            }
             return Ok(Arc::new(DumbObjectData));
         }
 
+        if !id.alive.as_ref().map(|a| a.load(Ordering::Acquire)).unwrap_or(false) {
+            return Err(InvalidId);
+        }
+
         let udata = unsafe {
             &*(ffi_dispatch!(wayland_client_handle(), wl_proxy_get_user_data, id.ptr)
                 as *mut ProxyUserData)
@@ -809,6 +842,8 @@ impl InnerBackend {
             );
         }
 
+        guard.object_hooks.notify_created(&object_id);
+
         object_id
     }
 }
@@ -981,19 +1016,21 @@ unsafe extern "C" fn dispatcher_func(
         },
     };
 
-    let ret = BACKEND.with(|backend| {
+    let (ret, object_hooks) = BACKEND.with(|backend| {
         let mut guard = backend.backend.lock_state();
         if let Some((ref new_id, _)) = created {
             guard.known_proxies.insert(new_id.ptr);
+            guard.object_hooks.notify_created(&ObjectId { id: new_id.clone() });
         }
         if message_desc.is_destructor {
             guard.known_proxies.remove(&proxy);
         }
+        let object_hooks = guard.object_hooks.clone();
         std::mem::drop(guard);
-        udata.data.clone().event(
-            backend,
-            Message { sender_id: id.clone(), opcode: opcode as u16, args: parsed_args },
-        )
+        let msg = Message { sender_id: id.clone(), opcode: opcode as u16, args: parsed_args };
+        object_hooks.notify_event(&msg);
+        let ret = udata.data.clone().event(backend, msg);
+        (ret, object_hooks)
     });
 
     if message_desc.is_destructor {
@@ -1001,7 +1038,8 @@ unsafe extern "C" fn dispatcher_func(
         let udata = unsafe { Box::from_raw(udata_ptr) };
         ffi_dispatch!(wayland_client_handle(), wl_proxy_set_user_data, proxy, std::ptr::null_mut());
         udata.alive.store(false, Ordering::Release);
-        udata.data.destroyed(id);
+        udata.data.destroyed(id.clone());
+        object_hooks.notify_destroyed(&id);
         ffi_dispatch!(wayland_client_handle(), wl_proxy_destroy, proxy);
     }
 
@@ -11,10 +11,11 @@ use std::{
         atomic::{AtomicBool, Ordering},
         Arc, Mutex, Weak,
     },
+    time::{Duration, Instant},
 };
 
 use crate::protocol::{
-    check_for_signature, same_interface, AllowNull, Argument, ArgumentType, Interface, Message,
+    check_signature, same_interface, AllowNull, Argument, ArgumentType, Interface, Message,
     ObjectInfo, ANONYMOUS_INTERFACE,
 };
 use scoped_tls::scoped_thread_local;
@@ -25,7 +26,10 @@ use wayland_sys::{common::*, ffi_dispatch, server::*};
 use super::{free_arrays, server::*, RUST_MANAGED};
 
 #[allow(unused_imports)]
-pub use crate::types::server::{Credentials, DisconnectReason, GlobalInfo, InitError, InvalidId};
+pub use crate::types::server::{
+    ClientControlError, Credentials, DisconnectReason, GlobalError, GlobalInfo, InitError,
+    InvalidId,
+};
 
 scoped_thread_local! {
     // scoped_tls does not allow unsafe_op_in_unsafe_fn internally
@@ -104,6 +108,18 @@ impl InnerObjectId {
         my_client_ptr == other_client_ptr
     }
 
+    pub fn client_id(&self) -> Option<InnerClientId> {
+        if !self.alive.load(Ordering::Acquire) {
+            return None;
+        }
+        // Safety: the resource is alive, so it belongs to a valid and initialized wl_client
+        unsafe {
+            let client_ptr =
+                ffi_dispatch!(wayland_server_handle(), wl_resource_get_client, self.ptr);
+            client_id_from_ptr(client_ptr)
+        }
+    }
+
     pub fn protocol_id(&self) -> u32 {
         self.id
     }
@@ -299,8 +315,39 @@ pub struct State<D: 'static> {
     display: *mut wl_display,
     pending_destructors: Vec<PendingDestructor<D>>,
     timer_source: *mut wl_event_source,
+    destructor_wakeup: bool,
     _data: std::marker::PhantomData<fn(&mut D)>,
     known_globals: Vec<InnerGlobalId>,
+    retired_globals: Vec<(Instant, InnerGlobalId)>,
+    fd_validator: Option<FdValidator>,
+    bind_interceptor: Option<BindInterceptor>,
+}
+
+impl<D> State<D> {
+    /// Arm the timer so that the inner event loop wakes up when the next pending
+    /// destructor or retired global needs processing
+    fn arm_timer(&self) {
+        let now = Instant::now();
+        let delay = if self.destructor_wakeup && !self.pending_destructors.is_empty() {
+            Some(1)
+        } else {
+            self.retired_globals.iter().map(|&(deadline, _)| deadline).min().map(|deadline| {
+                // round up, and never pass 0 as it would disarm the timer
+                let remaining = deadline.saturating_duration_since(now);
+                ((remaining.as_nanos() + 999_999) / 1_000_000).clamp(1, i32::MAX as u128) as i32
+            })
+        };
+        if let Some(delay) = delay {
+            unsafe {
+                ffi_dispatch!(
+                    wayland_server_handle(),
+                    wl_event_source_timer_update,
+                    self.timer_source,
+                    delay
+                )
+            };
+        }
+    }
 }
 
 unsafe impl<D> Send for State<D> {}
@@ -366,8 +413,12 @@ impl<D> InnerBackend<D> {
                 display,
                 pending_destructors: Vec::new(),
                 timer_source,
+                destructor_wakeup: true,
                 _data: std::marker::PhantomData,
                 known_globals: Vec::new(),
+                retired_globals: Vec::new(),
+                fd_validator: None,
+                bind_interceptor: None,
             })),
             display_ptr: display,
         })
@@ -377,10 +428,28 @@ impl<D> InnerBackend<D> {
         self.state.lock().unwrap().flush(client)
     }
 
+    pub fn flush_all(&mut self) -> std::io::Result<Vec<ClientId>> {
+        // libwayland handles EAGAIN on client sockets internally, by waiting for them to be writable
+        self.state.lock().unwrap().flush(None)?;
+        Ok(Vec::new())
+    }
+
     pub fn handle(&self) -> Handle {
         Handle { handle: InnerHandle { state: self.state.clone() as Arc<_> } }
     }
 
+    pub fn set_fd_validator(&mut self, validator: FdValidator) {
+        self.state.lock().unwrap().fd_validator = Some(validator);
+    }
+
+    pub fn set_bind_interceptor(&mut self, interceptor: BindInterceptor) {
+        self.state.lock().unwrap().bind_interceptor = Some(interceptor);
+    }
+
+    pub fn set_destructor_wakeup(&mut self, enabled: bool) {
+        self.state.lock().unwrap().destructor_wakeup = enabled;
+    }
+
     pub fn poll_fd(&self) -> BorrowedFd {
         unsafe {
             let evl_ptr =
@@ -402,6 +471,8 @@ impl<D> InnerBackend<D> {
     }
 
     pub fn dispatch_all_clients(&mut self, data: &mut D) -> std::io::Result<usize> {
+        self.remove_expired_globals();
+
         let state = self.state.clone() as Arc<Mutex<dyn ErasedState + Send>>;
         let display = self.display_ptr;
         let ret = HANDLE.set(&(state, data as *mut _ as *mut c_void), || unsafe {
@@ -423,6 +494,23 @@ impl<D> InnerBackend<D> {
             Ok(ret as usize)
         }
     }
+
+    fn remove_expired_globals(&mut self) {
+        let now = Instant::now();
+        let expired: Vec<_> = {
+            let mut state = self.state.lock().unwrap();
+            let (expired, pending) = std::mem::take(&mut state.retired_globals)
+                .into_iter()
+                .partition(|&(deadline, _)| deadline <= now);
+            state.retired_globals = pending;
+            expired
+        };
+        let handle = self.handle();
+        for (_, id) in expired {
+            handle.handle.remove_global::<D>(id);
+        }
+        self.state.lock().unwrap().arm_timer();
+    }
 }
 
 impl<D> Drop for State<D> {
@@ -478,6 +566,11 @@ impl WeakInnerHandle {
     pub fn upgrade(&self) -> Option<InnerHandle> {
         self.state.upgrade().map(|state| InnerHandle { state })
     }
+
+    pub fn object_is_alive(&self, id: &InnerObjectId) -> bool {
+        // no need to access the backend state, the id carries its liveness flag
+        self.state.strong_count() > 0 && id.alive.load(Ordering::Acquire)
+    }
 }
 
 impl InnerHandle {
@@ -509,6 +602,14 @@ impl InnerHandle {
         self.state.lock().unwrap().get_client_credentials(id)
     }
 
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    pub fn get_client_security_context(
+        &self,
+        id: InnerClientId,
+    ) -> std::io::Result<Option<Vec<u8>>> {
+        self.state.lock().unwrap().get_client_security_context(id)
+    }
+
     pub fn with_all_clients(&self, mut f: impl FnMut(ClientId)) {
         self.state.lock().unwrap().with_all_clients(&mut f)
     }
@@ -575,7 +676,7 @@ impl InnerHandle {
         }
     }
 
-    pub fn send_event(&self, msg: Message<ObjectId, RawFd>) -> Result<(), InvalidId> {
+    pub fn send_event(&self, msg: Message<ObjectId, RawFd>) -> Result<(), SendEventError> {
         self.state.lock().unwrap().send_event(msg)
     }
 
@@ -671,12 +772,51 @@ impl InnerHandle {
         self.state.lock().unwrap().kill_client(client_id, reason)
     }
 
+    pub fn set_client_paused(
+        &self,
+        _client_id: InnerClientId,
+        _paused: bool,
+    ) -> Result<(), ClientControlError> {
+        Err(ClientControlError::Unsupported)
+    }
+
+    #[cfg(feature = "test-util")]
+    pub fn set_deterministic_server_ids(
+        &self,
+        _client_id: InnerClientId,
+        _base: u32,
+    ) -> Result<(), ClientControlError> {
+        Err(ClientControlError::Unsupported)
+    }
+
+    pub fn create_globals<D: 'static>(
+        &self,
+        globals: impl IntoIterator<Item = (&'static Interface, u32, Arc<dyn GlobalHandler<D>>)>,
+    ) -> Vec<InnerGlobalId> {
+        // the state cannot stay locked while libwayland creates the globals, as it may invoke the
+        // global filter
+        globals
+            .into_iter()
+            .map(|(interface, version, handler)| self.create_global(interface, version, handler))
+            .collect()
+    }
+
     pub fn create_global<D: 'static>(
         &self,
         interface: &'static Interface,
         version: u32,
         handler: Arc<dyn GlobalHandler<D>>,
     ) -> InnerGlobalId {
+        self.try_create_global(interface, version, handler)
+            .unwrap_or_else(|err| panic!("[wayland-backend-sys] {}", err))
+    }
+
+    pub fn try_create_global<D: 'static>(
+        &self,
+        interface: &'static Interface,
+        version: u32,
+        handler: Arc<dyn GlobalHandler<D>>,
+    ) -> Result<InnerGlobalId, GlobalError> {
         let display = {
             let mut state = self.state.lock().unwrap();
             let state = (&mut *state as &mut dyn ErasedState)
@@ -685,10 +825,13 @@ impl InnerHandle {
             state.display
         };
 
-        let alive = Arc::new(AtomicBool::new(true));
+        if version > interface.version {
+            return Err(GlobalError::UnsupportedVersion { interface, version });
+        }
 
-        let interface_ptr =
-            interface.c_ptr.expect("Interface without c_ptr are unsupported by the sys backend.");
+        let interface_ptr = interface.c_ptr.ok_or(GlobalError::NoCInterface(interface))?;
+
+        let alive = Arc::new(AtomicBool::new(true));
 
         let udata = Box::into_raw(Box::new(GlobalUserData {
             handler,
@@ -714,9 +857,7 @@ impl InnerHandle {
         if ret.is_null() {
             // free the user data as global creation failed
             let _ = unsafe { Box::from_raw(udata) };
-            panic!(
-                "[wayland-backend-sys] Invalid global specification or memory allocation failure."
-            );
+            return Err(GlobalError::CreationFailed);
         }
 
         unsafe {
@@ -730,7 +871,7 @@ impl InnerHandle {
 
         let id = InnerGlobalId { ptr: ret, alive };
         state.known_globals.push(id.clone());
-        id
+        Ok(id)
     }
 
     pub fn disable_global<D: 'static>(&self, id: InnerGlobalId) {
@@ -770,6 +911,7 @@ impl InnerHandle {
                 .downcast_mut::<State<D>>()
                 .expect("Wrong type parameter passed to Handle::remove_global().");
             state.known_globals.retain(|g| g != &id);
+            state.retired_globals.retain(|(_, g)| g != &id);
         }
 
         if !id.alive.load(Ordering::Acquire) {
@@ -787,6 +929,28 @@ impl InnerHandle {
         });
     }
 
+    pub fn retire_global<D: 'static>(&self, id: InnerGlobalId, delay: Duration) {
+        // check that `D` is correct
+        {
+            let mut state = self.state.lock().unwrap();
+            let _state = (&mut *state as &mut dyn ErasedState)
+                .downcast_mut::<State<D>>()
+                .expect("Wrong type parameter passed to Handle::retire_global().");
+        }
+
+        if !id.alive.load(Ordering::Acquire) {
+            return;
+        }
+
+        self.disable_global::<D>(id.clone());
+
+        let mut state = self.state.lock().unwrap();
+        let state = (&mut *state as &mut dyn ErasedState).downcast_mut::<State<D>>().unwrap();
+        state.retired_globals.retain(|(_, g)| g != &id);
+        state.retired_globals.push((Instant::now() + delay, id));
+        state.arm_timer();
+    }
+
     pub fn global_info(&self, id: InnerGlobalId) -> Result<GlobalInfo, InvalidId> {
         self.state.lock().unwrap().global_info(id)
     }
@@ -831,6 +995,8 @@ pub(crate) trait ErasedState: downcast_rs::Downcast {
     ) -> std::io::Result<InnerClientId>;
     fn get_client(&self, id: InnerObjectId) -> Result<ClientId, InvalidId>;
     fn get_client_credentials(&self, id: InnerClientId) -> Result<Credentials, InvalidId>;
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    fn get_client_security_context(&self, id: InnerClientId) -> std::io::Result<Option<Vec<u8>>>;
     fn get_client_data(&self, id: InnerClientId) -> Result<Arc<dyn ClientData>, InvalidId>;
     fn with_all_clients(&self, f: &mut dyn FnMut(ClientId));
     fn with_all_objects_for(
@@ -848,13 +1014,15 @@ pub(crate) trait ErasedState: downcast_rs::Downcast {
         &self,
         id: InnerObjectId,
     ) -> Result<Arc<dyn std::any::Any + Send + Sync>, InvalidId>;
-    fn send_event(&mut self, msg: Message<ObjectId, RawFd>) -> Result<(), InvalidId>;
+    fn send_event(&mut self, msg: Message<ObjectId, RawFd>) -> Result<(), SendEventError>;
     fn post_error(&mut self, object_id: InnerObjectId, error_code: u32, message: CString);
     fn kill_client(&mut self, client_id: InnerClientId, reason: DisconnectReason);
     fn global_info(&self, id: InnerGlobalId) -> Result<GlobalInfo, InvalidId>;
     fn is_known_global(&self, global_ptr: *const wl_global) -> bool;
     fn flush(&mut self, client: Option<ClientId>) -> std::io::Result<()>;
     fn display_ptr(&self) -> *mut wl_display;
+    fn fd_validator(&self) -> Option<FdValidator>;
+    fn bind_interceptor(&self) -> Option<BindInterceptor>;
 }
 
 downcast_rs::impl_downcast!(ErasedState);
@@ -940,19 +1108,31 @@ impl<D: 'static> ErasedState for State<D> {
         Ok(creds)
     }
 
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    fn get_client_security_context(&self, id: InnerClientId) -> std::io::Result<Option<Vec<u8>>> {
+        if !id.alive.load(Ordering::Acquire) {
+            return Err(std::io::Error::new(std::io::ErrorKind::NotFound, InvalidId));
+        }
+
+        let fd = unsafe { ffi_dispatch!(wayland_server_handle(), wl_client_get_fd, id.ptr) };
+        // Safety: the fd is owned by the client, which is still alive
+        crate::types::server::get_peer_security_context(unsafe { BorrowedFd::borrow_raw(fd) })
+    }
+
     fn with_all_clients(&self, f: &mut dyn FnMut(ClientId)) {
-        let mut client_list = unsafe {
+        let client_list = unsafe {
             ffi_dispatch!(wayland_server_handle(), wl_display_get_client_list, self.display)
         };
         unsafe {
-            while (*client_list).next != client_list {
-                let client =
-                    ffi_dispatch!(wayland_server_handle(), wl_client_from_link, client_list);
+            // the list head is not a client, iterate over the links until we get back to it
+            let mut link = (*client_list).next;
+            while link != client_list {
+                let client = ffi_dispatch!(wayland_server_handle(), wl_client_from_link, link);
                 if let Some(id) = client_id_from_ptr(client) {
                     f(ClientId { id })
                 }
 
-                client_list = (*client_list).next;
+                link = (*link).next;
             }
         }
     }
@@ -1049,23 +1229,23 @@ impl<D: 'static> ErasedState for State<D> {
     fn send_event(
         &mut self,
         Message { sender_id: ObjectId { id }, opcode, args }: Message<ObjectId, RawFd>,
-    ) -> Result<(), InvalidId> {
+    ) -> Result<(), SendEventError> {
         if !id.alive.load(Ordering::Acquire) || id.ptr.is_null() {
-            return Err(InvalidId);
+            return Err(SendEventError::InvalidId);
         }
 
         // check that the argument list is valid
         let message_desc = match id.interface.events.get(opcode as usize) {
             Some(msg) => msg,
             None => {
-                panic!("Unknown opcode {} for object {}@{}.", opcode, id.interface.name, id.id);
+                return Err(SendEventError::UnknownOpcode { interface: id.interface, opcode });
             }
         };
-        if !check_for_signature(message_desc.signature, &args) {
-            panic!(
-                "Unexpected signature for request {}@{}.{}: expected {:?}, got {:?}.",
+        if !check_signature(message_desc.signature, &args) {
+            return Err(SendEventError::InvalidArguments(format!(
+                "Unexpected signature for event {}@{}.{}: expected {:?}, got {:?}.",
                 id.interface.name, id.id, message_desc.name, message_desc.signature, args
-            );
+            )));
         }
 
         let mut argument_list = SmallVec::<[wl_argument; 4]>::with_capacity(args.len());
@@ -1091,25 +1271,28 @@ impl<D: 'static> ErasedState for State<D> {
                     if !o.id.ptr.is_null() {
                         if !o.id.alive.load(Ordering::Acquire) {
                             unsafe { free_arrays(message_desc.signature, &argument_list) };
-                            return Err(InvalidId);
+                            return Err(SendEventError::InvalidId);
                         }
                         // check that the object belongs to the right client
                         if self.get_client(id.clone()).unwrap().id.ptr
                             != self.get_client(o.id.clone()).unwrap().id.ptr
                         {
-                            panic!("Attempting to send an event with objects from wrong client.");
+                            unsafe { free_arrays(message_desc.signature, &argument_list) };
+                            return Err(SendEventError::WrongClient);
                         }
                         if !same_interface(next_interface, o.id.interface) {
-                            panic!("Event {}@{}.{} expects an argument of interface {} but {} was provided instead.", id.interface.name, id.id, message_desc.name, next_interface.name, o.id.interface.name);
+                            unsafe { free_arrays(message_desc.signature, &argument_list) };
+                            return Err(SendEventError::InvalidArguments(format!("Event {}@{}.{} expects an argument of interface {} but {} was provided instead.", id.interface.name, id.id, message_desc.name, next_interface.name, o.id.interface.name)));
                         }
                     } else if !matches!(
                         message_desc.signature[i],
                         ArgumentType::Object(AllowNull::Yes)
                     ) {
-                        panic!(
+                        unsafe { free_arrays(message_desc.signature, &argument_list) };
+                        return Err(SendEventError::InvalidArguments(format!(
                             "Event {}@{}.{} expects an non-null object argument.",
                             id.interface.name, id.id, message_desc.name
-                        );
+                        )));
                     }
                     argument_list.push(wl_argument { o: o.id.ptr as *const _ })
                 }
@@ -1117,26 +1300,32 @@ impl<D: 'static> ErasedState for State<D> {
                     if !o.id.ptr.is_null() {
                         if !id.alive.load(Ordering::Acquire) {
                             unsafe { free_arrays(message_desc.signature, &argument_list) };
-                            return Err(InvalidId);
+                            return Err(SendEventError::InvalidId);
                         }
                         // check that the object belongs to the right client
                         if self.get_client(id.clone()).unwrap().id.ptr
                             != self.get_client(o.id.clone()).unwrap().id.ptr
                         {
-                            panic!("Attempting to send an event with objects from wrong client.");
+                            unsafe { free_arrays(message_desc.signature, &argument_list) };
+                            return Err(SendEventError::WrongClient);
                         }
                         let child_interface = match message_desc.child_interface {
                             Some(iface) => iface,
-                            None => panic!("Trying to send event {}@{}.{} which creates an object without specifying its interface, this is unsupported.", id.interface.name, id.id, message_desc.name),
+                            None => {
+                                unsafe { free_arrays(message_desc.signature, &argument_list) };
+                                return Err(SendEventError::InvalidArguments(format!("Trying to send event {}@{}.{} which creates an object without specifying its interface, this is unsupported.", id.interface.name, id.id, message_desc.name)));
+                            }
                         };
                         if !same_interface(child_interface, o.id.interface) {
-                            panic!("Event {}@{}.{} expects an argument of interface {} but {} was provided instead.", id.interface.name, id.id, message_desc.name, child_interface.name, o.id.interface.name);
+                            unsafe { free_arrays(message_desc.signature, &argument_list) };
+                            return Err(SendEventError::InvalidArguments(format!("Event {}@{}.{} expects an argument of interface {} but {} was provided instead.", id.interface.name, id.id, message_desc.name, child_interface.name, o.id.interface.name)));
                         }
                     } else if !matches!(message_desc.signature[i], ArgumentType::NewId) {
-                        panic!(
+                        unsafe { free_arrays(message_desc.signature, &argument_list) };
+                        return Err(SendEventError::InvalidArguments(format!(
                             "Event {}@{}.{} expects an non-null object argument.",
                             id.interface.name, id.id, message_desc.name
-                        );
+                        )));
                     }
                     argument_list.push(wl_argument { o: o.id.ptr as *const _ })
                 }
@@ -1229,6 +1418,14 @@ impl<D: 'static> ErasedState for State<D> {
         self.known_globals.iter().any(|ginfo| (ginfo.ptr as *const wl_global) == global_ptr)
     }
 
+    fn fd_validator(&self) -> Option<FdValidator> {
+        self.fd_validator.clone()
+    }
+
+    fn bind_interceptor(&self) -> Option<BindInterceptor> {
+        self.bind_interceptor.clone()
+    }
+
     fn flush(&mut self, client: Option<ClientId>) -> std::io::Result<()> {
         if let Some(ClientId { id: client_id }) = client {
             if client_id.alive.load(Ordering::Acquire) {
@@ -1243,18 +1440,9 @@ impl<D: 'static> ErasedState for State<D> {
                 },
             );
         }
-        if !self.pending_destructors.is_empty() {
-            // Arm the timer to trigger a wakeup of the inner event loop in 1ms, so that the user
-            // is indicated to call dispatch_clients() and have the destructors run
-            unsafe {
-                ffi_dispatch!(
-                    wayland_server_handle(),
-                    wl_event_source_timer_update,
-                    self.timer_source,
-                    1
-                )
-            };
-        }
+        // Arm the timer to trigger a wakeup of the inner event loop, so that the user
+        // is indicated to call dispatch_clients() and have the destructors run
+        self.arm_timer();
         Ok(())
     }
 
@@ -1347,6 +1535,15 @@ unsafe extern "C" fn global_bind<D: 'static>(
     HANDLE.with(|&(ref state_arc, data_ptr)| {
         // Safety: the data_ptr is a valid pointer that live outside code put there
         let data = unsafe { &mut *(data_ptr as *mut D) };
+        let interceptor = state_arc.lock().unwrap().bind_interceptor();
+        let version = match interceptor {
+            Some(interceptor) => interceptor.intercept(
+                ClientId { id: client_id.clone() },
+                GlobalId { id: global_id.clone() },
+                version,
+            ),
+            None => version,
+        };
         // create the object
         let resource = ffi_dispatch!(
             wayland_server_handle(),
@@ -1466,6 +1663,49 @@ unsafe extern "C" fn resource_dispatcher<D: 'static>(
         }
     };
 
+    let fd_validator = if message_desc.signature.contains(&ArgumentType::Fd) {
+        HANDLE.with(|(state_arc, _)| state_arc.lock().unwrap().fd_validator())
+    } else {
+        None
+    };
+    if let Some(validator) = fd_validator {
+        // Safety: the args array provided by libwayland is well-formed
+        let fds = message_desc
+            .signature
+            .iter()
+            .enumerate()
+            .filter(|(_, typ)| **typ == ArgumentType::Fd)
+            .map(|(i, _)| unsafe { (*args.add(i)).h });
+        // Safety: the fds are valid and owned by us until the end of this function
+        let rejected = fds.clone().any(|fd| {
+            !validator.validate(interface, opcode as u16, unsafe { BorrowedFd::borrow_raw(fd) })
+        });
+        if rejected {
+            for fd in fds {
+                // Safety: the fds are owned by us, and are not used anywhere else
+                drop(unsafe { OwnedFd::from_raw_fd(fd) });
+            }
+            let message = CString::new(format!(
+                "invalid file descriptor for {}@{}.{}",
+                interface.name, resource_id, message_desc.name
+            ))
+            .unwrap();
+            // Safety: the client ptr is valid and provided by libwayland
+            let client_id = unsafe { client_id_from_ptr(client) }.unwrap();
+            client_id.alive.store(false, Ordering::Release);
+            let display_resource =
+                ffi_dispatch!(wayland_server_handle(), wl_client_get_object, client, 1);
+            ffi_dispatch!(
+                wayland_server_handle(),
+                wl_resource_post_error,
+                display_resource,
+                1, // wl_display.error.invalid_method
+                message.as_ptr()
+            );
+            return 0;
+        }
+    }
+
     let mut parsed_args =
         SmallVec::<[Argument<ObjectId, OwnedFd>; 4]>::with_capacity(message_desc.signature.len());
     let mut arg_interfaces = message_desc.arg_interfaces.iter().copied();
@@ -0,0 +1,57 @@
+use super::*;
+
+use crate::types::server::ClientControlError;
+
+#[test]
+fn pause_client_rs() {
+    let (_tx, rx) = std::os::unix::net::UnixStream::pair().unwrap();
+    let server = server_rs::Backend::<()>::new().unwrap();
+    let mut handle = server.handle();
+    let client_id = handle.insert_client(rx, Arc::new(())).unwrap();
+
+    handle.set_client_paused(client_id.clone(), true).unwrap();
+    handle.set_client_paused(client_id, false).unwrap();
+}
+
+// libwayland-server cannot stop reading from a client
+#[test]
+fn pause_client_sys_unsupported() {
+    let (_tx, rx) = std::os::unix::net::UnixStream::pair().unwrap();
+    let server = server_sys::Backend::<()>::new().unwrap();
+    let mut handle = server.handle();
+    let client_id = handle.insert_client(rx, Arc::new(())).unwrap();
+
+    assert!(matches!(
+        handle.set_client_paused(client_id, true),
+        Err(ClientControlError::Unsupported)
+    ));
+}
+
+#[cfg(feature = "test-util")]
+#[test]
+fn deterministic_server_ids_invalid_base() {
+    let (_tx, rx) = std::os::unix::net::UnixStream::pair().unwrap();
+    let server = server_rs::Backend::<()>::new().unwrap();
+    let mut handle = server.handle();
+    let client_id = handle.insert_client(rx, Arc::new(())).unwrap();
+
+    assert!(matches!(
+        handle.set_deterministic_server_ids(client_id, 0x10),
+        Err(ClientControlError::InvalidServerIdBase(0x10))
+    ));
+}
+
+// libwayland-server allocates the server object IDs itself
+#[cfg(feature = "test-util")]
+#[test]
+fn deterministic_server_ids_sys_unsupported() {
+    let (_tx, rx) = std::os::unix::net::UnixStream::pair().unwrap();
+    let server = server_sys::Backend::<()>::new().unwrap();
+    let mut handle = server.handle();
+    let client_id = handle.insert_client(rx, Arc::new(())).unwrap();
+
+    assert!(matches!(
+        handle.set_deterministic_server_ids(client_id, 0xFF00_0000),
+        Err(ClientControlError::Unsupported)
+    ));
+}
@@ -190,6 +190,55 @@ expand_test!(destructor_cleanup, {
     assert!(server_data.0.load(Ordering::Acquire));
 });
 
+expand_test!(destructor_cleanup_without_wakeup, {
+    let (tx, rx) = std::os::unix::net::UnixStream::pair().unwrap();
+    let mut server = server_backend::Backend::new().unwrap();
+    server.set_destructor_wakeup(false);
+    let _client_id = server.handle().insert_client(rx, Arc::new(())).unwrap();
+    let client = client_backend::Backend::connect(tx).unwrap();
+
+    let server_data = Arc::new(ServerData(AtomicBool::new(false)));
+
+    server.handle().create_global(&interfaces::TEST_GLOBAL_INTERFACE, 3, server_data.clone());
+
+    let client_display = client.display_id();
+    let registry_id = client
+        .send_request(
+            message!(client_display, 1, [Argument::NewId(client_backend::ObjectId::null())],),
+            Some(Arc::new(DoNothingData)),
+            Some((&interfaces::WL_REGISTRY_INTERFACE, 1)),
+        )
+        .unwrap();
+    client
+        .send_request(
+            message!(
+                registry_id,
+                0,
+                [
+                    Argument::Uint(1),
+                    Argument::Str(Some(Box::new(
+                        CString::new(interfaces::TEST_GLOBAL_INTERFACE.name.as_bytes()).unwrap(),
+                    ))),
+                    Argument::Uint(3),
+                    Argument::NewId(client_backend::ObjectId::null()),
+                ],
+            ),
+            Some(Arc::new(DoNothingData)),
+            Some((&interfaces::TEST_GLOBAL_INTERFACE, 3)),
+        )
+        .unwrap();
+
+    client.flush().unwrap();
+    server.dispatch_all_clients(&mut ()).unwrap();
+    std::mem::drop(client);
+
+    // without the wakeup, the destructors are still run by the next dispatch
+    server.flush(None).unwrap();
+    server.dispatch_all_clients(&mut ()).unwrap();
+
+    assert!(server_data.0.load(Ordering::Acquire));
+});
+
 struct ServerClientData(AtomicBool);
 
 macro_rules! impl_server_clientdata {
@@ -222,3 +271,175 @@ expand_test!(destructor_client_cleanup, {
 
     assert!(client_data.0.load(Ordering::Acquire));
 });
+
+static DOUBLE_DESTRUCTOR_INTERFACE: crate::protocol::Interface = crate::protocol::Interface {
+    name: "double_destructor",
+    version: 1,
+    requests: &[crate::protocol::MessageDesc {
+        name: "destroy",
+        signature: &[],
+        since: 1,
+        is_destructor: true,
+        child_interface: None,
+        arg_interfaces: &[],
+    }],
+    events: &[crate::protocol::MessageDesc {
+        name: "done",
+        signature: &[],
+        since: 1,
+        is_destructor: true,
+        child_interface: None,
+        arg_interfaces: &[],
+    }],
+    c_ptr: None,
+};
+
+// answers the destructor request with a destructor event, destroying the object twice
+struct DoubleDestructorData;
+
+impl server_rs::ObjectData<()> for DoubleDestructorData {
+    fn request(
+        self: Arc<Self>,
+        handle: &server_rs::Handle,
+        _: &mut (),
+        _: server_rs::ClientId,
+        msg: Message<server_rs::ObjectId, OwnedFd>,
+    ) -> Option<Arc<dyn server_rs::ObjectData<()>>> {
+        handle.send_event(message!(msg.sender_id, 0, [])).unwrap();
+        None
+    }
+
+    fn destroyed(
+        self: Arc<Self>,
+        _: &server_rs::Handle,
+        _: &mut (),
+        _: server_rs::ClientId,
+        _: server_rs::ObjectId,
+    ) {
+    }
+}
+
+impl server_rs::GlobalHandler<()> for DoubleDestructorData {
+    fn bind(
+        self: Arc<Self>,
+        _: &server_rs::Handle,
+        _: &mut (),
+        _: server_rs::ClientId,
+        _: server_rs::GlobalId,
+        _: server_rs::ObjectId,
+    ) -> Arc<dyn server_rs::ObjectData<()>> {
+        self
+    }
+}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic(expected = "double_destructor@3 was destroyed twice")]
+fn double_destruction_is_caught() {
+    let (tx, rx) = std::os::unix::net::UnixStream::pair().unwrap();
+    let mut server = server_rs::Backend::new().unwrap();
+    let _client_id = server.handle().insert_client(rx, Arc::new(())).unwrap();
+    let client = client_rs::Backend::connect(tx).unwrap();
+
+    server.handle().create_global(&DOUBLE_DESTRUCTOR_INTERFACE, 1, Arc::new(DoubleDestructorData));
+
+    let client_display = client.display_id();
+    let registry_id = client
+        .send_request(
+            message!(client_display, 1, [Argument::NewId(client_rs::ObjectId::null())],),
+            Some(Arc::new(DoNothingData)),
+            Some((&interfaces::WL_REGISTRY_INTERFACE, 1)),
+        )
+        .unwrap();
+    let object_id = client
+        .send_request(
+            message!(
+                registry_id,
+                0,
+                [
+                    Argument::Uint(1),
+                    Argument::Str(Some(Box::new(
+                        CString::new(DOUBLE_DESTRUCTOR_INTERFACE.name.as_bytes()).unwrap(),
+                    ))),
+                    Argument::Uint(1),
+                    Argument::NewId(client_rs::ObjectId::null()),
+                ],
+            ),
+            Some(Arc::new(DoNothingData)),
+            Some((&DOUBLE_DESTRUCTOR_INTERFACE, 1)),
+        )
+        .unwrap();
+    client.send_request(message!(object_id, 0, []), None, None).unwrap();
+    client.flush().unwrap();
+
+    server.dispatch_all_clients(&mut ()).unwrap();
+}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic(expected = "double_destructor@4278190080 was destroyed twice")]
+fn server_id_double_destruction_is_caught() {
+    let (mut tx, rx) = std::os::unix::net::UnixStream::pair().unwrap();
+    let mut server = server_rs::Backend::new().unwrap();
+    let client_id = server.handle().insert_client(rx, Arc::new(())).unwrap();
+
+    let object_id = server
+        .handle()
+        .create_object::<()>(
+            client_id,
+            &DOUBLE_DESTRUCTOR_INTERFACE,
+            1,
+            Arc::new(DoubleDestructorData),
+        )
+        .unwrap();
+    assert_eq!(server.handle().object_info(object_id).unwrap().id, 0xFF00_0000);
+
+    // the client destroys the server-created object
+    write_words(&mut tx, &[0xFF00_0000, 8 << 16]);
+
+    server.dispatch_all_clients(&mut ()).unwrap();
+}
+
+#[test]
+fn delete_id_only_for_client_ids() {
+    let (mut tx, rx) = std::os::unix::net::UnixStream::pair().unwrap();
+    tx.set_nonblocking(true).unwrap();
+    let mut server = server_rs::Backend::new().unwrap();
+    let client_id = server.handle().insert_client(rx, Arc::new(())).unwrap();
+
+    // the callback of wl_display.sync is allocated by the client, its destruction is acknowledged
+    write_words(&mut tx, &[1, 12 << 16, 2]);
+    server.dispatch_all_clients(&mut ()).unwrap();
+    server.flush(None).unwrap();
+    let words = read_words(&mut tx);
+    assert_eq!(words.len(), 6);
+    assert_eq!(words[..2], [2, 12 << 16]);
+    assert_eq!(words[3..], [1, (12 << 16) | 1, 2]);
+
+    // a server-allocated callback is released silently
+    let callback_id = server
+        .handle()
+        .create_object::<()>(
+            client_id,
+            &interfaces::WL_CALLBACK_INTERFACE,
+            1,
+            Arc::new(DoNothingData),
+        )
+        .unwrap();
+    server.handle().send_event(message!(callback_id, 0, [Argument::Uint(42)])).unwrap();
+    server.flush(None).unwrap();
+    assert_eq!(read_words(&mut tx), [0xFF00_0000, 12 << 16, 42]);
+}
+
+fn write_words(stream: &mut std::os::unix::net::UnixStream, words: &[u32]) {
+    use std::io::Write;
+    let bytes = words.iter().flat_map(|word| word.to_ne_bytes()).collect::<Vec<_>>();
+    stream.write_all(&bytes).unwrap();
+}
+
+fn read_words(stream: &mut std::os::unix::net::UnixStream) -> Vec<u32> {
+    use std::io::Read;
+    let mut buffer = [0; 64];
+    let len = stream.read(&mut buffer).unwrap();
+    buffer[..len].chunks(4).map(|word| u32::from_ne_bytes(word.try_into().unwrap())).collect()
+}
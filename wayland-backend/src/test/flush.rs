@@ -0,0 +1,160 @@
+use std::{
+    ffi::CString,
+    io::Read,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use super::*;
+use crate::core_interfaces::WL_DISPLAY_INTERFACE;
+
+// fill the socket of a client that does not read, and check that flush_all reports it
+#[test]
+fn flush_all_would_block() {
+    let (mut tx, rx) = std::os::unix::net::UnixStream::pair().unwrap();
+    let mut server = server_rs::Backend::<()>::new().unwrap();
+    let client_id = server.handle().insert_client(rx, Arc::new(())).unwrap();
+    let display = server
+        .handle()
+        .object_for_protocol_id(client_id.clone(), &WL_DISPLAY_INTERFACE, 1)
+        .unwrap();
+
+    let mut blocked = false;
+    for _ in 0..10_000 {
+        // stay below the size of the outgoing buffer between flushes
+        for _ in 0..256 {
+            server.handle().send_event(message!(display.clone(), 1, [Argument::Uint(42)])).unwrap();
+        }
+        let outcome = server.flush_all().unwrap();
+        if !outcome.would_block.is_empty() {
+            assert_eq!(outcome.would_block, vec![client_id.clone()]);
+            blocked = true;
+            break;
+        }
+    }
+    assert!(blocked);
+
+    // once the client reads its socket, the flush can complete
+    tx.set_nonblocking(true).unwrap();
+    let mut buffer = [0; 4096];
+    while tx.read(&mut buffer).is_ok() {}
+    assert!(server.flush_all().unwrap().would_block.is_empty());
+}
+
+struct CountingData(AtomicUsize);
+
+macro_rules! impl_server_objectdata {
+    ($server_backend:tt) => {
+        impl $server_backend::ObjectData<()> for CountingData {
+            fn request(
+                self: Arc<Self>,
+                _: &$server_backend::Handle,
+                _: &mut (),
+                _: $server_backend::ClientId,
+                _: Message<$server_backend::ObjectId, OwnedFd>,
+            ) -> Option<Arc<dyn $server_backend::ObjectData<()>>> {
+                self.0.fetch_add(1, Ordering::SeqCst);
+                Some(self)
+            }
+
+            fn destroyed(
+                self: Arc<Self>,
+                _: &$server_backend::Handle,
+                _: &mut (),
+                _: $server_backend::ClientId,
+                _: $server_backend::ObjectId,
+            ) {
+            }
+        }
+
+        impl $server_backend::GlobalHandler<()> for CountingData {
+            fn bind(
+                self: Arc<Self>,
+                _: &$server_backend::Handle,
+                _: &mut (),
+                _: $server_backend::ClientId,
+                _: $server_backend::GlobalId,
+                _: $server_backend::ObjectId,
+            ) -> Arc<dyn $server_backend::ObjectData<()>> {
+                self
+            }
+        }
+    };
+}
+
+impl_server_objectdata!(server_rs);
+impl_server_objectdata!(server_sys);
+
+// the requests sent before the shutdown all reach the server, without flushing them explicitly
+expand_test!(client_shutdown, {
+    let (tx, rx) = std::os::unix::net::UnixStream::pair().unwrap();
+    let mut server = server_backend::Backend::new().unwrap();
+    let _client_id = server.handle().insert_client(rx, Arc::new(())).unwrap();
+    let client = client_backend::Backend::connect(tx).unwrap();
+
+    let server_data = Arc::new(CountingData(AtomicUsize::new(0)));
+    server.handle().create_global(&interfaces::TEST_GLOBAL_INTERFACE, 3, server_data.clone());
+
+    let client_display = client.display_id();
+    let registry_id = client
+        .send_request(
+            message!(client_display, 1, [Argument::NewId(client_backend::ObjectId::null())],),
+            Some(Arc::new(DoNothingData)),
+            Some((&interfaces::WL_REGISTRY_INTERFACE, 1)),
+        )
+        .unwrap();
+    let test_global_id = client
+        .send_request(
+            message!(
+                registry_id,
+                0,
+                [
+                    Argument::Uint(1),
+                    Argument::Str(Some(Box::new(
+                        CString::new(interfaces::TEST_GLOBAL_INTERFACE.name.as_bytes()).unwrap(),
+                    ))),
+                    Argument::Uint(3),
+                    Argument::NewId(client_backend::ObjectId::null()),
+                ],
+            ),
+            Some(Arc::new(DoNothingData)),
+            Some((&interfaces::TEST_GLOBAL_INTERFACE, 3)),
+        )
+        .unwrap();
+    for _ in 0..100 {
+        client
+            .send_request(
+                message!(
+                    test_global_id.clone(),
+                    1, // get_secondary
+                    [Argument::NewId(client_backend::ObjectId::null())]
+                ),
+                Some(Arc::new(DoNothingData)),
+                Some((&interfaces::SECONDARY_INTERFACE, 3)),
+            )
+            .unwrap();
+    }
+
+    // the shutdown waits for the server to close the connection
+    let shutdown = std::thread::spawn(move || client.shutdown(None));
+    while !shutdown.is_finished() {
+        server.dispatch_all_clients(&mut ()).unwrap();
+        server.flush(None).unwrap();
+        std::thread::sleep(std::time::Duration::from_millis(1));
+    }
+    shutdown.join().unwrap().unwrap();
+
+    assert_eq!(server_data.0.load(Ordering::SeqCst), 100);
+});
+
+// the shutdown gives up once the timeout expires if the server never closes the connection
+expand_test!(client_shutdown_timeout, {
+    let (tx, rx) = std::os::unix::net::UnixStream::pair().unwrap();
+    let server = server_backend::Backend::<()>::new().unwrap();
+    let _client_id = server.handle().insert_client(rx, Arc::new(())).unwrap();
+    let client = client_backend::Backend::connect(tx).unwrap();
+
+    let err = client.shutdown(Some(std::time::Duration::from_millis(10))).unwrap_err();
+    assert!(
+        matches!(err, client_backend::WaylandError::Io(e) if e.kind() == std::io::ErrorKind::TimedOut)
+    );
+});
@@ -197,3 +197,89 @@ expand_test!(many_args, {
 
     assert!(server_data.0.load(Ordering::SeqCst));
 });
+
+// the same request is not dispatched if the server rejects its fd
+expand_test!(many_args_rejected_fd, {
+    let (tx, rx) = std::os::unix::net::UnixStream::pair().unwrap();
+    let mut server = server_backend::Backend::new().unwrap();
+    let _client_id = server.handle().insert_client(rx, Arc::new(())).unwrap();
+    let client = client_backend::Backend::connect(tx).unwrap();
+
+    let server_data = Arc::new(ServerData(AtomicBool::new(false)));
+    let client_data = Arc::new(ClientData(AtomicBool::new(false)));
+
+    let validator_called = Arc::new(AtomicBool::new(false));
+    let validator_called2 = validator_called.clone();
+    server.set_fd_validator(move |interface, opcode, _| {
+        assert_eq!(interface.name, interfaces::TEST_GLOBAL_INTERFACE.name);
+        assert_eq!(opcode, 0);
+        validator_called2.store(true, Ordering::SeqCst);
+        false
+    });
+
+    server.handle().create_global(&interfaces::TEST_GLOBAL_INTERFACE, 1, server_data.clone());
+
+    let client_display = client.display_id();
+    let registry_id = client
+        .send_request(
+            message!(client_display, 1, [Argument::NewId(client_backend::ObjectId::null())],),
+            Some(Arc::new(DoNothingData)),
+            Some((&interfaces::WL_REGISTRY_INTERFACE, 1)),
+        )
+        .unwrap();
+    let test_global_id = client
+        .send_request(
+            message!(
+                registry_id,
+                0,
+                [
+                    Argument::Uint(1),
+                    Argument::Str(Some(Box::new(
+                        CString::new(interfaces::TEST_GLOBAL_INTERFACE.name.as_bytes()).unwrap(),
+                    ))),
+                    Argument::Uint(1),
+                    Argument::NewId(client_backend::ObjectId::null()),
+                ],
+            ),
+            Some(client_data.clone()),
+            Some((&interfaces::TEST_GLOBAL_INTERFACE, 1)),
+        )
+        .unwrap();
+
+    client.flush().unwrap();
+    server.dispatch_all_clients(&mut ()).unwrap();
+    server.flush(None).unwrap();
+    client.prepare_read().unwrap().read().unwrap();
+    assert!(client_data.0.load(Ordering::SeqCst));
+
+    client
+        .send_request(
+            message!(
+                test_global_id,
+                0,
+                [
+                    Argument::Uint(42),
+                    Argument::Int(-13),
+                    Argument::Fixed(4589),
+                    Argument::Array(Box::new(vec![1, 2, 3, 4, 5, 6, 7, 8, 9])),
+                    Argument::Str(Some(Box::new(
+                        CString::new("I like trains".as_bytes()).unwrap()
+                    ))),
+                    Argument::Fd(0), // stdin
+                ],
+            ),
+            None,
+            None,
+        )
+        .unwrap();
+    client.flush().unwrap();
+
+    let _ = server.dispatch_all_clients(&mut ());
+    server.flush(None).unwrap();
+
+    assert!(validator_called.load(Ordering::SeqCst));
+    assert!(!server_data.0.load(Ordering::SeqCst));
+
+    // the client was disconnected with a protocol error
+    assert!(client.prepare_read().unwrap().read().is_err());
+});
@@ -55,12 +55,17 @@ mod interfaces {
     );
 }
 
+mod client_control;
 mod destructors;
+mod flush;
 mod many_args;
 mod object_args;
 mod protocol_error;
 mod server_created_objects;
 mod sync;
+mod unknown_object;
+#[cfg(feature = "xml")]
+mod xml;
 
 /*
  * Assertion of Send/Sync for all relevant objects
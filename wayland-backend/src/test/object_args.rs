@@ -43,6 +43,8 @@ macro_rules! impl_server_objectdata {
                             handle.object_info(secondary.clone()).unwrap().interface.name,
                             "secondary"
                         );
+                        assert!(secondary.has_interface(&interfaces::SECONDARY_INTERFACE));
+                        assert!(!secondary.has_interface(&interfaces::TERTIARY_INTERFACE));
                         if *u == 1 {
                             assert!(tertiary.is_null());
                         } else if *u == 2 {
@@ -59,6 +61,7 @@ macro_rules! impl_server_objectdata {
                     if let [Argument::NewId(_), Argument::Object(sec), Argument::Object(ter)] = &msg.args[..] {
                         assert!(sec.is_null());
                         assert!(&ter.interface().name == &interfaces::TERTIARY_INTERFACE.name);
+                        assert!(ter.has_interface(&interfaces::TERTIARY_INTERFACE));
                     } else {
                         panic!("Bad argument list!");
                     }
@@ -488,3 +491,66 @@ expand_test!(new_id_null_and_non_null, {
 
     assert!(server_data.0.load(Ordering::SeqCst));
 });
+
+expand_test!(send_event_errors, {
+    let (tx_1, rx_1) = std::os::unix::net::UnixStream::pair().unwrap();
+    let (tx_2, rx_2) = std::os::unix::net::UnixStream::pair().unwrap();
+    let server = server_backend::Backend::<()>::new().unwrap();
+    let client_id_1 = server.handle().insert_client(rx_1, Arc::new(())).unwrap();
+    let client_id_2 = server.handle().insert_client(rx_2, Arc::new(())).unwrap();
+    let _client_1 = client_backend::Backend::connect(tx_1).unwrap();
+    let _client_2 = client_backend::Backend::connect(tx_2).unwrap();
+
+    let handle = server.handle();
+    let global_1 = handle
+        .create_object::<()>(
+            client_id_1.clone(),
+            &interfaces::TEST_GLOBAL_INTERFACE,
+            3,
+            Arc::new(DoNothingData),
+        )
+        .unwrap();
+    let secondary_1 = handle
+        .create_object::<()>(
+            client_id_1,
+            &interfaces::SECONDARY_INTERFACE,
+            3,
+            Arc::new(DoNothingData),
+        )
+        .unwrap();
+    let secondary_2 = handle
+        .create_object::<()>(
+            client_id_2,
+            &interfaces::SECONDARY_INTERFACE,
+            3,
+            Arc::new(DoNothingData),
+        )
+        .unwrap();
+
+    // wl_test_global.ack_secondary with a secondary of the other client
+    assert!(matches!(
+        handle.send_event(message!(global_1.clone(), 1, [Argument::Object(secondary_2)])),
+        Err(server_backend::SendEventError::WrongClient)
+    ));
+    // opcode out of range
+    assert!(matches!(
+        handle.send_event(message!(global_1.clone(), 42, [])),
+        Err(server_backend::SendEventError::UnknownOpcode { opcode: 42, .. })
+    ));
+    // bad argument list
+    assert!(matches!(
+        handle.send_event(message!(global_1.clone(), 1, [Argument::Uint(0)])),
+        Err(server_backend::SendEventError::InvalidArguments(_))
+    ));
+    // null object where it is not allowed
+    assert!(matches!(
+        handle.send_event(message!(
+            global_1.clone(),
+            1,
+            [Argument::Object(server_backend::ObjectId::null())]
+        )),
+        Err(server_backend::SendEventError::InvalidArguments(_))
+    ));
+    // a valid event is still sent
+    handle.send_event(message!(global_1, 1, [Argument::Object(secondary_1)])).unwrap();
+});
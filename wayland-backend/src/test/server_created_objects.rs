@@ -155,3 +155,35 @@ expand_test!(server_created_object, {
 
     assert_eq!(client_data.0.load(Ordering::SeqCst), 2);
 });
+
+#[cfg(feature = "test-util")]
+#[test]
+fn deterministic_server_ids() {
+    let (_tx, rx) = std::os::unix::net::UnixStream::pair().unwrap();
+    let server = server_rs::Backend::<()>::new().unwrap();
+    let mut handle = server.handle();
+    let client_id = handle.insert_client(rx, Arc::new(())).unwrap();
+
+    handle.set_deterministic_server_ids(client_id.clone(), 0xFF00_0010).unwrap();
+
+    let create = || {
+        handle
+            .create_object::<()>(
+                client_id.clone(),
+                &interfaces::WL_CALLBACK_INTERFACE,
+                1,
+                Arc::new(DoNothingData),
+            )
+            .unwrap()
+    };
+    let obj_1 = create();
+    let obj_2 = create();
+    assert_eq!(handle.object_info(obj_1.clone()).unwrap().id, 0xFF00_0010);
+    assert_eq!(handle.object_info(obj_2).unwrap().id, 0xFF00_0011);
+
+    // the id of a destroyed object is not reused
+    handle.send_event(message!(obj_1.clone(), 0, [Argument::Uint(0)])).unwrap();
+    assert!(handle.object_info(obj_1).is_err());
+    let obj_3 = create();
+    assert_eq!(handle.object_info(obj_3).unwrap().id, 0xFF00_0012);
+}
@@ -0,0 +1,61 @@
+use std::io::Write;
+use std::sync::Mutex;
+
+use super::*;
+
+// an event addressed to object 42, which the client never created
+fn unknown_object_event() -> [u8; 8] {
+    let mut event = [0; 8];
+    event[..4].copy_from_slice(&42u32.to_ne_bytes());
+    event[4..].copy_from_slice(&(8u32 << 16).to_ne_bytes());
+    event
+}
+
+#[test]
+fn unknown_object_fatal() {
+    let (mut tx, rx) = std::os::unix::net::UnixStream::pair().unwrap();
+    let client = client_rs::Backend::connect(rx).unwrap();
+
+    tx.write_all(&unknown_object_event()).unwrap();
+    let ret = client.prepare_read().unwrap().read();
+    assert!(matches!(ret, Err(client_rs::WaylandError::Protocol(_))));
+    assert!(client.last_error().is_some());
+}
+
+#[test]
+fn unknown_object_ignore() {
+    let (mut tx, rx) = std::os::unix::net::UnixStream::pair().unwrap();
+    let client = client_rs::Backend::connect(rx).unwrap();
+    client.set_unknown_object_policy(client_rs::UnknownObjectPolicy::Ignore);
+
+    tx.write_all(&unknown_object_event()).unwrap();
+    // the event is discarded, leaving nothing to dispatch
+    let ret = client.prepare_read().unwrap().read();
+    assert!(
+        matches!(ret, Err(client_rs::WaylandError::Io(e)) if e.kind() == std::io::ErrorKind::WouldBlock)
+    );
+    assert!(client.last_error().is_none());
+}
+
+#[test]
+fn wire_trace_unknown_object() {
+    let (mut tx, rx) = std::os::unix::net::UnixStream::pair().unwrap();
+    let client = client_rs::Backend::connect(rx).unwrap();
+    client.set_unknown_object_policy(client_rs::UnknownObjectPolicy::Ignore);
+
+    let traced = Arc::new(Mutex::new(Vec::new()));
+    let traced2 = traced.clone();
+    client.set_wire_trace(Some(Arc::new(move |bytes, fds| {
+        traced2.lock().unwrap().push((bytes.to_vec(), fds.len()));
+    })));
+
+    // the message is traced even though it is discarded
+    tx.write_all(&unknown_object_event()).unwrap();
+    let _ = client.prepare_read().unwrap().read();
+    assert_eq!(*traced.lock().unwrap(), vec![(unknown_object_event().to_vec(), 0)]);
+
+    client.set_wire_trace(None);
+    tx.write_all(&unknown_object_event()).unwrap();
+    let _ = client.prepare_read().unwrap().read();
+    assert_eq!(traced.lock().unwrap().len(), 1);
+}
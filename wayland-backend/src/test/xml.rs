@@ -0,0 +1,104 @@
+use std::{
+    ffi::CString,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use super::*;
+use crate::protocol::Interface;
+
+struct ServerData(AtomicUsize);
+
+impl server_rs::ObjectData<()> for ServerData {
+    fn request(
+        self: Arc<Self>,
+        _: &server_rs::Handle,
+        _: &mut (),
+        _: server_rs::ClientId,
+        _: Message<server_rs::ObjectId, OwnedFd>,
+    ) -> Option<Arc<dyn server_rs::ObjectData<()>>> {
+        self.0.fetch_add(1, Ordering::SeqCst);
+        Some(self)
+    }
+
+    fn destroyed(
+        self: Arc<Self>,
+        _: &server_rs::Handle,
+        _: &mut (),
+        _: server_rs::ClientId,
+        _: server_rs::ObjectId,
+    ) {
+    }
+}
+
+impl server_rs::GlobalHandler<()> for ServerData {
+    fn bind(
+        self: Arc<Self>,
+        _: &server_rs::Handle,
+        _: &mut (),
+        _: server_rs::ClientId,
+        _: server_rs::GlobalId,
+        _: server_rs::ObjectId,
+    ) -> Arc<dyn server_rs::ObjectData<()>> {
+        self
+    }
+}
+
+// the rust backend can dispatch the messages of interfaces loaded at runtime
+#[test]
+fn dispatch_loaded_interfaces() {
+    let xml = std::fs::read_to_string("../wayland-scanner/tests/scanner_assets/test-protocol.xml")
+        .unwrap();
+    let loaded = Interface::from_xml(&xml, &[]).unwrap();
+    let find = |name| *loaded.iter().find(|iface| iface.name == name).unwrap();
+    let test_global = find("test_global");
+    let secondary = find("secondary");
+
+    let (tx, rx) = std::os::unix::net::UnixStream::pair().unwrap();
+    let mut server = server_rs::Backend::new().unwrap();
+    let _client_id = server.handle().insert_client(rx, Arc::new(())).unwrap();
+    let client = client_rs::Backend::connect(tx).unwrap();
+
+    let server_data = Arc::new(ServerData(AtomicUsize::new(0)));
+    server.handle().create_global(test_global, 3, server_data.clone());
+
+    let client_display = client.display_id();
+    let registry_id = client
+        .send_request(
+            message!(client_display, 1, [Argument::NewId(client_rs::ObjectId::null())],),
+            Some(Arc::new(DoNothingData)),
+            Some((&interfaces::WL_REGISTRY_INTERFACE, 1)),
+        )
+        .unwrap();
+    let test_global_id = client
+        .send_request(
+            message!(
+                registry_id,
+                0,
+                [
+                    Argument::Uint(1),
+                    Argument::Str(Some(Box::new(CString::new(test_global.name).unwrap()))),
+                    Argument::Uint(3),
+                    Argument::NewId(client_rs::ObjectId::null()),
+                ],
+            ),
+            Some(Arc::new(DoNothingData)),
+            Some((test_global, 3)),
+        )
+        .unwrap();
+    let secondary_id = client
+        .send_request(
+            message!(
+                test_global_id,
+                1, // get_secondary
+                [Argument::NewId(client_rs::ObjectId::null())]
+            ),
+            Some(Arc::new(DoNothingData)),
+            Some((secondary, 3)),
+        )
+        .unwrap();
+    assert!(secondary_id.has_interface(secondary));
+    client.flush().unwrap();
+
+    server.dispatch_all_clients(&mut ()).unwrap();
+    assert_eq!(server_data.0.load(Ordering::SeqCst), 1);
+}
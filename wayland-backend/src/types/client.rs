@@ -11,6 +11,39 @@ impl std::fmt::Display for NoWaylandLib {
     }
 }
 
+/// An error when trying to establish a Wayland connection.
+#[derive(Debug)]
+pub enum ConnectError {
+    /// The wayland library could not be loaded.
+    NoWaylandLib,
+
+    /// Could not find wayland compositor
+    NoCompositor,
+
+    /// `WAYLAND_SOCKET` was set but contained garbage
+    InvalidFd,
+}
+
+impl std::error::Error for ConnectError {}
+
+impl std::fmt::Display for ConnectError {
+    #[cfg_attr(coverage, coverage(off))]
+    fn fmt(&self, f: &mut ::std::fmt::Formatter) -> Result<(), ::std::fmt::Error> {
+        match self {
+            Self::NoWaylandLib => write!(f, "The wayland library could not be loaded"),
+            Self::NoCompositor => write!(f, "Could not find wayland compositor"),
+            Self::InvalidFd => write!(f, "WAYLAND_SOCKET was set but contained garbage"),
+        }
+    }
+}
+
+impl From<NoWaylandLib> for ConnectError {
+    #[cfg_attr(coverage, coverage(off))]
+    fn from(_: NoWaylandLib) -> Self {
+        Self::NoWaylandLib
+    }
+}
+
 /// An error that can occur when using a Wayland connection
 #[derive(Debug)]
 pub enum WaylandError {
@@ -70,6 +103,20 @@ impl From<std::io::Error> for WaylandError {
     }
 }
 
+/// How a client backend handles the events addressed to an object it does not know
+///
+/// See [`Backend::set_unknown_object_policy()`][crate::client::Backend::set_unknown_object_policy()].
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum UnknownObjectPolicy {
+    /// Silently discard the event
+    Ignore,
+    /// Discard the event and log a warning
+    Log,
+    /// Treat the event as a protocol error, closing the connection
+    #[default]
+    Fatal,
+}
+
 /// An error generated when trying to act on an invalid `ObjectId`.
 #[derive(Clone, Debug)]
 pub struct InvalidId;
@@ -53,6 +53,122 @@ impl std::fmt::Display for InvalidId {
     }
 }
 
+/// An error generated when an event could not be sent
+#[derive(Clone, Debug)]
+pub enum SendEventError {
+    /// The sender of the event, or one of its object arguments, is no longer valid
+    InvalidId,
+    /// The opcode does not match any event of the interface of the sender
+    UnknownOpcode {
+        /// The interface of the sender
+        interface: &'static Interface,
+        /// The invalid opcode
+        opcode: u16,
+    },
+    /// An object argument of the event belongs to a different client than its sender
+    WrongClient,
+    /// The arguments of the event do not match its signature in the protocol specification
+    InvalidArguments(String),
+}
+
+impl std::error::Error for SendEventError {}
+
+impl std::fmt::Display for SendEventError {
+    #[cfg_attr(coverage, coverage(off))]
+    fn fmt(&self, f: &mut ::std::fmt::Formatter) -> Result<(), ::std::fmt::Error> {
+        match self {
+            SendEventError::InvalidId => f.write_str("Invalid Id"),
+            SendEventError::UnknownOpcode { interface, opcode } => {
+                write!(f, "Unknown opcode {} for an object of interface {}", opcode, interface.name)
+            }
+            SendEventError::WrongClient => {
+                f.write_str("Attempting to send an event with objects from wrong client")
+            }
+            SendEventError::InvalidArguments(msg) => f.write_str(msg),
+        }
+    }
+}
+
+impl From<InvalidId> for SendEventError {
+    fn from(_: InvalidId) -> Self {
+        SendEventError::InvalidId
+    }
+}
+
+/// An error generated when changing how the backend handles a client
+#[derive(Clone, Debug)]
+pub enum ClientControlError {
+    /// The client is no longer alive
+    InvalidId,
+    /// The operation is not supported by the backend in use
+    Unsupported,
+    /// The base of the server-allocated IDs is outside of their range, which starts at `0xff000000`
+    InvalidServerIdBase(u32),
+}
+
+impl std::error::Error for ClientControlError {}
+
+impl std::fmt::Display for ClientControlError {
+    #[cfg_attr(coverage, coverage(off))]
+    fn fmt(&self, f: &mut ::std::fmt::Formatter) -> Result<(), ::std::fmt::Error> {
+        match self {
+            ClientControlError::InvalidId => f.write_str("Invalid Id"),
+            ClientControlError::Unsupported => {
+                f.write_str("This operation is not supported by the backend in use")
+            }
+            ClientControlError::InvalidServerIdBase(base) => {
+                write!(f, "Server IDs start at 0xff000000, got {:#x}", base)
+            }
+        }
+    }
+}
+
+impl From<InvalidId> for ClientControlError {
+    fn from(_: InvalidId) -> Self {
+        ClientControlError::InvalidId
+    }
+}
+
+/// An error generated when a global could not be created
+#[derive(Clone, Debug)]
+pub enum GlobalError {
+    /// The requested version is higher than the one supported by the interface
+    UnsupportedVersion {
+        /// The interface of the global
+        interface: &'static Interface,
+        /// The requested version
+        version: u32,
+    },
+    /// The interface has no C representation, which the system backend requires
+    NoCInterface(&'static Interface),
+    /// The system library failed to create the global, because of an invalid specification or a
+    /// memory allocation failure
+    CreationFailed,
+}
+
+impl std::error::Error for GlobalError {}
+
+impl std::fmt::Display for GlobalError {
+    #[cfg_attr(coverage, coverage(off))]
+    fn fmt(&self, f: &mut ::std::fmt::Formatter) -> Result<(), ::std::fmt::Error> {
+        match self {
+            GlobalError::UnsupportedVersion { interface, version } => write!(
+                f,
+                "Cannot create global {} version {}: maximum supported version is {}",
+                interface.name, version, interface.version
+            ),
+            GlobalError::NoCInterface(interface) => write!(
+                f,
+                "Interface {} has no c_ptr, which is unsupported by the sys backend",
+                interface.name
+            ),
+            GlobalError::CreationFailed => {
+                f.write_str("Invalid global specification or memory allocation failure")
+            }
+        }
+    }
+}
+
 /// Describes why a client has been disconnected from the server.
 #[derive(Debug)]
 pub enum DisconnectReason {
@@ -72,3 +188,59 @@ pub struct Credentials {
     /// gid of the client
     pub gid: rustix::process::RawGid,
 }
+
+/// Read the security context of the peer of a socket with `SO_PEERSEC`
+///
+/// Returns `None` if no Linux Security Module providing such labels is active.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+pub(crate) fn get_peer_security_context(
+    fd: std::os::unix::io::BorrowedFd<'_>,
+) -> std::io::Result<Option<Vec<u8>>> {
+    use linux_raw_sys::net::{SOL_SOCKET, SO_PEERSEC};
+    use rustix::io::Errno;
+    use std::os::{
+        raw::{c_int, c_uint, c_void},
+        unix::io::AsRawFd,
+    };
+
+    // rustix has no getter for SO_PEERSEC, so the function of the C library is called directly
+    extern "C" {
+        fn getsockopt(
+            fd: c_int,
+            level: c_int,
+            name: c_int,
+            value: *mut c_void,
+            len: *mut c_uint,
+        ) -> c_int;
+    }
+
+    let mut buf = vec![0u8; 256];
+    loop {
+        let mut len = buf.len() as c_uint;
+        // SAFETY: the buffer is valid for writes of len bytes
+        let ret = unsafe {
+            getsockopt(
+                fd.as_raw_fd(),
+                SOL_SOCKET as c_int,
+                SO_PEERSEC as c_int,
+                buf.as_mut_ptr().cast(),
+                &mut len,
+            )
+        };
+        if ret == 0 {
+            buf.truncate(len as usize);
+            // some modules include the terminating nul byte in the label
+            if buf.last() == Some(&0) {
+                buf.pop();
+            }
+            return Ok(if buf.is_empty() { None } else { Some(buf) });
+        }
+        let err = std::io::Error::last_os_error();
+        match Errno::from_io_error(&err) {
+            // the buffer was too small, len now holds the required size
+            Some(Errno::RANGE) if len as usize > buf.len() => buf.resize(len as usize, 0),
+            Some(Errno::NOPROTOOPT) => return Ok(None),
+            _ => return Err(err),
+        }
+    }
+}
@@ -0,0 +1,445 @@
+//! Loading of protocol interfaces from their XML description at runtime
+
+use std::collections::HashMap;
+
+use quick_xml::{
+    events::{BytesStart, Event},
+    Reader,
+};
+
+use crate::protocol::{AllowNull, ArgumentType, Interface, MessageDesc, ANONYMOUS_INTERFACE};
+
+/// An error that occurred while loading interfaces from a protocol XML description
+#[derive(Debug, Clone)]
+pub struct XmlError {
+    message: String,
+}
+
+impl XmlError {
+    fn new(message: impl Into<String>) -> Self {
+        Self { message: message.into() }
+    }
+}
+
+impl std::error::Error for XmlError {}
+
+impl std::fmt::Display for XmlError {
+    #[cfg_attr(coverage, coverage(off))]
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Invalid protocol XML: {}", self.message)
+    }
+}
+
+impl Interface {
+    /// Load the interfaces described by a protocol XML file
+    ///
+    /// This is the runtime equivalent of the `generate_interfaces!()` macro of `wayland-scanner`, for
+    /// programs handling protocols that are not known at compile time. The interfaces are returned in the
+    /// order of the XML file, and are leaked to be given a `'static` lifetime, so this should only be
+    /// invoked once per protocol.
+    ///
+    /// The interfaces referenced by the protocol but not described by it (like `wl_surface` for most
+    /// extension protocols) are looked up by name in `known`. If they are not found, they are replaced
+    /// by an interface with the same name and no messages: this is enough for object arguments, which
+    /// are checked by name, but objects created with such an interface cannot be used.
+    ///
+    /// The returned interfaces have no C representation, so they can only be used with the rust backend.
+    ///
+    /// Returns an error if the XML is not a valid protocol description, or if interfaces create each
+    /// other in a cycle through `new_id` arguments, which cannot be represented without the static
+    /// definitions generated by `wayland-scanner`.
+    ///
+    /// Requires the `xml` cargo feature.
+    pub fn from_xml(
+        xml: &str,
+        known: &[&'static Interface],
+    ) -> Result<Vec<&'static Interface>, XmlError> {
+        let parsed = parse_protocol(xml)?;
+        let mut builder = Builder {
+            parsed: &parsed,
+            known,
+            built: HashMap::new(),
+            in_progress: Vec::new(),
+            stubs: HashMap::new(),
+        };
+        (0..parsed.len()).map(|idx| builder.build(idx)).collect()
+    }
+}
+
+struct ParsedInterface {
+    name: String,
+    version: u32,
+    requests: Vec<ParsedMessage>,
+    events: Vec<ParsedMessage>,
+}
+
+struct ParsedMessage {
+    name: String,
+    since: u32,
+    is_destructor: bool,
+    args: Vec<ParsedArg>,
+}
+
+struct ParsedArg {
+    typ: String,
+    interface: Option<String>,
+    allow_null: bool,
+}
+
+fn parse_protocol(xml: &str) -> Result<Vec<ParsedInterface>, XmlError> {
+    let mut reader = Reader::from_str(xml);
+    reader.config_mut().trim_text(true);
+
+    let mut in_protocol = false;
+    let mut interfaces = Vec::new();
+    let mut interface: Option<ParsedInterface> = None;
+    let mut message: Option<(bool, ParsedMessage)> = None;
+
+    loop {
+        let (tag, is_empty) = match reader.read_event() {
+            Ok(Event::Start(tag)) => (tag, false),
+            Ok(Event::Empty(tag)) => (tag, true),
+            Ok(Event::End(tag)) => {
+                match tag.name().as_ref() {
+                    b"protocol" => in_protocol = false,
+                    b"interface" => interfaces.extend(interface.take()),
+                    b"request" | b"event" => {
+                        if let (Some(interface), Some((is_request, msg))) =
+                            (interface.as_mut(), message.take())
+                        {
+                            if is_request {
+                                interface.requests.push(msg);
+                            } else {
+                                interface.events.push(msg);
+                            }
+                        }
+                    }
+                    _ => {}
+                }
+                continue;
+            }
+            Ok(Event::Eof) => break,
+            Ok(_) => continue,
+            Err(e) => return Err(XmlError::new(e.to_string())),
+        };
+
+        match tag.name().as_ref() {
+            b"protocol" => in_protocol = true,
+            b"interface" if in_protocol => {
+                let iface = ParsedInterface {
+                    name: required_attr(&tag, "name")?,
+                    version: parse_attr(&tag, "version")?
+                        .ok_or_else(|| XmlError::new("interface without a version"))?,
+                    requests: Vec::new(),
+                    events: Vec::new(),
+                };
+                if is_empty {
+                    interfaces.push(iface);
+                } else {
+                    interface = Some(iface);
+                }
+            }
+            name @ (b"request" | b"event") if interface.is_some() => {
+                let msg = ParsedMessage {
+                    name: required_attr(&tag, "name")?,
+                    since: parse_attr(&tag, "since")?.unwrap_or(1),
+                    is_destructor: attr(&tag, "type")?.as_deref() == Some("destructor"),
+                    args: Vec::new(),
+                };
+                let is_request = name == b"request";
+                match (is_empty, interface.as_mut()) {
+                    (true, Some(interface)) if is_request => interface.requests.push(msg),
+                    (true, Some(interface)) => interface.events.push(msg),
+                    _ => message = Some((is_request, msg)),
+                }
+            }
+            b"arg" => {
+                if let Some((_, msg)) = message.as_mut() {
+                    msg.args.push(ParsedArg {
+                        typ: required_attr(&tag, "type")?,
+                        interface: attr(&tag, "interface")?,
+                        allow_null: attr(&tag, "allow-null")?.as_deref() == Some("true"),
+                    });
+                }
+            }
+            _ => {}
+        }
+    }
+
+    if interfaces.is_empty() {
+        return Err(XmlError::new("no interface found"));
+    }
+    for (i, iface) in interfaces.iter().enumerate() {
+        if interfaces[..i].iter().any(|other| other.name == iface.name) {
+            return Err(XmlError::new(format!(
+                "interface {} is declared several times",
+                iface.name
+            )));
+        }
+    }
+    Ok(interfaces)
+}
+
+fn attr(tag: &BytesStart<'_>, name: &str) -> Result<Option<String>, XmlError> {
+    for attr in tag.attributes() {
+        let attr = attr.map_err(|e| XmlError::new(e.to_string()))?;
+        if attr.key.as_ref() == name.as_bytes() {
+            let value = attr.unescape_value().map_err(|e| XmlError::new(e.to_string()))?;
+            return Ok(Some(value.into_owned()));
+        }
+    }
+    Ok(None)
+}
+
+fn required_attr(tag: &BytesStart<'_>, name: &str) -> Result<String, XmlError> {
+    attr(tag, name)?.ok_or_else(|| {
+        XmlError::new(format!(
+            "missing attribute {} on <{}>",
+            name,
+            String::from_utf8_lossy(tag.name().as_ref())
+        ))
+    })
+}
+
+fn parse_attr(tag: &BytesStart<'_>, name: &str) -> Result<Option<u32>, XmlError> {
+    attr(tag, name)?
+        .map(|value| {
+            value.parse().map_err(|_| XmlError::new(format!("invalid {} value: {}", name, value)))
+        })
+        .transpose()
+}
+
+fn leak_str(txt: &str) -> &'static str {
+    Box::leak(txt.to_owned().into_boxed_str())
+}
+
+struct Builder<'a> {
+    parsed: &'a [ParsedInterface],
+    known: &'a [&'static Interface],
+    built: HashMap<usize, &'static Interface>,
+    in_progress: Vec<usize>,
+    stubs: HashMap<String, &'static Interface>,
+}
+
+impl Builder<'_> {
+    fn build(&mut self, idx: usize) -> Result<&'static Interface, XmlError> {
+        if let Some(iface) = self.built.get(&idx) {
+            return Ok(iface);
+        }
+        // the interfaces created through new_id arguments must be complete, so they are built first
+        if self.in_progress.contains(&idx) {
+            return Err(XmlError::new(format!(
+                "interface {} creates itself through new_id arguments",
+                self.parsed[idx].name
+            )));
+        }
+        self.in_progress.push(idx);
+        let parsed = &self.parsed[idx];
+        let requests = self.build_messages(&parsed.requests)?;
+        let events = self.build_messages(&parsed.events)?;
+        self.in_progress.pop();
+
+        let iface = Box::leak(Box::new(Interface {
+            name: leak_str(&parsed.name),
+            version: parsed.version,
+            requests,
+            events,
+            c_ptr: None,
+        }));
+        self.built.insert(idx, iface);
+        Ok(iface)
+    }
+
+    fn build_messages(
+        &mut self,
+        messages: &[ParsedMessage],
+    ) -> Result<&'static [MessageDesc], XmlError> {
+        let mut descs = Vec::with_capacity(messages.len());
+        for msg in messages {
+            let mut signature = Vec::with_capacity(msg.args.len());
+            let mut child_interface = None;
+            let mut arg_interfaces = Vec::new();
+            for arg in &msg.args {
+                let allow_null = if arg.allow_null { AllowNull::Yes } else { AllowNull::No };
+                match arg.typ.as_str() {
+                    "int" => signature.push(ArgumentType::Int),
+                    "uint" => signature.push(ArgumentType::Uint),
+                    "fixed" => signature.push(ArgumentType::Fixed),
+                    "string" => signature.push(ArgumentType::Str(allow_null)),
+                    "array" => signature.push(ArgumentType::Array),
+                    "fd" => signature.push(ArgumentType::Fd),
+                    "object" => {
+                        signature.push(ArgumentType::Object(allow_null));
+                        arg_interfaces.push(match arg.interface {
+                            Some(ref name) => self.resolve_arg(name),
+                            None => &ANONYMOUS_INTERFACE,
+                        });
+                    }
+                    "new_id" => match arg.interface {
+                        Some(ref name) => {
+                            signature.push(ArgumentType::NewId);
+                            child_interface = Some(self.resolve_child(name)?);
+                        }
+                        // the interface and version of the created object are sent along its id
+                        None => signature.extend([
+                            ArgumentType::Str(AllowNull::No),
+                            ArgumentType::Uint,
+                            ArgumentType::NewId,
+                        ]),
+                    },
+                    other => {
+                        return Err(XmlError::new(format!(
+                            "unknown argument type {} in message {}",
+                            other, msg.name
+                        )))
+                    }
+                }
+            }
+            descs.push(MessageDesc {
+                name: leak_str(&msg.name),
+                signature: Box::leak(signature.into_boxed_slice()),
+                since: msg.since,
+                is_destructor: msg.is_destructor,
+                child_interface,
+                arg_interfaces: Box::leak(arg_interfaces.into_boxed_slice()),
+            });
+        }
+        Ok(Box::leak(descs.into_boxed_slice()))
+    }
+
+    fn resolve_child(&mut self, name: &str) -> Result<&'static Interface, XmlError> {
+        match self.parsed.iter().position(|iface| iface.name == name) {
+            Some(idx) => self.build(idx),
+            None => Ok(self.resolve_arg(name)),
+        }
+    }
+
+    fn resolve_arg(&mut self, name: &str) -> &'static Interface {
+        let local = self.parsed.iter().position(|iface| iface.name == name);
+        if let Some(iface) = local.and_then(|idx| self.built.get(&idx)) {
+            return iface;
+        }
+        if let Some(iface) = self.known.iter().find(|iface| iface.name == name) {
+            return iface;
+        }
+        // object arguments are checked by name, so an interface that is not built yet can be replaced
+        self.stubs.entry(name.to_owned()).or_insert_with(|| {
+            Box::leak(Box::new(Interface {
+                name: leak_str(name),
+                version: 0,
+                requests: &[],
+                events: &[],
+                c_ptr: None,
+            }))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    mod interfaces {
+        use crate as wayland_backend;
+        wayland_scanner::generate_interfaces!(
+            "../wayland-scanner/tests/scanner_assets/test-protocol.xml"
+        );
+    }
+
+    fn assert_same_messages(loaded: &[MessageDesc], generated: &[MessageDesc]) {
+        assert_eq!(loaded.len(), generated.len());
+        for (loaded, generated) in loaded.iter().zip(generated) {
+            assert_eq!(loaded.name, generated.name);
+            assert_eq!(loaded.signature, generated.signature);
+            assert_eq!(loaded.since, generated.since);
+            assert_eq!(loaded.is_destructor, generated.is_destructor);
+            assert_eq!(
+                loaded.child_interface.map(|iface| iface.name),
+                generated.child_interface.map(|iface| iface.name)
+            );
+            let names =
+                |list: &[&Interface]| list.iter().map(|iface| iface.name).collect::<Vec<_>>();
+            assert_eq!(names(loaded.arg_interfaces), names(generated.arg_interfaces));
+        }
+    }
+
+    #[test]
+    fn load_test_protocol() {
+        let xml =
+            std::fs::read_to_string("../wayland-scanner/tests/scanner_assets/test-protocol.xml")
+                .unwrap();
+        let loaded = Interface::from_xml(&xml, &[]).unwrap();
+
+        let generated = [
+            &interfaces::WL_DISPLAY_INTERFACE,
+            &interfaces::WL_REGISTRY_INTERFACE,
+            &interfaces::WL_CALLBACK_INTERFACE,
+            &interfaces::TEST_GLOBAL_INTERFACE,
+            &interfaces::SECONDARY_INTERFACE,
+            &interfaces::TERTIARY_INTERFACE,
+            &interfaces::QUAD_INTERFACE,
+        ];
+        assert_eq!(loaded.len(), generated.len());
+        for (loaded, generated) in loaded.iter().zip(generated) {
+            assert_eq!(loaded.name, generated.name);
+            assert_eq!(loaded.version, generated.version);
+            assert!(loaded.c_ptr.is_none());
+            assert_same_messages(loaded.requests, generated.requests);
+            assert_same_messages(loaded.events, generated.events);
+        }
+    }
+
+    #[test]
+    fn known_and_created_interfaces() {
+        let xml = r#"<protocol name="ext">
+            <interface name="ext_manager" version="2">
+                <request name="get_child">
+                    <arg name="id" type="new_id" interface="ext_child"/>
+                    <arg name="surface" type="object" interface="wl_callback"/>
+                </request>
+            </interface>
+            <interface name="ext_child" version="2">
+                <request name="destroy" type="destructor"/>
+                <event name="parent">
+                    <arg name="manager" type="object" interface="ext_manager" allow-null="true"/>
+                </event>
+            </interface>
+        </protocol>"#;
+        let loaded = Interface::from_xml(xml, &[&interfaces::WL_CALLBACK_INTERFACE]).unwrap();
+
+        let get_child = &loaded[0].requests[0];
+        // the created interface is the complete one
+        assert!(std::ptr::eq(get_child.child_interface.unwrap(), loaded[1]));
+        assert!(std::ptr::eq(get_child.arg_interfaces[0], &interfaces::WL_CALLBACK_INTERFACE));
+        assert!(loaded[1].requests[0].is_destructor);
+        // ext_manager is not built yet when ext_child is, so it is referenced by name only
+        let parent = &loaded[1].events[0];
+        assert_eq!(parent.signature, &[ArgumentType::Object(AllowNull::Yes)]);
+        assert!(crate::protocol::same_interface(parent.arg_interfaces[0], loaded[0]));
+    }
+
+    #[test]
+    fn invalid_protocols() {
+        let cycle = r#"<protocol name="cycle">
+            <interface name="first" version="1">
+                <request name="make"><arg name="id" type="new_id" interface="first"/></request>
+            </interface>
+        </protocol>"#;
+        assert!(Interface::from_xml(cycle, &[]).is_err());
+
+        let duplicate = r#"<protocol name="duplicate">
+            <interface name="first" version="1"/>
+            <interface name="first" version="1"/>
+        </protocol>"#;
+        assert!(Interface::from_xml(duplicate, &[]).is_err());
+
+        let bad_type = r#"<protocol name="bad">
+            <interface name="first" version="1">
+                <request name="set"><arg name="value" type="float"/></request>
+            </interface>
+        </protocol>"#;
+        assert!(Interface::from_xml(bad_type, &[]).is_err());
+
+        assert!(Interface::from_xml("<protocol name=\"unclosed\"><interface", &[]).is_err());
+    }
+}
@@ -144,6 +144,9 @@ mod server {
 
         // Backend
         assert_impl!(server::Backend<()>: Send, Sync);
+
+        // FlushOutcome
+        assert_impl!(server::FlushOutcome: std::fmt::Debug, Clone, Default, Send, Sync);
     }
 }
 
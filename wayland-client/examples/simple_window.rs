@@ -18,7 +18,7 @@ fn main() {
     let qhandle = event_queue.handle();
 
     let display = conn.display();
-    display.get_registry(&qhandle, ());
+    let _registry = display.get_registry(&qhandle, ());
 
     let mut state = State {
         running: true,
@@ -1,9 +1,7 @@
 use std::{
-    env, fmt,
-    io::ErrorKind,
-    os::unix::io::{AsFd, AsRawFd, BorrowedFd, FromRawFd, OwnedFd},
+    io::{ErrorKind, Read, Write},
+    os::unix::io::{AsFd, AsRawFd, BorrowedFd, OwnedFd},
     os::unix::net::UnixStream,
-    path::PathBuf,
     sync::{
         atomic::{AtomicBool, Ordering},
         Arc,
@@ -11,10 +9,16 @@ use std::{
 };
 
 use wayland_backend::{
-    client::{Backend, InvalidId, ObjectData, ObjectId, ReadEventsGuard, WaylandError},
-    protocol::{ObjectInfo, ProtocolError},
+    client::{
+        Backend, ConnectError, InvalidId, ObjectData, ObjectId, ReadEventsGuard, WaylandError,
+        WeakBackend,
+    },
+    protocol::{Message, ObjectInfo, ProtocolError},
 };
 
+#[cfg(feature = "object_hooks")]
+use wayland_backend::client::EventObserver;
+
 use crate::{protocol::wl_display::WlDisplay, EventQueue, Proxy};
 
 /// The Wayland connection
@@ -34,66 +38,32 @@ use crate::{protocol::wl_display::WlDisplay, EventQueue, Proxy};
 /// In case you need to plug yourself into an external Wayland connection that you don't control, you'll
 /// likely get access to it as a [`Backend`], in which case you can create a [`Connection`] from it using
 /// the [`from_backend()`][Self::from_backend()] method.
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone)]
 pub struct Connection {
     pub(crate) backend: Backend,
+    interrupt: Option<Arc<Interrupt>>,
 }
 
+impl PartialEq for Connection {
+    fn eq(&self, other: &Self) -> bool {
+        self.backend == other.backend
+    }
+}
+
+impl Eq for Connection {}
+
 impl Connection {
     /// Try to connect to the Wayland server following the environment
     ///
     /// This is the standard way to initialize a Wayland connection.
     pub fn connect_to_env() -> Result<Self, ConnectError> {
-        let stream = if let Ok(txt) = env::var("WAYLAND_SOCKET") {
-            // We should connect to the provided WAYLAND_SOCKET
-            let fd = txt.parse::<i32>().map_err(|_| ConnectError::InvalidFd)?;
-            let fd = unsafe { OwnedFd::from_raw_fd(fd) };
-            // remove the variable so any child processes don't see it
-            env::remove_var("WAYLAND_SOCKET");
-            // set the CLOEXEC flag on this FD
-            let flags = rustix::io::fcntl_getfd(&fd);
-            let result = flags
-                .map(|f| f | rustix::io::FdFlags::CLOEXEC)
-                .and_then(|f| rustix::io::fcntl_setfd(&fd, f));
-            match result {
-                Ok(_) => {
-                    // setting the O_CLOEXEC worked
-                    UnixStream::from(fd)
-                }
-                Err(_) => {
-                    // something went wrong in F_GETFD or F_SETFD
-                    return Err(ConnectError::InvalidFd);
-                }
-            }
-        } else {
-            let socket_name = env::var_os("WAYLAND_DISPLAY")
-                .map(Into::<PathBuf>::into)
-                .ok_or(ConnectError::NoCompositor)?;
-
-            let socket_path = if socket_name.is_absolute() {
-                socket_name
-            } else {
-                let mut socket_path = env::var_os("XDG_RUNTIME_DIR")
-                    .map(Into::<PathBuf>::into)
-                    .ok_or(ConnectError::NoCompositor)?;
-                if !socket_path.is_absolute() {
-                    return Err(ConnectError::NoCompositor);
-                }
-                socket_path.push(socket_name);
-                socket_path
-            };
-
-            UnixStream::connect(socket_path).map_err(|_| ConnectError::NoCompositor)?
-        };
-
-        let backend = Backend::connect(stream).map_err(|_| ConnectError::NoWaylandLib)?;
-        Ok(Self { backend })
+        Backend::connect_to_env().map(Self::from_backend)
     }
 
     /// Initialize a Wayland connection from an already existing Unix stream
     pub fn from_socket(stream: UnixStream) -> Result<Self, ConnectError> {
         let backend = Backend::connect(stream).map_err(|_| ConnectError::NoWaylandLib)?;
-        Ok(Self { backend })
+        Ok(Self::from_backend(backend))
     }
 
     /// Get the `WlDisplay` associated with this connection
@@ -109,7 +79,31 @@ impl Connection {
 
     /// Wrap an existing [`Backend`] into a [`Connection`]
     pub fn from_backend(backend: Backend) -> Self {
-        Self { backend }
+        Self { backend, interrupt: None }
+    }
+
+    /// Create a [`Connection`] from a foreign `*mut wl_display`
+    ///
+    /// This is the checked counterpart of [`Backend::from_foreign_display()`], for plugging into a
+    /// Wayland connection created by a C library or by a toolkit. Like it, the returned connection is in
+    /// "guest" mode and does not close the Wayland connection when dropped.
+    ///
+    /// Returns an error if the pointer is null.
+    ///
+    /// Requires the `system` cargo feature.
+    ///
+    /// # Safety
+    ///
+    /// If not null, `ptr` must point to a valid `wl_display`, which must remain live as long as the
+    /// returned [`Connection`] (or its clones) exist.
+    #[cfg(feature = "system")]
+    pub unsafe fn from_display_ptr(
+        ptr: *mut wayland_sys::client::wl_display,
+    ) -> Result<Self, InvalidPtr> {
+        if ptr.is_null() {
+            return Err(InvalidPtr);
+        }
+        Ok(Self::from_backend(unsafe { Backend::from_foreign_display(ptr) }))
     }
 
     /// Get the [`Backend`] underlying this [`Connection`]
@@ -117,6 +111,62 @@ impl Connection {
         self.backend.clone()
     }
 
+    /// Get a [`WeakConnection`] to this connection
+    ///
+    /// Unlike a [`Connection`], it does not keep the Wayland connection alive, so it can be stored in the
+    /// user data of your objects without creating a reference cycle.
+    pub fn downgrade(&self) -> WeakConnection {
+        WeakConnection { backend: self.backend.downgrade(), interrupt: self.interrupt.clone() }
+    }
+
+    /// Get the file descriptor of the Wayland connection, for polling
+    ///
+    /// Unlike [`ReadEventsGuard::connection_fd()`], it is not tied to a read guard, so it can be
+    /// registered once in an external event loop (like `epoll`) when the connection is created. This
+    /// is the same file descriptor as the one provided by the [`AsFd`] implementation of [`Connection`].
+    ///
+    /// This registration is only meant to be notified of readiness: the actual reading must still be done
+    /// between [`prepare_read()`][Self::prepare_read()] and [`ReadEventsGuard::read()`], so that it is
+    /// synchronized with the other threads possibly reading the socket.
+    pub fn backend_fd(&self) -> BorrowedFd<'_> {
+        self.as_fd()
+    }
+
+    /// Allow this connection to be interrupted with [`interrupt()`][Self::interrupt()]
+    ///
+    /// This allocates the file descriptor used to wake up a thread blocked reading events. It is shared with
+    /// the clones of this [`Connection`] and the [`EventQueue`]s created from it *after* this call, so it
+    /// should be invoked right after connecting. Invoking it several times has no additional effect.
+    pub fn enable_interrupt(&mut self) -> std::io::Result<()> {
+        if self.interrupt.is_none() {
+            self.interrupt = Some(Arc::new(Interrupt::new()?));
+        }
+        Ok(())
+    }
+
+    /// Wake up a thread blocked in [`EventQueue::blocking_dispatch()`]
+    ///
+    /// The blocked call returns `Ok(0)` if no event was received in the meantime, letting the thread check
+    /// some external state (for example requests queued by other threads that need to be flushed). If no
+    /// thread is currently blocked, the next call to [`EventQueue::blocking_dispatch()`] returns immediately.
+    /// Other blocking methods, like the roundtrips, are not interrupted.
+    ///
+    /// Returns an error of kind [`Unsupported`][ErrorKind::Unsupported] if
+    /// [`enable_interrupt()`][Self::enable_interrupt()] was not called beforehand.
+    pub fn interrupt(&self) -> std::io::Result<()> {
+        match self.interrupt {
+            Some(ref interrupt) => interrupt.notify(),
+            None => Err(std::io::Error::new(
+                ErrorKind::Unsupported,
+                "interrupts are not enabled on this connection",
+            )),
+        }
+    }
+
+    pub(crate) fn interrupt_source(&self) -> Option<&Interrupt> {
+        self.interrupt.as_deref()
+    }
+
     /// Flush pending outgoing events to the server
     ///
     /// This needs to be done regularly to ensure the server receives all your requests, though several
@@ -144,6 +194,10 @@ impl Connection {
     /// preceding requests. This is notably useful during the initial setup of an app, to wait for
     /// the initial state from the server.
     ///
+    /// The `wl_callback` used for the synchronization is not attached to any event queue, so this does
+    /// not depend on any queue being dispatched. The events received in the meantime are only read and
+    /// buffered in their respective queues.
+    ///
     /// See [`EventQueue::roundtrip()`] for a version that includes the dispatching of the event queue.
     pub fn roundtrip(&self) -> Result<usize, WaylandError> {
         let done = Arc::new(SyncData::default());
@@ -161,7 +215,7 @@ impl Connection {
             self.backend.flush()?;
 
             if let Some(guard) = self.backend.prepare_read() {
-                dispatched += blocking_read(guard)?;
+                dispatched += blocking_read(guard, None)?;
             } else {
                 dispatched += self.backend.dispatch_inner_queue()?;
             }
@@ -201,6 +255,28 @@ impl Connection {
         self.backend.send_request(msg, data, child_spec)
     }
 
+    /// Send a raw request for an object identified by its [`ObjectId`]
+    ///
+    /// This allows sending requests for an object without having access to its typed proxy, for
+    /// example from a thread that only received its [`ObjectId`]. The object the request is sent to
+    /// is the `sender_id` of the message.
+    ///
+    /// If the request creates an object, `data` will be its [`ObjectData`] and the id of the new object
+    /// is returned. Otherwise `data` is ignored and a null [`ObjectId`] is returned. The interface and
+    /// version of the new object are inferred from the protocol.
+    ///
+    /// **Panic:** This method panics if the message does not match the signature of the request, or if it is
+    /// a generic constructor (like `wl_registry.bind`) whose interface cannot be inferred. Use
+    /// [`Backend::send_request()`] for those.
+    pub fn send_raw_request(
+        &self,
+        msg: Message<ObjectId, BorrowedFd<'_>>,
+        data: Option<Arc<dyn ObjectData>>,
+    ) -> Result<ObjectId, InvalidId> {
+        let msg = msg.map_fd(|fd| fd.as_raw_fd());
+        self.backend.send_request(msg, data, None)
+    }
+
     /// Get the protocol information related to given object ID
     pub fn object_info(&self, id: ObjectId) -> Result<ObjectInfo, InvalidId> {
         self.backend.info(id)
@@ -214,23 +290,166 @@ impl Connection {
     pub fn get_object_data(&self, id: ObjectId) -> Result<Arc<dyn ObjectData>, InvalidId> {
         self.backend.get_data(id)
     }
+
+    /// Register a callback invoked every time an object is created on this connection
+    ///
+    /// It is invoked for objects created by your requests as well as objects created by the server
+    /// through events, and replaces any previously registered callback. The callback may be invoked
+    /// while internal locks are held, so it must not send requests or otherwise use the connection.
+    ///
+    /// Requires the `object_hooks` cargo feature.
+    #[cfg(feature = "object_hooks")]
+    pub fn on_object_created<F: Fn(ObjectId) + Send + Sync + 'static>(&self, f: F) {
+        self.backend.set_object_created_hook(Some(Arc::new(f)))
+    }
+
+    /// Register a callback invoked every time an object of this connection is destroyed
+    ///
+    /// It replaces any previously registered callback. The callback may be invoked while internal locks
+    /// are held, so it must not send requests or otherwise use the connection.
+    ///
+    /// Requires the `object_hooks` cargo feature.
+    #[cfg(feature = "object_hooks")]
+    pub fn on_object_destroyed<F: Fn(ObjectId) + Send + Sync + 'static>(&self, f: F) {
+        self.backend.set_object_destroyed_hook(Some(Arc::new(f)))
+    }
+
+    /// Observe all the events received on this connection
+    ///
+    /// The callback is given a reference to every event received by the objects of this connection, whatever
+    /// their event queue, without taking over their dispatching: the events are still delivered to their
+    /// queues as usual, in the same order. It is invoked when the events are read from the socket, before
+    /// they are dispatched, and may thus run on any thread reading the socket. This is mostly useful for
+    /// debugging tools.
+    ///
+    /// The events are observed until the returned [`SpyQueue`] is dropped. Only one spy queue is active
+    /// at a time on a connection: creating a new one replaces the previous one. The callback may be invoked
+    /// while internal locks are held, so it must not send requests or otherwise use the connection.
+    ///
+    /// Requires the `object_hooks` cargo feature.
+    #[cfg(feature = "object_hooks")]
+    pub fn new_spy_queue<F: Fn(&Message<ObjectId, OwnedFd>) + Send + Sync + 'static>(
+        &self,
+        f: F,
+    ) -> SpyQueue {
+        let active = Arc::new(AtomicBool::new(true));
+        let observing = active.clone();
+        let observer: EventObserver = Arc::new(move |msg: &Message<ObjectId, OwnedFd>| {
+            if observing.load(Ordering::Acquire) {
+                f(msg)
+            }
+        });
+        self.backend.set_event_observer(Some(observer.clone()));
+        SpyQueue { active, backend: self.backend.downgrade(), observer }
+    }
 }
 
-pub(crate) fn blocking_read(guard: ReadEventsGuard) -> Result<usize, WaylandError> {
+/// An observer of the events of a connection
+///
+/// See [`Connection::new_spy_queue()`]. The events are no longer observed once it is dropped.
+///
+/// Requires the `object_hooks` cargo feature.
+#[cfg(feature = "object_hooks")]
+pub struct SpyQueue {
+    active: Arc<AtomicBool>,
+    backend: WeakBackend,
+    observer: EventObserver,
+}
+
+#[cfg(feature = "object_hooks")]
+impl std::fmt::Debug for SpyQueue {
+    #[cfg_attr(coverage, coverage(off))]
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SpyQueue").field("active", &self.active).finish_non_exhaustive()
+    }
+}
+
+#[cfg(feature = "object_hooks")]
+impl Drop for SpyQueue {
+    fn drop(&mut self) {
+        self.active.store(false, Ordering::Release);
+        // a spy queue created since then has replaced our observer and must keep its own
+        if let Some(backend) = self.backend.upgrade() {
+            backend.remove_event_observer(&self.observer);
+        }
+    }
+}
+
+/// An error generated when trying to create a [`Connection`] from a null `wl_display` pointer
+///
+/// Requires the `system` cargo feature.
+#[cfg(feature = "system")]
+#[derive(Clone, Debug)]
+pub struct InvalidPtr;
+
+#[cfg(feature = "system")]
+impl std::error::Error for InvalidPtr {}
+
+#[cfg(feature = "system")]
+impl std::fmt::Display for InvalidPtr {
+    #[cfg_attr(coverage, coverage(off))]
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Invalid wl_display pointer")
+    }
+}
+
+/// A weak handle to a [`Connection`]
+///
+/// This handle behaves similarly to [`Weak`][std::sync::Weak], it can be obtained with
+/// [`Connection::downgrade()`] and gives access to the connection as long as it is alive, without
+/// preventing it from being dropped.
+#[derive(Debug, Clone)]
+pub struct WeakConnection {
+    backend: WeakBackend,
+    interrupt: Option<Arc<Interrupt>>,
+}
+
+impl WeakConnection {
+    /// Try to upgrade this weak handle to a [`Connection`]
+    ///
+    /// Returns [`None`] if the connection was already dropped.
+    pub fn upgrade(&self) -> Option<Connection> {
+        let backend = self.backend.upgrade()?;
+        Some(Connection { backend, interrupt: self.interrupt.clone() })
+    }
+}
+
+/// Block until the connection is readable and read its events
+///
+/// If `interrupt` is provided and gets notified while waiting, this returns `Ok(0)` without reading.
+pub(crate) fn blocking_read(
+    guard: ReadEventsGuard,
+    interrupt: Option<&Interrupt>,
+) -> Result<usize, WaylandError> {
     let fd = guard.connection_fd();
-    let mut fds = [rustix::event::PollFd::new(
-        &fd,
-        rustix::event::PollFlags::IN | rustix::event::PollFlags::ERR,
-    )];
+    let interrupt_fd = interrupt.map(|interrupt| interrupt.reader.as_fd()).unwrap_or(fd);
+    let mut fds = [
+        rustix::event::PollFd::new(
+            &fd,
+            rustix::event::PollFlags::IN | rustix::event::PollFlags::ERR,
+        ),
+        rustix::event::PollFd::new(&interrupt_fd, rustix::event::PollFlags::IN),
+    ];
+    let nfds = if interrupt.is_some() { 2 } else { 1 };
 
     loop {
-        match rustix::event::poll(&mut fds, -1) {
+        match rustix::event::poll(&mut fds[..nfds], -1) {
             Ok(_) => break,
             Err(rustix::io::Errno::INTR) => continue,
             Err(e) => return Err(WaylandError::Io(e.into())),
         }
     }
 
+    if let Some(interrupt) = interrupt {
+        if !fds[1].revents().is_empty() {
+            interrupt.drain();
+            if fds[0].revents().is_empty() {
+                // dropping the guard cancels the read
+                return Ok(0);
+            }
+        }
+    }
+
     // at this point the fd is ready
     match guard.read() {
         Ok(n) => Ok(n),
@@ -240,32 +459,35 @@ pub(crate) fn blocking_read(guard: ReadEventsGuard) -> Result<usize, WaylandErro
     }
 }
 
-/// An error when trying to establish a Wayland connection.
+/// The wakeup mechanism behind [`Connection::interrupt()`]
 #[derive(Debug)]
-pub enum ConnectError {
-    /// The wayland library could not be loaded.
-    NoWaylandLib,
-
-    /// Could not find wayland compositor
-    NoCompositor,
-
-    /// `WAYLAND_SOCKET` was set but contained garbage
-    InvalidFd,
+pub(crate) struct Interrupt {
+    reader: UnixStream,
+    writer: UnixStream,
 }
 
-impl std::error::Error for ConnectError {}
+impl Interrupt {
+    fn new() -> std::io::Result<Self> {
+        let (reader, writer) = UnixStream::pair()?;
+        reader.set_nonblocking(true)?;
+        writer.set_nonblocking(true)?;
+        Ok(Self { reader, writer })
+    }
+
+    fn notify(&self) -> std::io::Result<()> {
+        match (&self.writer).write(&[1]) {
+            Ok(_) => Ok(()),
+            // the socket is full, a wakeup is already pending
+            Err(e) if e.kind() == ErrorKind::WouldBlock => Ok(()),
+            Err(e) => Err(e),
+        }
+    }
 
-impl fmt::Display for ConnectError {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        match self {
-            ConnectError::NoWaylandLib => {
-                write!(f, "The wayland library could not be loaded")
-            }
-            ConnectError::NoCompositor => {
-                write!(f, "Could not find wayland compositor")
-            }
-            ConnectError::InvalidFd => {
-                write!(f, "WAYLAND_SOCKET was set but contained garbage")
+    fn drain(&self) {
+        let mut buf = [0u8; 64];
+        while let Ok(n) = (&self.reader).read(&mut buf) {
+            if n < buf.len() {
+                break;
             }
         }
     }
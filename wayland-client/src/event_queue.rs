@@ -1,10 +1,13 @@
-use std::any::Any;
-use std::collections::VecDeque;
+use std::any::{Any, TypeId};
+use std::cell::RefCell;
+use std::collections::{HashMap, VecDeque};
 use std::convert::Infallible;
 use std::marker::PhantomData;
 use std::os::unix::io::{AsFd, BorrowedFd, OwnedFd};
-use std::sync::{atomic::Ordering, Arc, Condvar, Mutex};
+use std::sync::{atomic::Ordering, Arc, Condvar, Mutex, MutexGuard};
 use std::task;
+#[cfg(feature = "profiling")]
+use std::time::{Duration, Instant};
 
 use wayland_backend::{
     client::{Backend, ObjectData, ObjectId, ReadEventsGuard, WaylandError},
@@ -118,6 +121,37 @@ where
         qhandle: &QueueHandle<State>,
     );
 
+    /// Called with a run of consecutive events received by the same object
+    ///
+    /// **Note:** Lone events never reach this method. An event that is not directly followed by another
+    /// event of the same object does not form a batch, it is given to [`Dispatch::event()`] directly. An
+    /// implementation overriding this method must thus still handle all the events in
+    /// [`Dispatch::event()`].
+    ///
+    /// The event queue groups the events of an object that directly follow each other in the queue, and
+    /// hands them to this method at once. This allows processing related events together, for example the
+    /// events of a `wl_pointer` or `wl_touch` along with the `frame` event ending them. A batch only
+    /// contains the events already received: if the end of a frame has not been read from the socket yet,
+    /// its remaining events are delivered in a later batch.
+    ///
+    /// If an event of the run cannot be parsed, the events before it are still handed to this method and
+    /// the dispatching then fails with a [`DispatchError::BadMessage`]. The events following the invalid one
+    /// in the run are dropped along with the file descriptors they carry.
+    ///
+    /// The default implementation calls [`Dispatch::event()`] for each of the events, in order.
+    fn event_batch(
+        state: &mut State,
+        proxy: &I,
+        events: Vec<I::Event>,
+        data: &UserData,
+        conn: &Connection,
+        qhandle: &QueueHandle<State>,
+    ) {
+        for event in events {
+            Self::event(state, proxy, event, data, conn, qhandle);
+        }
+    }
+
     /// Method used to initialize the user-data of objects created by events
     ///
     /// If the interface does not have any such event, you can ignore it. If not, the
@@ -190,6 +224,7 @@ macro_rules! event_created_child {
 type QueueCallback<State> = fn(
     &Connection,
     Message<ObjectId, OwnedFd>,
+    Vec<Message<ObjectId, OwnedFd>>,
     &mut State,
     Arc<dyn ObjectData>,
     &QueueHandle<State>,
@@ -197,6 +232,22 @@ type QueueCallback<State> = fn(
 
 struct QueueEvent<State>(QueueCallback<State>, Message<ObjectId, OwnedFd>, Arc<dyn ObjectData>);
 
+/// Consecutive events of the same object, dispatched together
+///
+/// The events following the first one are kept apart, so that a lone event does not need any allocation.
+struct QueueBatch<State>(
+    QueueCallback<State>,
+    Message<ObjectId, OwnedFd>,
+    Vec<Message<ObjectId, OwnedFd>>,
+    Arc<dyn ObjectData>,
+);
+
+impl<State> QueueBatch<State> {
+    fn len(&self) -> usize {
+        1 + self.2.len()
+    }
+}
+
 impl<State> std::fmt::Debug for QueueEvent<State> {
     #[cfg_attr(coverage, coverage(off))]
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
@@ -318,13 +369,19 @@ impl<State> std::fmt::Debug for QueueEvent<State> {
 pub struct EventQueue<State> {
     handle: QueueHandle<State>,
     conn: Connection,
+    #[cfg(feature = "profiling")]
+    profiler: Option<Box<DispatchProfiler>>,
 }
 
+#[cfg(feature = "profiling")]
+type DispatchProfiler = dyn Fn(ObjectId, Duration) + Send + Sync;
+
 #[derive(Debug)]
 pub(crate) struct EventQueueInner<State> {
     queue: VecDeque<QueueEvent<State>>,
     freeze_count: usize,
     waker: Option<task::Waker>,
+    contexts: HashMap<TypeId, Arc<dyn Any + Send + Sync>>,
 }
 
 impl<State> EventQueueInner<State> {
@@ -345,6 +402,20 @@ impl<State> EventQueueInner<State> {
             }
         }
     }
+
+    /// Pop the next event of the queue, along with the following events sent by the same object
+    fn pop_batch(&mut self) -> Option<QueueBatch<State>> {
+        let QueueEvent(cb, msg, odata) = self.queue.pop_front()?;
+        let mut following = Vec::new();
+        while matches!(
+            self.queue.front(),
+            Some(QueueEvent(_, next, _)) if next.sender_id == msg.sender_id
+        ) {
+            let QueueEvent(_, next, _) = self.queue.pop_front().unwrap();
+            following.push(next);
+        }
+        Some(QueueBatch(cb, msg, following, odata))
+    }
 }
 
 impl<State> std::fmt::Debug for EventQueue<State> {
@@ -361,14 +432,61 @@ impl<State> AsFd for EventQueue<State> {
     }
 }
 
+thread_local! {
+    /// Addresses of the queues currently being dispatched on this thread
+    static DISPATCHING: RefCell<Vec<usize>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Marks a queue as being dispatched on the current thread for as long as it is alive
+///
+/// Dispatching a queue from within one of its own handlers would alias the `&mut State` borrow, so
+/// this is treated as a bug and causes a panic.
+struct ReentrancyGuard {
+    addr: usize,
+}
+
+impl ReentrancyGuard {
+    fn enter<State>(inner: &Arc<Mutex<EventQueueInner<State>>>) -> Self {
+        let addr = Arc::as_ptr(inner) as *const () as usize;
+        DISPATCHING.with(|dispatching| {
+            let mut dispatching = dispatching.borrow_mut();
+            if dispatching.contains(&addr) {
+                panic!(
+                    "Reentrant dispatch detected: an EventQueue was dispatched while it was already being dispatched on this thread."
+                );
+            }
+            dispatching.push(addr);
+        });
+        Self { addr }
+    }
+}
+
+impl Drop for ReentrancyGuard {
+    fn drop(&mut self) {
+        // `try_with` as this may run during thread teardown while unwinding
+        let _ = DISPATCHING.try_with(|dispatching| {
+            let mut dispatching = dispatching.borrow_mut();
+            if let Some(pos) = dispatching.iter().rposition(|&a| a == self.addr) {
+                dispatching.remove(pos);
+            }
+        });
+    }
+}
+
 impl<State> EventQueue<State> {
     pub(crate) fn new(conn: Connection) -> Self {
         let inner = Arc::new(Mutex::new(EventQueueInner {
             queue: VecDeque::new(),
             freeze_count: 0,
             waker: None,
+            contexts: HashMap::new(),
         }));
-        Self { handle: QueueHandle { inner }, conn }
+        Self {
+            handle: QueueHandle { inner },
+            conn,
+            #[cfg(feature = "profiling")]
+            profiler: None,
+        }
     }
 
     /// Get a [`QueueHandle`] for this event queue
@@ -384,8 +502,58 @@ impl<State> EventQueue<State> {
     /// the [`Dispatch`] implementations on the provided `&mut D`.
     ///
     /// Note: this may block if another thread has frozen the queue.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if it is invoked while this queue is already being dispatched on the current
+    /// thread (for example from within one of its [`Dispatch`] handlers).
     pub fn dispatch_pending(&mut self, data: &mut State) -> Result<usize, DispatchError> {
-        Self::dispatching_impl(&self.conn, &self.handle, data)
+        self.dispatching_impl(data, None)
+    }
+
+    /// Register a callback measuring the time spent dispatching the events of each object
+    ///
+    /// Every time the [`Dispatch`] implementation of an object is invoked by this queue, the callback is
+    /// given the ID of the object and the time spent in the handler. Consecutive events of the same object
+    /// are measured together, as they are handed to [`Dispatch::event_batch()`] at once. This replaces any
+    /// previously registered callback.
+    ///
+    /// This is meant to find the handlers that take too long, for example the ones delaying the drawing
+    /// of a frame, without instrumenting each of them.
+    ///
+    /// Requires the `profiling` cargo feature.
+    #[cfg(feature = "profiling")]
+    pub fn set_dispatch_profiler<F: Fn(ObjectId, Duration) + Send + Sync + 'static>(
+        &mut self,
+        f: F,
+    ) {
+        self.profiler = Some(Box::new(f));
+    }
+
+    /// Remove the callback registered with [`set_dispatch_profiler()`][Self::set_dispatch_profiler()]
+    ///
+    /// Requires the `profiling` cargo feature.
+    #[cfg(feature = "profiling")]
+    pub fn clear_dispatch_profiler(&mut self) {
+        self.profiler = None;
+    }
+
+    /// Number of events buffered in this queue and not yet dispatched
+    ///
+    /// This includes the events already read from the socket by the backend but not yet sorted into their
+    /// queues. It does not read the socket, and the counted events are left in the queue for
+    /// [`dispatch_pending()`][Self::dispatch_pending] to process.
+    pub fn pending_count(&self) -> usize {
+        // See dispatching_impl() for why the error is ignored
+        let _ = self.conn.backend.dispatch_inner_queue();
+        self.handle.inner.lock().unwrap().queue.len()
+    }
+
+    /// Whether this queue holds events that have not been dispatched yet
+    ///
+    /// See [`pending_count()`][Self::pending_count].
+    pub fn has_pending(&self) -> bool {
+        self.pending_count() > 0
     }
 
     /// Block waiting for events and dispatch them
@@ -394,9 +562,20 @@ impl<State> EventQueue<State> {
     /// pending events it will also flush the connection and block waiting for the Wayland server to send an
     /// event.
     ///
+    /// If interrupts were enabled on the [`Connection`] of this queue, another thread can make this method
+    /// return early using [`Connection::interrupt()`], in which case it may return `Ok(0)`.
+    ///
     /// A simple app event loop can consist of invoking this method in a loop.
     pub fn blocking_dispatch(&mut self, data: &mut State) -> Result<usize, DispatchError> {
-        let dispatched = self.dispatch_pending(data)?;
+        self.blocking_dispatch_impl(data, None)
+    }
+
+    fn blocking_dispatch_impl(
+        &mut self,
+        data: &mut State,
+        mut counts: Option<&mut Vec<(ObjectId, usize)>>,
+    ) -> Result<usize, DispatchError> {
+        let dispatched = self.dispatching_impl(data, counts.as_deref_mut())?;
         if dispatched > 0 {
             return Ok(dispatched);
         }
@@ -404,10 +583,40 @@ impl<State> EventQueue<State> {
         self.conn.flush()?;
 
         if let Some(guard) = self.conn.prepare_read() {
-            crate::conn::blocking_read(guard)?;
+            crate::conn::blocking_read(guard, self.conn.interrupt_source())?;
         }
 
-        self.dispatch_pending(data)
+        self.dispatching_impl(data, counts)
+    }
+
+    /// Read and dispatch events once the Wayland socket is known to be readable
+    ///
+    /// This is meant for event loops polling the Wayland socket along with other file descriptors: once
+    /// the [`backend_fd()`][Connection::backend_fd()] of the connection is reported as readable, this method
+    /// takes care of the [`prepare_read()`][Self::prepare_read()] and [`ReadEventsGuard::read()`] sequence
+    /// and dispatches the events of this queue, including the ones that were already pending.
+    ///
+    /// If the socket turns out not to be readable, for example because another thread read the events
+    /// first, this does not block and returns `Ok(0)` if nothing was dispatched.
+    ///
+    /// This does not flush the connection: you should invoke [`flush()`][Self::flush()] before going back
+    /// to polling.
+    pub fn dispatch_after_readable(&mut self, data: &mut State) -> Result<usize, DispatchError> {
+        let mut dispatched = self.dispatch_pending(data)?;
+
+        if let Some(guard) = self.conn.prepare_read() {
+            match guard.read() {
+                Ok(_) => {}
+                // spurious wakeup, there is nothing to read
+                Err(WaylandError::Io(e)) if e.kind() == std::io::ErrorKind::WouldBlock => {
+                    return Ok(dispatched)
+                }
+                Err(e) => return Err(e.into()),
+            }
+        }
+
+        dispatched += self.dispatch_pending(data)?;
+        Ok(dispatched)
     }
 
     /// Synchronous roundtrip
@@ -417,7 +626,37 @@ impl<State> EventQueue<State> {
     ///
     /// This function may be useful during initial setup of your app. This function may also be useful
     /// where you need to guarantee all requests prior to calling this function are completed.
+    ///
+    /// The `wl_callback` used for the synchronization is not attached to any event queue: its `done` event
+    /// is handled as soon as it is read from the socket, whichever queue triggered the read. The roundtrip
+    /// thus always completes, even if other queues are not dispatched and regardless of the queue the
+    /// other objects were created on. Only the events of this queue are dispatched.
     pub fn roundtrip(&mut self, data: &mut State) -> Result<usize, DispatchError> {
+        self.roundtrip_impl(data, None)
+    }
+
+    /// Synchronous roundtrip, counting the events dispatched to each object
+    ///
+    /// This behaves like [`roundtrip()`][Self::roundtrip()], but returns the number of events of each
+    /// object of this queue that were dispatched during the roundtrip, rather than their total. The objects
+    /// are listed in the order their first event was dispatched. This is mostly useful in tests, to check
+    /// exactly which events the server sent in response to some requests.
+    ///
+    /// The events handled directly by the backend, without going through an event queue, are not counted.
+    pub fn roundtrip_counted(
+        &mut self,
+        data: &mut State,
+    ) -> Result<Vec<(ObjectId, usize)>, DispatchError> {
+        let mut counts = Vec::new();
+        self.roundtrip_impl(data, Some(&mut counts))?;
+        Ok(counts)
+    }
+
+    fn roundtrip_impl(
+        &mut self,
+        data: &mut State,
+        mut counts: Option<&mut Vec<(ObjectId, usize)>>,
+    ) -> Result<usize, DispatchError> {
         let done = Arc::new(SyncData::default());
 
         let display = self.conn.display();
@@ -432,7 +671,7 @@ impl<State> EventQueue<State> {
         let mut dispatched = 0;
 
         while !done.done.load(Ordering::Relaxed) {
-            dispatched += self.blocking_dispatch(data)?;
+            dispatched += self.blocking_dispatch_impl(data, counts.as_deref_mut())?;
         }
 
         Ok(dispatched)
@@ -459,16 +698,59 @@ impl<State> EventQueue<State> {
 
     /// Flush pending outgoing events to the server
     ///
-    /// This needs to be done regularly to ensure the server receives all your requests.
-    /// /// This method is identical to [`Connection::flush()`].
+    /// This needs to be done regularly to ensure the server receives all your requests. This does not
+    /// read from the socket nor dispatch any event, so it can be used to send the requests queued so far
+    /// before waiting for the socket readiness in your own event loop.
+    ///
+    /// This method is identical to [`Connection::flush()`].
     pub fn flush(&self) -> Result<(), WaylandError> {
         self.conn.flush()
     }
 
+    /// Turn this event queue into a stream of raw messages
+    ///
+    /// Rather than being dispatched to [`Dispatch`] implementations, the messages received by the objects
+    /// assigned to this queue are returned as-is by the methods of [`RawEventStream`]. Objects created
+    /// with the [`ObjectData`] returned by [`QueueHandle::make_raw_data()`] are assigned to this queue
+    /// without requiring any [`Dispatch`] implementation, as are the objects they create.
+    ///
+    /// This is mostly useful for tooling, like protocol dumpers.
+    pub fn into_raw_stream(self) -> RawEventStream<State> {
+        RawEventStream { queue: self }
+    }
+
+    /// Dispatch a list of messages as if they had been received from the server
+    ///
+    /// Each message is handed to the object designated by its `sender_id`, bypassing the Wayland socket,
+    /// and the pending events of this queue are then dispatched like with
+    /// [`dispatch_pending()`][Self::dispatch_pending]. This is mostly useful to unit-test [`Dispatch`]
+    /// implementations with a deterministic sequence of events.
+    ///
+    /// The target objects must be alive, otherwise a [`DispatchError::BadMessage`] is returned. Messages
+    /// targeting objects of other event queues are queued there without being dispatched. Events creating
+    /// new objects are not supported, as their objects are not created by the backend.
+    pub fn dispatch_messages(
+        &mut self,
+        data: &mut State,
+        msgs: Vec<Message<ObjectId, OwnedFd>>,
+    ) -> Result<usize, DispatchError> {
+        for msg in msgs {
+            let odata = self.conn.backend.get_data(msg.sender_id.clone()).map_err(|_| {
+                DispatchError::BadMessage {
+                    sender_id: msg.sender_id.clone(),
+                    interface: msg.sender_id.interface().name,
+                    opcode: msg.opcode,
+                }
+            })?;
+            odata.event(&self.conn.backend, msg);
+        }
+        self.dispatch_pending(data)
+    }
+
     fn dispatching_impl(
-        backend: &Connection,
-        qhandle: &QueueHandle<State>,
+        &self,
         data: &mut State,
+        mut counts: Option<&mut Vec<(ObjectId, usize)>>,
     ) -> Result<usize, DispatchError> {
         // This call will most of the time do nothing, but ensure that if the Connection is in guest mode
         // from some external connection, only invoking `EventQueue::dispatch_pending()` will be enough to
@@ -476,16 +758,49 @@ impl<State> EventQueue<State> {
         //
         // We purposefully ignore the possible error, as that would make us early return in a way that might
         // lose events, and the potential socket error will be caught in other places anyway.
-        let mut dispatched = backend.backend.dispatch_inner_queue().unwrap_or_default();
+        let _guard = ReentrancyGuard::enter(&self.handle.inner);
+
+        let mut dispatched = self.conn.backend.dispatch_inner_queue().unwrap_or_default();
 
-        while let Some(QueueEvent(cb, msg, odata)) = Self::try_next(&qhandle.inner) {
-            cb(backend, msg, data, odata, qhandle)?;
-            dispatched += 1;
+        while let Some(batch) = Self::lock_unfrozen(&self.handle.inner).pop_batch() {
+            dispatched += batch.len();
+            if let Some(counts) = counts.as_deref_mut() {
+                let sender = &batch.1.sender_id;
+                match counts.iter_mut().find(|(id, _)| id == sender) {
+                    Some((_, count)) => *count += batch.len(),
+                    None => counts.push((sender.clone(), batch.len())),
+                }
+            }
+            self.dispatch_batch(batch, data)?;
         }
         Ok(dispatched)
     }
 
+    /// Invoke the handler of a batch of events, measuring it if a profiler is registered
+    fn dispatch_batch(
+        &self,
+        QueueBatch(cb, msg, following, odata): QueueBatch<State>,
+        data: &mut State,
+    ) -> Result<(), DispatchError> {
+        #[cfg(feature = "profiling")]
+        if let Some(profiler) = &self.profiler {
+            let id = msg.sender_id.clone();
+            let start = Instant::now();
+            let ret = cb(&self.conn, msg, following, data, odata, &self.handle);
+            profiler(id, start.elapsed());
+            return ret;
+        }
+        cb(&self.conn, msg, following, data, odata, &self.handle)
+    }
+
     fn try_next(inner: &Mutex<EventQueueInner<State>>) -> Option<QueueEvent<State>> {
+        Self::lock_unfrozen(inner).queue.pop_front()
+    }
+
+    /// Lock the queue, waiting for it to be unfrozen if it contains events
+    fn lock_unfrozen(
+        inner: &Mutex<EventQueueInner<State>>,
+    ) -> MutexGuard<'_, EventQueueInner<State>> {
         let mut lock = inner.lock().unwrap();
         if lock.freeze_count != 0 && !lock.queue.is_empty() {
             let waker = Arc::new(DispatchWaker { cond: Condvar::new() });
@@ -494,7 +809,7 @@ impl<State> EventQueue<State> {
                 lock = waker.cond.wait(lock).unwrap();
             }
         }
-        lock.queue.pop_front()
+        lock
     }
 
     /// Attempt to dispatch events from this queue, registering the current task for wakeup if no
@@ -547,6 +862,7 @@ impl<State> EventQueue<State> {
         cx: &mut task::Context,
         data: &mut State,
     ) -> task::Poll<Result<Infallible, DispatchError>> {
+        let _guard = ReentrancyGuard::enter(&self.handle.inner);
         loop {
             if let Err(e) = self.conn.backend.dispatch_inner_queue() {
                 return task::Poll::Ready(Err(e.into()));
@@ -556,14 +872,14 @@ impl<State> EventQueue<State> {
                 lock.waker = Some(cx.waker().clone());
                 return task::Poll::Pending;
             }
-            let QueueEvent(cb, msg, odata) = if let Some(elt) = lock.queue.pop_front() {
-                elt
+            let batch = if let Some(batch) = lock.pop_batch() {
+                batch
             } else {
                 lock.waker = Some(cx.waker().clone());
                 return task::Poll::Pending;
             };
             drop(lock);
-            cb(&self.conn, msg, data, odata, &self.handle)?
+            self.dispatch_batch(batch, data)?
         }
     }
 }
@@ -622,6 +938,16 @@ impl<State: 'static> QueueHandle<State> {
         })
     }
 
+    /// Create an object data forwarding raw messages to this event queue
+    ///
+    /// The objects created by the events of an object using this data get the same kind of data. Their
+    /// messages are meant to be retrieved through a [`RawEventStream`], see
+    /// [`EventQueue::into_raw_stream()`]. If the queue is dispatched with
+    /// [`EventQueue::dispatch_pending()`] instead, these messages are discarded.
+    pub fn make_raw_data(&self) -> Arc<dyn ObjectData> {
+        Arc::new(RawQueueData { handle: self.clone() })
+    }
+
     /// Temporarily block processing on this queue.
     ///
     /// This will cause the associated queue to block (or return `NotReady` to poll) until all
@@ -630,6 +956,25 @@ impl<State: 'static> QueueHandle<State> {
         self.inner.lock().unwrap().freeze_count += 1;
         QueueFreezeGuard { qh: self }
     }
+
+    /// Attach a context to the event queue of this handle
+    ///
+    /// The context is shared by all the handles of the queue, and can be retrieved with
+    /// [`context()`][Self::context()], for example from the [`Dispatch`] implementations of a library, which
+    /// are given the handle of the queue but cannot access data outside of the `State` of the app.
+    ///
+    /// A queue holds one context per type, so that several libraries can each attach their own. This
+    /// replaces the context of type `T` previously attached, if any.
+    pub fn set_context<T: Any + Send + Sync>(&self, ctx: T) {
+        self.inner.lock().unwrap().contexts.insert(TypeId::of::<T>(), Arc::new(ctx));
+    }
+
+    /// Retrieve the context attached to the event queue of this handle
+    ///
+    /// Returns `None` if no context of type `T` was attached with [`set_context()`][Self::set_context()].
+    pub fn context<T: Any + Send + Sync>(&self) -> Option<Arc<T>> {
+        self.inner.lock().unwrap().contexts.get(&TypeId::of::<T>())?.clone().downcast().ok()
+    }
 }
 
 impl<State> Drop for QueueFreezeGuard<'_, State> {
@@ -651,14 +996,33 @@ fn queue_callback<
 >(
     handle: &Connection,
     msg: Message<ObjectId, OwnedFd>,
+    following: Vec<Message<ObjectId, OwnedFd>>,
     data: &mut State,
     odata: Arc<dyn ObjectData>,
     qhandle: &QueueHandle<State>,
 ) -> Result<(), DispatchError> {
     let (proxy, event) = I::parse_event(handle, msg)?;
     let udata = odata.data_as_any().downcast_ref().expect("Wrong user_data value for object");
-    <State as Dispatch<I, U, State>>::event(data, &proxy, event, udata, handle, qhandle);
-    Ok(())
+    if following.is_empty() {
+        <State as Dispatch<I, U, State>>::event(data, &proxy, event, udata, handle, qhandle);
+        return Ok(());
+    }
+
+    let mut events = Vec::with_capacity(1 + following.len());
+    events.push(event);
+    // the events parsed before an invalid one are still dispatched, the ones after it are dropped
+    let mut result = Ok(());
+    for msg in following {
+        match I::parse_event(handle, msg) {
+            Ok((_, event)) => events.push(event),
+            Err(err) => {
+                result = Err(err);
+                break;
+            }
+        }
+    }
+    <State as Dispatch<I, U, State>>::event_batch(data, &proxy, events, udata, handle, qhandle);
+    result
 }
 
 /// The [`ObjectData`] implementation used by Wayland proxies, integrating with [`Dispatch`]
@@ -694,6 +1058,10 @@ where
     fn data_as_any(&self) -> &dyn Any {
         &self.udata
     }
+
+    fn queue_as_any(&self) -> Option<&dyn Any> {
+        Some(&self.handle)
+    }
 }
 
 impl<I: Proxy, U: std::fmt::Debug, State> std::fmt::Debug for QueueProxyData<I, U, State> {
@@ -703,6 +1071,126 @@ impl<I: Proxy, U: std::fmt::Debug, State> std::fmt::Debug for QueueProxyData<I,
     }
 }
 
+/*
+ * Raw event streams
+ */
+
+/// A stream of the raw messages received by the objects of an event queue
+///
+/// See [`EventQueue::into_raw_stream()`].
+pub struct RawEventStream<State> {
+    queue: EventQueue<State>,
+}
+
+impl<State> std::fmt::Debug for RawEventStream<State> {
+    #[cfg_attr(coverage, coverage(off))]
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RawEventStream").field("handle", &self.queue.handle).finish_non_exhaustive()
+    }
+}
+
+impl<State> RawEventStream<State> {
+    /// Get a [`QueueHandle`] for the underlying event queue
+    pub fn handle(&self) -> QueueHandle<State> {
+        self.queue.handle.clone()
+    }
+
+    /// Retrieve the next message already received by this queue, if any
+    ///
+    /// This does not read the socket. Like [`EventQueue::dispatch_pending()`], this may block if another
+    /// thread has frozen the queue.
+    pub fn next_pending(&mut self) -> Option<Message<ObjectId, OwnedFd>> {
+        // See EventQueue::dispatching_impl() for why the error is ignored
+        let _ = self.queue.conn.backend.dispatch_inner_queue();
+        EventQueue::try_next(&self.queue.handle.inner).map(|QueueEvent(_, msg, _)| msg)
+    }
+
+    /// Retrieve the next message of this queue, blocking until the server sends one if necessary
+    ///
+    /// The connection is flushed before blocking.
+    pub fn blocking_next(&mut self) -> Result<Message<ObjectId, OwnedFd>, DispatchError> {
+        loop {
+            if let Some(msg) = self.next_pending() {
+                return Ok(msg);
+            }
+
+            self.queue.conn.flush()?;
+
+            if let Some(guard) = self.queue.conn.prepare_read() {
+                crate::conn::blocking_read(guard, None)?;
+            }
+        }
+    }
+
+    /// Flush pending outgoing requests to the server
+    ///
+    /// This method is identical to [`Connection::flush()`].
+    pub fn flush(&self) -> Result<(), WaylandError> {
+        self.queue.conn.flush()
+    }
+}
+
+impl<State> AsFd for RawEventStream<State> {
+    /// Provides fd from [`Backend::poll_fd`] for polling.
+    fn as_fd(&self) -> BorrowedFd<'_> {
+        self.queue.as_fd()
+    }
+}
+
+/// The [`ObjectData`] implementation forwarding raw messages to an event queue
+struct RawQueueData<State> {
+    handle: QueueHandle<State>,
+}
+
+impl<State: 'static> ObjectData for RawQueueData<State> {
+    fn event(
+        self: Arc<Self>,
+        _: &Backend,
+        msg: Message<ObjectId, OwnedFd>,
+    ) -> Option<Arc<dyn ObjectData>> {
+        let new_data = msg
+            .args
+            .iter()
+            .any(|arg| matches!(arg, Argument::NewId(id) if !id.is_null()))
+            .then(|| self.handle.make_raw_data());
+
+        let mut inner = self.handle.inner.lock().unwrap();
+        inner.queue.push_back(QueueEvent(raw_queue_callback::<State>, msg, self.clone()));
+        if inner.freeze_count == 0 {
+            if let Some(waker) = inner.waker.take() {
+                waker.wake();
+            }
+        }
+
+        new_data
+    }
+
+    fn destroyed(&self, _: ObjectId) {}
+
+    fn queue_as_any(&self) -> Option<&dyn Any> {
+        Some(&self.handle)
+    }
+}
+
+impl<State> std::fmt::Debug for RawQueueData<State> {
+    #[cfg_attr(coverage, coverage(off))]
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RawQueueData").finish_non_exhaustive()
+    }
+}
+
+// Raw messages dispatched through the typed machinery have no handler, they are discarded
+fn raw_queue_callback<State>(
+    _: &Connection,
+    _: Message<ObjectId, OwnedFd>,
+    _: Vec<Message<ObjectId, OwnedFd>>,
+    _: &mut State,
+    _: Arc<dyn ObjectData>,
+    _: &QueueHandle<State>,
+) -> Result<(), DispatchError> {
+    Ok(())
+}
+
 /*
  * Dispatch delegation helpers
  */
@@ -788,6 +1276,17 @@ macro_rules! delegate_dispatch {
                 <$dispatch_to as $crate::Dispatch<$interface, $udata, Self>>::event(state, proxy, event, data, conn, qhandle)
             }
 
+            fn event_batch(
+                state: &mut Self,
+                proxy: &$interface,
+                events: ::std::vec::Vec<<$interface as $crate::Proxy>::Event>,
+                data: &$udata,
+                conn: &$crate::Connection,
+                qhandle: &$crate::QueueHandle<Self>,
+            ) {
+                <$dispatch_to as $crate::Dispatch<$interface, $udata, Self>>::event_batch(state, proxy, events, data, conn, qhandle)
+            }
+
             fn event_created_child(
                 opcode: u16,
                 qhandle: &$crate::QueueHandle<Self>
@@ -854,3 +1353,39 @@ macro_rules! delegate_noop {
         }
     };
 }
+
+#[cfg(test)]
+mod tests {
+    use std::os::unix::net::UnixStream;
+
+    use super::ReentrancyGuard;
+    use crate::Connection;
+
+    #[test]
+    fn nested_dispatch_of_distinct_queues() {
+        let (client_socket, _server_socket) = UnixStream::pair().unwrap();
+        let conn = Connection::from_socket(client_socket).unwrap();
+        let mut queue_1 = conn.new_event_queue::<()>();
+        let mut queue_2 = conn.new_event_queue::<()>();
+
+        // dispatching another queue while this one is being dispatched is fine
+        let guard = ReentrancyGuard::enter(&queue_1.handle.inner);
+        queue_2.dispatch_pending(&mut ()).unwrap();
+        drop(guard);
+
+        // once the outer dispatch is over, the queue can be dispatched again
+        queue_1.dispatch_pending(&mut ()).unwrap();
+    }
+
+    #[test]
+    #[should_panic(expected = "Reentrant dispatch detected")]
+    fn reentrant_dispatch_panics() {
+        let (client_socket, _server_socket) = UnixStream::pair().unwrap();
+        let conn = Connection::from_socket(client_socket).unwrap();
+        let mut queue = conn.new_event_queue::<()>();
+
+        // simulate a dispatch of this queue being in progress further up the stack
+        let _guard = ReentrancyGuard::enter(&queue.handle.inner);
+        let _ = queue.dispatch_pending(&mut ());
+    }
+}
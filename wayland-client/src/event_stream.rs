@@ -0,0 +1,159 @@
+//! Consuming the events of a single object as an async stream
+
+use std::{
+    collections::VecDeque,
+    os::unix::io::OwnedFd,
+    pin::Pin,
+    sync::{Arc, Mutex},
+    task::{Context, Poll, Waker},
+};
+
+use wayland_backend::{
+    client::{Backend, InvalidId, ObjectData, ObjectId},
+    protocol::{Argument, Message},
+};
+
+use crate::{Connection, Proxy, QueueHandle};
+
+/// A stream of the events of a single object
+///
+/// This is an alternative to implementing [`Dispatch`][crate::Dispatch] for objects whose events are
+/// best consumed by async code, like `wl_callback` or `xdg_toplevel`:
+///
+/// ```no_run
+/// # async fn example<State: 'static>(
+/// #     conn: wayland_client::Connection,
+/// #     qh: wayland_client::QueueHandle<State>,
+/// #     callback: wayland_client::protocol::wl_callback::WlCallback,
+/// # ) {
+/// use futures_util::StreamExt;
+/// use wayland_client::EventStream;
+///
+/// let mut stream = EventStream::new(&callback, &qh).unwrap();
+/// while let Some(event) = stream.next().await {
+///     println!("{:?}", event);
+/// }
+/// # }
+/// ```
+///
+/// The stream ends once the object is destroyed and all its events have been retrieved.
+///
+/// The stream does not read the Wayland socket itself: it only receives the events that are read from it
+/// by the dispatching of an [`EventQueue`][crate::EventQueue] of the same connection, for example by
+/// [`EventQueue::poll_dispatch_pending()`][crate::EventQueue::poll_dispatch_pending()] in another task.
+///
+/// Requires the `async` cargo feature.
+pub struct EventStream<I: Proxy> {
+    inner: Arc<Mutex<StreamInner<I>>>,
+}
+
+struct StreamInner<I: Proxy> {
+    events: VecDeque<I::Event>,
+    waker: Option<Waker>,
+    destroyed: bool,
+}
+
+impl<I: Proxy> std::fmt::Debug for EventStream<I> {
+    #[cfg_attr(coverage, coverage(off))]
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EventStream").finish_non_exhaustive()
+    }
+}
+
+impl<I: Proxy + 'static> EventStream<I>
+where
+    I::Event: Send,
+{
+    /// Create a stream of the events of this proxy
+    ///
+    /// This replaces the object data of the proxy, so its previous user data is no longer accessible
+    /// through [`Proxy::data()`], and its events are no longer delivered to any [`Dispatch`][crate::Dispatch]
+    /// implementation. The objects created by its events are assigned to the queue of `qh` like the ones
+    /// using the data given by [`QueueHandle::make_raw_data()`], and can be turned into streams as well.
+    ///
+    /// Returns an error if the proxy is no longer alive.
+    pub fn new<State: 'static>(proxy: &I, qh: &QueueHandle<State>) -> Result<Self, InvalidId> {
+        let backend = proxy.backend().upgrade().ok_or(InvalidId)?;
+        let inner = Arc::new(Mutex::new(StreamInner {
+            events: VecDeque::new(),
+            waker: None,
+            destroyed: false,
+        }));
+        let handle = qh.clone();
+        let data = StreamData::<I> {
+            inner: inner.clone(),
+            make_child_data: Box::new(move || handle.make_raw_data()),
+        };
+        backend.set_data(proxy.id(), Arc::new(data))?;
+        Ok(Self { inner })
+    }
+}
+
+impl<I: Proxy> futures_core::Stream for EventStream<I> {
+    type Item = I::Event;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<I::Event>> {
+        let mut inner = self.inner.lock().unwrap();
+        if let Some(event) = inner.events.pop_front() {
+            Poll::Ready(Some(event))
+        } else if inner.destroyed {
+            Poll::Ready(None)
+        } else {
+            inner.waker = Some(cx.waker().clone());
+            Poll::Pending
+        }
+    }
+}
+
+/// The [`ObjectData`] implementation feeding an [`EventStream`]
+struct StreamData<I: Proxy> {
+    inner: Arc<Mutex<StreamInner<I>>>,
+    make_child_data: Box<dyn Fn() -> Arc<dyn ObjectData> + Send + Sync>,
+}
+
+impl<I: Proxy + 'static> ObjectData for StreamData<I>
+where
+    I::Event: Send,
+{
+    fn event(
+        self: Arc<Self>,
+        backend: &Backend,
+        msg: Message<ObjectId, OwnedFd>,
+    ) -> Option<Arc<dyn ObjectData>> {
+        let new_data = msg
+            .args
+            .iter()
+            .any(|arg| matches!(arg, Argument::NewId(id) if !id.is_null()))
+            .then(|| (self.make_child_data)());
+
+        match I::parse_event(&Connection::from_backend(backend.clone()), msg) {
+            Ok((_, event)) => {
+                let mut inner = self.inner.lock().unwrap();
+                inner.events.push_back(event);
+                if let Some(waker) = inner.waker.take() {
+                    waker.wake();
+                }
+            }
+            Err(err) => {
+                crate::log_warn!("Discarding an event that could not be parsed: {}", err);
+            }
+        }
+
+        new_data
+    }
+
+    fn destroyed(&self, _: ObjectId) {
+        let mut inner = self.inner.lock().unwrap();
+        inner.destroyed = true;
+        if let Some(waker) = inner.waker.take() {
+            waker.wake();
+        }
+    }
+}
+
+impl<I: Proxy> std::fmt::Debug for StreamData<I> {
+    #[cfg_attr(coverage, coverage(off))]
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("StreamData").finish_non_exhaustive()
+    }
+}
@@ -64,7 +64,7 @@ use std::{
 
 use wayland_backend::{
     client::{Backend, InvalidId, ObjectData, ObjectId, WaylandError},
-    protocol::Message,
+    protocol::{Interface, Message},
 };
 
 use crate::{
@@ -123,7 +123,8 @@ impl GlobalList {
     /// This function is not intended to be used with globals that have multiple instances such as `wl_output`
     /// and `wl_seat`. These types of globals need their own initialization mechanism because these
     /// multi-instance globals may be removed at runtime. To handle then, you should instead rely on the
-    /// `Dispatch` implementation for `WlRegistry` of your `State`.
+    /// `Dispatch` implementation for `WlRegistry` of your `State`. The instances advertised at startup can
+    /// be bound using [`bind_all()`][Self::bind_all()].
     ///
     /// # Panics
     ///
@@ -135,6 +136,29 @@ impl GlobalList {
         version: RangeInclusive<u32>,
         udata: U,
     ) -> Result<I, BindError>
+    where
+        I: Proxy + 'static,
+        State: Dispatch<I, U> + 'static,
+        U: Send + Sync + 'static,
+    {
+        self.bind_named(qh, version, udata).map(|(proxy, _)| proxy)
+    }
+
+    /// Binds a global like [`bind()`][Self::bind()], also returning its numeric name in the registry.
+    ///
+    /// This name is the one carried by the `global_remove` event of the registry when the global is removed,
+    /// and can be used to match this event with the returned protocol object.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if the maximum requested version is greater than the known maximum version of
+    /// the interface. The known maximum version is determined by the code generated using wayland-scanner.
+    pub fn bind_named<I, State, U>(
+        &self,
+        qh: &QueueHandle<State>,
+        version: RangeInclusive<u32>,
+        udata: U,
+    ) -> Result<(I, u32), BindError>
     where
         I: Proxy + 'static,
         State: Dispatch<I, U> + 'static,
@@ -168,19 +192,122 @@ impl GlobalList {
 
         // Test version requirements
         if version < version_start {
-            return Err(BindError::UnsupportedVersion);
+            return Err(BindError::UnsupportedVersion {
+                interface,
+                requested: version_start..=version_end,
+                available: version,
+            });
         }
 
         // To get the version to bind, take the lower of the version advertised by the server and the maximum
         // requested version.
         let version = version.min(version_end);
 
-        Ok(self.registry.bind(name, version, qh, udata))
+        Ok((self.registry.bind(name, version, qh, udata), name))
+    }
+
+    /// Binds all the globals of an interface, returning a new protocol object for each of them.
+    ///
+    /// This is meant for globals that have multiple instances such as `wl_output` and `wl_seat`. The objects
+    /// are returned in the order the globals were advertised, and each of them is bound with the lower of the
+    /// maximum requested version and its advertised version. If no global of this interface was advertised,
+    /// an empty list is returned.
+    ///
+    /// If any of the globals is advertised with a version lower than the lower bound of `version`, then
+    /// [`BindError::UnsupportedVersion`] is returned and no global is bound.
+    ///
+    /// Only the globals currently in the list are bound: the ones advertised later are not bound by this
+    /// call, and the objects it returns are not destroyed when their global is removed. Both need to be
+    /// handled in the `Dispatch` implementation for `WlRegistry` of your `State`.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if the maximum requested version is greater than the known maximum version of
+    /// the interface. The known maximum version is determined by the code generated using wayland-scanner.
+    pub fn bind_all<I, State, U>(
+        &self,
+        qh: &QueueHandle<State>,
+        version: RangeInclusive<u32>,
+        udata: U,
+    ) -> Result<Vec<I>, BindError>
+    where
+        I: Proxy + 'static,
+        State: Dispatch<I, U> + 'static,
+        U: Clone + Send + Sync + 'static,
+    {
+        self.bind_all_named(qh, version, udata)
+            .map(|bound| bound.into_iter().map(|(proxy, _)| proxy).collect())
+    }
+
+    /// Binds all instances of a global like [`bind_all()`][Self::bind_all()], also returning the numeric
+    /// name of each of them in the registry.
+    ///
+    /// These names are the ones carried by the `global_remove` event of the registry, which allows to find
+    /// the protocol object to destroy when an instance of a multi-instance global like `wl_output` is removed.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if the maximum requested version is greater than the known maximum version of
+    /// the interface. The known maximum version is determined by the code generated using wayland-scanner.
+    pub fn bind_all_named<I, State, U>(
+        &self,
+        qh: &QueueHandle<State>,
+        version: RangeInclusive<u32>,
+        udata: U,
+    ) -> Result<Vec<(I, u32)>, BindError>
+    where
+        I: Proxy + 'static,
+        State: Dispatch<I, U> + 'static,
+        U: Clone + Send + Sync + 'static,
+    {
+        let version_start = *version.start();
+        let version_end = *version.end();
+        let interface = I::interface();
+
+        if *version.end() > interface.version {
+            // This is a panic because it's a compile-time programmer error, not a runtime error.
+            panic!("Maximum version ({}) of {} was higher than the proxy's maximum version ({}); outdated wayland XML files?",
+                version.end(), interface.name, interface.version);
+        }
+
+        let globals = &self.registry.data::<GlobalListContents>().unwrap().contents;
+        let matching = globals
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|global| interface.name == &global.interface[..])
+            .map(|global| (global.name, global.version))
+            .collect::<Vec<_>>();
+
+        // Test version requirements before binding anything
+        if let Some(&(_, available)) =
+            matching.iter().find(|&&(_, version)| version < version_start)
+        {
+            return Err(BindError::UnsupportedVersion {
+                interface,
+                requested: version_start..=version_end,
+                available,
+            });
+        }
+
+        Ok(matching
+            .into_iter()
+            .map(|(name, version)| {
+                (self.registry.bind(name, version.min(version_end), qh, udata.clone()), name)
+            })
+            .collect())
     }
 
     /// Returns the [`WlRegistry`][wl_registry] protocol object.
     ///
     /// This may be used if more direct control when creating globals is needed.
+    ///
+    /// The `global` and `global_remove` events this registry receives after [`registry_queue_init()`] are
+    /// delivered to your `Dispatch<WlRegistry, GlobalListContents>` implementation, and the
+    /// [`contents()`][Self::contents()] of this list are updated before that, so they do not need to be
+    /// tracked manually. Note that the object data of this registry must not be replaced (for example
+    /// through [`Backend::set_data()`]), as this would stop both the updates of the contents and the
+    /// delivery of the events.
     pub fn registry(&self) -> &wl_registry::WlRegistry {
         &self.registry
     }
@@ -232,7 +359,14 @@ impl From<InvalidId> for GlobalError {
 #[derive(Debug)]
 pub enum BindError {
     /// The requested version of the global is not supported.
-    UnsupportedVersion,
+    UnsupportedVersion {
+        /// The interface of the global
+        interface: &'static Interface,
+        /// The range of versions that was requested
+        requested: RangeInclusive<u32>,
+        /// The version advertised by the compositor
+        available: u32,
+    },
 
     /// The requested global was not found in the registry.
     NotPresent,
@@ -243,8 +377,16 @@ impl std::error::Error for BindError {}
 impl fmt::Display for BindError {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
         match self {
-            BindError::UnsupportedVersion {} => {
-                write!(f, "the requested version of the global is not supported")
+            BindError::UnsupportedVersion { interface, requested, available } => {
+                write!(
+                    f,
+                    "the requested version of the global is not supported: wanted {} {}..={} but \
+                    the compositor only advertises version {}",
+                    interface.name,
+                    requested.start(),
+                    requested.end(),
+                    available
+                )
             }
             BindError::NotPresent {} => {
                 write!(f, "the requested global was not found in the registry")
@@ -358,4 +500,8 @@ where
     fn data_as_any(&self) -> &dyn std::any::Any {
         &self.globals
     }
+
+    fn queue_as_any(&self) -> Option<&dyn std::any::Any> {
+        Some(&self.handle)
+    }
 }
@@ -153,6 +153,8 @@
 //! - If you need to send pointers to FFI, you can retrive the `*mut wl_proxy` pointers from the proxies by
 //!   first getting the [`ObjectId`] using the [`Proxy::id()`] method, and then
 //!   using the [`ObjectId::as_ptr()`] method.
+//! - If you receive a `*mut wl_display` from FFI, you can enable the `system` cargo feature of this crate
+//!   and create a [`Connection`] from it using `Connection::from_display_ptr()`.
 //  - If you need to receive pointers from FFI, you need to first create a
 //    [`Backend`][backend::Backend] from the `*mut wl_display` using
 //    [`Backend::from_external_display()`][backend::Backend::from_foreign_display()], and then
@@ -170,17 +172,20 @@
 use std::{
     fmt,
     hash::{Hash, Hasher},
-    os::unix::io::{BorrowedFd, OwnedFd},
+    os::unix::io::{AsRawFd, BorrowedFd, OwnedFd},
     sync::Arc,
 };
 use wayland_backend::{
     client::{InvalidId, ObjectData, ObjectId, WaylandError, WeakBackend},
-    protocol::{Interface, Message},
+    protocol::{same_interface, Argument, Interface, Message, ObjectInfo},
 };
 
 mod conn;
 mod event_queue;
+#[cfg(feature = "async")]
+mod event_stream;
 pub mod globals;
+pub mod util;
 
 /// Backend reexports
 pub mod backend {
@@ -192,10 +197,19 @@ pub mod backend {
     pub use wayland_backend::smallvec;
 }
 
+pub use wayland_backend::client::ConnectError;
 pub use wayland_backend::protocol::WEnum;
 
-pub use conn::{ConnectError, Connection};
-pub use event_queue::{Dispatch, EventQueue, QueueFreezeGuard, QueueHandle, QueueProxyData};
+#[cfg(feature = "system")]
+pub use conn::InvalidPtr;
+#[cfg(feature = "object_hooks")]
+pub use conn::SpyQueue;
+pub use conn::{Connection, WeakConnection};
+pub use event_queue::{
+    Dispatch, EventQueue, QueueFreezeGuard, QueueHandle, QueueProxyData, RawEventStream,
+};
+#[cfg(feature = "async")]
+pub use event_stream::EventStream;
 
 // internal imports for dispatching logging depending on the `log` feature
 #[cfg(feature = "log")]
@@ -231,6 +245,15 @@ pub trait Proxy: Clone + std::fmt::Debug + Sized {
     /// The interface description
     fn interface() -> &'static Interface;
 
+    /// Check if an object ID designates an object of this interface
+    ///
+    /// This is less error-prone than comparing the names of the interfaces, see
+    /// [`ObjectId::has_interface()`].
+    #[inline]
+    fn is_interface_of(id: &ObjectId) -> bool {
+        id.has_interface(Self::interface())
+    }
+
     /// The ID of this object
     fn id(&self) -> ObjectId;
 
@@ -246,6 +269,13 @@ pub trait Proxy: Clone + std::fmt::Debug + Sized {
         }
     }
 
+    /// Retrieve the id, interface and version of this object at once
+    ///
+    /// Returns `None` if the object is no longer alive.
+    fn info(&self) -> Option<ObjectInfo> {
+        self.backend().upgrade()?.info(self.id()).ok()
+    }
+
     /// Access the user-data associated with this object
     fn data<U: Send + Sync + 'static>(&self) -> Option<&U>;
 
@@ -258,6 +288,16 @@ pub trait Proxy: Clone + std::fmt::Debug + Sized {
     /// Access the backend associated with this object
     fn backend(&self) -> &backend::WeakBackend;
 
+    /// Retrieve the handle of the event queue this object is assigned to
+    ///
+    /// This allows creating other objects on the same queue when only this proxy is at hand. It returns
+    /// `None` if the state type of the queue is not `State`, or if the object data of this proxy does not
+    /// come from a [`QueueHandle`], for example for objects turned into an `EventStream` or created with a
+    /// custom [`ObjectData`].
+    fn queue_handle<State: 'static>(&self) -> Option<QueueHandle<State>> {
+        self.object_data()?.queue_as_any()?.downcast_ref::<QueueHandle<State>>().cloned()
+    }
+
     /// Create an object proxy from its ID
     ///
     /// Returns an error this the provided object ID does not correspond to
@@ -289,6 +329,52 @@ pub trait Proxy: Clone + std::fmt::Debug + Sized {
         data: Arc<dyn ObjectData>,
     ) -> Result<I, InvalidId>;
 
+    /// Send a request for this object that creates another object, with an explicit version
+    ///
+    /// This behaves like [`send_constructor()`][Self::send_constructor()], but if `version` is
+    /// `Some(_)`, it overrides the version of the created object that was inferred from the request.
+    /// This is mostly useful for generic constructors like `wl_registry.bind`, where it allows to
+    /// deliberately request a version lower than the one initially specified. The version sent to the
+    /// server along with the interface of the new object is replaced as well, so that both sides agree
+    /// on the version of the object.
+    ///
+    /// **Panic:** For requests whose created interface is specified by the protocol, the new object
+    /// always has the same version as its parent, and providing a different version will cause a panic.
+    fn send_constructor_versioned<I: Proxy>(
+        &self,
+        req: Self::Request<'_>,
+        data: Arc<dyn ObjectData>,
+        version: Option<u32>,
+    ) -> Result<I, InvalidId> {
+        let conn = Connection::from_backend(self.backend().upgrade().ok_or(InvalidId)?);
+        let (mut msg, child_spec) = self.write_request(&conn, req)?;
+        let child_spec = match version {
+            Some(version) => {
+                let generic = Self::interface()
+                    .requests
+                    .get(msg.opcode as usize)
+                    .map_or(false, |desc| desc.child_interface.is_none());
+                // generic constructors send the version of the new object right before its id
+                if generic {
+                    if let Some(pos) =
+                        msg.args.iter().position(|arg| matches!(arg, Argument::NewId(_)))
+                    {
+                        if let Some(arg @ Argument::Uint(_)) =
+                            pos.checked_sub(1).and_then(|i| msg.args.get_mut(i))
+                        {
+                            *arg = Argument::Uint(version);
+                        }
+                    }
+                }
+                child_spec.map(|(interface, _)| (interface, version))
+            }
+            None => child_spec,
+        };
+        let msg = msg.map_fd(|fd| fd.as_raw_fd());
+        let id = conn.backend().send_request(msg, Some(data), child_spec)?;
+        I::from_id(&conn, id)
+    }
+
     /// Parse a event for this object
     ///
     /// **Note:** This method is mostly meant as an implementation detail to be
@@ -319,6 +405,21 @@ pub trait Proxy: Clone + std::fmt::Debug + Sized {
     fn downgrade(&self) -> Weak<Self> {
         Weak { backend: self.backend().clone(), id: self.id(), _iface: std::marker::PhantomData }
     }
+
+    /// Reinterpret this proxy as a proxy of interface `I`
+    ///
+    /// This is useful when a proxy was obtained through an interface-agnostic code path but its
+    /// concrete interface is known. This will fail if either:
+    /// - the object does not actually have the interface of `I`
+    /// - the Wayland connection has already been closed
+    fn cast<I: Proxy>(&self) -> Result<I, InvalidId> {
+        let id = self.id();
+        if !same_interface(id.interface(), I::interface()) {
+            return Err(InvalidId);
+        }
+        let backend = self.backend().upgrade().ok_or(InvalidId)?;
+        I::from_id(&Connection::from_backend(backend), id)
+    }
 }
 
 /// Wayland dispatching error
@@ -390,6 +491,19 @@ impl<I: Proxy> Weak<I> {
         I::from_id(&conn, self.id.clone())
     }
 
+    /// Check if this weak handle still refers to a live object.
+    ///
+    /// This is cheaper than [`upgrade()`][Self::upgrade()] as it neither upgrades the connection nor
+    /// reconstructs the proxy.
+    ///
+    /// This will return `false` if either:
+    /// - the object represented by this handle has already been destroyed at the protocol level
+    /// - the Wayland connection has already been closed
+    #[inline]
+    pub fn is_alive(&self) -> bool {
+        self.backend.object_is_alive(&self.id)
+    }
+
     /// The underlying [`ObjectId`]
     pub fn id(&self) -> ObjectId {
         self.id.clone()
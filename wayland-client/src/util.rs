@@ -0,0 +1,105 @@
+//! Helpers for common client tasks
+//!
+//! These are small utilities for the parts of the core protocol that every client ends up handling the
+//! same way, independently of how its state is organized.
+
+use std::{
+    ffi::c_void,
+    io,
+    os::unix::io::OwnedFd,
+    ptr::{self, NonNull},
+};
+
+use rustix::mm::{mmap, munmap, MapFlags, ProtFlags};
+
+/// A string mapped in memory from a file descriptor, like the keymap of a `wl_keyboard`
+///
+/// It dereferences to the contents of the string, and is unmapped when dropped.
+///
+/// See [`mmap_keymap()`].
+pub struct MmapString {
+    ptr: NonNull<c_void>,
+    map_len: usize,
+    len: usize,
+}
+
+// SAFETY: the mapping is private and read-only, and is owned by this value until it is dropped
+unsafe impl Send for MmapString {}
+unsafe impl Sync for MmapString {}
+
+impl MmapString {
+    /// The contents of the string
+    pub fn as_str(&self) -> &str {
+        // SAFETY: the first `len` bytes of the mapping were checked to be valid UTF-8 on creation
+        unsafe {
+            std::str::from_utf8_unchecked(std::slice::from_raw_parts(
+                self.ptr.as_ptr() as *const u8,
+                self.len,
+            ))
+        }
+    }
+}
+
+impl std::ops::Deref for MmapString {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl AsRef<str> for MmapString {
+    fn as_ref(&self) -> &str {
+        self.as_str()
+    }
+}
+
+impl std::fmt::Debug for MmapString {
+    #[cfg_attr(coverage, coverage(off))]
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MmapString").field("len", &self.len).finish_non_exhaustive()
+    }
+}
+
+impl Drop for MmapString {
+    fn drop(&mut self) {
+        // SAFETY: the mapping was created with this length and is not accessible anymore
+        let _ = unsafe { munmap(self.ptr.as_ptr(), self.map_len) };
+    }
+}
+
+/// Map the keymap received in a `wl_keyboard.keymap` event
+///
+/// The file descriptor is mapped read-only and privately, as required by the protocol since version 7
+/// of `wl_keyboard`, and closed once mapped. The returned string stops at the first NUL byte, as
+/// compositors generally include the NUL terminator of the keymap in `size`.
+///
+/// The `format` of the event must be checked beforehand: this is only meaningful for
+/// [`KeymapFormat::XkbV1`][crate::protocol::wl_keyboard::KeymapFormat::XkbV1], as a
+/// [`KeymapFormat::NoKeymap`][crate::protocol::wl_keyboard::KeymapFormat::NoKeymap] keymap comes with
+/// an arbitrary file descriptor.
+///
+/// Returns an error if the mapping fails, if `size` is zero, or if the keymap is not valid UTF-8.
+pub fn mmap_keymap(fd: OwnedFd, size: u32) -> io::Result<MmapString> {
+    let map_len = size as usize;
+    if map_len == 0 {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "empty keymap"));
+    }
+    // SAFETY: a new mapping is created, it does not alias any existing memory
+    let ptr =
+        unsafe { mmap(ptr::null_mut(), map_len, ProtFlags::READ, MapFlags::PRIVATE, &fd, 0)? };
+    drop(fd);
+    // a successful mmap never returns a null pointer
+    let ptr = NonNull::new(ptr).ok_or_else(|| io::Error::from(io::ErrorKind::Other))?;
+    let mut string = MmapString { ptr, map_len, len: 0 };
+
+    // SAFETY: the mapping is readable for `map_len` bytes
+    let bytes = unsafe { std::slice::from_raw_parts(ptr.as_ptr() as *const u8, map_len) };
+    let len = bytes.iter().position(|&b| b == 0).unwrap_or(map_len);
+    if let Err(err) = std::str::from_utf8(&bytes[..len]) {
+        // dropping the string unmaps it
+        return Err(io::Error::new(io::ErrorKind::InvalidData, err));
+    }
+    string.len = len;
+    Ok(string)
+}
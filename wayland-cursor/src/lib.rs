@@ -37,8 +37,7 @@
 //!     // Here, we obtain the right cursor frame...
 //!     let buffer = &cursor[fr_info.frame_index];
 //!     // and attach it to a wl_surface.
-//!     cursor_surface.attach(Some(&buffer), 0, 0);
-//!     cursor_surface.commit();
+//!     buffer.attach_to(cursor_surface);
 //!
 //!     sleep(Duration::from_millis(fr_info.frame_duration as u64));
 //! }
@@ -67,18 +66,66 @@ use wayland_client::backend::{InvalidId, ObjectData, WeakBackend};
 use wayland_client::protocol::wl_buffer::WlBuffer;
 use wayland_client::protocol::wl_shm::{self, Format, WlShm};
 use wayland_client::protocol::wl_shm_pool::{self, WlShmPool};
+use wayland_client::protocol::wl_surface::WlSurface;
 use wayland_client::{Connection, Proxy, WEnum};
 
 use xcursor::parser as xparser;
 use xcursor::CursorTheme as XCursorTheme;
 use xparser::Image as XCursorImage;
 
+/// Error that can occur while loading a cursor theme
+#[derive(Debug)]
+pub enum CursorError {
+    /// The shared memory backing the theme could not be allocated or written to
+    Shm(IoError),
+    /// The Wayland objects needed by the theme could not be created
+    InvalidId(InvalidId),
+}
+
+impl std::error::Error for CursorError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Shm(err) => Some(err),
+            Self::InvalidId(err) => Some(err),
+        }
+    }
+}
+
+impl std::fmt::Display for CursorError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Shm(err) => write!(f, "shared memory allocation failed: {}", err),
+            Self::InvalidId(_) => f.write_str("invalid wayland object"),
+        }
+    }
+}
+
+impl From<IoError> for CursorError {
+    fn from(err: IoError) -> Self {
+        Self::Shm(err)
+    }
+}
+
+impl From<InvalidId> for CursorError {
+    fn from(err: InvalidId) -> Self {
+        Self::InvalidId(err)
+    }
+}
+
 /// Represents a cursor theme loaded from the system.
 #[derive(Debug)]
 pub struct CursorTheme {
     name: String,
     cursors: Vec<Cursor>,
+    /// The solid color cursors created so far, by color
+    solid_cursors: Vec<([u8; 4], CursorImageBuffer)>,
     size: u32,
+    /// The name and size provided when loading the theme, used by `reload()`
+    requested_name: String,
+    requested_size: u32,
+    /// Whether the requested name and size are overridden by the environment
+    use_env: bool,
+    shm: WlShm,
     pool: WlShmPool,
     pool_size: i32,
     file: File,
@@ -111,12 +158,13 @@ impl CursorTheme {
     /// Same as calling the following:
     /// ```
     /// # use wayland_cursor::CursorTheme;
-    /// # use wayland_client::{Connection, backend::InvalidId, protocol::wl_shm};
-    /// # fn example(conn: &Connection, shm: wl_shm::WlShm, size: u32) -> Result<CursorTheme, InvalidId> {
+    /// # use wayland_cursor::CursorError;
+    /// # use wayland_client::{Connection, protocol::wl_shm};
+    /// # fn example(conn: &Connection, shm: wl_shm::WlShm, size: u32) -> Result<CursorTheme, CursorError> {
     /// CursorTheme::load_or(conn, shm, "default", size)
     /// # }
     /// ```
-    pub fn load(conn: &Connection, shm: WlShm, size: u32) -> Result<Self, InvalidId> {
+    pub fn load(conn: &Connection, shm: WlShm, size: u32) -> Result<Self, CursorError> {
         Self::load_or(conn, shm, "default", size)
     }
 
@@ -129,66 +177,90 @@ impl CursorTheme {
         conn: &Connection,
         shm: WlShm,
         name: &str,
-        mut size: u32,
-    ) -> Result<Self, InvalidId> {
-        let name_string = String::from(name);
-        let name = &env::var("XCURSOR_THEME").unwrap_or(name_string);
-
-        if let Ok(var) = env::var("XCURSOR_SIZE") {
-            if let Ok(int) = var.parse() {
-                size = int;
-            }
-        }
-
-        Self::load_from_name(conn, shm, name, size)
+        size: u32,
+    ) -> Result<Self, CursorError> {
+        let (env_name, env_size) = theme_from_env(name, size);
+        let mut theme = Self::load_from_name(conn, shm, &env_name, env_size)?;
+        theme.requested_name = String::from(name);
+        theme.requested_size = size;
+        theme.use_env = true;
+        Ok(theme)
     }
 
     /// Create a new cursor theme, ignoring the system defaults.
+    ///
+    /// This fails if the shared memory pool holding the cursor images could not be allocated, in which
+    /// case you may want to fall back to a server-side cursor if one is available.
     pub fn load_from_name(
         conn: &Connection,
         shm: WlShm,
         name: &str,
         size: u32,
-    ) -> Result<Self, InvalidId> {
-        // Set some minimal cursor size to hold it. We're not using `size` argument for that,
-        // because the actual size that we'll use depends on theme sizes available on a system.
-        // The minimal size covers most common minimal theme size, which is 16.
-        const INITIAL_POOL_SIZE: i32 = 16 * 16 * 4;
-
-        //  Create shm.
-        let mem_fd = create_shm_fd().expect("Shm fd allocation failed");
-        let mut file = File::from(mem_fd);
-        file.set_len(INITIAL_POOL_SIZE as u64).expect("Failed to set buffer length");
-
-        // Ensure that we have the same we requested.
-        file.write_all(&[0; INITIAL_POOL_SIZE as usize]).expect("Write to shm fd failed");
-        // Flush to ensure the compositor has access to the buffer when it tries to map it.
-        file.flush().expect("Flush on shm fd failed");
-
-        let pool_id = conn.send_request(
-            &shm,
-            wl_shm::Request::CreatePool { fd: file.as_fd(), size: INITIAL_POOL_SIZE },
-            Some(Arc::new(IgnoreObjectData)),
-        )?;
-        let pool = WlShmPool::from_id(conn, pool_id)?;
+    ) -> Result<Self, CursorError> {
+        let (file, pool) = create_pool(conn, &shm)?;
 
         let name = String::from(name);
 
         Ok(Self {
+            requested_name: name.clone(),
+            requested_size: size,
+            use_env: false,
             name,
             file,
             size,
+            shm,
             pool,
             pool_size: INITIAL_POOL_SIZE,
             cursors: Vec::new(),
+            solid_cursors: Vec::new(),
             backend: conn.backend().downgrade(),
             fallback: None,
         })
     }
 
+    /// Reload the theme, to pick up a change of the system cursor theme or size.
+    ///
+    /// If the theme was loaded with [`load()`][Self::load()] or [`load_or()`][Self::load_or()], the
+    /// `XCURSOR_THEME` and `XCURSOR_SIZE` environment variables are read again, otherwise the theme is
+    /// reloaded with the same name and size.
+    ///
+    /// All cursors loaded so far are discarded, and subsequent calls to [`get_cursor()`][Self::get_cursor()]
+    /// load them from the new theme on a new shared memory pool. The buffers of the previous cursors are
+    /// destroyed: the compositor keeps the surfaces they are attached to displaying them until new buffers
+    /// are attached, but clones of these cursors are invalidated, and
+    /// [`CursorImageBuffer::attach_to()`] fails for their images.
+    pub fn reload(&mut self) -> Result<(), CursorError> {
+        let conn = Connection::from_backend(self.backend.upgrade().ok_or(InvalidId)?);
+        let (file, pool) = create_pool(&conn, &self.shm)?;
+
+        let (name, size) = if self.use_env {
+            theme_from_env(&self.requested_name, self.requested_size)
+        } else {
+            (self.requested_name.clone(), self.requested_size)
+        };
+
+        for cursor in self.cursors.drain(..) {
+            for image in cursor.images {
+                image.buffer.destroy();
+            }
+        }
+        for (_, image) in self.solid_cursors.drain(..) {
+            image.buffer.destroy();
+        }
+        self.pool.destroy();
+
+        self.name = name;
+        self.size = size;
+        self.file = file;
+        self.pool = pool;
+        self.pool_size = INITIAL_POOL_SIZE;
+        Ok(())
+    }
+
     /// Retrieve a cursor from the theme.
     ///
-    /// This method returns [`None`] if this cursor is not provided either by the theme, or by one of its parents.
+    /// This method returns [`None`] if this cursor is not provided either by the theme, or by one of its parents,
+    /// or if its images could not be written to the shared memory pool.
     ///
     /// If a [fallback is set], it will use the data returned by the fallback.
     ///
@@ -203,7 +275,7 @@ impl CursorTheme {
                         let data = fallback.0(name, self.size)?;
                         let images = xparser::parse_xcursor(&data)?;
                         let conn = Connection::from_backend(self.backend.upgrade()?);
-                        Cursor::new(&conn, name, self, &images, self.size)
+                        Cursor::new(&conn, name, self, &images, self.size).ok()?
                     }
                     Some(cursor) => cursor,
                 };
@@ -213,6 +285,38 @@ impl CursorTheme {
         }
     }
 
+    /// Create a cursor image of a single pixel of the given color
+    ///
+    /// This provides a cursor without loading any image from the theme, for example a transparent cursor
+    /// with `[0, 0, 0, 0]` to hide the pointer while keeping a surface for it, or a dot of a solid color.
+    /// The color is given as non-premultiplied RGBA. The hotspot of the image is its only pixel.
+    ///
+    /// The image is written to the shared memory pool of the theme, and repeated calls with the same color
+    /// return the same buffer. Like the other cursors, it is destroyed by [`reload()`][Self::reload()].
+    pub fn solid_cursor(&mut self, rgba: [u8; 4]) -> Result<CursorImageBuffer, CursorError> {
+        if let Some((_, image)) = self.solid_cursors.iter().find(|(color, _)| *color == rgba) {
+            return Ok(image.clone());
+        }
+
+        let conn = Connection::from_backend(self.backend.upgrade().ok_or(InvalidId)?);
+        let [r, g, b, a] = rgba;
+        let premultiply = |c: u8| ((c as u32 * a as u32 + 127) / 255) as u8;
+        let image = XCursorImage {
+            size: 1,
+            width: 1,
+            height: 1,
+            xhot: 0,
+            yhot: 0,
+            delay: 0,
+            // little-endian ARGB8888, like the pixels of xcursor files
+            pixels_rgba: vec![premultiply(b), premultiply(g), premultiply(r), a],
+            pixels_argb: vec![a, premultiply(r), premultiply(g), premultiply(b)],
+        };
+        let buffer = CursorImageBuffer::new(&conn, self, &image)?;
+        self.solid_cursors.push((rgba, buffer.clone()));
+        Ok(buffer)
+    }
+
     /// Set a fallback to load the cursor data, in case the system theme is missing a cursor that you need.
     ///
     /// Your fallback will be invoked with the name and size of the requested cursor and should return a byte
@@ -221,8 +325,9 @@ impl CursorTheme {
     /// For example, this defines a generic fallback cursor image and uses it for all missing cursors:
     /// ```ignore
     /// use wayland_cursor::CursorTheme;
-    /// use wayland_client::{Connection, backend::InvalidId, protocol::wl_shm};
-    /// fn example(conn: &Connection, shm: wl_shm::WlShm, size: u32) -> Result<CursorTheme, InvalidId> {
+    /// use wayland_cursor::CursorError;
+    /// use wayland_client::{Connection, protocol::wl_shm};
+    /// fn example(conn: &Connection, shm: wl_shm::WlShm, size: u32) -> Result<CursorTheme, CursorError> {
     ///   let mut theme = CursorTheme::load_or(conn, shm, "default", size)?;
     ///   theme.set_fallback(|name, size| {
     ///       include_bytes!("./icons/default")
@@ -251,19 +356,61 @@ impl CursorTheme {
             xparser::parse_xcursor(&buf)?
         };
 
-        Some(Cursor::new(&conn, name, self, &images, size))
+        Cursor::new(&conn, name, self, &images, size).ok()
     }
 
     /// Grow the wl_shm_pool this theme is stored on.
     ///
     /// This method does nothing if the provided size is smaller or equal to the pool's current size.
-    fn grow(&mut self, size: i32) {
+    fn grow(&mut self, size: i32) -> IoResult<()> {
         if size > self.pool_size {
-            self.file.set_len(size as u64).expect("Failed to set new buffer length");
+            self.file.set_len(size as u64)?;
             self.pool.resize(size);
             self.pool_size = size;
         }
+        Ok(())
+    }
+}
+
+// Set some minimal cursor size to hold it. We're not using the theme size for that,
+// because the actual size that we'll use depends on theme sizes available on a system.
+// The minimal size covers most common minimal theme size, which is 16.
+const INITIAL_POOL_SIZE: i32 = 16 * 16 * 4;
+
+/// Read the theme name and cursor size from the `XCURSOR_THEME` and `XCURSOR_SIZE` environment
+/// variables, using the provided values if those are invalid.
+fn theme_from_env(name: &str, mut size: u32) -> (String, u32) {
+    let name = env::var("XCURSOR_THEME").unwrap_or_else(|_| String::from(name));
+
+    if let Ok(var) = env::var("XCURSOR_SIZE") {
+        if let Ok(int) = var.parse() {
+            size = int;
+        }
     }
+
+    (name, size)
+}
+
+/// Create the shared memory pool the cursor images are written to.
+fn create_pool(conn: &Connection, shm: &WlShm) -> Result<(File, WlShmPool), CursorError> {
+    //  Create shm.
+    let mem_fd = create_shm_fd()?;
+    let mut file = File::from(mem_fd);
+    file.set_len(INITIAL_POOL_SIZE as u64)?;
+
+    // Ensure that we have the same we requested.
+    file.write_all(&[0; INITIAL_POOL_SIZE as usize])?;
+    // Flush to ensure the compositor has access to the buffer when it tries to map it.
+    file.flush()?;
+
+    let pool_id = conn.send_request(
+        shm,
+        wl_shm::Request::CreatePool { fd: file.as_fd(), size: INITIAL_POOL_SIZE },
+        Some(Arc::new(IgnoreObjectData)),
+    )?;
+    let pool = WlShmPool::from_id(conn, pool_id)?;
+
+    Ok((file, pool))
 }
 
 /// A cursor from a theme. Can contain several images if animated.
@@ -285,18 +432,26 @@ impl Cursor {
         theme: &mut CursorTheme,
         images: &[XCursorImage],
         size: u32,
-    ) -> Self {
+    ) -> Result<Self, CursorError> {
         let mut total_duration = 0;
-        let images: Vec<CursorImageBuffer> = Self::nearest_images(size, images)
-            .map(|image| {
-                let buffer = CursorImageBuffer::new(conn, theme, image);
-                total_duration += buffer.delay;
-
-                buffer
-            })
-            .collect();
+        let mut buffers = Vec::new();
+        for image in Self::nearest_images(size, images) {
+            match CursorImageBuffer::new(conn, theme, image) {
+                Ok(buffer) => {
+                    total_duration += buffer.delay;
+                    buffers.push(buffer);
+                }
+                Err(err) => {
+                    // the buffers of the previous images would otherwise never be destroyed
+                    for buffer in buffers {
+                        buffer.buffer.destroy();
+                    }
+                    return Err(err);
+                }
+            }
+        }
 
-        Self { total_duration, name: String::from(name), images }
+        Ok(Self { total_duration, name: String::from(name), images: buffers })
     }
 
     fn nearest_images(size: u32, images: &[XCursorImage]) -> impl Iterator<Item = &XCursorImage> {
@@ -363,40 +518,42 @@ impl CursorImageBuffer {
     ///
     /// This function appends the pixels of the image to the provided file,
     /// and constructs a wl_buffer on that data.
-    fn new(conn: &Connection, theme: &mut CursorTheme, image: &XCursorImage) -> Self {
+    fn new(
+        conn: &Connection,
+        theme: &mut CursorTheme,
+        image: &XCursorImage,
+    ) -> Result<Self, CursorError> {
         let buf = &image.pixels_rgba;
-        let offset = theme.file.seek(SeekFrom::End(0)).unwrap();
+        let offset = theme.file.seek(SeekFrom::End(0))?;
 
         // Resize memory before writing to it to handle shm correctly.
         let new_size = offset + buf.len() as u64;
-        theme.grow(new_size as i32);
-
-        theme.file.write_all(buf).unwrap();
-
-        let buffer_id = conn
-            .send_request(
-                &theme.pool,
-                wl_shm_pool::Request::CreateBuffer {
-                    offset: offset as i32,
-                    width: image.width as i32,
-                    height: image.height as i32,
-                    stride: (image.width * 4) as i32,
-                    format: WEnum::Value(Format::Argb8888),
-                },
-                Some(Arc::new(IgnoreObjectData)),
-            )
-            .unwrap();
-
-        let buffer = WlBuffer::from_id(conn, buffer_id).unwrap();
-
-        Self {
+        theme.grow(new_size as i32)?;
+
+        theme.file.write_all(buf)?;
+
+        let buffer_id = conn.send_request(
+            &theme.pool,
+            wl_shm_pool::Request::CreateBuffer {
+                offset: offset as i32,
+                width: image.width as i32,
+                height: image.height as i32,
+                stride: (image.width * 4) as i32,
+                format: WEnum::Value(Format::Argb8888),
+            },
+            Some(Arc::new(IgnoreObjectData)),
+        )?;
+
+        let buffer = WlBuffer::from_id(conn, buffer_id)?;
+
+        Ok(Self {
             buffer,
             delay: image.delay,
             xhot: image.xhot,
             yhot: image.yhot,
             width: image.width,
             height: image.height,
-        }
+        })
     }
 
     /// Dimensions of this image
@@ -405,14 +562,52 @@ impl CursorImageBuffer {
     }
 
     /// Location of the pointer hotspot in this image
+    ///
+    /// It is given in buffer pixels. See [`scaled_hotspot()`][Self::scaled_hotspot()] for the hotspot to
+    /// give to `wl_pointer.set_cursor`.
     pub fn hotspot(&self) -> (u32, u32) {
         (self.xhot, self.yhot)
     }
 
+    /// Location of the pointer hotspot in the coordinates of a surface with the given buffer scale
+    ///
+    /// The hotspot given to `wl_pointer.set_cursor` is in surface-local coordinates, while the images
+    /// of a cursor are in buffer pixels. When this image is displayed on a surface with a buffer scale
+    /// (typically a theme loaded with its size multiplied by the scale of the output, and attached to a
+    /// surface after `wl_surface.set_buffer_scale(scale)`), the hotspot must thus be divided by the scale,
+    /// otherwise the cursor appears shifted on scaled outputs.
+    ///
+    /// The result is rounded down if the hotspot is not a multiple of the scale. A `scale` lower than 1 is
+    /// treated as 1.
+    pub fn scaled_hotspot(&self, scale: i32) -> (i32, i32) {
+        let scale = scale.max(1);
+        (self.xhot as i32 / scale, self.yhot as i32 / scale)
+    }
+
     /// Time (in milliseconds) for which this image should be displayed
     pub fn delay(&self) -> u32 {
         self.delay
     }
+
+    /// Attach this image to a surface, damage it entirely and commit the surface
+    ///
+    /// The damage is sent with `wl_surface.damage_buffer` if the surface supports it (version 4 and
+    /// later), and with `wl_surface.damage` otherwise.
+    ///
+    /// This fails without sending anything if the buffer was destroyed by [`CursorTheme::reload()`].
+    pub fn attach_to(&self, surface: &WlSurface) -> Result<(), InvalidId> {
+        if !self.buffer.is_alive() {
+            return Err(InvalidId);
+        }
+        surface.attach(Some(&self.buffer), 0, 0);
+        if surface.version() >= 4 {
+            surface.damage_buffer(0, 0, self.width as i32, self.height as i32);
+        } else {
+            surface.damage(0, 0, self.width as i32, self.height as i32);
+        }
+        surface.commit();
+        Ok(())
+    }
 }
 
 impl Deref for CursorImageBuffer {
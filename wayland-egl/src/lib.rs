@@ -10,7 +10,7 @@
 //!
 //! See [`WlEglSurface`] documentation for details.
 
-use std::{fmt, os::raw::c_void};
+use std::{cell::Cell, fmt, os::raw::c_void};
 
 use wayland_backend::client::ObjectId;
 use wayland_sys::{client::wl_proxy, egl::*, ffi_dispatch};
@@ -33,6 +33,7 @@ pub fn is_available() -> bool {
 #[derive(Debug)]
 pub struct WlEglSurface {
     ptr: *mut wl_egl_window,
+    requested_size: Cell<(i32, i32)>,
 }
 
 impl WlEglSurface {
@@ -75,10 +76,14 @@ impl WlEglSurface {
         if ptr.is_null() {
             panic!("egl window allocation failed");
         }
-        Ok(Self { ptr })
+        Ok(Self { ptr, requested_size: Cell::new((width, height)) })
     }
 
     /// Fetch current size of the EGL surface
+    ///
+    /// This is the size of the last buffer attached to the surface by the EGL implementation, which
+    /// only follows a [`resize()`][Self::resize()] once the next frame is submitted. See
+    /// [`last_requested_size()`][Self::last_requested_size()] for the size that was requested.
     pub fn get_size(&self) -> (i32, i32) {
         let mut w = 0i32;
         let mut h = 0i32;
@@ -101,6 +106,7 @@ impl WlEglSurface {
     /// of the top-left corner of the surface. It allows you to control the
     /// direction of the resizing if necessary.
     pub fn resize(&self, width: i32, height: i32, dx: i32, dy: i32) {
+        self.requested_size.set((width, height));
         unsafe {
             ffi_dispatch!(
                 wayland_egl_handle(),
@@ -121,6 +127,24 @@ impl WlEglSurface {
     pub fn ptr(&self) -> *const c_void {
         self.ptr as *const c_void
     }
+
+    /// Typed raw pointer to the underlying `wl_egl_window`
+    ///
+    /// This is the same pointer as [`ptr()`][Self::ptr()], for use with functions of
+    /// `libwayland-egl` not wrapped by this crate. It remains owned by this [`WlEglSurface`].
+    pub fn wl_egl_window(&self) -> *mut wl_egl_window {
+        self.ptr
+    }
+
+    /// The size given to the last call to [`resize()`][Self::resize()], or to the creation of the
+    /// surface if it was never resized
+    ///
+    /// Unlike [`get_size()`][Self::get_size()], this does not call into `libwayland-egl`, and is
+    /// already updated before the next frame is submitted. This is for example the size to use for
+    /// damage tracking with `eglSwapBuffersWithDamage` right after a resize.
+    pub fn last_requested_size(&self) -> (i32, i32) {
+        self.requested_size.get()
+    }
 }
 
 // SAFETY: We own the pointer to the wl_egl_window and can therefore be transferred to another thread.
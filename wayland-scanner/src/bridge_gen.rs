@@ -0,0 +1,196 @@
+use proc_macro2::{Ident, Span, TokenStream};
+
+use quote::quote;
+
+use crate::{
+    protocol::{Arg, Interface, Message, Protocol, Type},
+    util::{arg_ident, snake_to_camel},
+};
+
+pub fn generate_bridge_objects(protocol: &Protocol) -> TokenStream {
+    protocol
+        .interfaces
+        .iter()
+        // the server-side code is not generated for these interfaces, which are handled by the backend
+        .filter(|iface| iface.name != "wl_display" && iface.name != "wl_registry")
+        .map(generate_bridge_for)
+        .collect()
+}
+
+fn generate_bridge_for(interface: &Interface) -> TokenStream {
+    let mod_name = Ident::new(&interface.name, Span::call_site());
+    let doc = format!(
+        "Conversions between the client-side and server-side messages of `{}`",
+        interface.name
+    );
+
+    let request_arms = interface.requests.iter().map(|msg| gen_request_arm(&mod_name, msg));
+    let event_arms = interface.events.iter().map(|msg| gen_event_arm(&mod_name, msg));
+
+    quote! {
+        #[doc = #doc]
+        pub mod #mod_name {
+            #[allow(unused_imports)]
+            use std::os::unix::io::AsFd;
+
+            #[allow(unused_imports)]
+            use super::wayland_client::{Connection, Proxy};
+            #[allow(unused_imports)]
+            use super::wayland_server::{DisplayHandle, Resource};
+
+            /// Convert a request received by the server into the request to send to the upstream server
+            ///
+            /// The objects of the request are translated with `map`, which should return the ID of the
+            /// client-side counterpart of a server-side object. The objects created by the request are not
+            /// part of the returned request: the caller needs to send it with `Proxy::send_constructor()` and
+            /// to initialize the server-side object from the original request.
+            ///
+            /// Returns `None` if an object could not be translated, or if the request creates an object of
+            /// a dynamic interface, which cannot be bridged.
+            #[allow(unused_mut, unused_variables)]
+            pub fn request_to_client<'a>(
+                request: &'a super::server::#mod_name::Request,
+                conn: &Connection,
+                mut map: impl FnMut(&super::wayland_server::backend::ObjectId) -> Option<super::wayland_client::backend::ObjectId>,
+            ) -> Option<super::client::#mod_name::Request<'a>> {
+                #[allow(unreachable_patterns)]
+                match request {
+                    #(#request_arms,)*
+                    _ => None,
+                }
+            }
+
+            /// Convert an event received from the upstream server into the event to send to the client
+            ///
+            /// The objects of the event are translated with `map`, which should return the ID of the
+            /// server-side counterpart of a client-side object. This includes the objects created by the
+            /// event, which must have been created on the server-side beforehand.
+            ///
+            /// Returns `None` if an object could not be translated.
+            #[allow(unused_mut, unused_variables)]
+            pub fn event_to_server<'a>(
+                event: &'a super::client::#mod_name::Event,
+                dh: &DisplayHandle,
+                mut map: impl FnMut(&super::wayland_client::backend::ObjectId) -> Option<super::wayland_server::backend::ObjectId>,
+            ) -> Option<super::server::#mod_name::Event<'a>> {
+                #[allow(unreachable_patterns)]
+                match event {
+                    #(#event_arms,)*
+                    _ => None,
+                }
+            }
+        }
+    }
+}
+
+fn gen_request_arm(mod_name: &Ident, msg: &Message) -> TokenStream {
+    let msg_name = Ident::new(&snake_to_camel(&msg.name), Span::call_site());
+    let pattern = if msg.args.is_empty() {
+        quote! { super::server::#mod_name::Request::#msg_name }
+    } else {
+        let names = msg.args.iter().map(|arg| arg_ident(&arg.name));
+        quote! { super::server::#mod_name::Request::#msg_name { #(#names),* } }
+    };
+
+    // the client cannot create an object of an interface only known at runtime from its name
+    if msg.args.iter().any(|arg| arg.typ == Type::NewId && arg.interface.is_none()) {
+        return quote! { #pattern => None };
+    }
+
+    let fields = msg
+        .args
+        .iter()
+        .filter(|arg| arg.typ != Type::NewId)
+        .map(|arg| {
+            let name = arg_ident(&arg.name);
+            let value =
+                gen_converted_arg(arg, quote!(Resource::id), quote!(Proxy::from_id(conn, id)));
+            quote! { #name: #value }
+        })
+        .collect::<Vec<_>>();
+    let value = if msg.args.is_empty() {
+        quote! { super::client::#mod_name::Request::#msg_name }
+    } else {
+        quote! { super::client::#mod_name::Request::#msg_name { #(#fields),* } }
+    };
+    quote! { #pattern => Some(#value) }
+}
+
+fn gen_event_arm(mod_name: &Ident, msg: &Message) -> TokenStream {
+    let msg_name = Ident::new(&snake_to_camel(&msg.name), Span::call_site());
+    if msg.args.is_empty() {
+        return quote! {
+            super::client::#mod_name::Event::#msg_name => Some(super::server::#mod_name::Event::#msg_name)
+        };
+    }
+    let names = msg.args.iter().map(|arg| arg_ident(&arg.name));
+    let fields = msg.args.iter().map(|arg| {
+        let name = arg_ident(&arg.name);
+        let value = gen_converted_arg(arg, quote!(Proxy::id), quote!(Resource::from_id(dh, id)));
+        quote! { #name: #value }
+    });
+    quote! {
+        super::client::#mod_name::Event::#msg_name { #(#names),* } => {
+            Some(super::server::#mod_name::Event::#msg_name { #(#fields),* })
+        }
+    }
+}
+
+/// Expression converting a borrowed argument of a received message into an argument of the message to send
+///
+/// `get_id` extracts the ID of a received object, and `from_id` builds the object to send from the translated
+/// `id`.
+fn gen_converted_arg(arg: &Arg, get_id: TokenStream, from_id: TokenStream) -> TokenStream {
+    let name = arg_ident(&arg.name);
+    let convert = |value: TokenStream| -> TokenStream {
+        if arg.enum_.is_some() {
+            return quote! { From::from(u32::from(*#value)) };
+        }
+        match arg.typ {
+            Type::Uint | Type::Int | Type::Fixed => quote! { *#value },
+            Type::String | Type::Array => quote! { #value.clone() },
+            Type::Fd => quote! { #value.as_fd() },
+            Type::Object | Type::NewId if arg.interface.is_some() => quote! { {
+                let id = map(&#get_id(#value))?;
+                #from_id.ok()?
+            } },
+            Type::Object => quote! { map(#value)? },
+            // only events reach this point, with the name and version of the interface along with the ID
+            Type::NewId => quote! { (#value.0.clone(), #value.1, map(&#value.2)?) },
+            Type::Destructor => unreachable!(),
+        }
+    };
+    if arg.allow_null {
+        let converted = convert(quote!(value));
+        quote! {
+            match #name {
+                Some(value) => Some(#converted),
+                None => None,
+            }
+        }
+    } else {
+        convert(quote!(#name))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn bridge_gen() {
+        let protocol_file =
+            std::fs::File::open("./tests/scanner_assets/test-protocol.xml").unwrap();
+        let protocol_parsed = crate::parse::parse(protocol_file);
+        let generated: String = super::generate_bridge_objects(&protocol_parsed).to_string();
+        let generated = crate::format_rust_code(&generated);
+
+        let reference =
+            std::fs::read_to_string("./tests/scanner_assets/test-bridge-code.rs").unwrap();
+        let reference = crate::format_rust_code(&reference);
+
+        if reference != generated {
+            let diff = similar::TextDiff::from_lines(&reference, &generated);
+            print!("{}", diff.unified_diff().context_radius(10).header("reference", "generated"));
+            panic!("Generated does not match reference!")
+        }
+    }
+}
@@ -4,21 +4,43 @@ use quote::{format_ident, quote};
 
 use crate::{
     protocol::{Interface, Protocol, Type},
-    util::{description_to_doc_attr, dotted_to_relname, is_keyword, snake_to_camel, to_doc_attr},
-    Side,
+    util::{
+        arg_ident, description_to_doc_attr, dotted_to_relname, is_keyword, snake_to_camel,
+        to_doc_attr,
+    },
+    ClientOptions, Naming, Side,
 };
 
-pub fn generate_client_objects(protocol: &Protocol) -> TokenStream {
-    protocol.interfaces.iter().map(generate_objects_for).collect()
+pub fn generate_client_objects(protocol: &Protocol, options: &ClientOptions) -> TokenStream {
+    if let Some(error) = crate::common::check_enum_references(protocol) {
+        return error;
+    }
+    if let Some(error) = check_method_names(protocol, options) {
+        return error;
+    }
+    let mut tokens: TokenStream = protocol
+        .interfaces
+        .iter()
+        .map(|interface| generate_objects_for(interface, options))
+        .collect();
+    if options.protocol_info {
+        let visibility = crate::module_visibility(&options.visibility);
+        tokens.extend(crate::common::gen_protocol_info(
+            protocol,
+            protocol.interfaces.iter(),
+            &visibility,
+        ));
+    }
+    tokens
 }
 
-fn generate_objects_for(interface: &Interface) -> TokenStream {
+fn generate_objects_for(interface: &Interface, options: &ClientOptions) -> TokenStream {
     let mod_name = Ident::new(&interface.name, Span::call_site());
     let mod_doc = interface.description.as_ref().map(description_to_doc_attr);
     let iface_name = Ident::new(&snake_to_camel(&interface.name), Span::call_site());
     let iface_const_name = format_ident!("{}_INTERFACE", interface.name.to_ascii_uppercase());
 
-    let enums = crate::common::generate_enums_for(interface);
+    let enums = crate::common::generate_enums_for(interface, options.enum_tables);
     let sinces = crate::common::gen_msg_constants(&interface.requests, &interface.events);
 
     let requests = crate::common::gen_message_enum(
@@ -26,17 +48,26 @@ fn generate_objects_for(interface: &Interface) -> TokenStream {
         Side::Client,
         false,
         &interface.requests,
+        options.message_derives,
     );
     let events = crate::common::gen_message_enum(
         &format_ident!("Event"),
         Side::Client,
         true,
         &interface.events,
+        options.message_derives,
     );
 
     let parse_body = crate::common::gen_parse_body(interface, Side::Client);
+    let parse_raw_body = crate::common::gen_parse_raw_body(interface, Side::Client);
+    let try_from_message = crate::common::gen_try_from_message(interface, Side::Client);
     let write_body = crate::common::gen_write_body(interface, Side::Client);
-    let methods = gen_methods(interface);
+    let methods = gen_methods(interface, options.naming);
+    let event_handler = if options.event_handlers {
+        gen_event_handler(interface, options.naming)
+    } else {
+        TokenStream::new()
+    };
 
     let event_ref = if interface.events.is_empty() {
         "This interface has no events."
@@ -48,17 +79,18 @@ fn generate_objects_for(interface: &Interface) -> TokenStream {
         None => format!("{}\n\n{}", interface.name, event_ref),
     };
     let doc_attr = to_doc_attr(&docs);
+    let visibility = crate::module_visibility(&options.visibility);
 
     quote! {
         #mod_doc
-        pub mod #mod_name {
+        #visibility mod #mod_name {
             use std::sync::Arc;
             use std::os::unix::io::OwnedFd;
 
             use super::wayland_client::{
                 backend::{
                     Backend, WeakBackend, smallvec, ObjectData, ObjectId, InvalidId,
-                    protocol::{WEnum, Argument, Message, Interface, same_interface}
+                    protocol::{WEnum, Argument, Fixed, Message, Interface, same_interface}
                 },
                 QueueProxyData, Proxy, Connection, Dispatch, QueueHandle, DispatchError, Weak,
             };
@@ -68,6 +100,19 @@ fn generate_objects_for(interface: &Interface) -> TokenStream {
             #requests
             #events
 
+            /// Decode an event of this interface from its raw arguments, without a connection
+            ///
+            /// This is useful to decode recorded or replayed messages.
+            ///
+            /// **Note:** Events carrying objects, like `wl_surface.enter`, need a connection to create
+            /// their proxies, so `DispatchError::BadMessage` is always returned for them. Decode these
+            /// events with `Proxy::parse_event()` instead.
+            pub fn parse_event_raw(opcode: u16, args: impl IntoIterator<Item = Argument<ObjectId, OwnedFd>>) -> Result<Event, DispatchError> {
+                #parse_raw_body
+            }
+
+            #try_from_message
+
             #doc_attr
             #[derive(Debug, Clone)]
             pub struct #iface_name {
@@ -97,6 +142,24 @@ fn generate_objects_for(interface: &Interface) -> TokenStream {
                 }
             }
 
+            impl AsRef<ObjectId> for #iface_name {
+                fn as_ref(&self) -> &ObjectId {
+                    &self.id
+                }
+            }
+
+            impl From<#iface_name> for ObjectId {
+                fn from(value: #iface_name) -> ObjectId {
+                    value.id
+                }
+            }
+
+            impl From<&#iface_name> for ObjectId {
+                fn from(value: &#iface_name) -> ObjectId {
+                    value.id.clone()
+                }
+            }
+
             impl std::hash::Hash for #iface_name {
                 fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
                     self.id.hash(state)
@@ -176,15 +239,101 @@ fn generate_objects_for(interface: &Interface) -> TokenStream {
             impl #iface_name {
                 #methods
             }
+
+            #event_handler
+        }
+    }
+}
+
+/// Reports the messages whose methods would have the same name once the naming strategy is applied
+fn check_method_names(protocol: &Protocol, options: &ClientOptions) -> Option<TokenStream> {
+    let mut errors = Vec::new();
+    for interface in &protocol.interfaces {
+        let mut lists = vec![&interface.requests];
+        if options.event_handlers {
+            lists.push(&interface.events);
+        }
+        for messages in lists {
+            let mut seen: Vec<(String, &str)> = Vec::new();
+            for msg in messages {
+                let name = options.naming.apply(&msg.name);
+                match seen.iter().find(|(n, _)| *n == name) {
+                    Some((_, other)) => errors.push(format!(
+                        "{}.{} and {}.{} both generate a method named {}",
+                        interface.name, other, interface.name, msg.name, name
+                    )),
+                    None => seen.push((name, &msg.name)),
+                }
+            }
+        }
+    }
+    if errors.is_empty() {
+        return None;
+    }
+    let msg =
+        format!("Protocol {} has colliding method names: {}", protocol.name, errors.join(", "));
+    Some(quote! { compile_error!(#msg); })
+}
+
+fn method_ident(name: &str, naming: Naming) -> Ident {
+    let name = naming.apply(name);
+    format_ident!("{}{}", if is_keyword(&name) { "_" } else { "" }, name)
+}
+
+fn gen_event_handler(interface: &Interface, naming: Naming) -> TokenStream {
+    if interface.events.is_empty() {
+        return TokenStream::new();
+    }
+
+    let iface_name = Ident::new(&snake_to_camel(&interface.name), Span::call_site());
+
+    let (methods, cases): (Vec<_>, Vec<_>) = interface.events.iter().map(|event| {
+        let method_name = method_ident(&event.name, naming);
+        let variant = Ident::new(&snake_to_camel(&event.name), Span::call_site());
+        let doc_attr = event.description.as_ref().map(description_to_doc_attr);
+
+        let (names, types): (Vec<_>, Vec<_>) = event.args.iter().filter_map(|arg| {
+            let arg_name = arg_ident(&arg.name);
+            let arg_type = crate::common::gen_message_arg_type(arg, Side::Client, true)?;
+            Some((arg_name, arg_type))
+        }).unzip();
+
+        let method = quote! {
+            #doc_attr
+            #[allow(unused_variables, clippy::too_many_arguments)]
+            fn #method_name(&mut self, proxy: &#iface_name, #(#names: #types),*) {}
+        };
+        let case = if event.args.is_empty() {
+            quote! { Event::#variant => handler.#method_name(proxy) }
+        } else {
+            quote! { Event::#variant { #(#names),* } => handler.#method_name(proxy, #(#names),*) }
+        };
+        (method, case)
+    }).unzip();
+
+    quote! {
+        #[doc = "Handler for the events of this interface"]
+        #[doc = ""]
+        #[doc = "Each event is delivered to its own method, which does nothing by default. Use [`dispatch_event()`]"]
+        #[doc = "to forward an [`Event`] to the matching method."]
+        pub trait EventHandler {
+            #(#methods)*
+        }
+
+        #[doc = "Forward an event to the matching method of an [`EventHandler`]"]
+        pub fn dispatch_event<H: EventHandler + ?Sized>(handler: &mut H, proxy: &#iface_name, event: Event) {
+            match event {
+                #(#cases,)*
+            }
         }
     }
 }
 
-fn gen_methods(interface: &Interface) -> TokenStream {
+fn gen_methods(interface: &Interface, naming: Naming) -> TokenStream {
     interface.requests.iter().map(|request| {
         let created_interface = request.args.iter().find(|arg| arg.typ == Type::NewId).map(|arg| &arg.interface);
 
-        let method_name = format_ident!("{}{}", if is_keyword(&request.name) { "_" } else { "" }, request.name);
+        let method_name = method_ident(&request.name, naming);
         let enum_variant = Ident::new(&snake_to_camel(&request.name), Span::call_site());
 
         let fn_args = request.args.iter().flat_map(|arg| {
@@ -200,7 +349,7 @@ fn gen_methods(interface: &Interface) -> TokenStream {
                 }
             }
 
-            let arg_name = format_ident!("{}{}", if is_keyword(&arg.name) { "_" } else { "" }, arg.name);
+            let arg_name = arg_ident(&arg.name);
 
             let arg_type =  if let Some(ref enu) = arg.enum_ {
                 let enum_type = dotted_to_relname(enu);
@@ -231,7 +380,7 @@ fn gen_methods(interface: &Interface) -> TokenStream {
         });
 
         let enum_args = request.args.iter().flat_map(|arg| {
-            let arg_name = format_ident!("{}{}", if is_keyword(&arg.name) { "_" } else { "" }, arg.name);
+            let arg_name = arg_ident(&arg.name);
             if arg.enum_.is_some() {
                 Some(quote! { #arg_name: WEnum::Value(#arg_name) })
             } else if arg.typ == Type::NewId {
@@ -264,6 +413,7 @@ fn gen_methods(interface: &Interface) -> TokenStream {
                 quote! {
                     #doc_attr
                     #[allow(clippy::too_many_arguments)]
+                    #[must_use = "the created object can only be used through the returned proxy"]
                     pub fn #method_name<U: Send + Sync + 'static, D: Dispatch<super::#created_iface_mod::#created_iface_type, U> + 'static>(&self, #(#fn_args,)* qh: &QueueHandle<D>, udata: U) -> super::#created_iface_mod::#created_iface_type {
                         self.send_constructor(
                             Request::#enum_variant {
@@ -279,6 +429,7 @@ fn gen_methods(interface: &Interface) -> TokenStream {
                 quote! {
                     #doc_attr
                     #[allow(clippy::too_many_arguments)]
+                    #[must_use = "the created object can only be used through the returned proxy"]
                     pub fn #method_name<I: Proxy + 'static, U: Send + Sync + 'static, D: Dispatch<I, U> + 'static>(&self, #(#fn_args,)* qh: &QueueHandle<D>, udata: U) -> I {
                         self.send_constructor(
                             Request::#enum_variant {
@@ -321,7 +472,8 @@ mod tests {
         let protocol_file =
             std::fs::File::open("./tests/scanner_assets/test-protocol.xml").unwrap();
         let protocol_parsed = crate::parse::parse(protocol_file);
-        let generated: String = super::generate_client_objects(&protocol_parsed).to_string();
+        let generated: String =
+            super::generate_client_objects(&protocol_parsed, &Default::default()).to_string();
         let generated = crate::format_rust_code(&generated);
 
         let reference =
@@ -334,4 +486,274 @@ mod tests {
             panic!("Generated does not match reference!")
         }
     }
+
+    #[test]
+    fn client_gen_visibility() {
+        let protocol_file =
+            std::fs::File::open("./tests/scanner_assets/test-protocol.xml").unwrap();
+        let protocol_parsed = crate::parse::parse(protocol_file);
+        let options = crate::ClientOptions {
+            visibility: Some(quote::quote!(pub(crate))),
+            ..Default::default()
+        };
+        let generated: String =
+            super::generate_client_objects(&protocol_parsed, &options).to_string();
+        let generated = crate::format_rust_code(&generated);
+
+        for interface in &protocol_parsed.interfaces {
+            assert!(generated.contains(&format!("pub(crate) mod {} {{", interface.name)));
+            assert!(!generated.contains(&format!("pub mod {} {{", interface.name)));
+        }
+    }
+
+    #[test]
+    fn client_gen_enum_args() {
+        let protocol_xml = r#"<protocol name="enums">
+            <interface name="first" version="1">
+                <request name="set">
+                    <arg name="mode" type="uint" enum="mode"/>
+                    <arg name="kind" type="int" enum="second.kind"/>
+                </request>
+                <event name="changed">
+                    <arg name="mode" type="uint" enum="mode"/>
+                </event>
+                <enum name="mode">
+                    <entry name="on" value="0"/>
+                    <entry name="off" value="1"/>
+                </enum>
+            </interface>
+            <interface name="second" version="1">
+                <enum name="kind">
+                    <entry name="a" value="0"/>
+                </enum>
+            </interface>
+        </protocol>"#;
+        let protocol_parsed = crate::parse::parse(protocol_xml.as_bytes());
+        let generated: String =
+            super::generate_client_objects(&protocol_parsed, &Default::default()).to_string();
+        let generated = crate::format_rust_code(&generated);
+
+        assert!(generated.contains("mode: WEnum<Mode>"));
+        assert!(generated.contains("kind: WEnum<super::second::Kind>"));
+        assert!(generated.contains("mode: Mode"));
+        assert!(generated.contains("kind: super::second::Kind"));
+    }
+
+    #[test]
+    fn client_gen_keyword_args() {
+        let protocol_xml = r#"<protocol name="keywords">
+            <interface name="first" version="1">
+                <request name="set">
+                    <arg name="type" type="uint"/>
+                    <arg name="async" type="int"/>
+                    <arg name="self" type="object" interface="first"/>
+                </request>
+                <event name="changed">
+                    <arg name="type" type="uint"/>
+                </event>
+            </interface>
+        </protocol>"#;
+        let protocol_parsed = crate::parse::parse(protocol_xml.as_bytes());
+        let generated: String =
+            super::generate_client_objects(&protocol_parsed, &Default::default()).to_string();
+        let generated = crate::format_rust_code(&generated);
+
+        assert!(generated.contains("r#type: u32"));
+        assert!(generated.contains("r#async: i32"));
+        assert!(generated.contains("_self: &super::first::First"));
+        assert!(!generated.contains("_type"));
+    }
+
+    #[test]
+    fn client_gen_enum_tables() {
+        let protocol_xml = r#"<protocol name="enums">
+            <interface name="first" version="1">
+                <enum name="mode">
+                    <entry name="on" value="0"/>
+                    <entry name="off" value="1"/>
+                </enum>
+                <enum name="caps" bitfield="true">
+                    <entry name="read" value="1"/>
+                    <entry name="write" value="2"/>
+                </enum>
+            </interface>
+        </protocol>"#;
+        let protocol_parsed = crate::parse::parse(protocol_xml.as_bytes());
+
+        let generated: String =
+            super::generate_client_objects(&protocol_parsed, &Default::default()).to_string();
+        assert!(!generated.contains("ALL"));
+
+        let options = crate::ClientOptions { enum_tables: true, ..Default::default() };
+        let generated: String =
+            super::generate_client_objects(&protocol_parsed, &options).to_string();
+        let generated = crate::format_rust_code(&generated);
+
+        assert!(generated.contains(
+            r#"pub const ALL: &'static [(u32, &'static str)] = &[(0, "on"), (1, "off")];"#
+        ));
+        assert!(generated.contains(
+            r#"pub const ALL: &'static [(u32, &'static str)] = &[(1, "read"), (2, "write")];"#
+        ));
+    }
+
+    #[test]
+    fn client_gen_protocol_info() {
+        let protocol_xml = r#"<protocol name="info">
+            <interface name="first" version="3"/>
+            <interface name="second" version="1"/>
+        </protocol>"#;
+        let protocol_parsed = crate::parse::parse(protocol_xml.as_bytes());
+
+        let generated: String =
+            super::generate_client_objects(&protocol_parsed, &Default::default()).to_string();
+        assert!(!generated.contains("PROTOCOL_NAME"));
+
+        let options = crate::ClientOptions { protocol_info: true, ..Default::default() };
+        let generated: String =
+            super::generate_client_objects(&protocol_parsed, &options).to_string();
+        let generated = crate::format_rust_code(&generated);
+
+        assert!(generated.contains(r#"pub const PROTOCOL_NAME: &str = "info";"#));
+        assert!(generated.contains(
+            r#"pub const INTERFACE_VERSIONS: &[(&str, u32)] = &[("first", 3), ("second", 1)];"#
+        ));
+
+        // the constants follow the visibility of the interface modules
+        let options = crate::ClientOptions {
+            protocol_info: true,
+            visibility: Some(quote::quote!(pub(crate))),
+            ..Default::default()
+        };
+        let generated: String =
+            super::generate_client_objects(&protocol_parsed, &options).to_string();
+        let generated = crate::format_rust_code(&generated);
+
+        assert!(generated.contains(r#"pub(crate) const PROTOCOL_NAME: &str = "info";"#));
+        assert!(generated.contains("pub(crate) const INTERFACE_VERSIONS"));
+        assert!(!generated.contains("pub const"));
+    }
+
+    #[test]
+    fn client_gen_message_derives() {
+        let protocol_xml = r#"<protocol name="derives">
+            <interface name="scalars" version="1">
+                <request name="set">
+                    <arg name="x" type="uint"/>
+                    <arg name="y" type="fixed"/>
+                </request>
+                <event name="changed">
+                    <arg name="value" type="int"/>
+                </event>
+            </interface>
+            <interface name="buffers" version="1">
+                <request name="attach">
+                    <arg name="name" type="string"/>
+                    <arg name="data" type="array"/>
+                </request>
+                <event name="fd">
+                    <arg name="fd" type="fd"/>
+                </event>
+            </interface>
+        </protocol>"#;
+        let protocol_parsed = crate::parse::parse(protocol_xml.as_bytes());
+
+        let derives = |options: &crate::ClientOptions, module: &str, name: &str| {
+            let generated = super::generate_client_objects(&protocol_parsed, options).to_string();
+            let generated = crate::format_rust_code(&generated);
+            let module = &generated[generated.find(&format!("pub mod {} {{", module)).unwrap()..];
+            let before = &module[..module.find(&format!("pub enum {}", name)).unwrap()];
+            before[before.rfind("#[derive(").unwrap()..].lines().next().unwrap().to_owned()
+        };
+
+        let options = crate::ClientOptions::default();
+        assert_eq!(derives(&options, "scalars", "Request"), "#[derive(Debug)]");
+
+        let options = crate::ClientOptions { message_derives: true, ..Default::default() };
+        assert_eq!(derives(&options, "scalars", "Request"), "#[derive(Debug, Clone, Copy)]");
+        assert_eq!(derives(&options, "scalars", "Event"), "#[derive(Debug, Clone, Copy)]");
+        assert_eq!(derives(&options, "buffers", "Request"), "#[derive(Debug, Clone)]");
+        // received fds are owned
+        assert_eq!(derives(&options, "buffers", "Event"), "#[derive(Debug)]");
+    }
+
+    #[test]
+    fn client_gen_unknown_enum() {
+        let protocol_xml = r#"<protocol name="enums">
+            <interface name="first" version="1">
+                <request name="set">
+                    <arg name="mode" type="uint" enum="mode"/>
+                    <arg name="kind" type="uint" enum="wl_output.transform"/>
+                </request>
+            </interface>
+        </protocol>"#;
+        let protocol_parsed = crate::parse::parse(protocol_xml.as_bytes());
+        let generated: String =
+            super::generate_client_objects(&protocol_parsed, &Default::default()).to_string();
+        assert!(generated.starts_with("compile_error !"));
+        assert!(generated.contains("first.set.mode references unknown enum mode"));
+        assert!(!generated.contains("wl_output"));
+    }
+
+    #[test]
+    fn client_gen_naming() {
+        let protocol_xml = r#"<protocol name="naming">
+            <interface name="first" version="1">
+                <request name="setMode">
+                    <arg name="mode" type="uint"/>
+                </request>
+            </interface>
+        </protocol>"#;
+        let protocol_parsed = crate::parse::parse(protocol_xml.as_bytes());
+
+        let generated =
+            super::generate_client_objects(&protocol_parsed, &Default::default()).to_string();
+        assert!(generated.contains("pub fn setMode"));
+
+        let options = crate::ClientOptions { naming: crate::Naming::Snake, ..Default::default() };
+        let generated = super::generate_client_objects(&protocol_parsed, &options).to_string();
+        assert!(generated.contains("pub fn set_mode"));
+        assert!(!generated.contains("setMode"));
+    }
+
+    #[test]
+    fn client_gen_naming_collision() {
+        let protocol_xml = r#"<protocol name="naming">
+            <interface name="first" version="1">
+                <request name="set_mode"/>
+                <request name="setMode"/>
+            </interface>
+        </protocol>"#;
+        let protocol_parsed = crate::parse::parse(protocol_xml.as_bytes());
+        let options = crate::ClientOptions { naming: crate::Naming::Snake, ..Default::default() };
+        let generated = super::generate_client_objects(&protocol_parsed, &options).to_string();
+        assert!(generated.starts_with("compile_error !"));
+        assert!(generated
+            .contains("first.set_mode and first.setMode both generate a method named set_mode"));
+    }
+
+    #[test]
+    fn client_gen_event_handlers() {
+        let protocol_file =
+            std::fs::File::open("./tests/scanner_assets/test-protocol.xml").unwrap();
+        let protocol_parsed = crate::parse::parse(protocol_file);
+        let generated: String = protocol_parsed
+            .interfaces
+            .iter()
+            .map(|interface| super::gen_event_handler(interface, crate::Naming::Preserve))
+            .collect::<proc_macro2::TokenStream>()
+            .to_string();
+        let generated = crate::format_rust_code(&generated);
+
+        let reference =
+            std::fs::read_to_string("./tests/scanner_assets/test-client-event-handlers.rs")
+                .unwrap();
+        let reference = crate::format_rust_code(&reference);
+
+        if reference != generated {
+            let diff = similar::TextDiff::from_lines(&reference, &generated);
+            print!("{}", diff.unified_diff().context_radius(10).header("reference", "generated"));
+            panic!("Generated does not match reference!")
+        }
+    }
 }
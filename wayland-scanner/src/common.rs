@@ -6,14 +6,98 @@ use quote::{format_ident, quote, ToTokens};
 
 use crate::{protocol::*, util::*, Side};
 
-pub(crate) fn generate_enums_for(interface: &Interface) -> TokenStream {
-    interface.enums.iter().map(ToTokens::into_token_stream).collect()
+/// Check that the `enum` attributes of the message arguments can be resolved
+///
+/// Arguments with an `enum` attribute are typed with the referenced enum, so a dangling reference
+/// would otherwise surface as an obscure type resolution error in the generated code. References to
+/// interfaces of other protocols cannot be checked here and are assumed to be valid.
+pub(crate) fn check_enum_references(protocol: &Protocol) -> Option<TokenStream> {
+    let mut errors = Vec::new();
+    for interface in &protocol.interfaces {
+        for msg in interface.requests.iter().chain(&interface.events) {
+            for arg in &msg.args {
+                let Some(ref enu) = arg.enum_ else { continue };
+                if !matches!(arg.typ, Type::Uint | Type::Int) {
+                    errors.push(format!(
+                        "{}.{}.{} has an enum attribute but is not an integer",
+                        interface.name, msg.name, arg.name
+                    ));
+                    continue;
+                }
+                let (target, enum_name) = match enu.split_once('.') {
+                    Some((iface_name, enum_name)) => {
+                        match protocol.interfaces.iter().find(|i| i.name == iface_name) {
+                            Some(target) => (target, enum_name),
+                            None => continue,
+                        }
+                    }
+                    None => (interface, enu.as_str()),
+                };
+                if !target.enums.iter().any(|e| e.name == enum_name) {
+                    errors.push(format!(
+                        "{}.{}.{} references unknown enum {}",
+                        interface.name, msg.name, arg.name, enu
+                    ));
+                }
+            }
+        }
+    }
+    if errors.is_empty() {
+        return None;
+    }
+    let msg =
+        format!("Protocol {} has invalid enum arguments: {}", protocol.name, errors.join(", "));
+    Some(quote! { compile_error!(#msg); })
+}
+
+pub(crate) fn generate_enums_for(interface: &Interface, enum_tables: bool) -> TokenStream {
+    let mut tokens: TokenStream = interface.enums.iter().map(ToTokens::into_token_stream).collect();
+    if enum_tables {
+        tokens.extend(interface.enums.iter().map(gen_enum_table));
+    }
+    tokens
+}
+
+/// Generate the `PROTOCOL_NAME` and `INTERFACE_VERSIONS` constants describing the generated interfaces
+pub(crate) fn gen_protocol_info<'a>(
+    protocol: &Protocol,
+    interfaces: impl Iterator<Item = &'a Interface>,
+    visibility: &TokenStream,
+) -> TokenStream {
+    let name = &protocol.name;
+    let versions = interfaces.map(|interface| {
+        let iface_name = &interface.name;
+        let version = Literal::u32_unsuffixed(interface.version);
+        quote! { (#iface_name, #version) }
+    });
+    quote! {
+        /// The name of the protocol, as declared by its XML specification
+        #visibility const PROTOCOL_NAME: &str = #name;
+        /// The interfaces generated for this protocol, along with their maximal version
+        #visibility const INTERFACE_VERSIONS: &[(&str, u32)] = &[#(#versions),*];
+    }
+}
+
+/// Generate the `ALL` table listing the values of an enum along with their names
+fn gen_enum_table(enu: &Enum) -> TokenStream {
+    let ident = Ident::new(&snake_to_camel(&enu.name), Span::call_site());
+    let entries = enu.entries.iter().map(|entry| {
+        let value = Literal::u32_unsuffixed(entry.value);
+        let name = &entry.name;
+        quote! { (#value, #name) }
+    });
+    quote! {
+        impl #ident {
+            /// All the values defined by the protocol for this enum, along with their names
+            pub const ALL: &'static [(u32, &'static str)] = &[#(#entries),*];
+        }
+    }
 }
 
 impl ToTokens for Enum {
     fn to_tokens(&self, tokens: &mut TokenStream) {
         let enum_decl;
-        let enum_impl;
+        let mut enum_impl;
 
         let doc_attr = self.description.as_ref().map(description_to_doc_attr);
         let ident = Ident::new(&snake_to_camel(&self.name), Span::call_site());
@@ -115,6 +199,11 @@ impl ToTokens for Enum {
                     }
                 }
             };
+
+            // the error codes of an interface can be used directly as rust errors
+            if self.name == "error" {
+                enum_impl.extend(gen_error_impls(self, &ident));
+            }
         }
 
         enum_decl.to_tokens(tokens);
@@ -122,6 +211,32 @@ impl ToTokens for Enum {
     }
 }
 
+/// Generate the `Display` and `Error` implementations of an `error` enum
+///
+/// Each code is displayed as its summary, or as its name if it has none.
+fn gen_error_impls(enu: &Enum, ident: &Ident) -> TokenStream {
+    let display_arms = enu.entries.iter().map(|entry| {
+        let prefix = if entry.name.chars().next().unwrap().is_numeric() { "_" } else { "" };
+        let variant = format_ident!("{}{}", prefix, snake_to_camel(&entry.name));
+        let text = entry.summary.as_deref().unwrap_or(&entry.name);
+
+        quote! {
+            #ident::#variant => f.write_str(#text)
+        }
+    });
+
+    quote! {
+        impl std::fmt::Display for #ident {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                match self {
+                    #(#display_arms,)*
+                }
+            }
+        }
+        impl std::error::Error for #ident {}
+    }
+}
+
 pub(crate) fn gen_msg_constants(requests: &[Message], events: &[Message]) -> TokenStream {
     let req_constants = requests.iter().enumerate().map(|(opcode, msg)| {
         let since_cstname = format_ident!("REQ_{}_SINCE", msg.name.to_ascii_uppercase());
@@ -159,6 +274,7 @@ pub(crate) fn gen_message_enum(
     side: Side,
     receiver: bool,
     messages: &[Message],
+    derives: bool,
 ) -> TokenStream {
     let variants = messages
         .iter()
@@ -182,98 +298,31 @@ pub(crate) fn gen_message_enum(
 
             let doc_attr = to_doc_attr(&docs);
             let msg_name = Ident::new(&snake_to_camel(&msg.name), Span::call_site());
-            let msg_variant_decl =
-                if msg.args.is_empty() {
-                    msg_name.into_token_stream()
-                } else {
-                    let fields = msg.args.iter().flat_map(|arg| {
-                let field_name =
-                    format_ident!("{}{}", if is_keyword(&arg.name) { "_" } else { "" }, arg.name);
-                let field_type_inner = if let Some(ref enu) = arg.enum_ {
-                    let enum_type = dotted_to_relname(enu);
-                    quote! { WEnum<#enum_type> }
-                } else {
-                    match arg.typ {
-                        Type::Uint => quote! { u32 },
-                        Type::Int => quote! { i32 },
-                        Type::Fixed => quote! { f64 },
-                        Type::String => quote! { String },
-                        Type::Array => quote! { Vec<u8> },
-                        Type::Fd => {
-                            if receiver {
-                                quote! { OwnedFd }
-                            } else {
-                                quote! { std::os::unix::io::BorrowedFd<'a> }
-                            }
-                        }
-                        Type::Object => {
-                            if let Some(ref iface) = arg.interface {
-                                let iface_mod = Ident::new(iface, Span::call_site());
-                                let iface_type =
-                                    Ident::new(&snake_to_camel(iface), Span::call_site());
-                                quote! { super::#iface_mod::#iface_type }
-                            } else if side == Side::Client {
-                                quote! { super::wayland_client::ObjectId }
-                            } else {
-                                quote! { super::wayland_server::ObjectId }
-                            }
-                        }
-                        Type::NewId if !receiver && side == Side::Client => {
-                            // Client-side sending does not have a pre-existing object
-                            // so skip serializing it
-                            if arg.interface.is_some() {
-                                return None;
-                            } else {
-                                quote! { (&'static Interface, u32) }
-                            }
-                        }
-                        Type::NewId => {
-                            if let Some(ref iface) = arg.interface {
-                                let iface_mod = Ident::new(iface, Span::call_site());
-                                let iface_type =
-                                    Ident::new(&snake_to_camel(iface), Span::call_site());
-                                if receiver && side == Side::Server {
-                                    quote! { New<super::#iface_mod::#iface_type> }
-                                } else {
-                                    quote! { super::#iface_mod::#iface_type }
-                                }
-                            } else {
-                                // bind-like function
-                                if side == Side::Client {
-                                    quote! { (String, u32, super::wayland_client::ObjectId) }
-                                } else {
-                                    quote! { (String, u32, super::wayland_server::ObjectId) }
-                                }
-                            }
-                        }
-                        Type::Destructor => panic!("An argument cannot have type \"destructor\"."),
-                    }
-                };
-
-                let field_type = if arg.allow_null {
-                    quote! { Option<#field_type_inner> }
-                } else {
-                    field_type_inner.into_token_stream()
-                };
-
-                let doc_attr = arg
-                    .description
-                    .as_ref()
-                    .map(description_to_doc_attr)
-                    .or_else(|| arg.summary.as_ref().map(|s| to_doc_attr(s)));
-
-                Some(quote! {
-                    #doc_attr
-                    #field_name: #field_type
-                })
-            });
+            let msg_variant_decl = if msg.args.is_empty() {
+                msg_name.into_token_stream()
+            } else {
+                let fields = msg.args.iter().flat_map(|arg| {
+                    let field_name = arg_ident(&arg.name);
+                    let field_type = gen_message_arg_type(arg, side, receiver)?;
+
+                    let doc_attr = arg
+                        .description
+                        .as_ref()
+                        .map(description_to_doc_attr)
+                        .or_else(|| arg.summary.as_ref().map(|s| to_doc_attr(s)));
+
+                    Some(quote! {
+                        #doc_attr
+                        #field_name: #field_type
+                    })
+                });
 
-                    quote! {
-                        #msg_name {
-                            #(#fields,)*
-                        }
+                quote! {
+                    #msg_name {
+                        #(#fields,)*
                     }
-                };
+                }
+            };
 
             quote! {
                 #doc_attr
@@ -309,8 +358,24 @@ pub(crate) fn gen_message_enum(
         (quote! {}, quote! {}, quote! {})
     };
 
+    let derives = if derives {
+        match messages
+            .iter()
+            .flat_map(|msg| &msg.args)
+            .map(|arg| arg_derivable(arg, side, receiver))
+            .min()
+            .unwrap_or(Derivable::Copy)
+        {
+            Derivable::Nothing => quote! {},
+            Derivable::Clone => quote! { , Clone },
+            Derivable::Copy => quote! { , Clone, Copy },
+        }
+    } else {
+        quote! {}
+    };
+
     quote! {
-        #[derive(Debug)]
+        #[derive(Debug #derives)]
         #[non_exhaustive]
         pub enum #name<#generic> {
             #(#variants,)*
@@ -329,6 +394,107 @@ pub(crate) fn gen_message_enum(
     }
 }
 
+/// The traits that can be derived for a message enum, ordered from the most to the least restrictive
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+enum Derivable {
+    Nothing,
+    Clone,
+    Copy,
+}
+
+/// The traits that the field given by [`gen_message_arg_type()`] for an argument allows deriving
+fn arg_derivable(arg: &Arg, side: Side, receiver: bool) -> Derivable {
+    if arg.enum_.is_some() {
+        return Derivable::Copy;
+    }
+    match arg.typ {
+        Type::Uint | Type::Int | Type::Fixed => Derivable::Copy,
+        Type::String | Type::Array => Derivable::Clone,
+        // received fds are owned, sent ones are borrowed
+        Type::Fd if receiver => Derivable::Nothing,
+        Type::Fd => Derivable::Copy,
+        Type::Object => Derivable::Clone,
+        // either no field or an interface with a version
+        Type::NewId if !receiver && side == Side::Client => Derivable::Copy,
+        // `New` must be initialized exactly once
+        Type::NewId if receiver && side == Side::Server && arg.interface.is_some() => {
+            Derivable::Nothing
+        }
+        Type::NewId => Derivable::Clone,
+        Type::Destructor => panic!("An argument cannot have type \"destructor\"."),
+    }
+}
+
+/// The type of a message argument as a field of the generated message enum
+///
+/// Returns `None` for arguments that are not represented in the enum.
+pub(crate) fn gen_message_arg_type(arg: &Arg, side: Side, receiver: bool) -> Option<TokenStream> {
+    let field_type_inner = if let Some(ref enu) = arg.enum_ {
+        let enum_type = dotted_to_relname(enu);
+        quote! { WEnum<#enum_type> }
+    } else {
+        match arg.typ {
+            Type::Uint => quote! { u32 },
+            Type::Int => quote! { i32 },
+            Type::Fixed => quote! { f64 },
+            Type::String => quote! { String },
+            Type::Array => quote! { Vec<u8> },
+            Type::Fd => {
+                if receiver {
+                    quote! { OwnedFd }
+                } else {
+                    quote! { std::os::unix::io::BorrowedFd<'a> }
+                }
+            }
+            Type::Object => {
+                if let Some(ref iface) = arg.interface {
+                    let iface_mod = Ident::new(iface, Span::call_site());
+                    let iface_type = Ident::new(&snake_to_camel(iface), Span::call_site());
+                    quote! { super::#iface_mod::#iface_type }
+                } else if side == Side::Client {
+                    quote! { super::wayland_client::ObjectId }
+                } else {
+                    quote! { super::wayland_server::ObjectId }
+                }
+            }
+            Type::NewId if !receiver && side == Side::Client => {
+                // Client-side sending does not have a pre-existing object
+                // so skip serializing it
+                if arg.interface.is_some() {
+                    return None;
+                } else {
+                    quote! { (&'static Interface, u32) }
+                }
+            }
+            Type::NewId => {
+                if let Some(ref iface) = arg.interface {
+                    let iface_mod = Ident::new(iface, Span::call_site());
+                    let iface_type = Ident::new(&snake_to_camel(iface), Span::call_site());
+                    if receiver && side == Side::Server {
+                        quote! { New<super::#iface_mod::#iface_type> }
+                    } else {
+                        quote! { super::#iface_mod::#iface_type }
+                    }
+                } else {
+                    // bind-like function
+                    if side == Side::Client {
+                        quote! { (String, u32, super::wayland_client::ObjectId) }
+                    } else {
+                        quote! { (String, u32, super::wayland_server::ObjectId) }
+                    }
+                }
+            }
+            Type::Destructor => panic!("An argument cannot have type \"destructor\"."),
+        }
+    };
+
+    if arg.allow_null {
+        Some(quote! { Option<#field_type_inner> })
+    } else {
+        Some(field_type_inner)
+    }
+}
+
 pub(crate) fn gen_parse_body(interface: &Interface, side: Side) -> TokenStream {
     let msgs = match side {
         Side::Client => &interface.events,
@@ -352,45 +518,16 @@ pub(crate) fn gen_parse_body(interface: &Interface, side: Side) -> TokenStream {
     let match_arms = msgs.iter().enumerate().map(|(opcode, msg)| {
         let opcode = opcode as u16;
         let msg_name = Ident::new(&snake_to_camel(&msg.name), Span::call_site());
-        let args_pat = msg.args.iter().map(|arg| {
-            let arg_name = Ident::new(
-                &format!("{}{}", if is_keyword(&arg.name) { "_" } else { "" }, arg.name),
-                Span::call_site(),
-            );
-            match arg.typ {
-                Type::Uint => quote!{ Some(Argument::Uint(#arg_name)) },
-                Type::Int => quote!{ Some(Argument::Int(#arg_name)) },
-                Type::String => quote!{ Some(Argument::Str(#arg_name)) },
-                Type::Fixed => quote!{ Some(Argument::Fixed(#arg_name)) },
-                Type::Array => quote!{ Some(Argument::Array(#arg_name)) },
-                Type::Object => quote!{ Some(Argument::Object(#arg_name)) },
-                Type::NewId => quote!{ Some(Argument::NewId(#arg_name)) },
-                Type::Fd => quote!{ Some(Argument::Fd(#arg_name)) },
-                Type::Destructor => panic!("Argument {}.{}.{} has type destructor ?!", interface.name, msg.name, arg.name),
-            }
-        });
+        let args_pat = msg.args.iter().map(|arg| gen_arg_pattern(interface, msg, arg));
 
         let args_iter = msg.args.iter().map(|_| quote!{ arg_iter.next() });
 
         let arg_names = msg.args.iter().map(|arg| {
-            let arg_name = format_ident!("{}{}", if is_keyword(&arg.name) { "_" } else { "" }, arg.name);
-            if arg.enum_.is_some() {
-                quote! { #arg_name: From::from(#arg_name as u32) }
+            let arg_name = arg_ident(&arg.name);
+            if let Some(field) = gen_plain_arg_field(arg) {
+                field
             } else {
                 match arg.typ {
-                    Type::Uint | Type::Int | Type::Fd => quote!{ #arg_name },
-                    Type::Fixed => quote!{ #arg_name: (#arg_name as f64) / 256.},
-                    Type::String => {
-                        if arg.allow_null {
-                            quote! {
-                                #arg_name: #arg_name.as_ref().map(|s| String::from_utf8_lossy(s.as_bytes()).into_owned())
-                            }
-                        } else {
-                            quote! {
-                                #arg_name: String::from_utf8_lossy(#arg_name.as_ref().unwrap().as_bytes()).into_owned()
-                            }
-                        }
-                    },
                     Type::Object => {
                         let create_proxy = if let Some(ref created_interface) = arg.interface {
                             let created_iface_mod = Ident::new(created_interface, Span::call_site());
@@ -457,14 +594,7 @@ pub(crate) fn gen_parse_body(interface: &Interface, side: Side) -> TokenStream {
                             }
                         }
                     },
-                    Type::Array => {
-                        if arg.allow_null {
-                            quote! { if #arg_name.len() == 0 { None } else { Some(*#arg_name) } }
-                        } else {
-                            quote! { #arg_name: *#arg_name }
-                        }
-                    },
-                    Type::Destructor => unreachable!(),
+                    _ => unreachable!(),
                 }
             }
         });
@@ -490,6 +620,142 @@ pub(crate) fn gen_parse_body(interface: &Interface, side: Side) -> TokenStream {
     }
 }
 
+/// The pattern matching an argument of a message in the generated parsing code
+fn gen_arg_pattern(interface: &Interface, msg: &Message, arg: &Arg) -> TokenStream {
+    let arg_name = arg_ident(&arg.name);
+    match arg.typ {
+        Type::Uint => quote! { Some(Argument::Uint(#arg_name)) },
+        Type::Int => quote! { Some(Argument::Int(#arg_name)) },
+        // a null string where none is allowed is a malformed message
+        Type::String if !arg.allow_null => quote! { Some(Argument::Str(Some(#arg_name))) },
+        Type::String => quote! { Some(Argument::Str(#arg_name)) },
+        Type::Fixed => quote! { Some(Argument::Fixed(#arg_name)) },
+        Type::Array => quote! { Some(Argument::Array(#arg_name)) },
+        Type::Object => quote! { Some(Argument::Object(#arg_name)) },
+        Type::NewId => quote! { Some(Argument::NewId(#arg_name)) },
+        Type::Fd => quote! { Some(Argument::Fd(#arg_name)) },
+        Type::Destructor => {
+            panic!("Argument {}.{}.{} has type destructor ?!", interface.name, msg.name, arg.name)
+        }
+    }
+}
+
+/// The field initializer of an argument that does not need a connection to be decoded
+///
+/// Returns `None` for object arguments.
+fn gen_plain_arg_field(arg: &Arg) -> Option<TokenStream> {
+    let arg_name = arg_ident(&arg.name);
+    if arg.enum_.is_some() {
+        return Some(quote! { #arg_name: From::from(#arg_name as u32) });
+    }
+    let field = match arg.typ {
+        Type::Uint | Type::Int | Type::Fd => quote! { #arg_name },
+        Type::Fixed => quote! { #arg_name: Fixed::from_raw(#arg_name).to_f64() },
+        Type::String => {
+            if arg.allow_null {
+                quote! {
+                    #arg_name: #arg_name.as_ref().map(|s| String::from_utf8_lossy(s.as_bytes()).into_owned())
+                }
+            } else {
+                quote! {
+                    #arg_name: String::from_utf8_lossy(#arg_name.as_bytes()).into_owned()
+                }
+            }
+        }
+        Type::Array => {
+            if arg.allow_null {
+                quote! { if #arg_name.len() == 0 { None } else { Some(*#arg_name) } }
+            } else {
+                quote! { #arg_name: *#arg_name }
+            }
+        }
+        Type::Object | Type::NewId => return None,
+        Type::Destructor => unreachable!(),
+    };
+    Some(field)
+}
+
+/// Body of the generated `parse_event_raw()`/`parse_request_raw()` functions, decoding a message without
+/// a connection
+pub(crate) fn gen_parse_raw_body(interface: &Interface, side: Side) -> TokenStream {
+    let iface_name = &interface.name;
+    let (msgs, msg_type) = match side {
+        Side::Client => (&interface.events, format_ident!("Event")),
+        Side::Server => (&interface.requests, format_ident!("Request")),
+    };
+    if msgs.is_empty() {
+        return quote! {
+            let _ = args;
+            Err(DispatchError::BadMessage { sender_id: ObjectId::null(), interface: #iface_name, opcode })
+        };
+    }
+
+    let match_arms = msgs.iter().enumerate().map(|(opcode, msg)| {
+        let opcode = opcode as u16;
+        let msg_name = Ident::new(&snake_to_camel(&msg.name), Span::call_site());
+        let fields = msg.args.iter().map(gen_plain_arg_field).collect::<Option<Vec<_>>>();
+        // messages carrying objects need a connection to create their proxies or resources
+        let Some(fields) = fields else {
+            return quote! {
+                #opcode => Err(DispatchError::BadMessage { sender_id: ObjectId::null(), interface: INTERFACE_NAME, opcode })
+            };
+        };
+        let args_pat = msg.args.iter().map(|arg| gen_arg_pattern(interface, msg, arg));
+        let args_iter = msg.args.iter().map(|_| quote! { arg_iter.next() });
+        quote! {
+            #opcode => {
+                if let (#(#args_pat),*) = (#(#args_iter),*) {
+                    Ok(#msg_type::#msg_name { #(#fields),* })
+                } else {
+                    Err(DispatchError::BadMessage { sender_id: ObjectId::null(), interface: INTERFACE_NAME, opcode })
+                }
+            }
+        }
+    });
+
+    quote! {
+        const INTERFACE_NAME: &str = #iface_name;
+        let mut arg_iter = args.into_iter();
+        match opcode {
+            #(#match_arms,)*
+            _ => Err(DispatchError::BadMessage { sender_id: ObjectId::null(), interface: INTERFACE_NAME, opcode }),
+        }
+    }
+}
+
+/// `TryFrom<Message>` implementation for the received message enum, based on the raw parsing function
+pub(crate) fn gen_try_from_message(interface: &Interface, side: Side) -> TokenStream {
+    let iface_name = &interface.name;
+    let (msg_type, parse_raw) = match side {
+        Side::Client => (format_ident!("Event"), format_ident!("parse_event_raw")),
+        Side::Server => (format_ident!("Request"), format_ident!("parse_request_raw")),
+    };
+    let doc = match side {
+        Side::Client => quote! {
+            /// Decode an event with `parse_event_raw()`
+            ///
+            /// This always fails with `DispatchError::BadMessage` for events carrying objects, as they
+            /// need a connection to create their proxies. Use `Proxy::parse_event()` for them.
+        },
+        Side::Server => quote! {
+            /// Decode a request with `parse_request_raw()`
+            ///
+            /// This always fails with `DispatchError::BadMessage` for requests carrying objects, as they
+            /// need a display handle to create their resources. Use `Resource::parse_request()` for them.
+        },
+    };
+    quote! {
+        #doc
+        impl std::convert::TryFrom<Message<ObjectId, OwnedFd>> for #msg_type {
+            type Error = DispatchError;
+
+            fn try_from(msg: Message<ObjectId, OwnedFd>) -> Result<#msg_type, DispatchError> {
+                #parse_raw(msg.opcode, msg.args).map_err(|_| DispatchError::BadMessage { sender_id: msg.sender_id, interface: #iface_name, opcode: msg.opcode })
+            }
+        }
+    }
+}
+
 pub(crate) fn gen_write_body(interface: &Interface, side: Side) -> TokenStream {
     let msgs = match side {
         Side::Client => &interface.requests,
@@ -509,18 +775,18 @@ pub(crate) fn gen_write_body(interface: &Interface, side: Side) -> TokenStream {
             if arg.typ == Type::NewId && arg.interface.is_some() && side == Side::Client {
                 None
             } else {
-                Some(format_ident!("{}{}", if is_keyword(&arg.name) { "_" } else { "" }, arg.name))
+                Some(arg_ident(&arg.name))
             }
         });
         let mut child_spec = None;
         let args = msg.args.iter().flat_map(|arg| {
-            let arg_name = format_ident!("{}{}", if is_keyword(&arg.name) { "_" } else { "" }, arg.name);
+            let arg_name = arg_ident(&arg.name);
 
             match arg.typ {
                 Type::Int => vec![if arg.enum_.is_some() { quote!{ Argument::Int(Into::<u32>::into(#arg_name) as i32) } } else { quote!{ Argument::Int(#arg_name) } }],
                 Type::Uint => vec![if arg.enum_.is_some() { quote!{ Argument::Uint(#arg_name.into()) } } else { quote!{ Argument::Uint(#arg_name) } }],
                 Type::Fd => vec![quote!{ Argument::Fd(#arg_name) }],
-                Type::Fixed => vec![quote! { Argument::Fixed((#arg_name * 256.) as i32) }],
+                Type::Fixed => vec![quote! { Argument::Fixed(Fixed::from_f64(#arg_name).into_raw()) }],
                 Type::Object => if arg.allow_null {
                     if side == Side::Server {
                         vec![quote! { if let Some(obj) = #arg_name { Argument::Object(Resource::id(&obj)) } else { Argument::Object(ObjectId::null()) } }]
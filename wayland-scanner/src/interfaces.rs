@@ -5,17 +5,93 @@ use crate::protocol::{Interface, Message, Protocol, Type};
 use quote::{format_ident, quote};
 
 pub fn generate(protocol: &Protocol, with_c_interfaces: bool) -> TokenStream {
+    if let Some(error) = check_duplicate_interfaces(protocol) {
+        return error;
+    }
+    let warnings = check_wire_limits(protocol);
     let interfaces =
         protocol.interfaces.iter().map(|iface| generate_interface(iface, with_c_interfaces));
     if with_c_interfaces {
         let prefix = super::c_interfaces::generate_interfaces_prefix(protocol);
         quote! {
+            #warnings
             #prefix
             #(#interfaces)*
         }
     } else {
-        interfaces.collect()
+        quote! {
+            #warnings
+            #(#interfaces)*
+        }
+    }
+}
+
+/// Maximum number of fds the backends send along a single `sendmsg()`
+const MAX_FDS_PER_MESSAGE: usize = 28;
+/// Maximum size of a message on the wire, in bytes
+const MAX_MESSAGE_SIZE: usize = 4096;
+
+/// Emits a warning for each message that exceeds the limits of the wire format
+///
+/// Such messages are valid XML but may not be transmitted reliably. As proc-macros cannot emit
+/// warnings on stable, they are raised through the use of deprecated items.
+fn check_wire_limits(protocol: &Protocol) -> TokenStream {
+    let mut warnings = Vec::new();
+    for iface in &protocol.interfaces {
+        for msg in iface.requests.iter().chain(&iface.events) {
+            let fd_count = msg.args.iter().filter(|arg| arg.typ == Type::Fd).count();
+            if fd_count > MAX_FDS_PER_MESSAGE {
+                warnings.push(format!(
+                    "{}.{} has {} fd arguments, more than the {} that can be sent at once on all platforms",
+                    iface.name, msg.name, fd_count, MAX_FDS_PER_MESSAGE
+                ));
+            }
+            // the header takes two words, and all arguments other than fds at least one
+            let min_size = 4 * (2 + msg.args.len() - fd_count);
+            if min_size > MAX_MESSAGE_SIZE {
+                warnings.push(format!(
+                    "{}.{} takes at least {} bytes, more than the maximum message size of {} bytes",
+                    iface.name, msg.name, min_size, MAX_MESSAGE_SIZE
+                ));
+            }
+        }
     }
+    warnings
+        .into_iter()
+        .enumerate()
+        .map(|(i, warning)| {
+            let name = format_ident!("__wire_limit_warning_{}", i);
+            quote! {
+                const _: () = {
+                    #[deprecated(note = #warning)]
+                    #[allow(non_upper_case_globals)]
+                    const #name: () = ();
+                    #name
+                };
+            }
+        })
+        .collect()
+}
+
+/// Emits a `compile_error!` if several interfaces share the same name, as they would otherwise
+/// generate conflicting statics
+fn check_duplicate_interfaces(protocol: &Protocol) -> Option<TokenStream> {
+    let mut seen = std::collections::HashSet::new();
+    let mut duplicates = Vec::new();
+    for iface in &protocol.interfaces {
+        if !seen.insert(iface.name.as_str()) && !duplicates.contains(&iface.name.as_str()) {
+            duplicates.push(iface.name.as_str());
+        }
+    }
+    if duplicates.is_empty() {
+        return None;
+    }
+    let msg = format!(
+        "Protocol {} declares interface names multiple times: {}",
+        protocol.name,
+        duplicates.join(", ")
+    );
+    Some(quote! { compile_error!(#msg); })
 }
 
 pub(crate) fn generate_interface(interface: &Interface, with_c: bool) -> TokenStream {
@@ -139,4 +215,37 @@ mod tests {
             panic!("Generated does not match reference!")
         }
     }
+
+    #[test]
+    fn duplicate_interface_names() {
+        let protocol_xml = r#"<protocol name="dup">
+            <interface name="wl_output" version="1"/>
+            <interface name="wl_seat" version="1"/>
+            <interface name="wl_output" version="2"/>
+        </protocol>"#;
+        let protocol_parsed = crate::parse::parse(protocol_xml.as_bytes());
+        let generated = super::generate(&protocol_parsed, false).to_string();
+        assert!(generated.starts_with("compile_error !"));
+        assert!(generated.contains("wl_output"));
+        assert!(!generated.contains("wl_seat"));
+    }
+
+    #[test]
+    fn wire_limit_warnings() {
+        let fd_args =
+            (0..29).map(|i| format!(r#"<arg name="fd{}" type="fd"/>"#, i)).collect::<String>();
+        let protocol_xml = format!(
+            r#"<protocol name="fds">
+                <interface name="test_fds" version="1">
+                    <request name="few"><arg name="fd" type="fd"/></request>
+                    <request name="many">{}</request>
+                </interface>
+            </protocol>"#,
+            fd_args
+        );
+        let protocol_parsed = crate::parse::parse(protocol_xml.as_bytes());
+        let generated = super::generate(&protocol_parsed, false).to_string();
+        assert_eq!(generated.matches("deprecated").count(), 1);
+        assert!(generated.contains("test_fds.many has 29 fd arguments"));
+    }
 }
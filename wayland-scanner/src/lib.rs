@@ -35,9 +35,23 @@
 //!     wayland_scanner::generate_client_code!("./path/to/the/protocol.xml");
 //! }
 //! ```
+//!
+//! Instead of a path, all the macros also accept the XML specification itself as `inline = ` followed by a
+//! string literal, which is convenient for small protocols and tests:
+//!
+//! ```rust,ignore
+//! wayland_scanner::generate_client_code!(inline = r#"
+//!     <protocol name="my_protocol">
+//!         ...
+//!     </protocol>
+//! "#);
+//! ```
 
 use std::{ffi::OsString, path::PathBuf};
 
+use proc_macro2::TokenStream;
+
+mod bridge_gen;
 mod c_interfaces;
 mod client_gen;
 mod common;
@@ -49,60 +63,167 @@ mod token;
 mod util;
 
 /// Proc-macro for generating low-level interfaces associated with an XML specification
+///
+/// The path to the XML file can optionally be followed by a comma-separated list of options:
+///
+/// - `native = true | false`: whether to generate the C representation of the interfaces, referenced by
+///   their `c_ptr` field. It defaults to `true`. With `native = false` these tables are not generated and
+///   `c_ptr` is `None`, which reduces the size of the generated code. The resulting interfaces can only be
+///   used with the rust backend, as the system backend relies on `libwayland` and requires them.
+///
+/// ```rust,ignore
+/// wayland_scanner::generate_interfaces!("./path/to/the/protocol.xml", native = false);
+/// ```
 #[proc_macro]
 pub fn generate_interfaces(stream: proc_macro::TokenStream) -> proc_macro::TokenStream {
-    let path: OsString = token::parse_lit_str_token(stream).into();
-    let path = if let Some(manifest_dir) = std::env::var_os("CARGO_MANIFEST_DIR") {
-        let mut buf = PathBuf::from(manifest_dir);
-        buf.push(path);
-        buf
-    } else {
-        path.into()
-    };
-    let file = match std::fs::File::open(&path) {
-        Ok(file) => file,
-        Err(e) => panic!("Failed to open protocol file {}: {}", path.display(), e),
-    };
-    let protocol = parse::parse(file);
-    interfaces::generate(&protocol, true).into()
+    let (source, options) = token::parse_macro_args(stream);
+    let mut native = true;
+    for (name, value) in &options {
+        match (name.as_str(), value) {
+            ("native", Some(value)) => {
+                native = match value.to_string().as_str() {
+                    "true" => true,
+                    "false" => false,
+                    other => panic!(
+                        "Invalid value `{}` for the `native` option, expected `true` or `false`",
+                        other
+                    ),
+                }
+            }
+            _ => panic!("Invalid option `{}` for generate_interfaces!()", name),
+        }
+    }
+    let protocol = load_protocol(source);
+    interfaces::generate(&protocol, native).into()
 }
 
 /// Proc-macro for generating client-side API associated with an XML specification
+///
+/// The path to the XML file can optionally be followed by a comma-separated list of options:
+///
+/// - `event_handlers`: for each interface, also generate an `EventHandler` trait with one method per
+///   event and a `dispatch_event()` function forwarding an `Event` to the matching method. This allows
+///   implementing only the events you care about rather than matching on the `Event` enum.
+///
+/// - `visibility = <vis>`: the visibility given to the generated interface modules instead of `pub`, for
+///   example `pub(crate)`. This lets a crate wrapping a protocol only re-export a curated subset of it.
+///
+/// - `naming = preserve | snake`: how the names of the generated methods are derived from the names of the
+///   messages. With `preserve`, the default, the XML names are used as-is. With `snake`, they are converted
+///   to snake_case, which is useful for protocols using a different casing. Names colliding after the
+///   conversion are reported as a compile error.
+///
+/// - `enum_tables`: for each enum, also generate an associated `ALL: &[(u32, &str)]` constant listing the
+///   values defined by the protocol along with their names. This is mostly useful for fuzzing, to pick
+///   valid values or deliberately craft unknown ones.
+///
+/// - `protocol_info`: also generate, next to the interface modules, a `PROTOCOL_NAME: &str` constant with
+///   the name of the protocol and an `INTERFACE_VERSIONS: &[(&str, u32)]` constant listing the generated
+///   interfaces along with their maximal version. This allows advertising all the globals of a protocol
+///   without maintaining a separate table. They are given the same visibility as the interface modules.
+///
+/// - `message_derives`: derive `Clone` on the `Request` and `Event` enums of an interface when none of their
+///   fields is a received file descriptor or a `New` object, and `Copy` when all their fields are scalars,
+///   enums or borrowed file descriptors. As these derives depend on all the messages of the interface, a
+///   new message in a later version of the protocol may remove them, which is a breaking change for the
+///   code using them. Only enable this option for protocols whose evolution you control.
+///
+/// ```rust,ignore
+/// wayland_scanner::generate_client_code!("./path/to/the/protocol.xml", event_handlers);
+/// wayland_scanner::generate_client_code!("./path/to/the/protocol.xml", visibility = pub(crate));
+/// wayland_scanner::generate_client_code!("./path/to/the/protocol.xml", naming = snake);
+/// wayland_scanner::generate_client_code!("./path/to/the/protocol.xml", enum_tables);
+/// wayland_scanner::generate_client_code!("./path/to/the/protocol.xml", protocol_info);
+/// wayland_scanner::generate_client_code!("./path/to/the/protocol.xml", message_derives);
+/// ```
 #[proc_macro]
 pub fn generate_client_code(stream: proc_macro::TokenStream) -> proc_macro::TokenStream {
-    let path: OsString = token::parse_lit_str_token(stream).into();
-    let path = if let Some(manifest_dir) = std::env::var_os("CARGO_MANIFEST_DIR") {
-        let mut buf = PathBuf::from(manifest_dir);
-        buf.push(path);
-        buf
-    } else {
-        path.into()
-    };
-    let file = match std::fs::File::open(&path) {
-        Ok(file) => file,
-        Err(e) => panic!("Failed to open protocol file {}: {}", path.display(), e),
-    };
-    let protocol = parse::parse(file);
-    client_gen::generate_client_objects(&protocol).into()
+    let (source, options) = token::parse_macro_args(stream);
+    let options = ClientOptions::from_args(&options);
+    let protocol = load_protocol(source);
+    client_gen::generate_client_objects(&protocol, &options).into()
 }
 
 /// Proc-macro for generating server-side API associated with an XML specification
+///
+/// The path to the XML file can optionally be followed by a comma-separated list of options:
+///
+/// - `visibility = <vis>`: the visibility given to the generated interface modules instead of `pub`, see
+///   [`generate_client_code!`].
+///
+/// - `enum_tables`: for each enum, also generate an `ALL` table of its values, see
+///   [`generate_client_code!`].
+///
+/// - `protocol_info`: also generate the `PROTOCOL_NAME` and `INTERFACE_VERSIONS` constants, see
+///   [`generate_client_code!`]. `wl_display` and `wl_registry` are not listed, as no code is generated for
+///   them server-side.
+///
+/// - `message_derives`: derive `Clone` and `Copy` on the message enums when their fields allow it, see
+///   [`generate_client_code!`].
 #[proc_macro]
 pub fn generate_server_code(stream: proc_macro::TokenStream) -> proc_macro::TokenStream {
-    let path: OsString = token::parse_lit_str_token(stream).into();
-    let path = if let Some(manifest_dir) = std::env::var_os("CARGO_MANIFEST_DIR") {
-        let mut buf = PathBuf::from(manifest_dir);
-        buf.push(path);
-        buf
-    } else {
-        path.into()
-    };
-    let file = match std::fs::File::open(&path) {
-        Ok(file) => file,
-        Err(e) => panic!("Failed to open protocol file {}: {}", path.display(), e),
-    };
-    let protocol = parse::parse(file);
-    server_gen::generate_server_objects(&protocol).into()
+    let (source, options) = token::parse_macro_args(stream);
+    let options = ServerOptions::from_args(&options);
+    let protocol = load_protocol(source);
+    server_gen::generate_server_objects(&protocol, &options).into()
+}
+
+/// Proc-macro for generating the conversions between the client-side and server-side messages associated
+/// with an XML specification
+///
+/// This is intended for programs sitting between clients and a compositor, like proxies and bridges. For
+/// each interface, a module is generated with two functions:
+///
+/// - `request_to_client()` converts a `Request` received server-side into the `Request` to send client-side
+///   to the upstream compositor;
+/// - `event_to_server()` converts an `Event` received client-side into the `Event` to send server-side to
+///   the client.
+///
+/// Both take a closure translating the ID of an object of one side into the ID of its counterpart on the
+/// other side. The macro must be invoked in a module where `wayland_client` and `wayland_server` are in
+/// scope, as well as `client` and `server` modules containing the client-side and server-side code of the
+/// protocol:
+///
+/// ```rust,ignore
+/// pub mod bridge {
+///     use wayland_client;
+///     use wayland_server;
+///     use wayland_client::protocol as client;
+///     use wayland_server::protocol as server;
+///
+///     wayland_scanner::generate_bridge_code!("./path/to/the/protocol.xml");
+/// }
+/// ```
+#[proc_macro]
+pub fn generate_bridge_code(stream: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let (source, options) = token::parse_macro_args(stream);
+    if let Some((name, _)) = options.first() {
+        panic!("Invalid option `{}` for generate_bridge_code!()", name);
+    }
+    let protocol = load_protocol(source);
+    bridge_gen::generate_bridge_objects(&protocol).into()
+}
+
+/// Parse the protocol specification given to a code-generation macro
+fn load_protocol(source: token::ProtocolSource) -> protocol::Protocol {
+    match source {
+        token::ProtocolSource::Path(path) => {
+            let path: OsString = path.into();
+            let path = if let Some(manifest_dir) = std::env::var_os("CARGO_MANIFEST_DIR") {
+                let mut buf = PathBuf::from(manifest_dir);
+                buf.push(path);
+                buf
+            } else {
+                path.into()
+            };
+            let file = match std::fs::File::open(&path) {
+                Ok(file) => file,
+                Err(e) => panic!("Failed to open protocol file {}: {}", path.display(), e),
+            };
+            parse::parse(file)
+        }
+        token::ProtocolSource::Inline(xml) => parse::parse(xml.as_bytes()),
+    }
 }
 
 #[cfg(test)]
@@ -139,3 +260,103 @@ enum Side {
     /// wayland compositors
     Server,
 }
+
+/// Options of the client-side code generation
+#[derive(Clone, Default, Debug)]
+struct ClientOptions {
+    /// Generate an `EventHandler` trait and a `dispatch_event()` function for each interface
+    event_handlers: bool,
+    /// Visibility of the generated interface modules and protocol constants, `pub` if not set
+    visibility: Option<TokenStream>,
+    /// How method names are derived from message names
+    naming: Naming,
+    /// Generate an `ALL` table of the values of each enum
+    enum_tables: bool,
+    /// Generate the `PROTOCOL_NAME` and `INTERFACE_VERSIONS` constants
+    protocol_info: bool,
+    /// Derive `Clone` and `Copy` on the message enums when their fields allow it
+    message_derives: bool,
+}
+
+impl ClientOptions {
+    fn from_args(args: &[(String, Option<TokenStream>)]) -> Self {
+        let mut options = Self::default();
+        for (name, value) in args {
+            match (name.as_str(), value) {
+                ("event_handlers", None) => options.event_handlers = true,
+                ("enum_tables", None) => options.enum_tables = true,
+                ("protocol_info", None) => options.protocol_info = true,
+                ("message_derives", None) => options.message_derives = true,
+                ("visibility", Some(vis)) => options.visibility = Some(vis.clone()),
+                ("naming", Some(naming)) => {
+                    options.naming = match naming.to_string().as_str() {
+                        "preserve" => Naming::Preserve,
+                        "snake" => Naming::Snake,
+                        other => panic!(
+                            "Invalid value `{}` for the `naming` option, expected `preserve` or `snake`",
+                            other
+                        ),
+                    }
+                }
+                _ => panic!("Invalid option `{}` for generate_client_code!()", name),
+            }
+        }
+        options
+    }
+}
+
+/// Naming strategy of the generated methods
+#[derive(Copy, Clone, Default, PartialEq, Eq, Debug)]
+enum Naming {
+    /// Use the names of the XML file as-is
+    #[default]
+    Preserve,
+    /// Convert the names of the XML file to snake_case
+    Snake,
+}
+
+impl Naming {
+    fn apply(self, name: &str) -> String {
+        match self {
+            Naming::Preserve => name.to_owned(),
+            Naming::Snake => util::to_snake_case(name),
+        }
+    }
+}
+
+/// Options of the server-side code generation
+#[derive(Clone, Default, Debug)]
+struct ServerOptions {
+    /// Visibility of the generated interface modules and protocol constants, `pub` if not set
+    visibility: Option<TokenStream>,
+    /// Generate an `ALL` table of the values of each enum
+    enum_tables: bool,
+    /// Generate the `PROTOCOL_NAME` and `INTERFACE_VERSIONS` constants
+    protocol_info: bool,
+    /// Derive `Clone` and `Copy` on the message enums when their fields allow it
+    message_derives: bool,
+}
+
+impl ServerOptions {
+    fn from_args(args: &[(String, Option<TokenStream>)]) -> Self {
+        let mut options = Self::default();
+        for (name, value) in args {
+            match (name.as_str(), value) {
+                ("visibility", Some(vis)) => options.visibility = Some(vis.clone()),
+                ("enum_tables", None) => options.enum_tables = true,
+                ("protocol_info", None) => options.protocol_info = true,
+                ("message_derives", None) => options.message_derives = true,
+                _ => panic!("Invalid option `{}` for generate_server_code!()", name),
+            }
+        }
+        options
+    }
+}
+
+/// Visibility to give to the generated interface modules
+fn module_visibility(visibility: &Option<TokenStream>) -> TokenStream {
+    match visibility {
+        Some(vis) => vis.clone(),
+        None => quote::quote!(pub),
+    }
+}
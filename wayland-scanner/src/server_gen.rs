@@ -4,26 +4,39 @@ use quote::{format_ident, quote};
 
 use crate::{
     protocol::{Interface, Protocol, Type},
-    util::{description_to_doc_attr, dotted_to_relname, is_keyword, snake_to_camel, to_doc_attr},
-    Side,
+    util::{
+        arg_ident, description_to_doc_attr, dotted_to_relname, is_keyword, snake_to_camel,
+        to_doc_attr,
+    },
+    ServerOptions, Side,
 };
 
-pub fn generate_server_objects(protocol: &Protocol) -> TokenStream {
-    protocol
-        .interfaces
-        .iter()
-        .filter(|iface| iface.name != "wl_display" && iface.name != "wl_registry")
-        .map(generate_objects_for)
-        .collect()
+pub fn generate_server_objects(protocol: &Protocol, options: &ServerOptions) -> TokenStream {
+    if let Some(error) = crate::common::check_enum_references(protocol) {
+        return error;
+    }
+    let interfaces = || {
+        protocol
+            .interfaces
+            .iter()
+            .filter(|iface| iface.name != "wl_display" && iface.name != "wl_registry")
+    };
+    let mut tokens: TokenStream =
+        interfaces().map(|interface| generate_objects_for(interface, options)).collect();
+    if options.protocol_info {
+        let visibility = crate::module_visibility(&options.visibility);
+        tokens.extend(crate::common::gen_protocol_info(protocol, interfaces(), &visibility));
+    }
+    tokens
 }
 
-fn generate_objects_for(interface: &Interface) -> TokenStream {
+fn generate_objects_for(interface: &Interface, options: &ServerOptions) -> TokenStream {
     let mod_name = Ident::new(&interface.name, Span::call_site());
     let mod_doc = interface.description.as_ref().map(description_to_doc_attr);
     let iface_name = Ident::new(&snake_to_camel(&interface.name), Span::call_site());
     let iface_const_name = format_ident!("{}_INTERFACE", interface.name.to_ascii_uppercase());
 
-    let enums = crate::common::generate_enums_for(interface);
+    let enums = crate::common::generate_enums_for(interface, options.enum_tables);
     let msg_constants = crate::common::gen_msg_constants(&interface.requests, &interface.events);
 
     let requests = crate::common::gen_message_enum(
@@ -31,15 +44,19 @@ fn generate_objects_for(interface: &Interface) -> TokenStream {
         Side::Server,
         true,
         &interface.requests,
+        options.message_derives,
     );
     let events = crate::common::gen_message_enum(
         &format_ident!("Event"),
         Side::Server,
         false,
         &interface.events,
+        options.message_derives,
     );
 
     let parse_body = crate::common::gen_parse_body(interface, Side::Server);
+    let parse_raw_body = crate::common::gen_parse_raw_body(interface, Side::Server);
+    let try_from_message = crate::common::gen_try_from_message(interface, Side::Server);
     let write_body = crate::common::gen_write_body(interface, Side::Server);
     let methods = gen_methods(interface);
 
@@ -53,17 +70,18 @@ fn generate_objects_for(interface: &Interface) -> TokenStream {
         None => format!("{}\n\n{}", interface.name, event_ref),
     };
     let doc_attr = to_doc_attr(&docs);
+    let visibility = crate::module_visibility(&options.visibility);
 
     quote! {
         #mod_doc
-        pub mod #mod_name {
+        #visibility mod #mod_name {
             use std::sync::Arc;
             use std::os::unix::io::OwnedFd;
 
             use super::wayland_server::{
                 backend::{
-                    smallvec, ObjectData, ObjectId, InvalidId, WeakHandle,
-                    protocol::{WEnum, Argument, Message, Interface, same_interface}
+                    smallvec, ObjectData, ObjectId, InvalidId, SendEventError, WeakHandle,
+                    protocol::{WEnum, Argument, Fixed, Message, Interface, same_interface}
                 },
                 Resource, Dispatch, DisplayHandle, DispatchError, ResourceData, New, Weak,
             };
@@ -73,6 +91,19 @@ fn generate_objects_for(interface: &Interface) -> TokenStream {
             #requests
             #events
 
+            /// Decode a request of this interface from its raw arguments, without a display handle
+            ///
+            /// This is useful to decode recorded or replayed messages.
+            ///
+            /// **Note:** Requests carrying objects, like `wl_surface.attach`, need a display handle to
+            /// create their resources, so `DispatchError::BadMessage` is always returned for them. Decode
+            /// these requests with `Resource::parse_request()` instead.
+            pub fn parse_request_raw(opcode: u16, args: impl IntoIterator<Item = Argument<ObjectId, OwnedFd>>) -> Result<Request, DispatchError> {
+                #parse_raw_body
+            }
+
+            #try_from_message
+
             #doc_attr
             #[derive(Debug, Clone)]
             pub struct #iface_name {
@@ -105,6 +136,27 @@ fn generate_objects_for(interface: &Interface) -> TokenStream {
                 }
             }
 
+            impl AsRef<ObjectId> for #iface_name {
+                #[inline]
+                fn as_ref(&self) -> &ObjectId {
+                    &self.id
+                }
+            }
+
+            impl From<#iface_name> for ObjectId {
+                #[inline]
+                fn from(value: #iface_name) -> ObjectId {
+                    value.id
+                }
+            }
+
+            impl From<&#iface_name> for ObjectId {
+                #[inline]
+                fn from(value: &#iface_name) -> ObjectId {
+                    value.id.clone()
+                }
+            }
+
             impl std::hash::Hash for #iface_name {
                 #[inline]
                 fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
@@ -155,7 +207,7 @@ fn generate_objects_for(interface: &Interface) -> TokenStream {
                     Ok(#iface_name { id, data, version, handle: conn.backend_handle().downgrade() })
                 }
 
-                fn send_event(&self, evt: Self::Event<'_>) -> Result<(), InvalidId> {
+                fn send_event(&self, evt: Self::Event<'_>) -> Result<(), SendEventError> {
                     let handle = DisplayHandle::from(self.handle.upgrade().ok_or(InvalidId)?);
                     handle.send_event(self, evt)
                 }
@@ -193,8 +245,7 @@ fn gen_methods(interface: &Interface) -> TokenStream {
             let enum_variant = Ident::new(&snake_to_camel(&request.name), Span::call_site());
 
             let fn_args = request.args.iter().flat_map(|arg| {
-                let arg_name =
-                    format_ident!("{}{}", if is_keyword(&arg.name) { "_" } else { "" }, arg.name);
+                let arg_name = arg_ident(&arg.name);
 
                 let arg_type = if let Some(ref enu) = arg.enum_ {
                     let enum_type = dotted_to_relname(enu);
@@ -239,8 +290,7 @@ fn gen_methods(interface: &Interface) -> TokenStream {
             });
 
             let enum_args = request.args.iter().flat_map(|arg| {
-                let arg_name =
-                    format_ident!("{}{}", if is_keyword(&arg.name) { "_" } else { "" }, arg.name);
+                let arg_name = arg_ident(&arg.name);
                 if arg.enum_.is_some() {
                     Some(quote! { #arg_name: WEnum::Value(#arg_name) })
                 } else if arg.typ == Type::Object || arg.typ == Type::NewId {
@@ -278,7 +328,8 @@ mod tests {
         let protocol_file =
             std::fs::File::open("./tests/scanner_assets/test-protocol.xml").unwrap();
         let protocol_parsed = crate::parse::parse(protocol_file);
-        let generated: String = super::generate_server_objects(&protocol_parsed).to_string();
+        let generated: String =
+            super::generate_server_objects(&protocol_parsed, &Default::default()).to_string();
         let generated = crate::format_rust_code(&generated);
 
         let reference =
@@ -291,4 +342,63 @@ mod tests {
             panic!("Generated does not match reference!")
         }
     }
+    #[test]
+    fn server_gen_protocol_info() {
+        let protocol_xml = r#"<protocol name="info">
+            <interface name="wl_display" version="1"/>
+            <interface name="wl_registry" version="1"/>
+            <interface name="first" version="3"/>
+        </protocol>"#;
+        let protocol_parsed = crate::parse::parse(protocol_xml.as_bytes());
+
+        let generated: String =
+            super::generate_server_objects(&protocol_parsed, &Default::default()).to_string();
+        assert!(!generated.contains("PROTOCOL_NAME"));
+
+        let options = crate::ServerOptions {
+            protocol_info: true,
+            visibility: Some(quote::quote!(pub(crate))),
+            ..Default::default()
+        };
+        let generated: String =
+            super::generate_server_objects(&protocol_parsed, &options).to_string();
+        let generated = crate::format_rust_code(&generated);
+
+        assert!(generated.contains(r#"pub(crate) const PROTOCOL_NAME: &str = "info";"#));
+        // no code is generated server-side for wl_display and wl_registry
+        assert!(generated
+            .contains(r#"pub(crate) const INTERFACE_VERSIONS: &[(&str, u32)] = &[("first", 3)];"#));
+    }
+
+    #[test]
+    fn server_gen_message_derives() {
+        let protocol_xml = r#"<protocol name="derives">
+            <interface name="factory" version="1">
+                <request name="create">
+                    <arg name="id" type="new_id" interface="factory"/>
+                </request>
+                <event name="created">
+                    <arg name="object" type="object" interface="factory"/>
+                    <arg name="fd" type="fd"/>
+                </event>
+            </interface>
+        </protocol>"#;
+        let protocol_parsed = crate::parse::parse(protocol_xml.as_bytes());
+
+        let derives = |options: &crate::ServerOptions, name: &str| {
+            let generated = super::generate_server_objects(&protocol_parsed, options).to_string();
+            let generated = crate::format_rust_code(&generated);
+            let before = &generated[..generated.find(&format!("pub enum {}", name)).unwrap()];
+            before[before.rfind("#[derive(").unwrap()..].lines().next().unwrap().to_owned()
+        };
+
+        let options = crate::ServerOptions::default();
+        assert_eq!(derives(&options, "Event"), "#[derive(Debug)]");
+
+        let options = crate::ServerOptions { message_derives: true, ..Default::default() };
+        // `New` must be initialized exactly once
+        assert_eq!(derives(&options, "Request"), "#[derive(Debug)]");
+        // sent fds are borrowed
+        assert_eq!(derives(&options, "Event"), "#[derive(Debug, Clone)]");
+    }
 }
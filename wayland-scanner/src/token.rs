@@ -168,19 +168,74 @@ fn backslash_u(mut s: &str) -> (char, &str) {
 
 // End of code adapted from syn
 
-pub fn parse_lit_str_token(mut stream: proc_macro::TokenStream) -> String {
+/// Where the XML specification of a protocol is read from
+pub enum ProtocolSource {
+    /// A path to a file, relative to the crate root
+    Path(String),
+    /// The content of the specification itself
+    Inline(String),
+}
+
+/// Parse the arguments of a code-generation macro
+///
+/// They are made of a string literal, or `inline = ` followed by a string literal, optionally followed by a
+/// comma-separated list of options. Each option is either a plain name, or a name followed by `=` and a
+/// value made of arbitrary tokens.
+pub fn parse_macro_args(
+    mut stream: proc_macro::TokenStream,
+) -> (ProtocolSource, Vec<(String, Option<proc_macro2::TokenStream>)>) {
     loop {
-        let mut iter = stream.into_iter();
-        let token = iter.next().expect("expected string argument");
-        assert!(iter.next().is_none(), "unexpected trailing token");
+        let mut iter = stream.into_iter().peekable();
+        let mut token = iter.next().expect("expected string argument");
+        let mut inline = false;
+        if matches!(&token, proc_macro::TokenTree::Ident(ident) if ident.to_string() == "inline") {
+            match iter.next() {
+                Some(proc_macro::TokenTree::Punct(punct)) if punct.as_char() == '=' => {}
+                _ => panic!("expected `=` after `inline`"),
+            }
+            token = iter.next().expect("expected string argument after `inline =`");
+            inline = true;
+        }
         let literal = match token {
             proc_macro::TokenTree::Literal(literal) => literal,
-            proc_macro::TokenTree::Group(group) => {
+            proc_macro::TokenTree::Group(group) if !inline && iter.peek().is_none() => {
                 stream = group.stream();
                 continue;
             }
             _ => panic!("expected string argument found `{:?}`", token),
         };
-        return parse_lit_str(&literal.to_string());
+        let content = parse_lit_str(&literal.to_string());
+        let source =
+            if inline { ProtocolSource::Inline(content) } else { ProtocolSource::Path(content) };
+
+        let mut options = Vec::new();
+        while let Some(token) = iter.next() {
+            match token {
+                proc_macro::TokenTree::Punct(punct) if punct.as_char() == ',' => {}
+                _ => panic!("expected `,` found `{}`", token),
+            }
+            let name = match iter.next() {
+                Some(proc_macro::TokenTree::Ident(ident)) => ident.to_string(),
+                Some(token) => panic!("expected option name found `{}`", token),
+                // trailing comma
+                None => break,
+            };
+            let value = match iter.peek() {
+                Some(proc_macro::TokenTree::Punct(punct)) if punct.as_char() == '=' => {
+                    iter.next();
+                    let mut value = proc_macro::TokenStream::new();
+                    while let Some(token) = iter.peek() {
+                        if matches!(token, proc_macro::TokenTree::Punct(p) if p.as_char() == ',') {
+                            break;
+                        }
+                        value.extend(iter.next());
+                    }
+                    Some(value.into())
+                }
+                _ => None,
+            };
+            options.push((name, value));
+        }
+        return (source, options);
     }
 }
@@ -1,5 +1,5 @@
 use proc_macro2::{Ident, Span, TokenStream};
-use quote::{quote, ToTokens};
+use quote::{format_ident, quote, ToTokens};
 
 pub(crate) fn to_doc_attr(text: &str) -> TokenStream {
     let text = text.lines().map(str::trim).collect::<Vec<_>>().join("\n");
@@ -18,6 +18,8 @@ pub fn is_keyword(txt: &str) -> bool {
         "abstract"
             | "alignof"
             | "as"
+            | "async"
+            | "await"
             | "become"
             | "box"
             | "break"
@@ -25,6 +27,7 @@ pub fn is_keyword(txt: &str) -> bool {
             | "continue"
             | "crate"
             | "do"
+            | "dyn"
             | "else"
             | "enum"
             | "extern"
@@ -32,6 +35,7 @@ pub fn is_keyword(txt: &str) -> bool {
             | "final"
             | "fn"
             | "for"
+            | "gen"
             | "if"
             | "impl"
             | "in"
@@ -58,6 +62,7 @@ pub fn is_keyword(txt: &str) -> bool {
             | "super"
             | "trait"
             | "true"
+            | "try"
             | "type"
             | "typeof"
             | "unsafe"
@@ -72,6 +77,21 @@ pub fn is_keyword(txt: &str) -> bool {
     )
 }
 
+/// The identifier of a message argument
+///
+/// Keywords are turned into raw identifiers, so that the generated names match the ones of the protocol.
+/// The keywords that cannot be raw identifiers and the names reserved by the generated code are prefixed
+/// with an underscore instead.
+pub fn arg_ident(name: &str) -> Ident {
+    if matches!(name, "crate" | "self" | "Self" | "super" | "__handler" | "__object") {
+        format_ident!("_{}", name)
+    } else if is_keyword(name) {
+        Ident::new_raw(name, Span::call_site())
+    } else {
+        Ident::new(name, Span::call_site())
+    }
+}
+
 pub fn is_camel_keyword(txt: &str) -> bool {
     matches!(txt, "Self")
 }
@@ -99,6 +119,27 @@ pub fn snake_to_camel(input: &str) -> String {
     }
 }
 
+pub fn to_snake_case(input: &str) -> String {
+    let mut result = String::with_capacity(input.len());
+    let mut prev_lower = false;
+    for c in input.chars() {
+        if c.is_ascii_uppercase() {
+            if prev_lower {
+                result.push('_');
+            }
+            result.push(c.to_ascii_lowercase());
+            prev_lower = false;
+        } else if c == '-' {
+            result.push('_');
+            prev_lower = false;
+        } else {
+            result.push(c);
+            prev_lower = c.is_ascii_lowercase() || c.is_ascii_digit();
+        }
+    }
+    result
+}
+
 pub fn dotted_to_relname(input: &str) -> TokenStream {
     let mut it = input.split('.');
     match (it.next(), it.next()) {
@@ -0,0 +1,373 @@
+#[doc = "Conversions between the client-side and server-side messages of `wl_callback`"]
+pub mod wl_callback {
+    #[allow(unused_imports)]
+    use super::wayland_client::{Connection, Proxy};
+    #[allow(unused_imports)]
+    use super::wayland_server::{DisplayHandle, Resource};
+    #[allow(unused_imports)]
+    use std::os::unix::io::AsFd;
+    #[doc = r" Convert a request received by the server into the request to send to the upstream server"]
+    #[doc = r""]
+    #[doc = r" The objects of the request are translated with `map`, which should return the ID of the"]
+    #[doc = r" client-side counterpart of a server-side object. The objects created by the request are not"]
+    #[doc = r" part of the returned request: the caller needs to send it with `Proxy::send_constructor()` and"]
+    #[doc = r" to initialize the server-side object from the original request."]
+    #[doc = r""]
+    #[doc = r" Returns `None` if an object could not be translated, or if the request creates an object of"]
+    #[doc = r" a dynamic interface, which cannot be bridged."]
+    #[allow(unused_mut, unused_variables)]
+    pub fn request_to_client<'a>(
+        request: &'a super::server::wl_callback::Request,
+        conn: &Connection,
+        mut map: impl FnMut(
+            &super::wayland_server::backend::ObjectId,
+        ) -> Option<super::wayland_client::backend::ObjectId>,
+    ) -> Option<super::client::wl_callback::Request<'a>> {
+        #[allow(unreachable_patterns)]
+        match request {
+            _ => None,
+        }
+    }
+    #[doc = r" Convert an event received from the upstream server into the event to send to the client"]
+    #[doc = r""]
+    #[doc = r" The objects of the event are translated with `map`, which should return the ID of the"]
+    #[doc = r" server-side counterpart of a client-side object. This includes the objects created by the"]
+    #[doc = r" event, which must have been created on the server-side beforehand."]
+    #[doc = r""]
+    #[doc = r" Returns `None` if an object could not be translated."]
+    #[allow(unused_mut, unused_variables)]
+    pub fn event_to_server<'a>(
+        event: &'a super::client::wl_callback::Event,
+        dh: &DisplayHandle,
+        mut map: impl FnMut(
+            &super::wayland_client::backend::ObjectId,
+        ) -> Option<super::wayland_server::backend::ObjectId>,
+    ) -> Option<super::server::wl_callback::Event<'a>> {
+        #[allow(unreachable_patterns)]
+        match event {
+            super::client::wl_callback::Event::Done { callback_data } => {
+                Some(super::server::wl_callback::Event::Done { callback_data: *callback_data })
+            }
+            _ => None,
+        }
+    }
+}
+#[doc = "Conversions between the client-side and server-side messages of `test_global`"]
+pub mod test_global {
+    #[allow(unused_imports)]
+    use super::wayland_client::{Connection, Proxy};
+    #[allow(unused_imports)]
+    use super::wayland_server::{DisplayHandle, Resource};
+    #[allow(unused_imports)]
+    use std::os::unix::io::AsFd;
+    #[doc = r" Convert a request received by the server into the request to send to the upstream server"]
+    #[doc = r""]
+    #[doc = r" The objects of the request are translated with `map`, which should return the ID of the"]
+    #[doc = r" client-side counterpart of a server-side object. The objects created by the request are not"]
+    #[doc = r" part of the returned request: the caller needs to send it with `Proxy::send_constructor()` and"]
+    #[doc = r" to initialize the server-side object from the original request."]
+    #[doc = r""]
+    #[doc = r" Returns `None` if an object could not be translated, or if the request creates an object of"]
+    #[doc = r" a dynamic interface, which cannot be bridged."]
+    #[allow(unused_mut, unused_variables)]
+    pub fn request_to_client<'a>(
+        request: &'a super::server::test_global::Request,
+        conn: &Connection,
+        mut map: impl FnMut(
+            &super::wayland_server::backend::ObjectId,
+        ) -> Option<super::wayland_client::backend::ObjectId>,
+    ) -> Option<super::client::test_global::Request<'a>> {
+        #[allow(unreachable_patterns)]
+        match request {
+            super::server::test_global::Request::ManyArgs {
+                unsigned_int,
+                signed_int,
+                fixed_point,
+                number_array,
+                some_text,
+                file_descriptor,
+            } => Some(super::client::test_global::Request::ManyArgs {
+                unsigned_int: *unsigned_int,
+                signed_int: *signed_int,
+                fixed_point: *fixed_point,
+                number_array: number_array.clone(),
+                some_text: some_text.clone(),
+                file_descriptor: file_descriptor.as_fd(),
+            }),
+            super::server::test_global::Request::GetSecondary { sec } => {
+                Some(super::client::test_global::Request::GetSecondary {})
+            }
+            super::server::test_global::Request::GetTertiary { ter } => {
+                Some(super::client::test_global::Request::GetTertiary {})
+            }
+            super::server::test_global::Request::Link { sec, ter, time } => {
+                Some(super::client::test_global::Request::Link {
+                    sec: {
+                        let id = map(&Resource::id(sec))?;
+                        Proxy::from_id(conn, id).ok()?
+                    },
+                    ter: match ter {
+                        Some(value) => Some({
+                            let id = map(&Resource::id(value))?;
+                            Proxy::from_id(conn, id).ok()?
+                        }),
+                        None => None,
+                    },
+                    time: *time,
+                })
+            }
+            super::server::test_global::Request::Destroy => {
+                Some(super::client::test_global::Request::Destroy)
+            }
+            super::server::test_global::Request::ReverseLink { sec, ter } => {
+                Some(super::client::test_global::Request::ReverseLink {
+                    sec: match sec {
+                        Some(value) => Some({
+                            let id = map(&Resource::id(value))?;
+                            Proxy::from_id(conn, id).ok()?
+                        }),
+                        None => None,
+                    },
+                    ter: {
+                        let id = map(&Resource::id(ter))?;
+                        Proxy::from_id(conn, id).ok()?
+                    },
+                })
+            }
+            super::server::test_global::Request::NewidAndAllowNull { quad, sec, ter } => {
+                Some(super::client::test_global::Request::NewidAndAllowNull {
+                    sec: match sec {
+                        Some(value) => Some({
+                            let id = map(&Resource::id(value))?;
+                            Proxy::from_id(conn, id).ok()?
+                        }),
+                        None => None,
+                    },
+                    ter: {
+                        let id = map(&Resource::id(ter))?;
+                        Proxy::from_id(conn, id).ok()?
+                    },
+                })
+            }
+            _ => None,
+        }
+    }
+    #[doc = r" Convert an event received from the upstream server into the event to send to the client"]
+    #[doc = r""]
+    #[doc = r" The objects of the event are translated with `map`, which should return the ID of the"]
+    #[doc = r" server-side counterpart of a client-side object. This includes the objects created by the"]
+    #[doc = r" event, which must have been created on the server-side beforehand."]
+    #[doc = r""]
+    #[doc = r" Returns `None` if an object could not be translated."]
+    #[allow(unused_mut, unused_variables)]
+    pub fn event_to_server<'a>(
+        event: &'a super::client::test_global::Event,
+        dh: &DisplayHandle,
+        mut map: impl FnMut(
+            &super::wayland_client::backend::ObjectId,
+        ) -> Option<super::wayland_server::backend::ObjectId>,
+    ) -> Option<super::server::test_global::Event<'a>> {
+        #[allow(unreachable_patterns)]
+        match event {
+            super::client::test_global::Event::ManyArgsEvt {
+                unsigned_int,
+                signed_int,
+                fixed_point,
+                number_array,
+                some_text,
+                file_descriptor,
+            } => Some(super::server::test_global::Event::ManyArgsEvt {
+                unsigned_int: *unsigned_int,
+                signed_int: *signed_int,
+                fixed_point: *fixed_point,
+                number_array: number_array.clone(),
+                some_text: some_text.clone(),
+                file_descriptor: file_descriptor.as_fd(),
+            }),
+            super::client::test_global::Event::AckSecondary { sec } => {
+                Some(super::server::test_global::Event::AckSecondary {
+                    sec: {
+                        let id = map(&Proxy::id(sec))?;
+                        Resource::from_id(dh, id).ok()?
+                    },
+                })
+            }
+            super::client::test_global::Event::CycleQuad { new_quad, old_quad } => {
+                Some(super::server::test_global::Event::CycleQuad {
+                    new_quad: {
+                        let id = map(&Proxy::id(new_quad))?;
+                        Resource::from_id(dh, id).ok()?
+                    },
+                    old_quad: match old_quad {
+                        Some(value) => Some({
+                            let id = map(&Proxy::id(value))?;
+                            Resource::from_id(dh, id).ok()?
+                        }),
+                        None => None,
+                    },
+                })
+            }
+            _ => None,
+        }
+    }
+}
+#[doc = "Conversions between the client-side and server-side messages of `secondary`"]
+pub mod secondary {
+    #[allow(unused_imports)]
+    use super::wayland_client::{Connection, Proxy};
+    #[allow(unused_imports)]
+    use super::wayland_server::{DisplayHandle, Resource};
+    #[allow(unused_imports)]
+    use std::os::unix::io::AsFd;
+    #[doc = r" Convert a request received by the server into the request to send to the upstream server"]
+    #[doc = r""]
+    #[doc = r" The objects of the request are translated with `map`, which should return the ID of the"]
+    #[doc = r" client-side counterpart of a server-side object. The objects created by the request are not"]
+    #[doc = r" part of the returned request: the caller needs to send it with `Proxy::send_constructor()` and"]
+    #[doc = r" to initialize the server-side object from the original request."]
+    #[doc = r""]
+    #[doc = r" Returns `None` if an object could not be translated, or if the request creates an object of"]
+    #[doc = r" a dynamic interface, which cannot be bridged."]
+    #[allow(unused_mut, unused_variables)]
+    pub fn request_to_client<'a>(
+        request: &'a super::server::secondary::Request,
+        conn: &Connection,
+        mut map: impl FnMut(
+            &super::wayland_server::backend::ObjectId,
+        ) -> Option<super::wayland_client::backend::ObjectId>,
+    ) -> Option<super::client::secondary::Request<'a>> {
+        #[allow(unreachable_patterns)]
+        match request {
+            super::server::secondary::Request::Destroy => {
+                Some(super::client::secondary::Request::Destroy)
+            }
+            _ => None,
+        }
+    }
+    #[doc = r" Convert an event received from the upstream server into the event to send to the client"]
+    #[doc = r""]
+    #[doc = r" The objects of the event are translated with `map`, which should return the ID of the"]
+    #[doc = r" server-side counterpart of a client-side object. This includes the objects created by the"]
+    #[doc = r" event, which must have been created on the server-side beforehand."]
+    #[doc = r""]
+    #[doc = r" Returns `None` if an object could not be translated."]
+    #[allow(unused_mut, unused_variables)]
+    pub fn event_to_server<'a>(
+        event: &'a super::client::secondary::Event,
+        dh: &DisplayHandle,
+        mut map: impl FnMut(
+            &super::wayland_client::backend::ObjectId,
+        ) -> Option<super::wayland_server::backend::ObjectId>,
+    ) -> Option<super::server::secondary::Event<'a>> {
+        #[allow(unreachable_patterns)]
+        match event {
+            _ => None,
+        }
+    }
+}
+#[doc = "Conversions between the client-side and server-side messages of `tertiary`"]
+pub mod tertiary {
+    #[allow(unused_imports)]
+    use super::wayland_client::{Connection, Proxy};
+    #[allow(unused_imports)]
+    use super::wayland_server::{DisplayHandle, Resource};
+    #[allow(unused_imports)]
+    use std::os::unix::io::AsFd;
+    #[doc = r" Convert a request received by the server into the request to send to the upstream server"]
+    #[doc = r""]
+    #[doc = r" The objects of the request are translated with `map`, which should return the ID of the"]
+    #[doc = r" client-side counterpart of a server-side object. The objects created by the request are not"]
+    #[doc = r" part of the returned request: the caller needs to send it with `Proxy::send_constructor()` and"]
+    #[doc = r" to initialize the server-side object from the original request."]
+    #[doc = r""]
+    #[doc = r" Returns `None` if an object could not be translated, or if the request creates an object of"]
+    #[doc = r" a dynamic interface, which cannot be bridged."]
+    #[allow(unused_mut, unused_variables)]
+    pub fn request_to_client<'a>(
+        request: &'a super::server::tertiary::Request,
+        conn: &Connection,
+        mut map: impl FnMut(
+            &super::wayland_server::backend::ObjectId,
+        ) -> Option<super::wayland_client::backend::ObjectId>,
+    ) -> Option<super::client::tertiary::Request<'a>> {
+        #[allow(unreachable_patterns)]
+        match request {
+            super::server::tertiary::Request::Destroy => {
+                Some(super::client::tertiary::Request::Destroy)
+            }
+            _ => None,
+        }
+    }
+    #[doc = r" Convert an event received from the upstream server into the event to send to the client"]
+    #[doc = r""]
+    #[doc = r" The objects of the event are translated with `map`, which should return the ID of the"]
+    #[doc = r" server-side counterpart of a client-side object. This includes the objects created by the"]
+    #[doc = r" event, which must have been created on the server-side beforehand."]
+    #[doc = r""]
+    #[doc = r" Returns `None` if an object could not be translated."]
+    #[allow(unused_mut, unused_variables)]
+    pub fn event_to_server<'a>(
+        event: &'a super::client::tertiary::Event,
+        dh: &DisplayHandle,
+        mut map: impl FnMut(
+            &super::wayland_client::backend::ObjectId,
+        ) -> Option<super::wayland_server::backend::ObjectId>,
+    ) -> Option<super::server::tertiary::Event<'a>> {
+        #[allow(unreachable_patterns)]
+        match event {
+            _ => None,
+        }
+    }
+}
+#[doc = "Conversions between the client-side and server-side messages of `quad`"]
+pub mod quad {
+    #[allow(unused_imports)]
+    use super::wayland_client::{Connection, Proxy};
+    #[allow(unused_imports)]
+    use super::wayland_server::{DisplayHandle, Resource};
+    #[allow(unused_imports)]
+    use std::os::unix::io::AsFd;
+    #[doc = r" Convert a request received by the server into the request to send to the upstream server"]
+    #[doc = r""]
+    #[doc = r" The objects of the request are translated with `map`, which should return the ID of the"]
+    #[doc = r" client-side counterpart of a server-side object. The objects created by the request are not"]
+    #[doc = r" part of the returned request: the caller needs to send it with `Proxy::send_constructor()` and"]
+    #[doc = r" to initialize the server-side object from the original request."]
+    #[doc = r""]
+    #[doc = r" Returns `None` if an object could not be translated, or if the request creates an object of"]
+    #[doc = r" a dynamic interface, which cannot be bridged."]
+    #[allow(unused_mut, unused_variables)]
+    pub fn request_to_client<'a>(
+        request: &'a super::server::quad::Request,
+        conn: &Connection,
+        mut map: impl FnMut(
+            &super::wayland_server::backend::ObjectId,
+        ) -> Option<super::wayland_client::backend::ObjectId>,
+    ) -> Option<super::client::quad::Request<'a>> {
+        #[allow(unreachable_patterns)]
+        match request {
+            super::server::quad::Request::Destroy => Some(super::client::quad::Request::Destroy),
+            _ => None,
+        }
+    }
+    #[doc = r" Convert an event received from the upstream server into the event to send to the client"]
+    #[doc = r""]
+    #[doc = r" The objects of the event are translated with `map`, which should return the ID of the"]
+    #[doc = r" server-side counterpart of a client-side object. This includes the objects created by the"]
+    #[doc = r" event, which must have been created on the server-side beforehand."]
+    #[doc = r""]
+    #[doc = r" Returns `None` if an object could not be translated."]
+    #[allow(unused_mut, unused_variables)]
+    pub fn event_to_server<'a>(
+        event: &'a super::client::quad::Event,
+        dh: &DisplayHandle,
+        mut map: impl FnMut(
+            &super::wayland_client::backend::ObjectId,
+        ) -> Option<super::wayland_server::backend::ObjectId>,
+    ) -> Option<super::server::quad::Event<'a>> {
+        #[allow(unreachable_patterns)]
+        match event {
+            _ => None,
+        }
+    }
+}
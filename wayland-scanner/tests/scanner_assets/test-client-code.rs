@@ -2,7 +2,7 @@
 pub mod wl_display {
     use super::wayland_client::{
         backend::{
-            protocol::{same_interface, Argument, Interface, Message, WEnum},
+            protocol::{same_interface, Argument, Fixed, Interface, Message, WEnum},
             smallvec, Backend, InvalidId, ObjectData, ObjectId, WeakBackend,
         },
         Connection, Dispatch, DispatchError, Proxy, QueueHandle, QueueProxyData, Weak,
@@ -40,6 +40,19 @@ pub mod wl_display {
             val as u32
         }
     }
+    impl std::fmt::Display for Error {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            match self {
+                Error::InvalidObject => f.write_str("server couldn't find object"),
+                Error::InvalidMethod => f.write_str(
+                    "method doesn't exist on the specified interface or malformed request",
+                ),
+                Error::NoMemory => f.write_str("server is out of memory"),
+                Error::Implementation => f.write_str("implementation error in compositor"),
+            }
+        }
+    }
+    impl std::error::Error for Error {}
     #[doc = r" The minimal object version supporting this request"]
     pub const REQ_SYNC_SINCE: u32 = 1u32;
     #[doc = r" The wire opcode for this request"]
@@ -106,6 +119,57 @@ pub mod wl_display {
             }
         }
     }
+    #[doc = r" Decode an event of this interface from its raw arguments, without a connection"]
+    #[doc = r""]
+    #[doc = r" This is useful to decode recorded or replayed messages."]
+    #[doc = r""]
+    #[doc = r" **Note:** Events carrying objects, like `wl_surface.enter`, need a connection to create"]
+    #[doc = r" their proxies, so `DispatchError::BadMessage` is always returned for them. Decode these"]
+    #[doc = r" events with `Proxy::parse_event()` instead."]
+    pub fn parse_event_raw(
+        opcode: u16,
+        args: impl IntoIterator<Item = Argument<ObjectId, OwnedFd>>,
+    ) -> Result<Event, DispatchError> {
+        const INTERFACE_NAME: &str = "wl_display";
+        let mut arg_iter = args.into_iter();
+        match opcode {
+            0u16 => Err(DispatchError::BadMessage {
+                sender_id: ObjectId::null(),
+                interface: INTERFACE_NAME,
+                opcode,
+            }),
+            1u16 => {
+                if let (Some(Argument::Uint(id))) = (arg_iter.next()) {
+                    Ok(Event::DeleteId { id })
+                } else {
+                    Err(DispatchError::BadMessage {
+                        sender_id: ObjectId::null(),
+                        interface: INTERFACE_NAME,
+                        opcode,
+                    })
+                }
+            }
+            _ => Err(DispatchError::BadMessage {
+                sender_id: ObjectId::null(),
+                interface: INTERFACE_NAME,
+                opcode,
+            }),
+        }
+    }
+    #[doc = r" Decode an event with `parse_event_raw()`"]
+    #[doc = r""]
+    #[doc = r" This always fails with `DispatchError::BadMessage` for events carrying objects, as they"]
+    #[doc = r" need a connection to create their proxies. Use `Proxy::parse_event()` for them."]
+    impl std::convert::TryFrom<Message<ObjectId, OwnedFd>> for Event {
+        type Error = DispatchError;
+        fn try_from(msg: Message<ObjectId, OwnedFd>) -> Result<Event, DispatchError> {
+            parse_event_raw(msg.opcode, msg.args).map_err(|_| DispatchError::BadMessage {
+                sender_id: msg.sender_id,
+                interface: "wl_display",
+                opcode: msg.opcode,
+            })
+        }
+    }
     #[doc = "core global object\n\nThe core global object.  This is a special singleton object.  It\nis used for internal Wayland protocol features.\n\nSee also the [Event] enum for this interface."]
     #[derive(Debug, Clone)]
     pub struct WlDisplay {
@@ -130,6 +194,21 @@ pub mod wl_display {
             &self.id
         }
     }
+    impl AsRef<ObjectId> for WlDisplay {
+        fn as_ref(&self) -> &ObjectId {
+            &self.id
+        }
+    }
+    impl From<WlDisplay> for ObjectId {
+        fn from(value: WlDisplay) -> ObjectId {
+            value.id
+        }
+    }
+    impl From<&WlDisplay> for ObjectId {
+        fn from(value: &WlDisplay) -> ObjectId {
+            value.id.clone()
+        }
+    }
     impl std::hash::Hash for WlDisplay {
         fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
             self.id.hash(state)
@@ -200,7 +279,7 @@ pub mod wl_display {
                     if let (
                         Some(Argument::Object(object_id)),
                         Some(Argument::Uint(code)),
-                        Some(Argument::Str(message)),
+                        Some(Argument::Str(Some(message))),
                     ) = (arg_iter.next(), arg_iter.next(), arg_iter.next())
                     {
                         Ok((
@@ -208,10 +287,7 @@ pub mod wl_display {
                             Event::Error {
                                 object_id: object_id.clone(),
                                 code,
-                                message: String::from_utf8_lossy(
-                                    message.as_ref().unwrap().as_bytes(),
-                                )
-                                .into_owned(),
+                                message: String::from_utf8_lossy(message.as_bytes()).into_owned(),
                             },
                         ))
                     } else {
@@ -280,6 +356,7 @@ pub mod wl_display {
     impl WlDisplay {
         #[doc = "asynchronous roundtrip\n\nThe sync request asks the server to emit the 'done' event\non the returned wl_callback object.  Since requests are\nhandled in-order and events are delivered in-order, this can\nbe used as a barrier to ensure all previous requests and the\nresulting events have been handled.\n\nThe object returned by this request will be destroyed by the\ncompositor after the callback is fired and as such the client must not\nattempt to use it after that point.\n\nThe callback_data passed in the callback is the event serial."]
         #[allow(clippy::too_many_arguments)]
+        #[must_use = "the created object can only be used through the returned proxy"]
         pub fn sync<
             U: Send + Sync + 'static,
             D: Dispatch<super::wl_callback::WlCallback, U> + 'static,
@@ -296,6 +373,7 @@ pub mod wl_display {
         }
         #[doc = "get global registry object\n\nThis request creates a registry object that allows the client\nto list and bind the global objects available from the\ncompositor.\n\nIt should be noted that the server side resources consumed in\nresponse to a get_registry request can only be released when the\nclient disconnects, not when the client side proxy is destroyed.\nTherefore, clients should invoke get_registry as infrequently as\npossible to avoid wasting memory."]
         #[allow(clippy::too_many_arguments)]
+        #[must_use = "the created object can only be used through the returned proxy"]
         pub fn get_registry<
             U: Send + Sync + 'static,
             D: Dispatch<super::wl_registry::WlRegistry, U> + 'static,
@@ -316,7 +394,7 @@ pub mod wl_display {
 pub mod wl_registry {
     use super::wayland_client::{
         backend::{
-            protocol::{same_interface, Argument, Interface, Message, WEnum},
+            protocol::{same_interface, Argument, Fixed, Interface, Message, WEnum},
             smallvec, Backend, InvalidId, ObjectData, ObjectId, WeakBackend,
         },
         Connection, Dispatch, DispatchError, Proxy, QueueHandle, QueueProxyData, Weak,
@@ -387,6 +465,72 @@ pub mod wl_registry {
             }
         }
     }
+    #[doc = r" Decode an event of this interface from its raw arguments, without a connection"]
+    #[doc = r""]
+    #[doc = r" This is useful to decode recorded or replayed messages."]
+    #[doc = r""]
+    #[doc = r" **Note:** Events carrying objects, like `wl_surface.enter`, need a connection to create"]
+    #[doc = r" their proxies, so `DispatchError::BadMessage` is always returned for them. Decode these"]
+    #[doc = r" events with `Proxy::parse_event()` instead."]
+    pub fn parse_event_raw(
+        opcode: u16,
+        args: impl IntoIterator<Item = Argument<ObjectId, OwnedFd>>,
+    ) -> Result<Event, DispatchError> {
+        const INTERFACE_NAME: &str = "wl_registry";
+        let mut arg_iter = args.into_iter();
+        match opcode {
+            0u16 => {
+                if let (
+                    Some(Argument::Uint(name)),
+                    Some(Argument::Str(Some(interface))),
+                    Some(Argument::Uint(version)),
+                ) = (arg_iter.next(), arg_iter.next(), arg_iter.next())
+                {
+                    Ok(Event::Global {
+                        name,
+                        interface: String::from_utf8_lossy(interface.as_bytes()).into_owned(),
+                        version,
+                    })
+                } else {
+                    Err(DispatchError::BadMessage {
+                        sender_id: ObjectId::null(),
+                        interface: INTERFACE_NAME,
+                        opcode,
+                    })
+                }
+            }
+            1u16 => {
+                if let (Some(Argument::Uint(name))) = (arg_iter.next()) {
+                    Ok(Event::GlobalRemove { name })
+                } else {
+                    Err(DispatchError::BadMessage {
+                        sender_id: ObjectId::null(),
+                        interface: INTERFACE_NAME,
+                        opcode,
+                    })
+                }
+            }
+            _ => Err(DispatchError::BadMessage {
+                sender_id: ObjectId::null(),
+                interface: INTERFACE_NAME,
+                opcode,
+            }),
+        }
+    }
+    #[doc = r" Decode an event with `parse_event_raw()`"]
+    #[doc = r""]
+    #[doc = r" This always fails with `DispatchError::BadMessage` for events carrying objects, as they"]
+    #[doc = r" need a connection to create their proxies. Use `Proxy::parse_event()` for them."]
+    impl std::convert::TryFrom<Message<ObjectId, OwnedFd>> for Event {
+        type Error = DispatchError;
+        fn try_from(msg: Message<ObjectId, OwnedFd>) -> Result<Event, DispatchError> {
+            parse_event_raw(msg.opcode, msg.args).map_err(|_| DispatchError::BadMessage {
+                sender_id: msg.sender_id,
+                interface: "wl_registry",
+                opcode: msg.opcode,
+            })
+        }
+    }
     #[doc = "global registry object\n\nThe singleton global registry object.  The server has a number of\nglobal objects that are available to all clients.  These objects\ntypically represent an actual object in the server (for example,\nan input device) or they are singleton objects that provide\nextension functionality.\n\nWhen a client creates a registry object, the registry object\nwill emit a global event for each global currently in the\nregistry.  Globals come and go as a result of device or\nmonitor hotplugs, reconfiguration or other events, and the\nregistry will send out global and global_remove events to\nkeep the client up to date with the changes.  To mark the end\nof the initial burst of events, the client can use the\nwl_display.sync request immediately after calling\nwl_display.get_registry.\n\nA client can bind to a global object by using the bind\nrequest.  This creates a client-side handle that lets the object\nemit events to the client and lets the client invoke requests on\nthe object.\n\nSee also the [Event] enum for this interface."]
     #[derive(Debug, Clone)]
     pub struct WlRegistry {
@@ -411,6 +555,21 @@ pub mod wl_registry {
             &self.id
         }
     }
+    impl AsRef<ObjectId> for WlRegistry {
+        fn as_ref(&self) -> &ObjectId {
+            &self.id
+        }
+    }
+    impl From<WlRegistry> for ObjectId {
+        fn from(value: WlRegistry) -> ObjectId {
+            value.id
+        }
+    }
+    impl From<&WlRegistry> for ObjectId {
+        fn from(value: &WlRegistry) -> ObjectId {
+            value.id.clone()
+        }
+    }
     impl std::hash::Hash for WlRegistry {
         fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
             self.id.hash(state)
@@ -480,7 +639,7 @@ pub mod wl_registry {
                 0u16 => {
                     if let (
                         Some(Argument::Uint(name)),
-                        Some(Argument::Str(interface)),
+                        Some(Argument::Str(Some(interface))),
                         Some(Argument::Uint(version)),
                     ) = (arg_iter.next(), arg_iter.next(), arg_iter.next())
                     {
@@ -488,10 +647,7 @@ pub mod wl_registry {
                             me,
                             Event::Global {
                                 name,
-                                interface: String::from_utf8_lossy(
-                                    interface.as_ref().unwrap().as_bytes(),
-                                )
-                                .into_owned(),
+                                interface: String::from_utf8_lossy(interface.as_bytes()).into_owned(),
                                 version,
                             },
                         ))
@@ -551,6 +707,7 @@ pub mod wl_registry {
     impl WlRegistry {
         #[doc = "bind an object to the display\n\nBinds a new, client-created object to the server using the\nspecified name as the identifier."]
         #[allow(clippy::too_many_arguments)]
+        #[must_use = "the created object can only be used through the returned proxy"]
         pub fn bind<I: Proxy + 'static, U: Send + Sync + 'static, D: Dispatch<I, U> + 'static>(
             &self,
             name: u32,
@@ -570,7 +727,7 @@ pub mod wl_registry {
 pub mod wl_callback {
     use super::wayland_client::{
         backend::{
-            protocol::{same_interface, Argument, Interface, Message, WEnum},
+            protocol::{same_interface, Argument, Fixed, Interface, Message, WEnum},
             smallvec, Backend, InvalidId, ObjectData, ObjectId, WeakBackend,
         },
         Connection, Dispatch, DispatchError, Proxy, QueueHandle, QueueProxyData, Weak,
@@ -615,6 +772,52 @@ pub mod wl_callback {
             }
         }
     }
+    #[doc = r" Decode an event of this interface from its raw arguments, without a connection"]
+    #[doc = r""]
+    #[doc = r" This is useful to decode recorded or replayed messages."]
+    #[doc = r""]
+    #[doc = r" **Note:** Events carrying objects, like `wl_surface.enter`, need a connection to create"]
+    #[doc = r" their proxies, so `DispatchError::BadMessage` is always returned for them. Decode these"]
+    #[doc = r" events with `Proxy::parse_event()` instead."]
+    pub fn parse_event_raw(
+        opcode: u16,
+        args: impl IntoIterator<Item = Argument<ObjectId, OwnedFd>>,
+    ) -> Result<Event, DispatchError> {
+        const INTERFACE_NAME: &str = "wl_callback";
+        let mut arg_iter = args.into_iter();
+        match opcode {
+            0u16 => {
+                if let (Some(Argument::Uint(callback_data))) = (arg_iter.next()) {
+                    Ok(Event::Done { callback_data })
+                } else {
+                    Err(DispatchError::BadMessage {
+                        sender_id: ObjectId::null(),
+                        interface: INTERFACE_NAME,
+                        opcode,
+                    })
+                }
+            }
+            _ => Err(DispatchError::BadMessage {
+                sender_id: ObjectId::null(),
+                interface: INTERFACE_NAME,
+                opcode,
+            }),
+        }
+    }
+    #[doc = r" Decode an event with `parse_event_raw()`"]
+    #[doc = r""]
+    #[doc = r" This always fails with `DispatchError::BadMessage` for events carrying objects, as they"]
+    #[doc = r" need a connection to create their proxies. Use `Proxy::parse_event()` for them."]
+    impl std::convert::TryFrom<Message<ObjectId, OwnedFd>> for Event {
+        type Error = DispatchError;
+        fn try_from(msg: Message<ObjectId, OwnedFd>) -> Result<Event, DispatchError> {
+            parse_event_raw(msg.opcode, msg.args).map_err(|_| DispatchError::BadMessage {
+                sender_id: msg.sender_id,
+                interface: "wl_callback",
+                opcode: msg.opcode,
+            })
+        }
+    }
     #[doc = "callback object\n\nClients can handle the 'done' event to get notified when\nthe related request is done.\n\nSee also the [Event] enum for this interface."]
     #[derive(Debug, Clone)]
     pub struct WlCallback {
@@ -639,6 +842,21 @@ pub mod wl_callback {
             &self.id
         }
     }
+    impl AsRef<ObjectId> for WlCallback {
+        fn as_ref(&self) -> &ObjectId {
+            &self.id
+        }
+    }
+    impl From<WlCallback> for ObjectId {
+        fn from(value: WlCallback) -> ObjectId {
+            value.id
+        }
+    }
+    impl From<&WlCallback> for ObjectId {
+        fn from(value: &WlCallback) -> ObjectId {
+            value.id.clone()
+        }
+    }
     impl std::hash::Hash for WlCallback {
         fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
             self.id.hash(state)
@@ -741,7 +959,7 @@ pub mod wl_callback {
 pub mod test_global {
     use super::wayland_client::{
         backend::{
-            protocol::{same_interface, Argument, Interface, Message, WEnum},
+            protocol::{same_interface, Argument, Fixed, Interface, Message, WEnum},
             smallvec, Backend, InvalidId, ObjectData, ObjectId, WeakBackend,
         },
         Connection, Dispatch, DispatchError, Proxy, QueueHandle, QueueProxyData, Weak,
@@ -875,6 +1093,83 @@ pub mod test_global {
             }
         }
     }
+    #[doc = r" Decode an event of this interface from its raw arguments, without a connection"]
+    #[doc = r""]
+    #[doc = r" This is useful to decode recorded or replayed messages."]
+    #[doc = r""]
+    #[doc = r" **Note:** Events carrying objects, like `wl_surface.enter`, need a connection to create"]
+    #[doc = r" their proxies, so `DispatchError::BadMessage` is always returned for them. Decode these"]
+    #[doc = r" events with `Proxy::parse_event()` instead."]
+    pub fn parse_event_raw(
+        opcode: u16,
+        args: impl IntoIterator<Item = Argument<ObjectId, OwnedFd>>,
+    ) -> Result<Event, DispatchError> {
+        const INTERFACE_NAME: &str = "test_global";
+        let mut arg_iter = args.into_iter();
+        match opcode {
+            0u16 => {
+                if let (
+                    Some(Argument::Uint(unsigned_int)),
+                    Some(Argument::Int(signed_int)),
+                    Some(Argument::Fixed(fixed_point)),
+                    Some(Argument::Array(number_array)),
+                    Some(Argument::Str(Some(some_text))),
+                    Some(Argument::Fd(file_descriptor)),
+                ) = (
+                    arg_iter.next(),
+                    arg_iter.next(),
+                    arg_iter.next(),
+                    arg_iter.next(),
+                    arg_iter.next(),
+                    arg_iter.next(),
+                ) {
+                    Ok(Event::ManyArgsEvt {
+                        unsigned_int,
+                        signed_int,
+                        fixed_point: Fixed::from_raw(fixed_point).to_f64(),
+                        number_array: *number_array,
+                        some_text: String::from_utf8_lossy(some_text.as_bytes()).into_owned(),
+                        file_descriptor,
+                    })
+                } else {
+                    Err(DispatchError::BadMessage {
+                        sender_id: ObjectId::null(),
+                        interface: INTERFACE_NAME,
+                        opcode,
+                    })
+                }
+            }
+            1u16 => Err(DispatchError::BadMessage {
+                sender_id: ObjectId::null(),
+                interface: INTERFACE_NAME,
+                opcode,
+            }),
+            2u16 => Err(DispatchError::BadMessage {
+                sender_id: ObjectId::null(),
+                interface: INTERFACE_NAME,
+                opcode,
+            }),
+            _ => Err(DispatchError::BadMessage {
+                sender_id: ObjectId::null(),
+                interface: INTERFACE_NAME,
+                opcode,
+            }),
+        }
+    }
+    #[doc = r" Decode an event with `parse_event_raw()`"]
+    #[doc = r""]
+    #[doc = r" This always fails with `DispatchError::BadMessage` for events carrying objects, as they"]
+    #[doc = r" need a connection to create their proxies. Use `Proxy::parse_event()` for them."]
+    impl std::convert::TryFrom<Message<ObjectId, OwnedFd>> for Event {
+        type Error = DispatchError;
+        fn try_from(msg: Message<ObjectId, OwnedFd>) -> Result<Event, DispatchError> {
+            parse_event_raw(msg.opcode, msg.args).map_err(|_| DispatchError::BadMessage {
+                sender_id: msg.sender_id,
+                interface: "test_global",
+                opcode: msg.opcode,
+            })
+        }
+    }
     #[doc = "test_global\n\nSee also the [Event] enum for this interface."]
     #[derive(Debug, Clone)]
     pub struct TestGlobal {
@@ -899,6 +1194,21 @@ pub mod test_global {
             &self.id
         }
     }
+    impl AsRef<ObjectId> for TestGlobal {
+        fn as_ref(&self) -> &ObjectId {
+            &self.id
+        }
+    }
+    impl From<TestGlobal> for ObjectId {
+        fn from(value: TestGlobal) -> ObjectId {
+            value.id
+        }
+    }
+    impl From<&TestGlobal> for ObjectId {
+        fn from(value: &TestGlobal) -> ObjectId {
+            value.id.clone()
+        }
+    }
     impl std::hash::Hash for TestGlobal {
         fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
             self.id.hash(state)
@@ -971,7 +1281,7 @@ pub mod test_global {
                         Some(Argument::Int(signed_int)),
                         Some(Argument::Fixed(fixed_point)),
                         Some(Argument::Array(number_array)),
-                        Some(Argument::Str(some_text)),
+                        Some(Argument::Str(Some(some_text))),
                         Some(Argument::Fd(file_descriptor)),
                     ) = (
                         arg_iter.next(),
@@ -986,12 +1296,9 @@ pub mod test_global {
                             Event::ManyArgsEvt {
                                 unsigned_int,
                                 signed_int,
-                                fixed_point: (fixed_point as f64) / 256.,
+                                fixed_point: Fixed::from_raw(fixed_point).to_f64(),
                                 number_array: *number_array,
-                                some_text: String::from_utf8_lossy(
-                                    some_text.as_ref().unwrap().as_bytes(),
-                                )
-                                .into_owned(),
+                                some_text: String::from_utf8_lossy(some_text.as_bytes()).into_owned(),
                                 file_descriptor,
                             },
                         ))
@@ -1108,7 +1415,7 @@ pub mod test_global {
                     let args = smallvec::SmallVec::from_vec(vec![
                         Argument::Uint(unsigned_int),
                         Argument::Int(signed_int),
-                        Argument::Fixed((fixed_point * 256.) as i32),
+                        Argument::Fixed(Fixed::from_f64(fixed_point).into_raw()),
                         Argument::Array(Box::new(number_array)),
                         Argument::Str(Some(Box::new(std::ffi::CString::new(some_text).unwrap()))),
                         Argument::Fd(file_descriptor),
@@ -1226,6 +1533,7 @@ pub mod test_global {
             );
         }
         #[allow(clippy::too_many_arguments)]
+        #[must_use = "the created object can only be used through the returned proxy"]
         pub fn get_secondary<
             U: Send + Sync + 'static,
             D: Dispatch<super::secondary::Secondary, U> + 'static,
@@ -1241,6 +1549,7 @@ pub mod test_global {
             .unwrap_or_else(|_| Proxy::inert(self.backend.clone()))
         }
         #[allow(clippy::too_many_arguments)]
+        #[must_use = "the created object can only be used through the returned proxy"]
         pub fn get_tertiary<
             U: Send + Sync + 'static,
             D: Dispatch<super::tertiary::Tertiary, U> + 'static,
@@ -1303,6 +1612,7 @@ pub mod test_global {
         }
         #[doc = "a newid request that also takes allow null arg"]
         #[allow(clippy::too_many_arguments)]
+        #[must_use = "the created object can only be used through the returned proxy"]
         pub fn newid_and_allow_null<
             U: Send + Sync + 'static,
             D: Dispatch<super::quad::Quad, U> + 'static,
@@ -1324,7 +1634,7 @@ pub mod test_global {
 pub mod secondary {
     use super::wayland_client::{
         backend::{
-            protocol::{same_interface, Argument, Interface, Message, WEnum},
+            protocol::{same_interface, Argument, Fixed, Interface, Message, WEnum},
             smallvec, Backend, InvalidId, ObjectData, ObjectId, WeakBackend,
         },
         Connection, Dispatch, DispatchError, Proxy, QueueHandle, QueueProxyData, Weak,
@@ -1364,6 +1674,38 @@ pub mod secondary {
             match *self {}
         }
     }
+    #[doc = r" Decode an event of this interface from its raw arguments, without a connection"]
+    #[doc = r""]
+    #[doc = r" This is useful to decode recorded or replayed messages."]
+    #[doc = r""]
+    #[doc = r" **Note:** Events carrying objects, like `wl_surface.enter`, need a connection to create"]
+    #[doc = r" their proxies, so `DispatchError::BadMessage` is always returned for them. Decode these"]
+    #[doc = r" events with `Proxy::parse_event()` instead."]
+    pub fn parse_event_raw(
+        opcode: u16,
+        args: impl IntoIterator<Item = Argument<ObjectId, OwnedFd>>,
+    ) -> Result<Event, DispatchError> {
+        let _ = args;
+        Err(DispatchError::BadMessage {
+            sender_id: ObjectId::null(),
+            interface: "secondary",
+            opcode,
+        })
+    }
+    #[doc = r" Decode an event with `parse_event_raw()`"]
+    #[doc = r""]
+    #[doc = r" This always fails with `DispatchError::BadMessage` for events carrying objects, as they"]
+    #[doc = r" need a connection to create their proxies. Use `Proxy::parse_event()` for them."]
+    impl std::convert::TryFrom<Message<ObjectId, OwnedFd>> for Event {
+        type Error = DispatchError;
+        fn try_from(msg: Message<ObjectId, OwnedFd>) -> Result<Event, DispatchError> {
+            parse_event_raw(msg.opcode, msg.args).map_err(|_| DispatchError::BadMessage {
+                sender_id: msg.sender_id,
+                interface: "secondary",
+                opcode: msg.opcode,
+            })
+        }
+    }
     #[doc = "secondary\n\nThis interface has no events."]
     #[derive(Debug, Clone)]
     pub struct Secondary {
@@ -1388,6 +1730,21 @@ pub mod secondary {
             &self.id
         }
     }
+    impl AsRef<ObjectId> for Secondary {
+        fn as_ref(&self) -> &ObjectId {
+            &self.id
+        }
+    }
+    impl From<Secondary> for ObjectId {
+        fn from(value: Secondary) -> ObjectId {
+            value.id
+        }
+    }
+    impl From<&Secondary> for ObjectId {
+        fn from(value: &Secondary) -> ObjectId {
+            value.id.clone()
+        }
+    }
     impl std::hash::Hash for Secondary {
         fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
             self.id.hash(state)
@@ -1494,7 +1851,7 @@ pub mod secondary {
 pub mod tertiary {
     use super::wayland_client::{
         backend::{
-            protocol::{same_interface, Argument, Interface, Message, WEnum},
+            protocol::{same_interface, Argument, Fixed, Interface, Message, WEnum},
             smallvec, Backend, InvalidId, ObjectData, ObjectId, WeakBackend,
         },
         Connection, Dispatch, DispatchError, Proxy, QueueHandle, QueueProxyData, Weak,
@@ -1534,6 +1891,38 @@ pub mod tertiary {
             match *self {}
         }
     }
+    #[doc = r" Decode an event of this interface from its raw arguments, without a connection"]
+    #[doc = r""]
+    #[doc = r" This is useful to decode recorded or replayed messages."]
+    #[doc = r""]
+    #[doc = r" **Note:** Events carrying objects, like `wl_surface.enter`, need a connection to create"]
+    #[doc = r" their proxies, so `DispatchError::BadMessage` is always returned for them. Decode these"]
+    #[doc = r" events with `Proxy::parse_event()` instead."]
+    pub fn parse_event_raw(
+        opcode: u16,
+        args: impl IntoIterator<Item = Argument<ObjectId, OwnedFd>>,
+    ) -> Result<Event, DispatchError> {
+        let _ = args;
+        Err(DispatchError::BadMessage {
+            sender_id: ObjectId::null(),
+            interface: "tertiary",
+            opcode,
+        })
+    }
+    #[doc = r" Decode an event with `parse_event_raw()`"]
+    #[doc = r""]
+    #[doc = r" This always fails with `DispatchError::BadMessage` for events carrying objects, as they"]
+    #[doc = r" need a connection to create their proxies. Use `Proxy::parse_event()` for them."]
+    impl std::convert::TryFrom<Message<ObjectId, OwnedFd>> for Event {
+        type Error = DispatchError;
+        fn try_from(msg: Message<ObjectId, OwnedFd>) -> Result<Event, DispatchError> {
+            parse_event_raw(msg.opcode, msg.args).map_err(|_| DispatchError::BadMessage {
+                sender_id: msg.sender_id,
+                interface: "tertiary",
+                opcode: msg.opcode,
+            })
+        }
+    }
     #[doc = "tertiary\n\nThis interface has no events."]
     #[derive(Debug, Clone)]
     pub struct Tertiary {
@@ -1558,6 +1947,21 @@ pub mod tertiary {
             &self.id
         }
     }
+    impl AsRef<ObjectId> for Tertiary {
+        fn as_ref(&self) -> &ObjectId {
+            &self.id
+        }
+    }
+    impl From<Tertiary> for ObjectId {
+        fn from(value: Tertiary) -> ObjectId {
+            value.id
+        }
+    }
+    impl From<&Tertiary> for ObjectId {
+        fn from(value: &Tertiary) -> ObjectId {
+            value.id.clone()
+        }
+    }
     impl std::hash::Hash for Tertiary {
         fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
             self.id.hash(state)
@@ -1664,7 +2068,7 @@ pub mod tertiary {
 pub mod quad {
     use super::wayland_client::{
         backend::{
-            protocol::{same_interface, Argument, Interface, Message, WEnum},
+            protocol::{same_interface, Argument, Fixed, Interface, Message, WEnum},
             smallvec, Backend, InvalidId, ObjectData, ObjectId, WeakBackend,
         },
         Connection, Dispatch, DispatchError, Proxy, QueueHandle, QueueProxyData, Weak,
@@ -1704,6 +2108,34 @@ pub mod quad {
             match *self {}
         }
     }
+    #[doc = r" Decode an event of this interface from its raw arguments, without a connection"]
+    #[doc = r""]
+    #[doc = r" This is useful to decode recorded or replayed messages."]
+    #[doc = r""]
+    #[doc = r" **Note:** Events carrying objects, like `wl_surface.enter`, need a connection to create"]
+    #[doc = r" their proxies, so `DispatchError::BadMessage` is always returned for them. Decode these"]
+    #[doc = r" events with `Proxy::parse_event()` instead."]
+    pub fn parse_event_raw(
+        opcode: u16,
+        args: impl IntoIterator<Item = Argument<ObjectId, OwnedFd>>,
+    ) -> Result<Event, DispatchError> {
+        let _ = args;
+        Err(DispatchError::BadMessage { sender_id: ObjectId::null(), interface: "quad", opcode })
+    }
+    #[doc = r" Decode an event with `parse_event_raw()`"]
+    #[doc = r""]
+    #[doc = r" This always fails with `DispatchError::BadMessage` for events carrying objects, as they"]
+    #[doc = r" need a connection to create their proxies. Use `Proxy::parse_event()` for them."]
+    impl std::convert::TryFrom<Message<ObjectId, OwnedFd>> for Event {
+        type Error = DispatchError;
+        fn try_from(msg: Message<ObjectId, OwnedFd>) -> Result<Event, DispatchError> {
+            parse_event_raw(msg.opcode, msg.args).map_err(|_| DispatchError::BadMessage {
+                sender_id: msg.sender_id,
+                interface: "quad",
+                opcode: msg.opcode,
+            })
+        }
+    }
     #[doc = "quad\n\nThis interface has no events."]
     #[derive(Debug, Clone)]
     pub struct Quad {
@@ -1728,6 +2160,21 @@ pub mod quad {
             &self.id
         }
     }
+    impl AsRef<ObjectId> for Quad {
+        fn as_ref(&self) -> &ObjectId {
+            &self.id
+        }
+    }
+    impl From<Quad> for ObjectId {
+        fn from(value: Quad) -> ObjectId {
+            value.id
+        }
+    }
+    impl From<&Quad> for ObjectId {
+        fn from(value: &Quad) -> ObjectId {
+            value.id.clone()
+        }
+    }
     impl std::hash::Hash for Quad {
         fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
             self.id.hash(state)
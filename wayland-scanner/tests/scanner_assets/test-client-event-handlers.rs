@@ -0,0 +1,116 @@
+#[doc = "Handler for the events of this interface"]
+#[doc = ""]
+#[doc = "Each event is delivered to its own method, which does nothing by default. Use [`dispatch_event()`]"]
+#[doc = "to forward an [`Event`] to the matching method."]
+pub trait EventHandler {
+    #[doc = "fatal error event\n\nThe error event is sent out when a fatal (non-recoverable)\nerror has occurred.  The object_id argument is the object\nwhere the error occurred, most often in response to a request\nto that object.  The code identifies the error and is defined\nby the object interface.  As such, each interface defines its\nown set of error codes.  The message is a brief description\nof the error, for (debugging) convenience."]
+    #[allow(unused_variables, clippy::too_many_arguments)]
+    fn error(
+        &mut self,
+        proxy: &WlDisplay,
+        object_id: super::wayland_client::ObjectId,
+        code: u32,
+        message: String,
+    ) {
+    }
+    #[doc = "acknowledge object ID deletion\n\nThis event is used internally by the object ID management\nlogic. When a client deletes an object that it had created,\nthe server will send this event to acknowledge that it has\nseen the delete request. When the client receives this event,\nit will know that it can safely reuse the object ID."]
+    #[allow(unused_variables, clippy::too_many_arguments)]
+    fn delete_id(&mut self, proxy: &WlDisplay, id: u32) {}
+}
+#[doc = "Forward an event to the matching method of an [`EventHandler`]"]
+pub fn dispatch_event<H: EventHandler + ?Sized>(handler: &mut H, proxy: &WlDisplay, event: Event) {
+    match event {
+        Event::Error { object_id, code, message } => handler.error(proxy, object_id, code, message),
+        Event::DeleteId { id } => handler.delete_id(proxy, id),
+    }
+}
+#[doc = "Handler for the events of this interface"]
+#[doc = ""]
+#[doc = "Each event is delivered to its own method, which does nothing by default. Use [`dispatch_event()`]"]
+#[doc = "to forward an [`Event`] to the matching method."]
+pub trait EventHandler {
+    #[doc = "announce global object\n\nNotify the client of global objects.\n\nThe event notifies the client that a global object with\nthe given name is now available, and it implements the\ngiven version of the given interface."]
+    #[allow(unused_variables, clippy::too_many_arguments)]
+    fn global(&mut self, proxy: &WlRegistry, name: u32, interface: String, version: u32) {}
+    #[doc = "announce removal of global object\n\nNotify the client of removed global objects.\n\nThis event notifies the client that the global identified\nby name is no longer available.  If the client bound to\nthe global using the bind request, the client should now\ndestroy that object.\n\nThe object remains valid and requests to the object will be\nignored until the client destroys it, to avoid races between\nthe global going away and a client sending a request to it."]
+    #[allow(unused_variables, clippy::too_many_arguments)]
+    fn global_remove(&mut self, proxy: &WlRegistry, name: u32) {}
+}
+#[doc = "Forward an event to the matching method of an [`EventHandler`]"]
+pub fn dispatch_event<H: EventHandler + ?Sized>(handler: &mut H, proxy: &WlRegistry, event: Event) {
+    match event {
+        Event::Global { name, interface, version } => {
+            handler.global(proxy, name, interface, version)
+        }
+        Event::GlobalRemove { name } => handler.global_remove(proxy, name),
+    }
+}
+#[doc = "Handler for the events of this interface"]
+#[doc = ""]
+#[doc = "Each event is delivered to its own method, which does nothing by default. Use [`dispatch_event()`]"]
+#[doc = "to forward an [`Event`] to the matching method."]
+pub trait EventHandler {
+    #[doc = "done event\n\nNotify the client when the related request is done."]
+    #[allow(unused_variables, clippy::too_many_arguments)]
+    fn done(&mut self, proxy: &WlCallback, callback_data: u32) {}
+}
+#[doc = "Forward an event to the matching method of an [`EventHandler`]"]
+pub fn dispatch_event<H: EventHandler + ?Sized>(handler: &mut H, proxy: &WlCallback, event: Event) {
+    match event {
+        Event::Done { callback_data } => handler.done(proxy, callback_data),
+    }
+}
+#[doc = "Handler for the events of this interface"]
+#[doc = ""]
+#[doc = "Each event is delivered to its own method, which does nothing by default. Use [`dispatch_event()`]"]
+#[doc = "to forward an [`Event`] to the matching method."]
+pub trait EventHandler {
+    #[doc = "an event with every possible non-object arg"]
+    #[allow(unused_variables, clippy::too_many_arguments)]
+    fn many_args_evt(
+        &mut self,
+        proxy: &TestGlobal,
+        unsigned_int: u32,
+        signed_int: i32,
+        fixed_point: f64,
+        number_array: Vec<u8>,
+        some_text: String,
+        file_descriptor: OwnedFd,
+    ) {
+    }
+    #[doc = "acking the creation of a secondary"]
+    #[allow(unused_variables, clippy::too_many_arguments)]
+    fn ack_secondary(&mut self, proxy: &TestGlobal, sec: super::secondary::Secondary) {}
+    #[doc = "create a new quad optionally replacing a previous one"]
+    #[allow(unused_variables, clippy::too_many_arguments)]
+    fn cycle_quad(
+        &mut self,
+        proxy: &TestGlobal,
+        new_quad: super::quad::Quad,
+        old_quad: Option<super::quad::Quad>,
+    ) {
+    }
+}
+#[doc = "Forward an event to the matching method of an [`EventHandler`]"]
+pub fn dispatch_event<H: EventHandler + ?Sized>(handler: &mut H, proxy: &TestGlobal, event: Event) {
+    match event {
+        Event::ManyArgsEvt {
+            unsigned_int,
+            signed_int,
+            fixed_point,
+            number_array,
+            some_text,
+            file_descriptor,
+        } => handler.many_args_evt(
+            proxy,
+            unsigned_int,
+            signed_int,
+            fixed_point,
+            number_array,
+            some_text,
+            file_descriptor,
+        ),
+        Event::AckSecondary { sec } => handler.ack_secondary(proxy, sec),
+        Event::CycleQuad { new_quad, old_quad } => handler.cycle_quad(proxy, new_quad, old_quad),
+    }
+}
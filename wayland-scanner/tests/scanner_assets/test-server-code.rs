@@ -2,8 +2,8 @@
 pub mod wl_callback {
     use super::wayland_server::{
         backend::{
-            protocol::{same_interface, Argument, Interface, Message, WEnum},
-            smallvec, InvalidId, ObjectData, ObjectId, WeakHandle,
+            protocol::{same_interface, Argument, Fixed, Interface, Message, WEnum},
+            smallvec, InvalidId, ObjectData, ObjectId, SendEventError, WeakHandle,
         },
         Dispatch, DispatchError, DisplayHandle, New, Resource, ResourceData, Weak,
     };
@@ -45,6 +45,38 @@ pub mod wl_callback {
             }
         }
     }
+    #[doc = r" Decode a request of this interface from its raw arguments, without a display handle"]
+    #[doc = r""]
+    #[doc = r" This is useful to decode recorded or replayed messages."]
+    #[doc = r""]
+    #[doc = r" **Note:** Requests carrying objects, like `wl_surface.attach`, need a display handle to"]
+    #[doc = r" create their resources, so `DispatchError::BadMessage` is always returned for them. Decode"]
+    #[doc = r" these requests with `Resource::parse_request()` instead."]
+    pub fn parse_request_raw(
+        opcode: u16,
+        args: impl IntoIterator<Item = Argument<ObjectId, OwnedFd>>,
+    ) -> Result<Request, DispatchError> {
+        let _ = args;
+        Err(DispatchError::BadMessage {
+            sender_id: ObjectId::null(),
+            interface: "wl_callback",
+            opcode,
+        })
+    }
+    #[doc = r" Decode a request with `parse_request_raw()`"]
+    #[doc = r""]
+    #[doc = r" This always fails with `DispatchError::BadMessage` for requests carrying objects, as they"]
+    #[doc = r" need a display handle to create their resources. Use `Resource::parse_request()` for them."]
+    impl std::convert::TryFrom<Message<ObjectId, OwnedFd>> for Request {
+        type Error = DispatchError;
+        fn try_from(msg: Message<ObjectId, OwnedFd>) -> Result<Request, DispatchError> {
+            parse_request_raw(msg.opcode, msg.args).map_err(|_| DispatchError::BadMessage {
+                sender_id: msg.sender_id,
+                interface: "wl_callback",
+                opcode: msg.opcode,
+            })
+        }
+    }
     #[doc = "callback object\n\nClients can handle the 'done' event to get notified when\nthe related request is done.\n\nThis interface has no requests."]
     #[derive(Debug, Clone)]
     pub struct WlCallback {
@@ -72,6 +104,24 @@ pub mod wl_callback {
             &self.id
         }
     }
+    impl AsRef<ObjectId> for WlCallback {
+        #[inline]
+        fn as_ref(&self) -> &ObjectId {
+            &self.id
+        }
+    }
+    impl From<WlCallback> for ObjectId {
+        #[inline]
+        fn from(value: WlCallback) -> ObjectId {
+            value.id
+        }
+    }
+    impl From<&WlCallback> for ObjectId {
+        #[inline]
+        fn from(value: &WlCallback) -> ObjectId {
+            value.id.clone()
+        }
+    }
     impl std::hash::Hash for WlCallback {
         #[inline]
         fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
@@ -116,7 +166,7 @@ pub mod wl_callback {
             let data = conn.get_object_data(id.clone()).ok();
             Ok(WlCallback { id, data, version, handle: conn.backend_handle().downgrade() })
         }
-        fn send_event(&self, evt: Self::Event<'_>) -> Result<(), InvalidId> {
+        fn send_event(&self, evt: Self::Event<'_>) -> Result<(), SendEventError> {
             let handle = DisplayHandle::from(self.handle.upgrade().ok_or(InvalidId)?);
             handle.send_event(self, evt)
         }
@@ -170,8 +220,8 @@ pub mod wl_callback {
 pub mod test_global {
     use super::wayland_server::{
         backend::{
-            protocol::{same_interface, Argument, Interface, Message, WEnum},
-            smallvec, InvalidId, ObjectData, ObjectId, WeakHandle,
+            protocol::{same_interface, Argument, Fixed, Interface, Message, WEnum},
+            smallvec, InvalidId, ObjectData, ObjectId, SendEventError, WeakHandle,
         },
         Dispatch, DispatchError, DisplayHandle, New, Resource, ResourceData, Weak,
     };
@@ -311,6 +361,109 @@ pub mod test_global {
             }
         }
     }
+    #[doc = r" Decode a request of this interface from its raw arguments, without a display handle"]
+    #[doc = r""]
+    #[doc = r" This is useful to decode recorded or replayed messages."]
+    #[doc = r""]
+    #[doc = r" **Note:** Requests carrying objects, like `wl_surface.attach`, need a display handle to"]
+    #[doc = r" create their resources, so `DispatchError::BadMessage` is always returned for them. Decode"]
+    #[doc = r" these requests with `Resource::parse_request()` instead."]
+    pub fn parse_request_raw(
+        opcode: u16,
+        args: impl IntoIterator<Item = Argument<ObjectId, OwnedFd>>,
+    ) -> Result<Request, DispatchError> {
+        const INTERFACE_NAME: &str = "test_global";
+        let mut arg_iter = args.into_iter();
+        match opcode {
+            0u16 => {
+                if let (
+                    Some(Argument::Uint(unsigned_int)),
+                    Some(Argument::Int(signed_int)),
+                    Some(Argument::Fixed(fixed_point)),
+                    Some(Argument::Array(number_array)),
+                    Some(Argument::Str(Some(some_text))),
+                    Some(Argument::Fd(file_descriptor)),
+                ) = (
+                    arg_iter.next(),
+                    arg_iter.next(),
+                    arg_iter.next(),
+                    arg_iter.next(),
+                    arg_iter.next(),
+                    arg_iter.next(),
+                ) {
+                    Ok(Request::ManyArgs {
+                        unsigned_int,
+                        signed_int,
+                        fixed_point: Fixed::from_raw(fixed_point).to_f64(),
+                        number_array: *number_array,
+                        some_text: String::from_utf8_lossy(some_text.as_bytes()).into_owned(),
+                        file_descriptor,
+                    })
+                } else {
+                    Err(DispatchError::BadMessage {
+                        sender_id: ObjectId::null(),
+                        interface: INTERFACE_NAME,
+                        opcode,
+                    })
+                }
+            }
+            1u16 => Err(DispatchError::BadMessage {
+                sender_id: ObjectId::null(),
+                interface: INTERFACE_NAME,
+                opcode,
+            }),
+            2u16 => Err(DispatchError::BadMessage {
+                sender_id: ObjectId::null(),
+                interface: INTERFACE_NAME,
+                opcode,
+            }),
+            3u16 => Err(DispatchError::BadMessage {
+                sender_id: ObjectId::null(),
+                interface: INTERFACE_NAME,
+                opcode,
+            }),
+            4u16 => {
+                if let () = () {
+                    Ok(Request::Destroy {})
+                } else {
+                    Err(DispatchError::BadMessage {
+                        sender_id: ObjectId::null(),
+                        interface: INTERFACE_NAME,
+                        opcode,
+                    })
+                }
+            }
+            5u16 => Err(DispatchError::BadMessage {
+                sender_id: ObjectId::null(),
+                interface: INTERFACE_NAME,
+                opcode,
+            }),
+            6u16 => Err(DispatchError::BadMessage {
+                sender_id: ObjectId::null(),
+                interface: INTERFACE_NAME,
+                opcode,
+            }),
+            _ => Err(DispatchError::BadMessage {
+                sender_id: ObjectId::null(),
+                interface: INTERFACE_NAME,
+                opcode,
+            }),
+        }
+    }
+    #[doc = r" Decode a request with `parse_request_raw()`"]
+    #[doc = r""]
+    #[doc = r" This always fails with `DispatchError::BadMessage` for requests carrying objects, as they"]
+    #[doc = r" need a display handle to create their resources. Use `Resource::parse_request()` for them."]
+    impl std::convert::TryFrom<Message<ObjectId, OwnedFd>> for Request {
+        type Error = DispatchError;
+        fn try_from(msg: Message<ObjectId, OwnedFd>) -> Result<Request, DispatchError> {
+            parse_request_raw(msg.opcode, msg.args).map_err(|_| DispatchError::BadMessage {
+                sender_id: msg.sender_id,
+                interface: "test_global",
+                opcode: msg.opcode,
+            })
+        }
+    }
     #[doc = "test_global\n\nSee also the [Request] enum for this interface."]
     #[derive(Debug, Clone)]
     pub struct TestGlobal {
@@ -338,6 +491,24 @@ pub mod test_global {
             &self.id
         }
     }
+    impl AsRef<ObjectId> for TestGlobal {
+        #[inline]
+        fn as_ref(&self) -> &ObjectId {
+            &self.id
+        }
+    }
+    impl From<TestGlobal> for ObjectId {
+        #[inline]
+        fn from(value: TestGlobal) -> ObjectId {
+            value.id
+        }
+    }
+    impl From<&TestGlobal> for ObjectId {
+        #[inline]
+        fn from(value: &TestGlobal) -> ObjectId {
+            value.id.clone()
+        }
+    }
     impl std::hash::Hash for TestGlobal {
         #[inline]
         fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
@@ -382,7 +553,7 @@ pub mod test_global {
             let data = conn.get_object_data(id.clone()).ok();
             Ok(TestGlobal { id, data, version, handle: conn.backend_handle().downgrade() })
         }
-        fn send_event(&self, evt: Self::Event<'_>) -> Result<(), InvalidId> {
+        fn send_event(&self, evt: Self::Event<'_>) -> Result<(), SendEventError> {
             let handle = DisplayHandle::from(self.handle.upgrade().ok_or(InvalidId)?);
             handle.send_event(self, evt)
         }
@@ -399,7 +570,7 @@ pub mod test_global {
                         Some(Argument::Int(signed_int)),
                         Some(Argument::Fixed(fixed_point)),
                         Some(Argument::Array(number_array)),
-                        Some(Argument::Str(some_text)),
+                        Some(Argument::Str(Some(some_text))),
                         Some(Argument::Fd(file_descriptor)),
                     ) = (
                         arg_iter.next(),
@@ -414,12 +585,9 @@ pub mod test_global {
                             Request::ManyArgs {
                                 unsigned_int,
                                 signed_int,
-                                fixed_point: (fixed_point as f64) / 256.,
+                                fixed_point: Fixed::from_raw(fixed_point).to_f64(),
                                 number_array: *number_array,
-                                some_text: String::from_utf8_lossy(
-                                    some_text.as_ref().unwrap().as_bytes(),
-                                )
-                                .into_owned(),
+                                some_text: String::from_utf8_lossy(some_text.as_bytes()).into_owned(),
                                 file_descriptor,
                             },
                         ))
@@ -697,7 +865,7 @@ pub mod test_global {
                     args: smallvec::SmallVec::from_vec(vec![
                         Argument::Uint(unsigned_int),
                         Argument::Int(signed_int),
-                        Argument::Fixed((fixed_point * 256.) as i32),
+                        Argument::Fixed(Fixed::from_f64(fixed_point).into_raw()),
                         Argument::Array(Box::new(number_array)),
                         Argument::Str(Some(Box::new(std::ffi::CString::new(some_text).unwrap()))),
                         Argument::Fd(file_descriptor),
@@ -779,8 +947,8 @@ pub mod test_global {
 pub mod secondary {
     use super::wayland_server::{
         backend::{
-            protocol::{same_interface, Argument, Interface, Message, WEnum},
-            smallvec, InvalidId, ObjectData, ObjectId, WeakHandle,
+            protocol::{same_interface, Argument, Fixed, Interface, Message, WEnum},
+            smallvec, InvalidId, ObjectData, ObjectId, SendEventError, WeakHandle,
         },
         Dispatch, DispatchError, DisplayHandle, New, Resource, ResourceData, Weak,
     };
@@ -821,6 +989,52 @@ pub mod secondary {
             }
         }
     }
+    #[doc = r" Decode a request of this interface from its raw arguments, without a display handle"]
+    #[doc = r""]
+    #[doc = r" This is useful to decode recorded or replayed messages."]
+    #[doc = r""]
+    #[doc = r" **Note:** Requests carrying objects, like `wl_surface.attach`, need a display handle to"]
+    #[doc = r" create their resources, so `DispatchError::BadMessage` is always returned for them. Decode"]
+    #[doc = r" these requests with `Resource::parse_request()` instead."]
+    pub fn parse_request_raw(
+        opcode: u16,
+        args: impl IntoIterator<Item = Argument<ObjectId, OwnedFd>>,
+    ) -> Result<Request, DispatchError> {
+        const INTERFACE_NAME: &str = "secondary";
+        let mut arg_iter = args.into_iter();
+        match opcode {
+            0u16 => {
+                if let () = () {
+                    Ok(Request::Destroy {})
+                } else {
+                    Err(DispatchError::BadMessage {
+                        sender_id: ObjectId::null(),
+                        interface: INTERFACE_NAME,
+                        opcode,
+                    })
+                }
+            }
+            _ => Err(DispatchError::BadMessage {
+                sender_id: ObjectId::null(),
+                interface: INTERFACE_NAME,
+                opcode,
+            }),
+        }
+    }
+    #[doc = r" Decode a request with `parse_request_raw()`"]
+    #[doc = r""]
+    #[doc = r" This always fails with `DispatchError::BadMessage` for requests carrying objects, as they"]
+    #[doc = r" need a display handle to create their resources. Use `Resource::parse_request()` for them."]
+    impl std::convert::TryFrom<Message<ObjectId, OwnedFd>> for Request {
+        type Error = DispatchError;
+        fn try_from(msg: Message<ObjectId, OwnedFd>) -> Result<Request, DispatchError> {
+            parse_request_raw(msg.opcode, msg.args).map_err(|_| DispatchError::BadMessage {
+                sender_id: msg.sender_id,
+                interface: "secondary",
+                opcode: msg.opcode,
+            })
+        }
+    }
     #[doc = "secondary\n\nSee also the [Request] enum for this interface."]
     #[derive(Debug, Clone)]
     pub struct Secondary {
@@ -848,6 +1062,24 @@ pub mod secondary {
             &self.id
         }
     }
+    impl AsRef<ObjectId> for Secondary {
+        #[inline]
+        fn as_ref(&self) -> &ObjectId {
+            &self.id
+        }
+    }
+    impl From<Secondary> for ObjectId {
+        #[inline]
+        fn from(value: Secondary) -> ObjectId {
+            value.id
+        }
+    }
+    impl From<&Secondary> for ObjectId {
+        #[inline]
+        fn from(value: &Secondary) -> ObjectId {
+            value.id.clone()
+        }
+    }
     impl std::hash::Hash for Secondary {
         #[inline]
         fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
@@ -892,7 +1124,7 @@ pub mod secondary {
             let data = conn.get_object_data(id.clone()).ok();
             Ok(Secondary { id, data, version, handle: conn.backend_handle().downgrade() })
         }
-        fn send_event(&self, evt: Self::Event<'_>) -> Result<(), InvalidId> {
+        fn send_event(&self, evt: Self::Event<'_>) -> Result<(), SendEventError> {
             let handle = DisplayHandle::from(self.handle.upgrade().ok_or(InvalidId)?);
             handle.send_event(self, evt)
         }
@@ -942,8 +1174,8 @@ pub mod secondary {
 pub mod tertiary {
     use super::wayland_server::{
         backend::{
-            protocol::{same_interface, Argument, Interface, Message, WEnum},
-            smallvec, InvalidId, ObjectData, ObjectId, WeakHandle,
+            protocol::{same_interface, Argument, Fixed, Interface, Message, WEnum},
+            smallvec, InvalidId, ObjectData, ObjectId, SendEventError, WeakHandle,
         },
         Dispatch, DispatchError, DisplayHandle, New, Resource, ResourceData, Weak,
     };
@@ -984,6 +1216,52 @@ pub mod tertiary {
             }
         }
     }
+    #[doc = r" Decode a request of this interface from its raw arguments, without a display handle"]
+    #[doc = r""]
+    #[doc = r" This is useful to decode recorded or replayed messages."]
+    #[doc = r""]
+    #[doc = r" **Note:** Requests carrying objects, like `wl_surface.attach`, need a display handle to"]
+    #[doc = r" create their resources, so `DispatchError::BadMessage` is always returned for them. Decode"]
+    #[doc = r" these requests with `Resource::parse_request()` instead."]
+    pub fn parse_request_raw(
+        opcode: u16,
+        args: impl IntoIterator<Item = Argument<ObjectId, OwnedFd>>,
+    ) -> Result<Request, DispatchError> {
+        const INTERFACE_NAME: &str = "tertiary";
+        let mut arg_iter = args.into_iter();
+        match opcode {
+            0u16 => {
+                if let () = () {
+                    Ok(Request::Destroy {})
+                } else {
+                    Err(DispatchError::BadMessage {
+                        sender_id: ObjectId::null(),
+                        interface: INTERFACE_NAME,
+                        opcode,
+                    })
+                }
+            }
+            _ => Err(DispatchError::BadMessage {
+                sender_id: ObjectId::null(),
+                interface: INTERFACE_NAME,
+                opcode,
+            }),
+        }
+    }
+    #[doc = r" Decode a request with `parse_request_raw()`"]
+    #[doc = r""]
+    #[doc = r" This always fails with `DispatchError::BadMessage` for requests carrying objects, as they"]
+    #[doc = r" need a display handle to create their resources. Use `Resource::parse_request()` for them."]
+    impl std::convert::TryFrom<Message<ObjectId, OwnedFd>> for Request {
+        type Error = DispatchError;
+        fn try_from(msg: Message<ObjectId, OwnedFd>) -> Result<Request, DispatchError> {
+            parse_request_raw(msg.opcode, msg.args).map_err(|_| DispatchError::BadMessage {
+                sender_id: msg.sender_id,
+                interface: "tertiary",
+                opcode: msg.opcode,
+            })
+        }
+    }
     #[doc = "tertiary\n\nSee also the [Request] enum for this interface."]
     #[derive(Debug, Clone)]
     pub struct Tertiary {
@@ -1011,6 +1289,24 @@ pub mod tertiary {
             &self.id
         }
     }
+    impl AsRef<ObjectId> for Tertiary {
+        #[inline]
+        fn as_ref(&self) -> &ObjectId {
+            &self.id
+        }
+    }
+    impl From<Tertiary> for ObjectId {
+        #[inline]
+        fn from(value: Tertiary) -> ObjectId {
+            value.id
+        }
+    }
+    impl From<&Tertiary> for ObjectId {
+        #[inline]
+        fn from(value: &Tertiary) -> ObjectId {
+            value.id.clone()
+        }
+    }
     impl std::hash::Hash for Tertiary {
         #[inline]
         fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
@@ -1055,7 +1351,7 @@ pub mod tertiary {
             let data = conn.get_object_data(id.clone()).ok();
             Ok(Tertiary { id, data, version, handle: conn.backend_handle().downgrade() })
         }
-        fn send_event(&self, evt: Self::Event<'_>) -> Result<(), InvalidId> {
+        fn send_event(&self, evt: Self::Event<'_>) -> Result<(), SendEventError> {
             let handle = DisplayHandle::from(self.handle.upgrade().ok_or(InvalidId)?);
             handle.send_event(self, evt)
         }
@@ -1105,8 +1401,8 @@ pub mod tertiary {
 pub mod quad {
     use super::wayland_server::{
         backend::{
-            protocol::{same_interface, Argument, Interface, Message, WEnum},
-            smallvec, InvalidId, ObjectData, ObjectId, WeakHandle,
+            protocol::{same_interface, Argument, Fixed, Interface, Message, WEnum},
+            smallvec, InvalidId, ObjectData, ObjectId, SendEventError, WeakHandle,
         },
         Dispatch, DispatchError, DisplayHandle, New, Resource, ResourceData, Weak,
     };
@@ -1147,6 +1443,52 @@ pub mod quad {
             }
         }
     }
+    #[doc = r" Decode a request of this interface from its raw arguments, without a display handle"]
+    #[doc = r""]
+    #[doc = r" This is useful to decode recorded or replayed messages."]
+    #[doc = r""]
+    #[doc = r" **Note:** Requests carrying objects, like `wl_surface.attach`, need a display handle to"]
+    #[doc = r" create their resources, so `DispatchError::BadMessage` is always returned for them. Decode"]
+    #[doc = r" these requests with `Resource::parse_request()` instead."]
+    pub fn parse_request_raw(
+        opcode: u16,
+        args: impl IntoIterator<Item = Argument<ObjectId, OwnedFd>>,
+    ) -> Result<Request, DispatchError> {
+        const INTERFACE_NAME: &str = "quad";
+        let mut arg_iter = args.into_iter();
+        match opcode {
+            0u16 => {
+                if let () = () {
+                    Ok(Request::Destroy {})
+                } else {
+                    Err(DispatchError::BadMessage {
+                        sender_id: ObjectId::null(),
+                        interface: INTERFACE_NAME,
+                        opcode,
+                    })
+                }
+            }
+            _ => Err(DispatchError::BadMessage {
+                sender_id: ObjectId::null(),
+                interface: INTERFACE_NAME,
+                opcode,
+            }),
+        }
+    }
+    #[doc = r" Decode a request with `parse_request_raw()`"]
+    #[doc = r""]
+    #[doc = r" This always fails with `DispatchError::BadMessage` for requests carrying objects, as they"]
+    #[doc = r" need a display handle to create their resources. Use `Resource::parse_request()` for them."]
+    impl std::convert::TryFrom<Message<ObjectId, OwnedFd>> for Request {
+        type Error = DispatchError;
+        fn try_from(msg: Message<ObjectId, OwnedFd>) -> Result<Request, DispatchError> {
+            parse_request_raw(msg.opcode, msg.args).map_err(|_| DispatchError::BadMessage {
+                sender_id: msg.sender_id,
+                interface: "quad",
+                opcode: msg.opcode,
+            })
+        }
+    }
     #[doc = "quad\n\nSee also the [Request] enum for this interface."]
     #[derive(Debug, Clone)]
     pub struct Quad {
@@ -1174,6 +1516,24 @@ pub mod quad {
             &self.id
         }
     }
+    impl AsRef<ObjectId> for Quad {
+        #[inline]
+        fn as_ref(&self) -> &ObjectId {
+            &self.id
+        }
+    }
+    impl From<Quad> for ObjectId {
+        #[inline]
+        fn from(value: Quad) -> ObjectId {
+            value.id
+        }
+    }
+    impl From<&Quad> for ObjectId {
+        #[inline]
+        fn from(value: &Quad) -> ObjectId {
+            value.id.clone()
+        }
+    }
     impl std::hash::Hash for Quad {
         #[inline]
         fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
@@ -1218,7 +1578,7 @@ pub mod quad {
             let data = conn.get_object_data(id.clone()).ok();
             Ok(Quad { id, data, version, handle: conn.backend_handle().downgrade() })
         }
-        fn send_event(&self, evt: Self::Event<'_>) -> Result<(), InvalidId> {
+        fn send_event(&self, evt: Self::Event<'_>) -> Result<(), SendEventError> {
             let handle = DisplayHandle::from(self.handle.upgrade().ok_or(InvalidId)?);
             handle.send_event(self, evt)
         }
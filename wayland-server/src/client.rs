@@ -1,4 +1,4 @@
-use std::sync::Arc;
+use std::{ffi::CString, sync::Arc};
 
 use wayland_backend::{
     protocol::ProtocolError,
@@ -32,6 +32,17 @@ impl Client {
         (*self.data).downcast_ref()
     }
 
+    /// Access the data associated to this client
+    ///
+    /// This is the same as [`get_data()`][Self::get_data()], named for symmetry with
+    /// [`Resource::data()`].
+    ///
+    /// Returns [`None`] if the provided `U` type parameter is not the correct one.
+    #[inline]
+    pub fn data<U: ClientData + 'static>(&self) -> Option<&U> {
+        self.get_data()
+    }
+
     /// Access the pid/uid/gid of this client
     ///
     /// **Note:** You should be careful if you plan tu use this for security purposes, as it is possible for
@@ -91,6 +102,10 @@ impl Client {
 
     /// Attempt to retrieve an object from this client's protocol state from its protocol id
     ///
+    /// This is useful to resolve an object ID received through other means than the Wayland protocol, for
+    /// example from a helper process the client handed an object to. The protocol id is the one returned
+    /// by [`ObjectId::protocol_id()`][crate::backend::ObjectId::protocol_id()].
+    ///
     /// Will fail if either the provided protocol id does not correspond to any object, or if the
     /// corresponding object is not of the interface `I`.
     pub fn object_from_protocol_id<I: Resource + 'static>(
@@ -107,6 +122,60 @@ impl Client {
     pub fn kill(&self, handle: &DisplayHandle, error: ProtocolError) {
         handle.handle.kill_client(self.id.clone(), DisconnectReason::ProtocolError(error))
     }
+
+    /// Send a protocol error on this client's `wl_display` object and disconnect it
+    ///
+    /// Unlike [`kill()`][Self::kill()], the client receives the `wl_display.error` event with the
+    /// provided code and message, so it can report a meaningful reason. Does nothing if the client is
+    /// already disconnected.
+    ///
+    /// Nul bytes cannot be sent in a protocol string, so any nul byte in `message` is removed.
+    ///
+    /// **Note:** `libwayland-client` does not report errors on the `wl_display` object as protocol errors,
+    /// clients using it will only see their connection fail with an error code derived from `code`.
+    pub fn post_display_error(&self, handle: &DisplayHandle, code: u32, message: String) {
+        if let Ok(display) = handle.handle.object_for_protocol_id(
+            self.id.clone(),
+            &crate::protocol::__interfaces::WL_DISPLAY_INTERFACE,
+            1,
+        ) {
+            handle.handle.post_error(display, code, error_message(message))
+        }
+    }
+
+    /// Send a protocol error referencing one of this client's objects and disconnect it
+    ///
+    /// The client receives a `wl_display.error` event designating `object` along with the provided code
+    /// and message, which is what client-side error handlers expect to report. The `code` is intended to
+    /// be from the `Error` enum declared alongside the interface of `object`.
+    ///
+    /// If `object` is dead or does not belong to this client, the error is posted on this client's
+    /// `wl_display` object instead, see [`post_display_error()`][Self::post_display_error()]. As with that
+    /// method, nul bytes in `message` are removed.
+    pub fn protocol_kill(
+        &self,
+        handle: &DisplayHandle,
+        object: &impl Resource,
+        code: u32,
+        message: String,
+    ) {
+        match handle.handle.get_client(object.id()) {
+            Ok(owner) if owner == self.id => {
+                handle.handle.post_error(object.id(), code, error_message(message))
+            }
+            _ => self.post_display_error(handle, code, message),
+        }
+    }
+}
+
+/// Convert an error message to a `CString`, removing the nul bytes it may contain
+fn error_message(message: String) -> CString {
+    CString::new(message).unwrap_or_else(|err| {
+        let mut bytes = err.into_vec();
+        bytes.retain(|&b| b != 0);
+        // all nul bytes were just removed
+        CString::new(bytes).unwrap()
+    })
 }
 
 impl PartialEq for Client {
@@ -145,6 +145,17 @@ impl<I> New<I> {
     }
 }
 
+impl<I: Resource> New<I> {
+    /// The version of the new object
+    ///
+    /// For an object created by binding a global, this is the version requested by the client, which
+    /// can be lower than the version of the global. It is the same as the version of the object returned
+    /// by [`DataInit::init()`].
+    pub fn version(&self) -> u32 {
+        self.id.version()
+    }
+}
+
 /// Helper to initialize client-created objects
 ///
 /// This helper is provided to you in your [`Dispatch`] and [`GlobalDispatch`][super::GlobalDispatch] to
@@ -175,6 +186,26 @@ impl<D> DataInit<'_, D> {
         obj
     }
 
+    /// Initialize an object with user-data computed from the object itself
+    ///
+    /// This is similar to [`init()`][Self::init()], but the user-data is returned by `f`, which is
+    /// given the new object. This allows the user-data to refer to its own object without wrapping
+    /// it in a lazily initialized cell.
+    ///
+    /// The object given to `f` does not carry its user-data yet, its [`Resource::data()`] returns
+    /// `None`. The user-data should thus keep a [`Weak`][crate::Weak] handle obtained with
+    /// [`Resource::downgrade()`], which upgrades to the initialized object.
+    pub fn init_with<I, U, F>(&mut self, resource: New<I>, f: F) -> I
+    where
+        I: Resource + 'static,
+        U: Send + Sync + 'static,
+        F: FnOnce(&I) -> U,
+        D: Dispatch<I, U> + 'static,
+    {
+        let data = f(&resource.id);
+        self.init(resource, data)
+    }
+
     /// Set a custom [`ObjectData`] for this object
     ///
     /// This object data is not managed by `wayland-server`, as a result you will not
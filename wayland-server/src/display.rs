@@ -2,11 +2,15 @@ use std::{
     os::unix::io::{AsFd, AsRawFd, BorrowedFd},
     os::unix::net::UnixStream,
     sync::Arc,
+    time::Duration,
 };
 
 use wayland_backend::{
     protocol::ObjectInfo,
-    server::{Backend, ClientData, GlobalId, Handle, InitError, InvalidId, ObjectId},
+    server::{
+        Backend, ClientControlError, ClientData, GlobalError, GlobalHandler, GlobalId, Handle,
+        InitError, InvalidId, ObjectId, SendEventError,
+    },
 };
 
 use crate::{
@@ -59,6 +63,26 @@ impl<State: 'static> Display<State> {
         self.backend.dispatch_all_clients(state)
     }
 
+    /// Dispatch the requests received from a single client to their respective callbacks.
+    ///
+    /// This is intended for event loops monitoring the socket of each client separately, to only dispatch
+    /// the clients that have pending requests. Returns the number of dispatched requests, which is `0` if
+    /// the client had no pending request.
+    ///
+    /// **Note:** This is only supported by the rust backend. With the system backend, this does the same
+    /// as [`dispatch_clients()`][Self::dispatch_clients()], dispatching the requests of all clients.
+    pub fn dispatch_client(
+        &mut self,
+        state: &mut State,
+        client: &Client,
+    ) -> std::io::Result<usize> {
+        match self.backend.dispatch_single_client(state, client.id()) {
+            // the rust backend reports a client without pending requests as an error
+            Err(e) if e.kind() == std::io::ErrorKind::WouldBlock => Ok(0),
+            ret => ret,
+        }
+    }
+
     /// Flush outgoing buffers into their respective sockets.
     pub fn flush_clients(&mut self) -> std::io::Result<()> {
         self.backend.flush(None)
@@ -70,6 +94,27 @@ impl<State: 'static> Display<State> {
     }
 }
 
+#[cfg(feature = "test-util")]
+impl<State: 'static> Display<State> {
+    /// Create a new display along with a client connection already inserted into it
+    ///
+    /// The client [`Connection`][wayland_client::Connection] is wired to the display over an internal
+    /// socket pair, so no socket is created on the filesystem and `XDG_RUNTIME_DIR` or `WAYLAND_DISPLAY`
+    /// are never read. This is intended for testing your [`Dispatch`][crate::Dispatch] and
+    /// [`GlobalDispatch`] implementations in-process. The returned [`Client`] is the server-side handle
+    /// of that connection, and has `()` as its [`ClientData`].
+    pub fn new_test_pair() -> Result<(Display<State>, Client, wayland_client::Connection), InitError>
+    {
+        let display = Display::new()?;
+        let (server_socket, client_socket) = UnixStream::pair().map_err(InitError::Io)?;
+        let client =
+            display.handle().insert_client(server_socket, Arc::new(())).map_err(InitError::Io)?;
+        let conn = wayland_client::Connection::from_socket(client_socket)
+            .map_err(|_| InitError::NoWaylandLib)?;
+        Ok((display, client, conn))
+    }
+}
+
 impl<State> AsFd for Display<State> {
     /// Provides fd from [`Backend::poll_fd`] for polling.
     fn as_fd(&self) -> BorrowedFd<'_> {
@@ -117,6 +162,19 @@ impl DisplayHandle {
         Client::from_id(self, client_id)
     }
 
+    /// Iterate over all the clients currently connected to this display
+    ///
+    /// The list of clients is taken when this method is called: clients connecting afterwards are
+    /// not included, and clients disconnecting afterwards are still returned.
+    pub fn clients(&self) -> impl Iterator<Item = Client> {
+        let mut ids = Vec::new();
+        self.handle.with_all_clients(|id| ids.push(id));
+        ids.into_iter()
+            .filter_map(|id| Client::from_id(self, id).ok())
+            .collect::<Vec<_>>()
+            .into_iter()
+    }
+
     /// Create a new protocol global
     ///
     /// This global will be advertized to clients through the `wl_registry` according to the rules
@@ -138,6 +196,49 @@ impl DisplayHandle {
         )
     }
 
+    /// Create a new protocol global, returning an error if it cannot be created
+    ///
+    /// This is the same as [`create_global()`][Self::create_global()], except that it returns an error
+    /// rather than panicking if the version is not supported by the interface, or if the system library
+    /// fails to create the global.
+    pub fn try_create_global<State, I: Resource + 'static, U: Send + Sync + 'static>(
+        &self,
+        version: u32,
+        data: U,
+    ) -> Result<GlobalId, GlobalError>
+    where
+        State: GlobalDispatch<I, U> + 'static,
+    {
+        self.handle.try_create_global::<State>(
+            I::interface(),
+            version,
+            Arc::new(GlobalData { data, _types: std::marker::PhantomData }),
+        )
+    }
+
+    /// Create several protocol globals of the same interface at once
+    ///
+    /// Each item of the iterator is the version and user data of a global, which behaves as if created
+    /// with [`create_global()`][Self::create_global()]. The globals are created under a single lock of the
+    /// backend, which is useful when creating many of them, for example outputs when a dock is plugged. Their
+    /// ids are returned in the same order as the iterator.
+    pub fn create_globals<State, I: Resource + 'static, U: Send + Sync + 'static>(
+        &self,
+        globals: impl IntoIterator<Item = (u32, U)>,
+    ) -> Vec<GlobalId>
+    where
+        State: GlobalDispatch<I, U> + 'static,
+    {
+        self.handle.create_globals::<State>(globals.into_iter().map(|(version, data)| {
+            (
+                I::interface(),
+                version,
+                Arc::new(GlobalData { data, _types: std::marker::PhantomData })
+                    as Arc<dyn GlobalHandler<State>>,
+            )
+        }))
+    }
+
     /// Disable this global
     ///
     /// Clients will be notified of the global removal, and it will not be advertized to new clients. However
@@ -161,6 +262,15 @@ impl DisplayHandle {
         self.handle.remove_global::<State>(id)
     }
 
+    /// Disable this global and remove it after some delay
+    ///
+    /// This performs the recommended "disable, wait, then remove" sequence for you: clients are notified of
+    /// the global removal immediately, and its state is freed once `delay` has elapsed. The removal happens
+    /// during [`Display::dispatch_clients()`], see [`Handle::retire_global()`] for details.
+    pub fn retire_global<State: 'static>(&self, id: GlobalId, delay: Duration) {
+        self.handle.retire_global::<State>(id, delay)
+    }
+
     /// Access the protocol information for a Wayland object
     ///
     /// Returns an error if the object is no longer valid.
@@ -176,7 +286,7 @@ impl DisplayHandle {
         &self,
         resource: &I,
         event: I::Event<'_>,
-    ) -> Result<(), InvalidId> {
+    ) -> Result<(), SendEventError> {
         let msg = resource.write_event(self, event)?;
         let msg = msg.map_fd(|fd| fd.as_raw_fd());
         self.handle.send_event(msg)
@@ -201,6 +311,24 @@ impl DisplayHandle {
         self.handle.get_object_data_any(id)
     }
 
+    /// Pause or resume the dispatching of the requests of a client
+    ///
+    /// While a client is paused, [`Display::dispatch_clients()`] does not read its requests, which stay
+    /// buffered in its socket without the client being disconnected. Once resumed, its pending requests are
+    /// dispatched normally. This can be used to implement grabs or modal states where only a single client
+    /// should be serviced. See [`Handle::set_client_paused()`] for details.
+    ///
+    /// Returns [`ClientControlError::InvalidId`] if the client is no longer alive, and
+    /// [`ClientControlError::Unsupported`] with the system backend (`server_system` feature of
+    /// `wayland-backend`), which cannot pause clients.
+    pub fn set_client_paused(
+        &self,
+        client: &Client,
+        paused: bool,
+    ) -> Result<(), ClientControlError> {
+        self.handle.set_client_paused(client.id(), paused)
+    }
+
     /// Flush outgoing buffers into their respective sockets.
     pub fn flush_clients(&mut self) -> std::io::Result<()> {
         self.handle.flush(None)
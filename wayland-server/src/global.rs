@@ -97,6 +97,15 @@ pub trait GlobalDispatch<I: Resource, GlobalData, State = Self>: Sized {
     ///
     /// The return value of this function should contain user data to associate the object created by the
     /// client.
+    ///
+    /// The new object is initialized through `data_init`, either with typed user data using
+    /// [`DataInit::init()`], or with a custom backend [`ObjectData`] using [`DataInit::custom_init()`] if
+    /// you need to handle its requests at a lower level.
+    ///
+    /// The version of the object is the one requested by the client, and is given by
+    /// [`resource.version()`][New::version()]. It can be lower than the version the global was created
+    /// with, so the events sent to this client (including the initial state sent from this callback)
+    /// must be chosen according to it, not to the version of the global.
     fn bind(
         state: &mut State,
         handle: &DisplayHandle,
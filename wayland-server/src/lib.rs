@@ -81,28 +81,31 @@ use std::{
     os::unix::io::OwnedFd,
 };
 use wayland_backend::{
-    protocol::{Interface, Message},
-    server::{InvalidId, ObjectId, WeakHandle},
+    protocol::{Interface, Message, ObjectInfo},
+    server::{InvalidId, ObjectId, SendEventError, WeakHandle},
 };
 
 mod client;
 mod dispatch;
 mod display;
 mod global;
+mod resource_map;
 mod socket;
 
 pub use client::Client;
 pub use dispatch::{DataInit, Dispatch, New, ResourceData};
 pub use display::{Display, DisplayHandle};
 pub use global::GlobalDispatch;
+pub use resource_map::ResourceMap;
 pub use socket::{BindError, ListeningSocket};
 
 /// Backend reexports
 pub mod backend {
     pub use wayland_backend::protocol;
     pub use wayland_backend::server::{
-        Backend, ClientData, ClientId, Credentials, DisconnectReason, GlobalHandler, GlobalId,
-        Handle, InitError, InvalidId, ObjectData, ObjectId, WeakHandle,
+        Backend, ClientControlError, ClientData, ClientId, Credentials, DisconnectReason,
+        FlushOutcome, GlobalError, GlobalHandler, GlobalId, Handle, InitError, InvalidId,
+        ObjectData, ObjectId, SendEventError, WeakHandle,
     };
     pub use wayland_backend::smallvec;
 }
@@ -143,6 +146,15 @@ pub trait Resource: Clone + std::fmt::Debug + Sized {
     /// The interface description
     fn interface() -> &'static Interface;
 
+    /// Check if an object ID designates an object of this interface
+    ///
+    /// This is less error-prone than comparing the names of the interfaces, see
+    /// [`ObjectId::has_interface()`].
+    #[inline]
+    fn is_interface_of(id: &ObjectId) -> bool {
+        id.has_interface(Self::interface())
+    }
+
     /// The ID of this object
     fn id(&self) -> ObjectId;
 
@@ -156,6 +168,16 @@ pub trait Resource: Clone + std::fmt::Debug + Sized {
         Client::from_id(&dh, client_id).ok()
     }
 
+    /// Checks if this object and an other one belong to the same client
+    ///
+    /// The two objects may be of different interfaces.
+    ///
+    /// *Note:* This may spuriously return `false` if one (or both) of the objects is no longer alive.
+    #[inline]
+    fn same_client_as<R: Resource>(&self, other: &R) -> bool {
+        self.id().same_client_as(&other.id())
+    }
+
     /// The version of this object
     fn version(&self) -> u32;
 
@@ -169,6 +191,23 @@ pub trait Resource: Clone + std::fmt::Debug + Sized {
         }
     }
 
+    /// Checks if this object was created by the server rather than by the client
+    ///
+    /// The objects created by events, like the `wl_data_offer` of `wl_data_device.data_offer`, have their
+    /// id allocated by the server in a reserved range, while the ones created by requests have their id
+    /// allocated by the client. This remains available once the object is dead.
+    #[inline]
+    fn is_server_created(&self) -> bool {
+        self.id().protocol_id() >= 0xFF00_0000
+    }
+
+    /// Retrieve the id, interface and version of this object at once
+    ///
+    /// Returns `None` if the object is no longer alive.
+    fn info(&self) -> Option<ObjectInfo> {
+        self.handle().upgrade()?.object_info(self.id()).ok()
+    }
+
     /// Access the user-data associated with this object
     fn data<U: 'static>(&self) -> Option<&U>;
 
@@ -192,7 +231,10 @@ pub trait Resource: Clone + std::fmt::Debug + Sized {
     fn from_id(dh: &DisplayHandle, id: ObjectId) -> Result<Self, InvalidId>;
 
     /// Send an event to this object
-    fn send_event(&self, evt: Self::Event<'_>) -> Result<(), InvalidId>;
+    ///
+    /// Returns an error if this object or an object argument of the event is no longer alive, or if
+    /// the event contains objects belonging to a different client.
+    fn send_event(&self, evt: Self::Event<'_>) -> Result<(), SendEventError>;
 
     /// Trigger a protocol error on this object
     ///
@@ -298,15 +340,15 @@ impl<I: Resource> Weak<I> {
 
     /// Check if this resource is still alive
     ///
+    /// This is cheaper than [`upgrade()`][Self::upgrade()] as it neither upgrades the display handle nor
+    /// reconstructs the resource.
+    ///
     /// This will return `false` if either:
     /// - the object represented by this handle has already been destroyed at the protocol level
     /// - the Wayland connection has already been closed
     #[inline]
     pub fn is_alive(&self) -> bool {
-        let Some(handle) = self.handle.upgrade() else {
-            return false;
-        };
-        handle.object_info(self.id.clone()).is_ok()
+        self.handle.object_is_alive(&self.id)
     }
 
     /// The underlying [`ObjectId`]
@@ -0,0 +1,92 @@
+use std::collections::HashMap;
+
+use wayland_backend::server::ObjectId;
+
+use crate::{Resource, Weak};
+
+/// A map associating values to resources, which forgets the resources once they are destroyed
+///
+/// The resources are stored as [`Weak`] handles keyed by their [`ObjectId`], so the map does not keep
+/// them alive, and a value is never returned for a destroyed resource, even if the client reused its
+/// protocol id for a new object.
+///
+/// The entries of destroyed resources are pruned when inserting, once the map has grown past twice the
+/// number of live entries found by the previous pruning, or explicitly with
+/// [`retain_live()`][Self::retain_live()].
+#[derive(Debug)]
+pub struct ResourceMap<I, V> {
+    entries: HashMap<ObjectId, (Weak<I>, V)>,
+    prune_at: usize,
+}
+
+const MIN_PRUNE_LEN: usize = 16;
+
+impl<I: Resource, V> ResourceMap<I, V> {
+    /// Create an empty map
+    pub fn new() -> Self {
+        Self { entries: HashMap::new(), prune_at: MIN_PRUNE_LEN }
+    }
+
+    /// Associate a value to a resource
+    ///
+    /// Returns the value previously associated to this resource, if any.
+    pub fn insert(&mut self, resource: &I, value: V) -> Option<V> {
+        if self.entries.len() >= self.prune_at {
+            self.retain_live();
+        }
+        self.entries.insert(resource.id(), (resource.downgrade(), value)).map(|(_, value)| value)
+    }
+
+    /// Get the value associated to a resource, if it is still alive
+    pub fn get(&self, resource: &I) -> Option<&V> {
+        self.entries.get(&resource.id()).filter(|(weak, _)| weak.is_alive()).map(|(_, value)| value)
+    }
+
+    /// Get mutable access to the value associated to a resource, if it is still alive
+    pub fn get_mut(&mut self, resource: &I) -> Option<&mut V> {
+        self.entries
+            .get_mut(&resource.id())
+            .filter(|(weak, _)| weak.is_alive())
+            .map(|(_, value)| value)
+    }
+
+    /// Remove the value associated to a resource
+    ///
+    /// This can be used with a resource that is already destroyed, from its
+    /// [`Dispatch::destroyed()`][crate::Dispatch::destroyed()] callback for example.
+    pub fn remove(&mut self, resource: &I) -> Option<V> {
+        self.remove_id(&resource.id())
+    }
+
+    /// Remove the value associated to the resource with the given id
+    pub fn remove_id(&mut self, id: &ObjectId) -> Option<V> {
+        self.entries.remove(id).map(|(_, value)| value)
+    }
+
+    /// Iterate over the resources that are still alive and their values
+    pub fn iter_live(&self) -> impl Iterator<Item = (I, &V)> + '_ {
+        self.entries.values().filter_map(|(weak, value)| Some((weak.upgrade().ok()?, value)))
+    }
+
+    /// Remove the entries of the resources that were destroyed
+    pub fn retain_live(&mut self) {
+        self.entries.retain(|_, (weak, _)| weak.is_alive());
+        self.prune_at = (2 * self.entries.len()).max(MIN_PRUNE_LEN);
+    }
+
+    /// The number of entries of the map, including the ones of destroyed resources not pruned yet
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Whether the map has no entries
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+impl<I: Resource, V> Default for ResourceMap<I, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
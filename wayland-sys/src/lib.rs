@@ -26,6 +26,8 @@
 //! Each module except `common` corresponds to a system library. They all define a function named
 //! `is_lib_available()` which returns whether the library could be loaded. They always return true
 //! if the feature `dlopen` is absent, as we link against the library directly in that case.
+//!
+//! The `safe` module provides checked wrappers for a few commonly used client functions.
 #![allow(non_camel_case_types)]
 #![forbid(improper_ctypes, unsafe_op_in_unsafe_fn)]
 // Doc feature labels can be tested locally by running RUSTDOCFLAGS="--cfg=docsrs" cargo +nightly doc -p <crate>
@@ -51,6 +53,9 @@ pub mod egl;
 #[cfg(all(feature = "cursor", feature = "client"))]
 pub mod cursor;
 
+#[cfg(feature = "client")]
+pub mod safe;
+
 #[cfg(feature = "server")]
 pub use libc::{gid_t, pid_t, uid_t};
 
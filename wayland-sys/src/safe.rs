@@ -0,0 +1,142 @@
+//! Thin safe wrappers around commonly used functions of `libwayland-client.so`
+//!
+//! These are meant for crates that hold raw pointers to the system library objects (for example
+//! obtained from a toolkit) and want to query them without sprinkling `unsafe` and `ffi_dispatch!`
+//! at every call site. Only the construction of the wrappers is `unsafe`, as it requires the
+//! caller to vouch for the validity of the pointer.
+
+use std::io;
+use std::marker::PhantomData;
+use std::os::unix::io::{BorrowedFd, RawFd};
+use std::ptr::NonNull;
+
+#[cfg(feature = "dlopen")]
+use crate::client::wayland_client_handle;
+#[cfg(not(feature = "dlopen"))]
+use crate::client::*;
+use crate::client::{wl_display, wl_proxy};
+use crate::ffi_dispatch;
+
+/// A borrowed reference to a `wl_display` of the system library
+#[derive(Debug, Clone, Copy)]
+pub struct DisplayRef<'a> {
+    ptr: NonNull<wl_display>,
+    _marker: PhantomData<&'a wl_display>,
+}
+
+impl<'a> DisplayRef<'a> {
+    /// Wrap a raw `wl_display` pointer
+    ///
+    /// Returns `None` if the pointer is null.
+    ///
+    /// # Safety
+    ///
+    /// If non-null, the pointer must point to a valid `wl_display` that stays connected for the
+    /// lifetime `'a`.
+    pub unsafe fn from_ptr(ptr: *mut wl_display) -> Option<Self> {
+        NonNull::new(ptr).map(|ptr| DisplayRef { ptr, _marker: PhantomData })
+    }
+
+    /// Access the underlying raw pointer
+    pub fn as_ptr(&self) -> *mut wl_display {
+        self.ptr.as_ptr()
+    }
+
+    /// Get the file descriptor of the connection socket
+    ///
+    /// Wraps `wl_display_get_fd`.
+    pub fn get_fd(&self) -> BorrowedFd<'a> {
+        let fd: RawFd =
+            unsafe { ffi_dispatch!(wayland_client_handle(), wl_display_get_fd, self.as_ptr()) };
+        // SAFETY: the socket is owned by the display and stays open as long as it is connected
+        unsafe { BorrowedFd::borrow_raw(fd) }
+    }
+
+    /// Do a blocking roundtrip with the server on the default queue
+    ///
+    /// Returns the number of dispatched events. Wraps `wl_display_roundtrip`.
+    pub fn roundtrip(&self) -> io::Result<u32> {
+        check_ret(unsafe {
+            ffi_dispatch!(wayland_client_handle(), wl_display_roundtrip, self.as_ptr())
+        })
+    }
+
+    /// Dispatch the events pending on the default queue without reading the socket
+    ///
+    /// Returns the number of dispatched events. Wraps `wl_display_dispatch_pending`.
+    pub fn dispatch_pending(&self) -> io::Result<u32> {
+        check_ret(unsafe {
+            ffi_dispatch!(wayland_client_handle(), wl_display_dispatch_pending, self.as_ptr())
+        })
+    }
+
+    /// Send all buffered requests to the server
+    ///
+    /// Returns the number of bytes sent. Wraps `wl_display_flush`.
+    pub fn flush(&self) -> io::Result<u32> {
+        check_ret(unsafe {
+            ffi_dispatch!(wayland_client_handle(), wl_display_flush, self.as_ptr())
+        })
+    }
+
+    /// Retrieve the last error that occurred on this display, if any
+    ///
+    /// Once an error occurred the connection is dead. Wraps `wl_display_get_error`.
+    pub fn last_error(&self) -> Option<io::Error> {
+        match unsafe { ffi_dispatch!(wayland_client_handle(), wl_display_get_error, self.as_ptr()) }
+        {
+            0 => None,
+            errno => Some(io::Error::from_raw_os_error(errno)),
+        }
+    }
+}
+
+/// A borrowed reference to a `wl_proxy` of the system library
+#[derive(Debug, Clone, Copy)]
+pub struct ProxyRef<'a> {
+    ptr: NonNull<wl_proxy>,
+    _marker: PhantomData<&'a wl_proxy>,
+}
+
+impl<'a> ProxyRef<'a> {
+    /// Wrap a raw `wl_proxy` pointer
+    ///
+    /// Returns `None` if the pointer is null.
+    ///
+    /// # Safety
+    ///
+    /// If non-null, the pointer must point to a valid `wl_proxy` that is not destroyed for the
+    /// lifetime `'a`.
+    pub unsafe fn from_ptr(ptr: *mut wl_proxy) -> Option<Self> {
+        NonNull::new(ptr).map(|ptr| ProxyRef { ptr, _marker: PhantomData })
+    }
+
+    /// Access the underlying raw pointer
+    pub fn as_ptr(&self) -> *mut wl_proxy {
+        self.ptr.as_ptr()
+    }
+
+    /// Protocol id of this proxy
+    ///
+    /// Wraps `wl_proxy_get_id`.
+    pub fn id(&self) -> u32 {
+        unsafe { ffi_dispatch!(wayland_client_handle(), wl_proxy_get_id, self.as_ptr()) }
+    }
+
+    /// Version of this proxy
+    ///
+    /// Wraps `wl_proxy_get_version`. Note that the system library reports `0` for objects created
+    /// by libwayland versions predating versioned proxies.
+    pub fn version(&self) -> u32 {
+        unsafe { ffi_dispatch!(wayland_client_handle(), wl_proxy_get_version, self.as_ptr()) }
+    }
+}
+
+/// Translate the `-1`/errno convention of libwayland into an `io::Result`
+fn check_ret(ret: std::os::raw::c_int) -> io::Result<u32> {
+    if ret < 0 {
+        Err(io::Error::last_os_error())
+    } else {
+        Ok(ret as u32)
+    }
+}
@@ -0,0 +1,242 @@
+#[macro_use]
+mod helpers;
+
+use helpers::{globals, roundtrip, wayc, ways, TestServer};
+
+use wayc::Proxy;
+use ways::protocol::{wl_compositor, wl_output, wl_region, wl_surface};
+use ways::Resource;
+
+mod bridge {
+    use super::helpers::wayc::protocol as client;
+    use super::helpers::ways::protocol as server;
+    use super::helpers::{wayc as wayland_client, ways as wayland_server};
+
+    wayland_scanner::generate_bridge_code!("../wayland-client/wayland.xml");
+}
+
+#[test]
+fn bridge_request_to_client() {
+    let mut server = TestServer::new();
+    server.display.handle().create_global::<ServerHandler, wl_compositor::WlCompositor, _>(1, ());
+    let mut server_ddata = ServerHandler::new();
+
+    let (_, mut client) = server.add_client();
+    let mut client_ddata = ClientHandler { globals: globals::GlobalList::new() };
+
+    let registry = client.display.get_registry(&client.event_queue.handle(), ());
+
+    roundtrip(&mut client, &mut server, &mut client_ddata, &mut server_ddata).unwrap();
+
+    let compositor = client_ddata
+        .globals
+        .bind::<wayc::protocol::wl_compositor::WlCompositor, _, _>(
+            &client.event_queue.handle(),
+            &registry,
+            1..2,
+            (),
+        )
+        .unwrap();
+    let surface = compositor.create_surface(&client.event_queue.handle(), ());
+    let region = compositor.create_region(&client.event_queue.handle(), ());
+    surface.set_input_region(Some(&region));
+    surface.damage(1, 2, 3, 4);
+
+    roundtrip(&mut client, &mut server, &mut client_ddata, &mut server_ddata).unwrap();
+
+    let server_region = server_ddata.regions[0].clone();
+    let map = |id: &ways::backend::ObjectId| (*id == server_region.id()).then(|| region.id());
+
+    // objects are translated to their client-side counterparts
+    let request =
+        bridge::wl_surface::request_to_client(&server_ddata.surface_requests[0], &client.conn, map)
+            .unwrap();
+    assert!(matches!(
+        &request,
+        wayc::protocol::wl_surface::Request::SetInputRegion { region: Some(r) } if *r == region
+    ));
+    let damage =
+        bridge::wl_surface::request_to_client(&server_ddata.surface_requests[1], &client.conn, map)
+            .unwrap();
+    assert!(matches!(
+        damage,
+        wayc::protocol::wl_surface::Request::Damage { x: 1, y: 2, width: 3, height: 4 }
+    ));
+
+    // the converted request can be sent as is
+    surface.send_request(request).unwrap();
+
+    roundtrip(&mut client, &mut server, &mut client_ddata, &mut server_ddata).unwrap();
+
+    assert!(matches!(
+        &server_ddata.surface_requests[2],
+        wl_surface::Request::SetInputRegion { region: Some(r) } if *r == server_region
+    ));
+
+    // requests with objects that cannot be translated are not converted
+    assert!(bridge::wl_surface::request_to_client(
+        &server_ddata.surface_requests[0],
+        &client.conn,
+        |_| None
+    )
+    .is_none());
+}
+
+#[test]
+fn bridge_event_to_server() {
+    let mut server = TestServer::new();
+    server.display.handle().create_global::<ServerHandler, wl_compositor::WlCompositor, _>(1, ());
+    server.display.handle().create_global::<ServerHandler, wl_output::WlOutput, _>(1, ());
+    let mut server_ddata = ServerHandler::new();
+
+    let (_, mut client) = server.add_client();
+    let mut client_ddata = ClientHandler { globals: globals::GlobalList::new() };
+
+    let registry = client.display.get_registry(&client.event_queue.handle(), ());
+
+    roundtrip(&mut client, &mut server, &mut client_ddata, &mut server_ddata).unwrap();
+
+    let output = client_ddata
+        .globals
+        .bind::<wayc::protocol::wl_output::WlOutput, _, _>(
+            &client.event_queue.handle(),
+            &registry,
+            1..2,
+            (),
+        )
+        .unwrap();
+
+    roundtrip(&mut client, &mut server, &mut client_ddata, &mut server_ddata).unwrap();
+
+    let server_output = server_ddata.outputs[0].clone();
+    let map = |id: &wayc::backend::ObjectId| (*id == output.id()).then(|| server_output.id());
+
+    // objects are translated to their server-side counterparts
+    let enter = wayc::protocol::wl_surface::Event::Enter { output: output.clone() };
+    let event = bridge::wl_surface::event_to_server(&enter, &server.display.handle(), map).unwrap();
+    assert!(matches!(
+        event,
+        wl_surface::Event::Enter { output } if output == server_output
+    ));
+
+    let mode = wayc::protocol::wl_output::Event::Mode {
+        flags: wayc::WEnum::Value(wayc::protocol::wl_output::Mode::Current),
+        width: 1920,
+        height: 1080,
+        refresh: 60000,
+    };
+    let event = bridge::wl_output::event_to_server(&mode, &server.display.handle(), map).unwrap();
+    assert!(matches!(
+        event,
+        wl_output::Event::Mode {
+            flags: ways::WEnum::Value(wl_output::Mode::Current),
+            width: 1920,
+            height: 1080,
+            refresh: 60000
+        }
+    ));
+
+    // events with objects that cannot be translated are not converted
+    assert!(
+        bridge::wl_surface::event_to_server(&enter, &server.display.handle(), |_| None).is_none()
+    );
+}
+
+/*
+ * Server Handler
+ */
+
+struct ServerHandler {
+    regions: Vec<wl_region::WlRegion>,
+    outputs: Vec<wl_output::WlOutput>,
+    surface_requests: Vec<wl_surface::Request>,
+}
+
+impl ServerHandler {
+    fn new() -> ServerHandler {
+        ServerHandler { regions: Vec::new(), outputs: Vec::new(), surface_requests: Vec::new() }
+    }
+}
+
+impl ways::Dispatch<wl_compositor::WlCompositor, ()> for ServerHandler {
+    fn request(
+        state: &mut Self,
+        _: &ways::Client,
+        _: &wl_compositor::WlCompositor,
+        request: wl_compositor::Request,
+        _: &(),
+        _: &ways::DisplayHandle,
+        init: &mut ways::DataInit<'_, Self>,
+    ) {
+        match request {
+            wl_compositor::Request::CreateSurface { id } => {
+                init.init(id, ());
+            }
+            wl_compositor::Request::CreateRegion { id } => {
+                state.regions.push(init.init(id, ()));
+            }
+            _ => panic!("Unexpected request!"),
+        }
+    }
+}
+
+impl ways::Dispatch<wl_surface::WlSurface, ()> for ServerHandler {
+    fn request(
+        state: &mut Self,
+        _: &ways::Client,
+        _: &wl_surface::WlSurface,
+        request: wl_surface::Request,
+        _: &(),
+        _: &ways::DisplayHandle,
+        _: &mut ways::DataInit<'_, Self>,
+    ) {
+        state.surface_requests.push(request);
+    }
+}
+
+impl ways::GlobalDispatch<wl_output::WlOutput, ()> for ServerHandler {
+    fn bind(
+        state: &mut Self,
+        _: &ways::DisplayHandle,
+        _: &ways::Client,
+        output: ways::New<wl_output::WlOutput>,
+        _: &(),
+        data_init: &mut ways::DataInit<'_, Self>,
+    ) {
+        state.outputs.push(data_init.init(output, ()));
+    }
+}
+
+server_ignore_impl!(ServerHandler => [
+    wl_region::WlRegion,
+    wl_output::WlOutput
+]);
+
+server_ignore_global_impl!(ServerHandler => [
+    wl_compositor::WlCompositor
+]);
+
+/*
+ * Client Handler
+ */
+
+struct ClientHandler {
+    globals: globals::GlobalList,
+}
+
+impl AsMut<globals::GlobalList> for ClientHandler {
+    fn as_mut(&mut self) -> &mut globals::GlobalList {
+        &mut self.globals
+    }
+}
+
+wayc::delegate_dispatch!(ClientHandler:
+    [wayc::protocol::wl_registry::WlRegistry: ()] => globals::GlobalList
+);
+
+client_ignore_impl!(ClientHandler => [
+    wayc::protocol::wl_compositor::WlCompositor,
+    wayc::protocol::wl_surface::WlSurface,
+    wayc::protocol::wl_region::WlRegion,
+    wayc::protocol::wl_output::WlOutput
+]);
@@ -23,7 +23,7 @@ fn main() {
     // connect the client
     let mut client = TestClient::new_from_env();
     let mut client_data = ClientHandler::new();
-    client.display.get_registry(&client.event_queue.handle(), ());
+    let _registry = client.display.get_registry(&client.event_queue.handle(), ());
 
     // setup server-side
     let client_stream = listening.accept().unwrap().unwrap();
@@ -40,6 +40,53 @@ fn main() {
     assert_eq!(output.name, 1);
     assert_eq!(output.interface, "wl_output");
     assert_eq!(output.version, 1);
+
+    absolute_socket_path();
+    backend_connect_to_env();
+}
+
+fn absolute_socket_path() {
+    let mut server = TestServer::new();
+    server.display.handle().create_global::<ServerData, ServerOutput, _>(1, ());
+
+    // bind the socket outside of the runtime dir, and point XDG_RUNTIME_DIR to another directory, so that
+    // connecting only succeeds if the absolute path is used as-is
+    let socket_dir = tempfile::tempdir().unwrap();
+    let runtime_dir = tempfile::tempdir().unwrap();
+    let socket_path = socket_dir.path().join("custom.sock");
+    let listening = ways::ListeningSocket::bind_absolute(socket_path.clone()).unwrap();
+
+    ::std::env::set_var("XDG_RUNTIME_DIR", runtime_dir.path());
+    ::std::env::set_var("WAYLAND_DISPLAY", &socket_path);
+
+    let mut client = TestClient::new_from_env();
+    let mut client_data = ClientHandler::new();
+    let _registry = client.display.get_registry(&client.event_queue.handle(), ());
+
+    let client_stream = listening.accept().unwrap().unwrap();
+    server
+        .display
+        .handle()
+        .insert_client(client_stream, std::sync::Arc::new(DumbClientData))
+        .unwrap();
+
+    roundtrip(&mut client, &mut server, &mut client_data, &mut ServerData).unwrap();
+    assert!(client_data.globals.list().len() == 1);
+}
+
+fn backend_connect_to_env() {
+    use wayland_backend::client::{Backend, ConnectError};
+
+    ::std::env::remove_var("WAYLAND_DISPLAY");
+    assert!(matches!(Backend::connect_to_env(), Err(ConnectError::NoCompositor)));
+
+    let socket_dir = tempfile::tempdir().unwrap();
+    let socket_path = socket_dir.path().join("backend.sock");
+    let listening = ways::ListeningSocket::bind_absolute(socket_path.clone()).unwrap();
+    ::std::env::set_var("WAYLAND_DISPLAY", &socket_path);
+
+    let _backend = Backend::connect_to_env().unwrap();
+    assert!(listening.accept().unwrap().is_some());
 }
 
 struct ServerData;
@@ -1,3 +1,4 @@
+#[macro_use]
 mod helpers;
 
 use helpers::*;
@@ -30,3 +31,559 @@ fn client_roundtrip() {
 
     server_thread.join().unwrap();
 }
+
+#[test]
+fn client_backend_fd() {
+    use std::os::unix::io::{AsFd, AsRawFd};
+
+    let mut server = TestServer::<()>::new();
+    let (_, client) = server.add_client::<()>();
+
+    let fd = client.conn.backend_fd().as_raw_fd();
+    assert_eq!(fd, client.conn.as_fd().as_raw_fd());
+    let guard = client.conn.prepare_read().unwrap();
+    assert_eq!(fd, guard.connection_fd().as_raw_fd());
+}
+
+#[test]
+fn client_queue_roundtrip_ignores_other_queues() {
+    let kill_switch = Arc::new(AtomicBool::new(false));
+    let server_kill_switch = kill_switch.clone();
+
+    let mut server = TestServer::<ServerHandler>::new();
+    server
+        .display
+        .handle()
+        .create_global::<ServerHandler, ways::protocol::wl_compositor::WlCompositor, _>(1, ());
+
+    let (_, client) = server.add_client::<()>();
+
+    let server_thread = ::std::thread::spawn(move || loop {
+        server.display.dispatch_clients(&mut ServerHandler).unwrap();
+        server.display.flush_clients().unwrap();
+        if server_kill_switch.load(Ordering::Acquire) {
+            break;
+        }
+    });
+
+    let mut queue_a = client.conn.new_event_queue::<()>();
+    let mut queue_b = client.conn.new_event_queue::<RegistryRecorder>();
+    let _registry = client.display.get_registry(&queue_b.handle(), ());
+
+    // the roundtrip on queue A completes even though the events all go to queue B
+    assert_eq!(queue_a.roundtrip(&mut ()).unwrap(), 0);
+
+    let mut recorder = RegistryRecorder { events: Vec::new() };
+    queue_b.dispatch_pending(&mut recorder).unwrap();
+    assert!(matches!(
+        recorder.events[..],
+        [wayc::protocol::wl_registry::Event::Global { name: 1, .. }]
+    ));
+
+    kill_switch.store(true, Ordering::Release);
+
+    server_thread.join().unwrap();
+}
+
+#[test]
+fn client_interrupt_blocking_dispatch() {
+    let mut server = TestServer::<()>::new();
+    let (_, client) = server.add_client::<()>();
+
+    let mut conn = client.conn.clone();
+    assert_eq!(conn.interrupt().unwrap_err().kind(), std::io::ErrorKind::Unsupported);
+    conn.enable_interrupt().unwrap();
+    let mut event_queue = conn.new_event_queue::<()>();
+
+    // the server never sends anything, so only the interrupt can wake up the dispatch
+    let interrupter = conn.clone();
+    let thread = std::thread::spawn(move || {
+        std::thread::sleep(std::time::Duration::from_millis(100));
+        interrupter.interrupt().unwrap();
+    });
+
+    assert_eq!(event_queue.blocking_dispatch(&mut ()).unwrap(), 0);
+    thread.join().unwrap();
+
+    // an interrupt sent while not blocked is not lost
+    conn.interrupt().unwrap();
+    assert_eq!(event_queue.blocking_dispatch(&mut ()).unwrap(), 0);
+}
+
+#[test]
+fn client_queue_pending_count() {
+    let kill_switch = Arc::new(AtomicBool::new(false));
+    let server_kill_switch = kill_switch.clone();
+
+    let mut server = TestServer::<ServerHandler>::new();
+    server
+        .display
+        .handle()
+        .create_global::<ServerHandler, ways::protocol::wl_compositor::WlCompositor, _>(1, ());
+    server
+        .display
+        .handle()
+        .create_global::<ServerHandler, ways::protocol::wl_compositor::WlCompositor, _>(1, ());
+
+    let (_, client) = server.add_client::<()>();
+
+    let server_thread = ::std::thread::spawn(move || loop {
+        server.display.dispatch_clients(&mut ServerHandler).unwrap();
+        server.display.flush_clients().unwrap();
+        if server_kill_switch.load(Ordering::Acquire) {
+            break;
+        }
+    });
+
+    let mut queue = client.conn.new_event_queue::<RegistryRecorder>();
+    let _registry = client.display.get_registry(&queue.handle(), ());
+    let mut recorder = RegistryRecorder { events: Vec::new() };
+
+    assert_eq!(queue.pending_count(), 0);
+    assert!(!queue.has_pending());
+
+    // the roundtrip reads the events, but does not dispatch this queue
+    client.conn.roundtrip().unwrap();
+
+    assert_eq!(queue.pending_count(), 2);
+    assert!(queue.has_pending());
+    assert_eq!(recorder.events.len(), 0);
+
+    assert_eq!(queue.dispatch_pending(&mut recorder).unwrap(), 2);
+    assert_eq!(recorder.events.len(), 2);
+    assert_eq!(queue.pending_count(), 0);
+
+    kill_switch.store(true, Ordering::Release);
+
+    server_thread.join().unwrap();
+}
+
+#[test]
+fn client_roundtrip_counted() {
+    use wayc::Proxy;
+
+    let kill_switch = Arc::new(AtomicBool::new(false));
+    let server_kill_switch = kill_switch.clone();
+
+    let mut server = TestServer::<ServerHandler>::new();
+    server
+        .display
+        .handle()
+        .create_global::<ServerHandler, ways::protocol::wl_compositor::WlCompositor, _>(1, ());
+    server
+        .display
+        .handle()
+        .create_global::<ServerHandler, ways::protocol::wl_compositor::WlCompositor, _>(1, ());
+
+    let (_, client) = server.add_client::<()>();
+
+    let server_thread = ::std::thread::spawn(move || loop {
+        server.display.dispatch_clients(&mut ServerHandler).unwrap();
+        server.display.flush_clients().unwrap();
+        if server_kill_switch.load(Ordering::Acquire) {
+            break;
+        }
+    });
+
+    let mut queue = client.conn.new_event_queue::<RegistryRecorder>();
+    let registry = client.display.get_registry(&queue.handle(), ());
+    let mut recorder = RegistryRecorder { events: Vec::new() };
+
+    let counts = queue.roundtrip_counted(&mut recorder).unwrap();
+    assert_eq!(counts, vec![(registry.id(), 2)]);
+    assert_eq!(recorder.events.len(), 2);
+
+    // nothing more is sent to the registry
+    assert!(queue.roundtrip_counted(&mut recorder).unwrap().is_empty());
+
+    kill_switch.store(true, Ordering::Release);
+
+    server_thread.join().unwrap();
+}
+
+#[test]
+fn client_queue_context() {
+    let mut server = TestServer::<()>::new();
+    let (_, client) = server.add_client::<()>();
+
+    let queue = client.conn.new_event_queue::<RegistryRecorder>();
+    assert!(queue.handle().context::<u32>().is_none());
+
+    let qh = queue.handle();
+    qh.set_context(42u32);
+    // the context is shared by all the handles of the queue
+    assert_eq!(*queue.handle().context::<u32>().unwrap(), 42);
+    assert_eq!(*qh.clone().context::<u32>().unwrap(), 42);
+    assert!(qh.context::<String>().is_none());
+
+    // setting a context of another type keeps the previous one
+    queue.handle().set_context(String::from("context"));
+    assert_eq!(*qh.context::<String>().unwrap(), "context");
+    assert_eq!(*qh.context::<u32>().unwrap(), 42);
+
+    // setting a context of the same type replaces it
+    qh.set_context(7u32);
+    assert_eq!(*qh.context::<u32>().unwrap(), 7);
+    assert_eq!(*qh.context::<String>().unwrap(), "context");
+
+    // other queues have their own context
+    let other = client.conn.new_event_queue::<RegistryRecorder>();
+    assert!(other.handle().context::<u32>().is_none());
+}
+
+#[test]
+fn client_dispatch_after_readable() {
+    let mut server = TestServer::<ServerHandler>::new();
+    server
+        .display
+        .handle()
+        .create_global::<ServerHandler, ways::protocol::wl_compositor::WlCompositor, _>(1, ());
+
+    let (_, client) = server.add_client::<()>();
+
+    let mut queue = client.conn.new_event_queue::<RegistryRecorder>();
+    let _registry = client.display.get_registry(&queue.handle(), ());
+    let mut recorder = RegistryRecorder { events: Vec::new() };
+
+    // nothing was sent yet, the socket is not readable
+    assert_eq!(queue.dispatch_after_readable(&mut recorder).unwrap(), 0);
+
+    queue.flush().unwrap();
+    server.display.dispatch_clients(&mut ServerHandler).unwrap();
+    server.display.flush_clients().unwrap();
+
+    assert_eq!(queue.dispatch_after_readable(&mut recorder).unwrap(), 1);
+    assert_eq!(recorder.events.len(), 1);
+}
+
+#[test]
+fn client_raw_event_stream() {
+    let kill_switch = Arc::new(AtomicBool::new(false));
+    let server_kill_switch = kill_switch.clone();
+
+    let mut server = TestServer::new();
+    server
+        .display
+        .handle()
+        .create_global::<ServerHandler, ways::protocol::wl_compositor::WlCompositor, _>(1, ());
+
+    let (_, client) = server.add_client::<()>();
+
+    let server_thread = ::std::thread::spawn(move || loop {
+        server.display.dispatch_clients(&mut ServerHandler).unwrap();
+        server.display.flush_clients().unwrap();
+        if server_kill_switch.load(Ordering::Acquire) {
+            break;
+        }
+    });
+
+    let mut stream = client.conn.new_event_queue::<()>().into_raw_stream();
+    client
+        .conn
+        .send_request(
+            &client.display,
+            wayc::protocol::wl_display::Request::GetRegistry {},
+            Some(stream.handle().make_raw_data()),
+        )
+        .unwrap();
+
+    let msg = stream.blocking_next().unwrap();
+    // wl_registry.global
+    assert_eq!(msg.opcode, 0);
+    assert_eq!(msg.args[0], wayc::backend::protocol::Argument::Uint(1));
+    match msg.args[1] {
+        wayc::backend::protocol::Argument::Str(Some(ref name)) => {
+            assert_eq!(name.to_bytes(), b"wl_compositor")
+        }
+        ref arg => panic!("Unexpected argument: {:?}", arg),
+    }
+
+    kill_switch.store(true, Ordering::Release);
+
+    server_thread.join().unwrap();
+}
+
+#[test]
+fn client_event_stream() {
+    use futures_util::{FutureExt, StreamExt};
+    use wayc::Proxy;
+
+    let kill_switch = Arc::new(AtomicBool::new(false));
+    let server_kill_switch = kill_switch.clone();
+
+    let mut server = TestServer::new();
+
+    let (_, client) = server.add_client::<()>();
+
+    let server_thread = ::std::thread::spawn(move || loop {
+        server.display.dispatch_clients(&mut ()).unwrap();
+        server.display.flush_clients().unwrap();
+        if server_kill_switch.load(Ordering::Acquire) {
+            break;
+        }
+    });
+
+    let queue = client.conn.new_event_queue::<()>();
+    let callback_id = client
+        .conn
+        .send_request(
+            &client.display,
+            wayc::protocol::wl_display::Request::Sync {},
+            Some(queue.handle().make_raw_data()),
+        )
+        .unwrap();
+    let callback =
+        wayc::protocol::wl_callback::WlCallback::from_id(&client.conn, callback_id).unwrap();
+    let mut stream = wayc::EventStream::new(&callback, &queue.handle()).unwrap();
+
+    // nothing was received yet
+    assert!(stream.next().now_or_never().is_none());
+
+    client.conn.roundtrip().unwrap();
+
+    assert!(matches!(
+        stream.next().now_or_never(),
+        Some(Some(wayc::protocol::wl_callback::Event::Done { .. }))
+    ));
+    // wl_callback.done is a destructor, which ends the stream
+    assert!(matches!(stream.next().now_or_never(), Some(None)));
+
+    kill_switch.store(true, Ordering::Release);
+
+    server_thread.join().unwrap();
+}
+
+#[test]
+fn client_dispatch_synthetic_messages() {
+    use std::ffi::CString;
+    use wayc::backend::protocol::{Argument, Message};
+    use wayc::Proxy;
+
+    let mut server = TestServer::<()>::new();
+    let (_, client) = server.add_client::<()>();
+
+    let mut event_queue = client.conn.new_event_queue::<RegistryRecorder>();
+    let registry = client.display.get_registry(&event_queue.handle(), ());
+    let mut recorder = RegistryRecorder { events: Vec::new() };
+
+    let dispatched = event_queue
+        .dispatch_messages(
+            &mut recorder,
+            vec![
+                Message {
+                    sender_id: registry.id(),
+                    opcode: 0,
+                    args: [
+                        Argument::Uint(1),
+                        Argument::Str(Some(Box::new(CString::new("wl_seat").unwrap()))),
+                        Argument::Uint(7),
+                    ]
+                    .into_iter()
+                    .collect(),
+                },
+                Message {
+                    sender_id: registry.id(),
+                    opcode: 1,
+                    args: [Argument::Uint(1)].into_iter().collect(),
+                },
+            ],
+        )
+        .unwrap();
+
+    assert_eq!(dispatched, 2);
+    assert_eq!(recorder.events.len(), 2);
+    assert!(matches!(
+        recorder.events[0],
+        wayc::protocol::wl_registry::Event::Global { name: 1, ref interface, version: 7 } if interface == "wl_seat"
+    ));
+    assert!(matches!(
+        recorder.events[1],
+        wayc::protocol::wl_registry::Event::GlobalRemove { name: 1 }
+    ));
+
+    // messages for objects that do not exist are rejected
+    assert!(event_queue
+        .dispatch_messages(
+            &mut recorder,
+            vec![Message {
+                sender_id: wayc::backend::ObjectId::null(),
+                opcode: 0,
+                args: Default::default()
+            }],
+        )
+        .is_err());
+}
+
+#[test]
+fn client_dispatch_profiler() {
+    use std::ffi::CString;
+    use std::sync::Mutex;
+    use wayc::backend::protocol::{Argument, Message};
+    use wayc::Proxy;
+
+    let mut server = TestServer::<()>::new();
+    let (_, client) = server.add_client::<()>();
+
+    let mut event_queue = client.conn.new_event_queue::<RegistryRecorder>();
+    let registry = client.display.get_registry(&event_queue.handle(), ());
+    let mut recorder = RegistryRecorder { events: Vec::new() };
+
+    let measured = Arc::new(Mutex::new(Vec::new()));
+    let measured2 = measured.clone();
+    event_queue.set_dispatch_profiler(move |id, duration| {
+        measured2.lock().unwrap().push((id, duration));
+    });
+
+    let global = |name| Message {
+        sender_id: registry.id(),
+        opcode: 0,
+        args: [
+            Argument::Uint(name),
+            Argument::Str(Some(Box::new(CString::new("wl_seat").unwrap()))),
+            Argument::Uint(7),
+        ]
+        .into_iter()
+        .collect(),
+    };
+
+    let dispatched =
+        event_queue.dispatch_messages(&mut recorder, vec![global(1), global(2)]).unwrap();
+
+    // the consecutive events of the registry are measured together
+    assert_eq!(dispatched, 2);
+    assert_eq!(measured.lock().unwrap().len(), 1);
+    assert_eq!(measured.lock().unwrap()[0].0, registry.id());
+
+    event_queue.clear_dispatch_profiler();
+    event_queue.dispatch_messages(&mut recorder, vec![global(3)]).unwrap();
+    assert_eq!(recorder.events.len(), 3);
+    assert_eq!(measured.lock().unwrap().len(), 1);
+}
+
+#[test]
+fn client_event_try_from_message() {
+    use wayc::backend::protocol::{Argument, Message};
+    use wayc::protocol::wl_registry::Event;
+
+    let event = Event::try_from(Message {
+        sender_id: wayc::backend::ObjectId::null(),
+        opcode: 1,
+        args: [Argument::Uint(7)].into_iter().collect(),
+    })
+    .unwrap();
+    assert!(matches!(event, Event::GlobalRemove { name: 7 }));
+
+    // arguments not matching the signature are rejected
+    assert!(matches!(
+        Event::try_from(Message {
+            sender_id: wayc::backend::ObjectId::null(),
+            opcode: 1,
+            args: [Argument::Int(7)].into_iter().collect(),
+        }),
+        Err(wayc::DispatchError::BadMessage { interface: "wl_registry", opcode: 1, .. })
+    ));
+}
+
+#[test]
+fn client_dispatch_event_batch() {
+    use wayc::backend::protocol::{Argument, Message};
+    use wayc::Proxy;
+
+    let mut server = TestServer::<()>::new();
+    let (_, client) = server.add_client::<()>();
+
+    let mut event_queue = client.conn.new_event_queue::<BatchRecorder>();
+    let registry_a = client.display.get_registry(&event_queue.handle(), ());
+    let registry_b = client.display.get_registry(&event_queue.handle(), ());
+    let mut recorder = BatchRecorder { batches: Vec::new(), events: Vec::new() };
+
+    let global_remove = |registry: &wayc::protocol::wl_registry::WlRegistry, name| Message {
+        sender_id: registry.id(),
+        opcode: 1,
+        args: [Argument::Uint(name)].into_iter().collect(),
+    };
+
+    let dispatched = event_queue
+        .dispatch_messages(
+            &mut recorder,
+            vec![
+                global_remove(&registry_a, 1),
+                global_remove(&registry_a, 2),
+                global_remove(&registry_b, 3),
+                global_remove(&registry_a, 4),
+            ],
+        )
+        .unwrap();
+
+    // consecutive events of the same object are dispatched together, lone events are dispatched alone
+    assert_eq!(dispatched, 4);
+    assert_eq!(recorder.batches, [(registry_a.id(), vec![1, 2])]);
+    assert_eq!(recorder.events, [(registry_b.id(), 3), (registry_a.id(), 4)]);
+}
+
+struct BatchRecorder {
+    batches: Vec<(wayc::backend::ObjectId, Vec<u32>)>,
+    events: Vec<(wayc::backend::ObjectId, u32)>,
+}
+
+impl wayc::Dispatch<wayc::protocol::wl_registry::WlRegistry, ()> for BatchRecorder {
+    fn event(
+        state: &mut Self,
+        proxy: &wayc::protocol::wl_registry::WlRegistry,
+        event: wayc::protocol::wl_registry::Event,
+        _: &(),
+        _: &wayc::Connection,
+        _: &wayc::QueueHandle<Self>,
+    ) {
+        use wayc::Proxy;
+
+        match event {
+            wayc::protocol::wl_registry::Event::GlobalRemove { name } => {
+                state.events.push((proxy.id(), name))
+            }
+            _ => panic!("Unexpected event"),
+        }
+    }
+
+    fn event_batch(
+        state: &mut Self,
+        proxy: &wayc::protocol::wl_registry::WlRegistry,
+        events: Vec<wayc::protocol::wl_registry::Event>,
+        _: &(),
+        _: &wayc::Connection,
+        _: &wayc::QueueHandle<Self>,
+    ) {
+        use wayc::Proxy;
+
+        let names = events
+            .into_iter()
+            .map(|event| match event {
+                wayc::protocol::wl_registry::Event::GlobalRemove { name } => name,
+                _ => panic!("Unexpected event"),
+            })
+            .collect();
+        state.batches.push((proxy.id(), names));
+    }
+}
+
+struct RegistryRecorder {
+    events: Vec<wayc::protocol::wl_registry::Event>,
+}
+
+impl wayc::Dispatch<wayc::protocol::wl_registry::WlRegistry, ()> for RegistryRecorder {
+    fn event(
+        state: &mut Self,
+        _: &wayc::protocol::wl_registry::WlRegistry,
+        event: wayc::protocol::wl_registry::Event,
+        _: &(),
+        _: &wayc::Connection,
+        _: &wayc::QueueHandle<Self>,
+    ) {
+        state.events.push(event);
+    }
+}
+
+struct ServerHandler;
+
+server_ignore_impl!(ServerHandler => [ways::protocol::wl_compositor::WlCompositor]);
+server_ignore_global_impl!(ServerHandler => [ways::protocol::wl_compositor::WlCompositor]);
@@ -13,8 +13,9 @@ use ways::protocol::wl_compositor::WlCompositor as ServerCompositor;
 use ways::protocol::wl_output::WlOutput as ServerOutput;
 use ways::protocol::wl_shell::WlShell as ServerShell;
 
-use wayc::globals::{registry_queue_init, Global, GlobalListContents};
-use wayc::protocol::{wl_compositor, wl_registry, wl_subcompositor};
+use wayc::globals::{registry_queue_init, BindError, Global, GlobalListContents};
+use wayc::protocol::{wl_compositor, wl_output, wl_registry, wl_subcompositor};
+use wayc::Proxy;
 
 #[test]
 fn client_global_helpers_init() {
@@ -50,7 +51,14 @@ fn client_global_helpers_init() {
 
     // ensure bind works as expected
     // Too high version fails
-    assert!(globals.bind::<wl_compositor::WlCompositor, _, _>(&queue.handle(), 5..=5, ()).is_err());
+    match globals.bind::<wl_compositor::WlCompositor, _, _>(&queue.handle(), 5..=5, ()) {
+        Err(BindError::UnsupportedVersion { interface, requested, available }) => {
+            assert_eq!(interface.name, "wl_compositor");
+            assert_eq!(requested, 5..=5);
+            assert_eq!(available, 4);
+        }
+        other => panic!("Unexpected bind result: {:?}", other),
+    }
     // Missing global fails
     assert!(globals
         .bind::<wl_subcompositor::WlSubcompositor, _, _>(&queue.handle(), 1..=1, ())
@@ -63,6 +71,61 @@ fn client_global_helpers_init() {
     server_thread.join().unwrap();
 }
 
+#[test]
+fn client_global_helpers_bind_all() {
+    let kill_switch = Arc::new(AtomicBool::new(false));
+    let server_kill_switch = kill_switch.clone();
+
+    let mut server = TestServer::new();
+    server.display.handle().create_global::<ServerHandler, ServerCompositor, _>(4, ());
+    server.display.handle().create_global::<ServerHandler, ServerOutput, _>(2, ());
+    server.display.handle().create_global::<ServerHandler, ServerOutput, _>(3, ());
+
+    let (_, client) = server.add_client::<()>();
+
+    // spawn a thread for the server loop as the client global init helpers to a blocking roundtrip
+    let server_thread = ::std::thread::spawn(move || loop {
+        server.display.dispatch_clients(&mut ServerHandler).unwrap();
+        server.display.flush_clients().unwrap();
+        if server_kill_switch.load(Ordering::Acquire) {
+            break;
+        }
+    });
+
+    let (globals, queue) = registry_queue_init::<ClientHandler>(&client.conn).unwrap();
+
+    // One of the globals has a too low version
+    assert!(matches!(
+        globals.bind_all::<wl_output::WlOutput, _, _>(&queue.handle(), 3..=4, ()),
+        Err(BindError::UnsupportedVersion { available: 2, .. })
+    ));
+    // Missing globals give an empty list
+    assert!(globals
+        .bind_all::<wl_subcompositor::WlSubcompositor, _, _>(&queue.handle(), 1..=1, ())
+        .unwrap()
+        .is_empty());
+    // Compatible spec binds all instances
+    let outputs =
+        globals.bind_all::<wl_output::WlOutput, _, _>(&queue.handle(), 1..=4, ()).unwrap();
+    assert_eq!(outputs.iter().map(|o| o.version()).collect::<Vec<_>>(), [2, 3]);
+
+    // The registry names are returned alongside the proxies
+    let (_, name) = globals
+        .bind_named::<wl_compositor::WlCompositor, _, _>(&queue.handle(), 1..=4, ())
+        .unwrap();
+    assert_eq!(name, 1);
+    let outputs =
+        globals.bind_all_named::<wl_output::WlOutput, _, _>(&queue.handle(), 1..=4, ()).unwrap();
+    assert_eq!(
+        outputs.iter().map(|(o, name)| (o.version(), *name)).collect::<Vec<_>>(),
+        [(2, 2), (3, 3)]
+    );
+
+    // cleanup
+    kill_switch.store(true, Ordering::Release);
+    server_thread.join().unwrap();
+}
+
 #[test]
 fn client_global_helpers_dynamic() {
     let kill_switch = Arc::new(AtomicBool::new(false));
@@ -213,5 +276,6 @@ impl wayc::Dispatch<wl_registry::WlRegistry, GlobalListContents> for ClientHandl
 
 client_ignore_impl!(ClientHandler => [
     wl_compositor::WlCompositor,
+    wl_output::WlOutput,
     wl_subcompositor::WlSubcompositor
 ]);
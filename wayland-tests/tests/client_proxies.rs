@@ -49,6 +49,368 @@ fn proxy_equals() {
     assert!(compositor1 == compositor3);
     assert!(compositor1 != compositor2);
     assert!(compositor2 != compositor3);
+
+    // proxies and weak handles compare in both directions
+    let weak1 = compositor1.downgrade();
+    assert!(compositor1 == weak1);
+    assert!(weak1 == compositor1);
+    assert!(compositor2 != weak1);
+    assert!(weak1 != compositor2);
+}
+
+#[test]
+fn proxy_cast() {
+    let mut server = TestServer::new();
+    server
+        .display
+        .handle()
+        .create_global::<ServerHandler, ways::protocol::wl_compositor::WlCompositor, _>(1, ());
+    let mut server_ddata = ServerHandler { output: None };
+
+    let (_, mut client) = server.add_client();
+    let mut client_ddata = ClientHandler::new();
+
+    let registry = client.display.get_registry(&client.event_queue.handle(), ());
+
+    roundtrip(&mut client, &mut server, &mut client_ddata, &mut server_ddata).unwrap();
+
+    let compositor = client_ddata
+        .globals
+        .bind::<wayc::protocol::wl_compositor::WlCompositor, _, _>(
+            &client.event_queue.handle(),
+            &registry,
+            1..2,
+            0,
+        )
+        .unwrap();
+
+    let same = compositor.cast::<wayc::protocol::wl_compositor::WlCompositor>().unwrap();
+    assert!(same == compositor);
+    assert_eq!(same.data::<usize>(), Some(&0));
+
+    assert!(compositor.cast::<wayc::protocol::wl_shm::WlShm>().is_err());
+
+    assert!(wayc::protocol::wl_compositor::WlCompositor::is_interface_of(&compositor.id()));
+    assert!(!wayc::protocol::wl_shm::WlShm::is_interface_of(&compositor.id()));
+}
+
+#[test]
+fn constructor_version_override() {
+    let mut server = TestServer::new();
+    server
+        .display
+        .handle()
+        .create_global::<ServerHandler, ways::protocol::wl_compositor::WlCompositor, _>(4, ());
+    let mut server_ddata = ServerHandler { output: None };
+
+    let (server_client, mut client) = server.add_client();
+    let mut client_ddata = ClientHandler::new();
+
+    let registry = client.display.get_registry(&client.event_queue.handle(), ());
+
+    roundtrip(&mut client, &mut server, &mut client_ddata, &mut server_ddata).unwrap();
+
+    let compositor: wayc::protocol::wl_compositor::WlCompositor = registry
+        .send_constructor_versioned(
+            wayc::protocol::wl_registry::Request::Bind {
+                name: 1,
+                id: (wayc::protocol::wl_compositor::WlCompositor::interface(), 4),
+            },
+            client
+                .event_queue
+                .handle()
+                .make_data::<wayc::protocol::wl_compositor::WlCompositor, _>(0usize),
+            Some(2),
+        )
+        .unwrap();
+    assert_eq!(compositor.version(), 2);
+
+    roundtrip(&mut client, &mut server, &mut client_ddata, &mut server_ddata).unwrap();
+    assert!(compositor.is_alive());
+
+    // the server created the object with the same version
+    let server_compositor = server_client
+        .object_from_protocol_id::<ways::protocol::wl_compositor::WlCompositor>(
+            &server.display.handle(),
+            compositor.id().protocol_id(),
+        )
+        .unwrap();
+    assert_eq!(server_compositor.version(), 2);
+}
+
+#[test]
+fn constructor_version_known_immediately() {
+    let mut server = TestServer::new();
+    server
+        .display
+        .handle()
+        .create_global::<ServerHandler, ways::protocol::wl_compositor::WlCompositor, _>(3, ());
+    let mut server_ddata = ServerHandler { output: None };
+
+    let (_, mut client) = server.add_client();
+    let mut client_ddata = ClientHandler::new();
+
+    let registry = client.display.get_registry(&client.event_queue.handle(), ());
+
+    roundtrip(&mut client, &mut server, &mut client_ddata, &mut server_ddata).unwrap();
+
+    let compositor = client_ddata
+        .globals
+        .bind::<wayc::protocol::wl_compositor::WlCompositor, _, _>(
+            &client.event_queue.handle(),
+            &registry,
+            1..4,
+            0usize,
+        )
+        .unwrap();
+    assert_eq!(compositor.version(), 3);
+
+    // the child inherits the version of its parent, without waiting for the server
+    let region = compositor.create_region(&client.event_queue.handle(), ());
+    assert_eq!(region.version(), 3);
+}
+
+#[test]
+fn send_raw_request_from_thread() {
+    let mut server = TestServer::new();
+    server
+        .display
+        .handle()
+        .create_global::<ServerHandler, ways::protocol::wl_compositor::WlCompositor, _>(1, ());
+    let mut server_ddata = ServerHandler { output: None };
+
+    let (_, mut client) = server.add_client();
+    let mut client_ddata = ClientHandler::new();
+
+    let registry = client.display.get_registry(&client.event_queue.handle(), ());
+
+    roundtrip(&mut client, &mut server, &mut client_ddata, &mut server_ddata).unwrap();
+
+    let compositor = client_ddata
+        .globals
+        .bind::<wayc::protocol::wl_compositor::WlCompositor, _, _>(
+            &client.event_queue.handle(),
+            &registry,
+            1..2,
+            0usize,
+        )
+        .unwrap();
+
+    // only the id and the connection cross the thread boundary
+    let conn = client.conn.clone();
+    let compositor_id = compositor.id();
+    let region_data =
+        client.event_queue.handle().make_data::<wayc::protocol::wl_region::WlRegion, _>(());
+    let region_id = std::thread::spawn(move || {
+        conn.send_raw_request(
+            wayc::backend::protocol::Message {
+                sender_id: compositor_id,
+                opcode: wayc::protocol::wl_compositor::REQ_CREATE_REGION_OPCODE,
+                args: wayc::backend::smallvec::smallvec![wayc::backend::protocol::Argument::NewId(
+                    wayc::backend::ObjectId::null()
+                )],
+            },
+            Some(region_data),
+        )
+        .unwrap()
+    })
+    .join()
+    .unwrap();
+
+    let info = client.conn.object_info(region_id).unwrap();
+    assert_eq!(info.interface.name, "wl_region");
+    assert_eq!(info.version, 1);
+
+    roundtrip(&mut client, &mut server, &mut client_ddata, &mut server_ddata).unwrap();
+    assert!(client.conn.protocol_error().is_none());
+}
+
+#[test]
+fn weak_is_alive() {
+    let mut server = TestServer::new();
+    server
+        .display
+        .handle()
+        .create_global::<ServerHandler, ways::protocol::wl_compositor::WlCompositor, _>(1, ());
+    let mut server_ddata = ServerHandler { output: None };
+
+    let (s_client, mut client) = server.add_client();
+    let mut client_ddata = ClientHandler::new();
+
+    let registry = client.display.get_registry(&client.event_queue.handle(), ());
+
+    roundtrip(&mut client, &mut server, &mut client_ddata, &mut server_ddata).unwrap();
+
+    let compositor = client_ddata
+        .globals
+        .bind::<wayc::protocol::wl_compositor::WlCompositor, _, _>(
+            &client.event_queue.handle(),
+            &registry,
+            1..2,
+            0usize,
+        )
+        .unwrap();
+
+    let region = compositor.create_region(&client.event_queue.handle(), ());
+    let weak = region.downgrade();
+    assert!(weak.is_alive());
+
+    roundtrip(&mut client, &mut server, &mut client_ddata, &mut server_ddata).unwrap();
+    let server_region = s_client
+        .object_from_protocol_id::<ways::protocol::wl_region::WlRegion>(
+            &server.display.handle(),
+            region.id().protocol_id(),
+        )
+        .unwrap();
+    let server_weak = server_region.downgrade();
+    assert!(server_weak.is_alive());
+
+    region.destroy();
+    assert!(!weak.is_alive());
+    assert!(weak.upgrade().is_err());
+
+    // server-side, the region is destroyed once the request is received
+    assert!(server_weak.is_alive());
+    roundtrip(&mut client, &mut server, &mut client_ddata, &mut server_ddata).unwrap();
+    assert!(!server_weak.is_alive());
+
+    // objects are no longer alive once the connection is gone
+    let weak_compositor = compositor.downgrade();
+    assert!(weak_compositor.is_alive());
+    drop(client);
+    assert!(!weak_compositor.is_alive());
+}
+
+#[test]
+fn weak_connection() {
+    let (client_socket, _server_socket) = std::os::unix::net::UnixStream::pair().unwrap();
+    let conn = wayc::Connection::from_socket(client_socket).unwrap();
+
+    let weak = conn.downgrade();
+    assert_eq!(weak.upgrade(), Some(conn.clone()));
+
+    drop(conn);
+    assert!(weak.upgrade().is_none());
+}
+
+#[test]
+fn proxy_queue_handle() {
+    let (client_socket, _server_socket) = std::os::unix::net::UnixStream::pair().unwrap();
+    let conn = wayc::Connection::from_socket(client_socket).unwrap();
+    let event_queue = conn.new_event_queue::<ClientHandler>();
+
+    let registry = conn.display().get_registry(&event_queue.handle(), ());
+    let compositor = registry.bind::<wayc::protocol::wl_compositor::WlCompositor, _, _>(
+        1,
+        1,
+        &event_queue.handle(),
+        0usize,
+    );
+
+    // the handle is only given for the state type of the queue
+    assert!(compositor.queue_handle::<()>().is_none());
+    let qh = compositor.queue_handle::<ClientHandler>().unwrap();
+
+    // and can be used to create objects on the same queue
+    let surface = compositor.create_surface(&qh, ());
+    assert!(surface.queue_handle::<ClientHandler>().is_some());
+
+    // the display has no queue
+    assert!(conn.display().queue_handle::<ClientHandler>().is_none());
+}
+
+#[test]
+fn parse_event_raw_without_connection() {
+    use std::ffi::CString;
+    use wayc::backend::protocol::Argument;
+    use wayc::protocol::{wl_callback, wl_registry, wl_surface};
+
+    let event = wl_registry::parse_event_raw(
+        0,
+        vec![
+            Argument::Uint(1),
+            Argument::Str(Some(Box::new(CString::new("wl_compositor").unwrap()))),
+            Argument::Uint(4),
+        ],
+    )
+    .unwrap();
+    assert!(matches!(
+        event,
+        wl_registry::Event::Global { name: 1, ref interface, version: 4 } if interface == "wl_compositor"
+    ));
+
+    let event = wl_callback::parse_event_raw(0, vec![Argument::Uint(42)]).unwrap();
+    assert!(matches!(event, wl_callback::Event::Done { callback_data: 42 }));
+
+    // malformed arguments and unknown opcodes are rejected
+    assert!(wl_callback::parse_event_raw(0, vec![Argument::Int(42)]).is_err());
+    assert!(wl_callback::parse_event_raw(1, vec![]).is_err());
+    // as are null strings where the protocol does not allow them
+    assert!(matches!(
+        wl_registry::parse_event_raw(
+            0,
+            vec![Argument::Uint(1), Argument::Str(None), Argument::Uint(4)]
+        ),
+        Err(wayc::DispatchError::BadMessage { interface: "wl_registry", opcode: 0, .. })
+    ));
+
+    // events carrying objects need a connection to be decoded
+    assert!(matches!(
+        wl_surface::parse_event_raw(0, vec![Argument::Object(wayc::backend::ObjectId::null())]),
+        Err(wayc::DispatchError::BadMessage { interface: "wl_surface", opcode: 0, .. })
+    ));
+}
+
+#[cfg(feature = "client_system")]
+#[test]
+fn foreign_display_proxy() {
+    use wayc::Proxy;
+
+    let mut server = TestServer::new();
+    server
+        .display
+        .handle()
+        .create_global::<ServerHandler, ways::protocol::wl_output::WlOutput, _>(3, ());
+    let mut server_ddata = ServerHandler { output: None };
+
+    let (_, client) = server.add_client::<ClientHandler>();
+
+    // wrap the display of the client as if it was created by some C library
+    let backend = unsafe {
+        wayc::backend::Backend::from_foreign_display(client.conn.backend().display_ptr())
+    };
+    let conn = wayc::Connection::from_backend(backend);
+    let display = conn.display();
+    assert_eq!(display.id().protocol_id(), 1);
+    assert_eq!(display.version(), 1);
+    assert!(display.is_alive());
+    assert!(display.object_data().is_some());
+
+    let mut foreign_client =
+        helpers::TestClient { event_queue: conn.new_event_queue(), display, conn: conn.clone() };
+    let mut client_ddata = ClientHandler::new();
+
+    let registry = foreign_client.display.get_registry(&foreign_client.event_queue.handle(), ());
+    assert_eq!(registry.version(), 1);
+
+    roundtrip(&mut foreign_client, &mut server, &mut client_ddata, &mut server_ddata).unwrap();
+
+    assert_eq!(client_ddata.globals.list().len(), 1);
+    assert_eq!(client_ddata.globals.list()[0].interface, "wl_output");
+}
+
+#[cfg(feature = "client_system")]
+#[test]
+fn connection_from_display_ptr() {
+    let mut server = TestServer::new();
+    let (_, client) = server.add_client::<()>();
+
+    assert!(unsafe { wayc::Connection::from_display_ptr(std::ptr::null_mut()) }.is_err());
+
+    let conn =
+        unsafe { wayc::Connection::from_display_ptr(client.conn.backend().display_ptr()) }.unwrap();
+    assert_eq!(conn.backend().display_ptr(), client.conn.backend().display_ptr());
+    assert_eq!(conn.display().version(), 1);
 }
 
 #[test]
@@ -130,6 +492,10 @@ fn dead_proxies() {
     assert!(output == output2);
     assert!(client.conn.object_info(output.id()).is_ok());
     assert!(client.conn.object_info(output2.id()).is_ok());
+    let info = output.info().unwrap();
+    assert_eq!(info.id, output.id().protocol_id());
+    assert_eq!(info.interface.name, "wl_output");
+    assert_eq!(info.version, 3);
 
     // kill the output
     output.release();
@@ -138,6 +504,7 @@ fn dead_proxies() {
     assert!(output == output2);
     assert!(client.conn.object_info(output.id()).is_err());
     assert!(client.conn.object_info(output2.id()).is_err());
+    assert!(output.info().is_none());
 }
 
 #[test]
@@ -179,7 +546,7 @@ fn dead_object_argument() {
         )
         .unwrap();
 
-    compositor.create_surface(&client.event_queue.handle(), ());
+    let _surface = compositor.create_surface(&client.event_queue.handle(), ());
     output.release();
 
     roundtrip(&mut client, &mut server, &mut client_ddata, &mut server_ddata).unwrap();
@@ -215,18 +582,25 @@ impl ways::Dispatch<ways::protocol::wl_compositor::WlCompositor, ()> for ServerH
         dhandle: &ways::DisplayHandle,
         data_init: &mut ways::DataInit<'_, Self>,
     ) {
-        if let ways::protocol::wl_compositor::Request::CreateSurface { id } = request {
-            let surface = data_init.init(id, ());
-            let output = state.output.clone().unwrap();
-            assert!(dhandle.object_info(output.id()).is_ok());
-            surface.enter(&output);
+        match request {
+            ways::protocol::wl_compositor::Request::CreateSurface { id } => {
+                let surface = data_init.init(id, ());
+                let output = state.output.clone().unwrap();
+                assert!(dhandle.object_info(output.id()).is_ok());
+                surface.enter(&output);
+            }
+            ways::protocol::wl_compositor::Request::CreateRegion { id } => {
+                data_init.init(id, ());
+            }
+            _ => {}
         }
     }
 }
 
 server_ignore_impl!(ServerHandler => [
     ways::protocol::wl_output::WlOutput,
-    ways::protocol::wl_surface::WlSurface
+    ways::protocol::wl_surface::WlSurface,
+    ways::protocol::wl_region::WlRegion
 ]);
 
 server_ignore_global_impl!(ServerHandler => [
@@ -285,5 +659,6 @@ impl wayc::Dispatch<wayc::protocol::wl_surface::WlSurface, ()> for ClientHandler
 }
 
 client_ignore_impl!(ClientHandler => [
-    wayc::protocol::wl_output::WlOutput
+    wayc::protocol::wl_output::WlOutput,
+    wayc::protocol::wl_region::WlRegion
 ]);
@@ -0,0 +1,31 @@
+use std::io::{Seek, SeekFrom, Write};
+use std::os::unix::io::OwnedFd;
+
+use wayland_client::util::mmap_keymap;
+
+fn keymap_fd(contents: &[u8]) -> OwnedFd {
+    let mut file = tempfile::tempfile().unwrap();
+    file.write_all(contents).unwrap();
+    file.seek(SeekFrom::Start(0)).unwrap();
+    file.into()
+}
+
+#[test]
+fn mmap_keymap_nul_terminated() {
+    let keymap = b"xkb_keymap {\n};\n\0";
+    let mapped = mmap_keymap(keymap_fd(keymap), keymap.len() as u32).unwrap();
+    assert_eq!(&*mapped, "xkb_keymap {\n};\n");
+}
+
+#[test]
+fn mmap_keymap_without_nul() {
+    let keymap = b"xkb_keymap {\n};\n";
+    let mapped = mmap_keymap(keymap_fd(keymap), keymap.len() as u32).unwrap();
+    assert_eq!(mapped.as_str(), "xkb_keymap {\n};\n");
+}
+
+#[test]
+fn mmap_keymap_invalid() {
+    assert!(mmap_keymap(keymap_fd(b"xkb_keymap"), 0).is_err());
+    assert!(mmap_keymap(keymap_fd(b"\xff\xfe\0"), 3).is_err());
+}
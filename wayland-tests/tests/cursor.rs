@@ -0,0 +1,378 @@
+#[macro_use]
+mod helpers;
+
+use std::{fs::File, os::unix::fs::FileExt};
+
+use helpers::{globals, roundtrip, wayc, ways, TestClient, TestServer};
+
+use ways::protocol::{wl_buffer, wl_compositor, wl_shm, wl_shm_pool, wl_surface};
+
+use wayc::Proxy;
+use wayland_cursor::{CursorImageBuffer, CursorTheme};
+
+#[test]
+fn attach_to_damages_buffer() {
+    let (mut server, mut client, mut server_ddata, mut client_ddata) = setup(4);
+
+    let mut theme = load_theme(&mut client_ddata, &client);
+    let image = fallback_image(&mut theme, (0, 0));
+    let surface = create_surface(&mut client_ddata, &client);
+    image.attach_to(&surface).unwrap();
+
+    roundtrip(&mut client, &mut server, &mut client_ddata, &mut server_ddata).unwrap();
+
+    let buffer = server_ddata.buffers.last().unwrap();
+    let requests = &server_ddata.surface_requests;
+    assert_eq!(requests.len(), 3);
+    assert!(matches!(
+        &requests[0],
+        wl_surface::Request::Attach { buffer: Some(attached), x: 0, y: 0 } if attached == buffer
+    ));
+    assert!(matches!(
+        requests[1],
+        wl_surface::Request::DamageBuffer { x: 0, y: 0, width: 8, height: 8 }
+    ));
+    assert!(matches!(requests[2], wl_surface::Request::Commit));
+}
+
+#[test]
+fn attach_to_damages_surface_before_v4() {
+    let (mut server, mut client, mut server_ddata, mut client_ddata) = setup(3);
+
+    let mut theme = load_theme(&mut client_ddata, &client);
+    let image = fallback_image(&mut theme, (0, 0));
+    let surface = create_surface(&mut client_ddata, &client);
+    image.attach_to(&surface).unwrap();
+
+    roundtrip(&mut client, &mut server, &mut client_ddata, &mut server_ddata).unwrap();
+
+    let buffer = server_ddata.buffers.last().unwrap();
+    let requests = &server_ddata.surface_requests;
+    assert_eq!(requests.len(), 3);
+    assert!(matches!(
+        &requests[0],
+        wl_surface::Request::Attach { buffer: Some(attached), x: 0, y: 0 } if attached == buffer
+    ));
+    assert!(matches!(requests[1], wl_surface::Request::Damage { x: 0, y: 0, width: 8, height: 8 }));
+    assert!(matches!(requests[2], wl_surface::Request::Commit));
+}
+
+#[test]
+fn solid_cursor() {
+    let (mut server, mut client, mut server_ddata, mut client_ddata) = setup(4);
+
+    let mut theme = load_theme(&mut client_ddata, &client);
+    let image = theme.solid_cursor([255, 128, 0, 128]).unwrap();
+
+    // images are cached by color
+    assert_eq!(*theme.solid_cursor([255, 128, 0, 128]).unwrap(), *image);
+    let other = theme.solid_cursor([0, 0, 0, 0]).unwrap();
+    assert_ne!(*other, *image);
+
+    roundtrip(&mut client, &mut server, &mut client_ddata, &mut server_ddata).unwrap();
+
+    assert_eq!(server_ddata.buffers.len(), 2);
+    // the pixel is written premultiplied, as little-endian ARGB8888
+    assert_eq!(server_ddata.read_pixel(0), [0, 64, 128, 128]);
+    assert_eq!(server_ddata.read_pixel(1), [0, 0, 0, 0]);
+
+    // reloading the theme destroys the images, and the next ones are new buffers
+    theme.reload().unwrap();
+    let reloaded = theme.solid_cursor([255, 128, 0, 128]).unwrap();
+    assert!(!image.is_alive());
+    assert!(!other.is_alive());
+    assert_ne!(*reloaded, *image);
+
+    roundtrip(&mut client, &mut server, &mut client_ddata, &mut server_ddata).unwrap();
+
+    assert_eq!(server_ddata.destroyed_buffers, server_ddata.buffers[..2]);
+    assert_eq!(server_ddata.buffers.len(), 3);
+    assert_eq!(server_ddata.read_pixel(2), [0, 64, 128, 128]);
+}
+
+#[test]
+fn scaled_hotspot() {
+    let (_server, client, _server_ddata, mut client_ddata) = setup(4);
+
+    let mut theme = load_theme(&mut client_ddata, &client);
+    let image = fallback_image(&mut theme, (5, 3));
+
+    assert_eq!(image.hotspot(), (5, 3));
+    assert_eq!(image.scaled_hotspot(1), (5, 3));
+    // the hotspot is rounded down when the scale does not divide it
+    assert_eq!(image.scaled_hotspot(2), (2, 1));
+    assert_eq!(image.scaled_hotspot(3), (1, 1));
+    // scales lower than 1 are treated as 1
+    assert_eq!(image.scaled_hotspot(0), (5, 3));
+}
+
+#[test]
+fn reload_destroys_buffers() {
+    let (mut server, mut client, mut server_ddata, mut client_ddata) = setup(4);
+
+    let mut theme = load_theme(&mut client_ddata, &client);
+    let image = fallback_image(&mut theme, (0, 0));
+    let clone = image.clone();
+    theme.reload().unwrap();
+
+    roundtrip(&mut client, &mut server, &mut client_ddata, &mut server_ddata).unwrap();
+
+    // the previous pool and buffer are destroyed, and a new pool is created
+    assert_eq!(server_ddata.pools.len(), 2);
+    assert_eq!(server_ddata.destroyed_pools, 1);
+    assert_eq!(server_ddata.destroyed_buffers, server_ddata.buffers);
+
+    // clones of the previous images are invalidated
+    assert!(!clone.is_alive());
+    let surface = create_surface(&mut client_ddata, &client);
+    assert!(clone.attach_to(&surface).is_err());
+
+    // new images are created on the new pool
+    let image = fallback_image(&mut theme, (0, 0));
+    image.attach_to(&surface).unwrap();
+
+    roundtrip(&mut client, &mut server, &mut client_ddata, &mut server_ddata).unwrap();
+
+    assert_eq!(server_ddata.buffers.len(), 2);
+    let buffer = server_ddata.buffers.last().unwrap();
+    assert!(matches!(
+        &server_ddata.surface_requests[0],
+        wl_surface::Request::Attach { buffer: Some(attached), .. } if attached == buffer
+    ));
+}
+
+fn setup(
+    compositor_version: u32,
+) -> (TestServer<ServerHandler>, TestClient<ClientHandler>, ServerHandler, ClientHandler) {
+    let mut server = TestServer::new();
+    server
+        .display
+        .handle()
+        .create_global::<ServerHandler, wl_compositor::WlCompositor, _>(compositor_version, ());
+    server.display.handle().create_global::<ServerHandler, wl_shm::WlShm, _>(1, ());
+    let mut server_ddata = ServerHandler {
+        pools: Vec::new(),
+        destroyed_pools: 0,
+        buffers: Vec::new(),
+        buffer_offsets: Vec::new(),
+        destroyed_buffers: Vec::new(),
+        surface_requests: Vec::new(),
+    };
+
+    let (_, mut client) = server.add_client();
+    let mut client_ddata = ClientHandler { globals: globals::GlobalList::new(), registry: None };
+
+    client_ddata.registry = Some(client.display.get_registry(&client.event_queue.handle(), ()));
+
+    roundtrip(&mut client, &mut server, &mut client_ddata, &mut server_ddata).unwrap();
+
+    (server, client, server_ddata, client_ddata)
+}
+
+fn load_theme(client_ddata: &mut ClientHandler, client: &TestClient<ClientHandler>) -> CursorTheme {
+    let shm = client_ddata
+        .globals
+        .bind::<wayc::protocol::wl_shm::WlShm, _, _>(
+            &client.event_queue.handle(),
+            client_ddata.registry.as_ref().unwrap(),
+            1..2,
+            (),
+        )
+        .unwrap();
+    CursorTheme::load_from_name(&client.conn, shm, "default", 24).unwrap()
+}
+
+// a cursor no system theme provides, so that it is loaded from the fallback
+const FALLBACK_CURSOR: &str = "wayland-rs-test-cursor";
+
+/// Load the image of a cursor provided by the fallback of the theme, with the given hotspot
+fn fallback_image(theme: &mut CursorTheme, (xhot, yhot): (u32, u32)) -> CursorImageBuffer {
+    theme.set_fallback(move |_, _| Some(xcursor_file(xhot, yhot).into()));
+    theme.get_cursor(FALLBACK_CURSOR).unwrap()[0].clone()
+}
+
+/// Contents of an xcursor file with a single transparent 8x8 image, of nominal size 24
+fn xcursor_file(xhot: u32, yhot: u32) -> Vec<u8> {
+    let mut words = vec![
+        // file header: magic, header size, version and number of entries in the table of contents
+        u32::from_le_bytes(*b"Xcur"),
+        16,
+        0x1_0000,
+        1,
+        // table of contents: type, nominal size and position of the image chunk
+        0xfffd_0002,
+        24,
+        28,
+        // image chunk header: header size, type, nominal size and version
+        36,
+        0xfffd_0002,
+        24,
+        1,
+        // width, height, hotspot and delay of the image
+        8,
+        8,
+        xhot,
+        yhot,
+        0,
+    ];
+    words.extend([0; 8 * 8]);
+    words.into_iter().flat_map(u32::to_le_bytes).collect()
+}
+
+fn create_surface(
+    client_ddata: &mut ClientHandler,
+    client: &TestClient<ClientHandler>,
+) -> wayc::protocol::wl_surface::WlSurface {
+    let compositor = client_ddata
+        .globals
+        .bind::<wayc::protocol::wl_compositor::WlCompositor, _, _>(
+            &client.event_queue.handle(),
+            client_ddata.registry.as_ref().unwrap(),
+            1..7,
+            (),
+        )
+        .unwrap();
+    compositor.create_surface(&client.event_queue.handle(), ())
+}
+
+/*
+ * Server Handler
+ */
+
+struct ServerHandler {
+    pools: Vec<File>,
+    destroyed_pools: usize,
+    buffers: Vec<wl_buffer::WlBuffer>,
+    // the pool and offset of each buffer
+    buffer_offsets: Vec<(usize, u64)>,
+    destroyed_buffers: Vec<wl_buffer::WlBuffer>,
+    surface_requests: Vec<wl_surface::Request>,
+}
+
+impl ServerHandler {
+    /// Read the first pixel of the given buffer from its pool
+    fn read_pixel(&self, buffer: usize) -> [u8; 4] {
+        let (pool, offset) = self.buffer_offsets[buffer];
+        let mut pixel = [0; 4];
+        self.pools[pool].read_exact_at(&mut pixel, offset).unwrap();
+        pixel
+    }
+}
+
+impl ways::Dispatch<wl_compositor::WlCompositor, ()> for ServerHandler {
+    fn request(
+        _: &mut Self,
+        _: &ways::Client,
+        _: &wl_compositor::WlCompositor,
+        request: wl_compositor::Request,
+        _: &(),
+        _: &ways::DisplayHandle,
+        init: &mut ways::DataInit<'_, Self>,
+    ) {
+        if let wl_compositor::Request::CreateSurface { id } = request {
+            init.init(id, ());
+        } else {
+            panic!("Unexpected request!");
+        }
+    }
+}
+
+impl ways::Dispatch<wl_surface::WlSurface, ()> for ServerHandler {
+    fn request(
+        state: &mut Self,
+        _: &ways::Client,
+        _: &wl_surface::WlSurface,
+        request: wl_surface::Request,
+        _: &(),
+        _: &ways::DisplayHandle,
+        _: &mut ways::DataInit<'_, Self>,
+    ) {
+        state.surface_requests.push(request);
+    }
+}
+
+impl ways::Dispatch<wl_shm::WlShm, ()> for ServerHandler {
+    fn request(
+        state: &mut Self,
+        _: &ways::Client,
+        _: &wl_shm::WlShm,
+        request: wl_shm::Request,
+        _: &(),
+        _: &ways::DisplayHandle,
+        init: &mut ways::DataInit<'_, Self>,
+    ) {
+        if let wl_shm::Request::CreatePool { id, fd, .. } = request {
+            init.init(id, state.pools.len());
+            state.pools.push(File::from(fd));
+        } else {
+            panic!("Unexpected request!");
+        }
+    }
+}
+
+impl ways::Dispatch<wl_shm_pool::WlShmPool, usize> for ServerHandler {
+    fn request(
+        state: &mut Self,
+        _: &ways::Client,
+        _: &wl_shm_pool::WlShmPool,
+        request: wl_shm_pool::Request,
+        pool: &usize,
+        _: &ways::DisplayHandle,
+        init: &mut ways::DataInit<'_, Self>,
+    ) {
+        match request {
+            wl_shm_pool::Request::CreateBuffer { id, offset, .. } => {
+                state.buffers.push(init.init(id, ()));
+                state.buffer_offsets.push((*pool, offset as u64));
+            }
+            wl_shm_pool::Request::Destroy => state.destroyed_pools += 1,
+            _ => {}
+        }
+    }
+}
+
+impl ways::Dispatch<wl_buffer::WlBuffer, ()> for ServerHandler {
+    fn request(
+        state: &mut Self,
+        _: &ways::Client,
+        buffer: &wl_buffer::WlBuffer,
+        request: wl_buffer::Request,
+        _: &(),
+        _: &ways::DisplayHandle,
+        _: &mut ways::DataInit<'_, Self>,
+    ) {
+        if let wl_buffer::Request::Destroy = request {
+            state.destroyed_buffers.push(buffer.clone());
+        }
+    }
+}
+
+server_ignore_global_impl!(ServerHandler => [
+    wl_shm::WlShm,
+    wl_compositor::WlCompositor
+]);
+
+/*
+ * Client Handler
+ */
+
+struct ClientHandler {
+    globals: globals::GlobalList,
+    registry: Option<wayc::protocol::wl_registry::WlRegistry>,
+}
+
+impl AsMut<globals::GlobalList> for ClientHandler {
+    fn as_mut(&mut self) -> &mut globals::GlobalList {
+        &mut self.globals
+    }
+}
+
+wayc::delegate_dispatch!(ClientHandler:
+    [wayc::protocol::wl_registry::WlRegistry: ()] => globals::GlobalList
+);
+
+client_ignore_impl!(ClientHandler => [
+    wayc::protocol::wl_compositor::WlCompositor,
+    wayc::protocol::wl_surface::WlSurface,
+    wayc::protocol::wl_shm::WlShm
+]);
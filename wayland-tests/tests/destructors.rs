@@ -5,7 +5,7 @@ use helpers::{globals, roundtrip, wayc, ways, TestServer};
 
 use std::sync::{
     atomic::{AtomicBool, Ordering},
-    Arc,
+    Arc, Mutex,
 };
 
 #[test]
@@ -116,6 +116,106 @@ fn client_destructor_cleanup() {
     assert!(destructor_called.load(Ordering::Acquire));
 }
 
+#[test]
+fn client_object_hooks() {
+    use wayc::Proxy;
+
+    let mut server = TestServer::new();
+    server
+        .display
+        .handle()
+        .create_global::<ServerHandler, ways::protocol::wl_output::WlOutput, _>(3, ());
+    let mut server_ddata = ServerHandler { destructor_called: Arc::new(AtomicBool::new(false)) };
+
+    let (_, mut client) = server.add_client();
+    let mut client_ddata = ClientHandler::new();
+
+    let created = Arc::new(Mutex::new(Vec::new()));
+    let destroyed = Arc::new(Mutex::new(Vec::new()));
+    let created2 = created.clone();
+    client.conn.on_object_created(move |id| created2.lock().unwrap().push(id));
+    let destroyed2 = destroyed.clone();
+    client.conn.on_object_destroyed(move |id| destroyed2.lock().unwrap().push(id));
+
+    let registry = client.display.get_registry(&client.event_queue.handle(), ());
+
+    roundtrip(&mut client, &mut server, &mut client_ddata, &mut server_ddata).unwrap();
+
+    let output = client_ddata
+        .globals
+        .bind::<wayc::protocol::wl_output::WlOutput, _, _>(
+            &client.event_queue.handle(),
+            &registry,
+            3..4,
+            (),
+        )
+        .unwrap();
+
+    assert!(created.lock().unwrap().contains(&registry.id()));
+    assert!(created.lock().unwrap().contains(&output.id()));
+    assert!(!destroyed.lock().unwrap().contains(&output.id()));
+
+    output.release();
+
+    assert!(destroyed.lock().unwrap().contains(&output.id()));
+    assert!(!destroyed.lock().unwrap().contains(&registry.id()));
+}
+
+#[test]
+fn client_spy_queue() {
+    use wayc::Proxy;
+
+    let mut server = TestServer::new();
+    server
+        .display
+        .handle()
+        .create_global::<ServerHandler, ways::protocol::wl_output::WlOutput, _>(3, ());
+    let mut server_ddata = ServerHandler { destructor_called: Arc::new(AtomicBool::new(false)) };
+
+    let (_, mut client) = server.add_client();
+    let mut client_ddata = ClientHandler::new();
+
+    let observed = Arc::new(Mutex::new(Vec::new()));
+    let observed2 = observed.clone();
+    let spy = client.conn.new_spy_queue(move |msg| {
+        observed2.lock().unwrap().push((msg.sender_id.clone(), msg.opcode));
+    });
+
+    let registry = client.display.get_registry(&client.event_queue.handle(), ());
+
+    roundtrip(&mut client, &mut server, &mut client_ddata, &mut server_ddata).unwrap();
+
+    // the global event was observed and still dispatched normally
+    assert!(observed.lock().unwrap().contains(&(registry.id(), 0)));
+    assert_eq!(client_ddata.globals.list().len(), 1);
+
+    // the observer is uninstalled and released once the spy queue is dropped
+    drop(spy);
+    assert_eq!(Arc::strong_count(&observed), 1);
+    let count = observed.lock().unwrap().len();
+    client_ddata
+        .globals
+        .bind::<wayc::protocol::wl_output::WlOutput, _, _>(
+            &client.event_queue.handle(),
+            &registry,
+            3..4,
+            (),
+        )
+        .unwrap();
+    roundtrip(&mut client, &mut server, &mut client_ddata, &mut server_ddata).unwrap();
+    assert_eq!(observed.lock().unwrap().len(), count);
+
+    // dropping a replaced spy queue does not remove the one that replaced it
+    let replaced = client.conn.new_spy_queue(|_| {});
+    let observed2 = observed.clone();
+    let _spy = client.conn.new_spy_queue(move |msg| {
+        observed2.lock().unwrap().push((msg.sender_id.clone(), msg.opcode));
+    });
+    drop(replaced);
+    roundtrip(&mut client, &mut server, &mut client_ddata, &mut server_ddata).unwrap();
+    assert!(observed.lock().unwrap().len() > count);
+}
+
 struct DestructorClientData(Arc<AtomicBool>);
 
 impl ways::backend::ClientData for DestructorClientData {
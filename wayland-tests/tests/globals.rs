@@ -1,6 +1,8 @@
 #[macro_use]
 mod helpers;
 
+use std::time::Duration;
+
 use helpers::{globals, roundtrip, wayc, ways, TestServer};
 
 use ways::protocol::wl_compositor::WlCompositor as ServerCompositor;
@@ -15,7 +17,7 @@ fn simple_global() {
     let (_, mut client) = server.add_client();
     let mut client_ddata = ClientHandler { globals: globals::GlobalList::new() };
 
-    client.display.get_registry(&client.event_queue.handle(), ());
+    let _registry = client.display.get_registry(&client.event_queue.handle(), ());
 
     roundtrip(&mut client, &mut server, &mut client_ddata, &mut ServerHandler).unwrap();
 
@@ -26,6 +28,26 @@ fn simple_global() {
     assert_eq!(globals[0].version, 1);
 }
 
+#[test]
+fn global_over_test_pair() {
+    let (display, _, conn) = ways::Display::<ServerHandler>::new_test_pair().unwrap();
+    let mut server = TestServer { display };
+    server.display.handle().create_global::<ServerHandler, ServerCompositor, _>(1, ());
+
+    let event_queue = conn.new_event_queue();
+    let display = conn.display();
+    let mut client = helpers::TestClient { conn, display, event_queue };
+    let mut client_ddata = ClientHandler { globals: globals::GlobalList::new() };
+
+    let _registry = client.display.get_registry(&client.event_queue.handle(), ());
+
+    roundtrip(&mut client, &mut server, &mut client_ddata, &mut ServerHandler).unwrap();
+
+    let globals = client_ddata.globals.list();
+    assert_eq!(globals.len(), 1);
+    assert_eq!(globals[0].interface, "wl_compositor");
+}
+
 #[test]
 fn multi_versions() {
     let mut server = TestServer::new();
@@ -37,7 +59,7 @@ fn multi_versions() {
     let (_, mut client) = server.add_client();
     let mut client_ddata = ClientHandler { globals: globals::GlobalList::new() };
 
-    client.display.get_registry(&client.event_queue.handle(), ());
+    let _registry = client.display.get_registry(&client.event_queue.handle(), ());
 
     roundtrip(&mut client, &mut server, &mut client_ddata, &mut ServerHandler).unwrap();
 
@@ -51,6 +73,78 @@ fn multi_versions() {
     assert_eq!(seen, [true, true, true, true]);
 }
 
+#[test]
+fn batch_globals() {
+    let mut server = TestServer::new();
+    let ids = server.display.handle().create_globals::<ServerHandler, ServerCompositor, _>([
+        (1, ()),
+        (2, ()),
+        (3, ()),
+    ]);
+    assert_eq!(ids.len(), 3);
+
+    let (_, mut client) = server.add_client();
+    let mut client_ddata = ClientHandler { globals: globals::GlobalList::new() };
+
+    let _registry = client.display.get_registry(&client.event_queue.handle(), ());
+
+    roundtrip(&mut client, &mut server, &mut client_ddata, &mut ServerHandler).unwrap();
+
+    let globals = client_ddata.globals.list();
+    assert_eq!(globals.len(), 3);
+    for (global, id) in globals.iter().zip(&ids) {
+        assert_eq!(global.interface, "wl_compositor");
+        let info = server.display.handle().backend_handle().global_info(id.clone()).unwrap();
+        assert_eq!(global.version, info.version);
+    }
+}
+
+#[test]
+fn bind_interceptor() {
+    use std::sync::{Arc, Mutex};
+    use wayc::Proxy;
+    use ways::Resource;
+
+    let mut server = TestServer::new();
+    let output_global =
+        server.display.handle().create_global::<ServerHandler, ServerOutput, _>(3, ());
+    let intercepted = Arc::new(Mutex::new(Vec::new()));
+    let intercepted2 = intercepted.clone();
+    server.display.backend().set_bind_interceptor(move |_, global, version| {
+        intercepted2.lock().unwrap().push((global, version));
+        // pretend version 3 is not rolled out yet
+        version.min(2)
+    });
+
+    let (server_client, mut client) = server.add_client();
+    let mut client_ddata = ClientHandler { globals: globals::GlobalList::new() };
+
+    let registry = client.display.get_registry(&client.event_queue.handle(), ());
+
+    roundtrip(&mut client, &mut server, &mut client_ddata, &mut ServerHandler).unwrap();
+
+    let output = client_ddata
+        .globals
+        .bind::<wayc::protocol::wl_output::WlOutput, _, _>(
+            &client.event_queue.handle(),
+            &registry,
+            3..4,
+            (),
+        )
+        .unwrap();
+
+    roundtrip(&mut client, &mut server, &mut client_ddata, &mut ServerHandler).unwrap();
+
+    assert_eq!(*intercepted.lock().unwrap(), [(output_global, 3)]);
+    let server_output = server_client
+        .object_from_protocol_id::<ServerOutput>(
+            &server.display.handle(),
+            output.id().protocol_id(),
+        )
+        .unwrap();
+    assert_eq!(server_output.version(), 2);
+}
+
 #[test]
 fn dynamic_global() {
     let mut server = TestServer::new();
@@ -59,7 +153,7 @@ fn dynamic_global() {
     let (_, mut client) = server.add_client();
     let mut client_ddata = ClientHandler { globals: globals::GlobalList::new() };
 
-    client.display.get_registry(&client.event_queue.handle(), ());
+    let _registry = client.display.get_registry(&client.event_queue.handle(), ());
 
     roundtrip(&mut client, &mut server, &mut client_ddata, &mut ServerHandler).unwrap();
     assert!(client_ddata.globals.list().len() == 1);
@@ -80,6 +174,38 @@ fn dynamic_global() {
     assert!(client_ddata.globals.list().len() == 2);
 }
 
+#[test]
+fn retire_global() {
+    let mut server = TestServer::new();
+    let output = server.display.handle().create_global::<ServerHandler, ServerOutput, _>(1, ());
+
+    let (_, mut client) = server.add_client();
+    let mut client_ddata = ClientHandler { globals: globals::GlobalList::new() };
+
+    let _registry = client.display.get_registry(&client.event_queue.handle(), ());
+
+    roundtrip(&mut client, &mut server, &mut client_ddata, &mut ServerHandler).unwrap();
+    assert!(client_ddata.globals.list().len() == 1);
+
+    // a long delay: the removal is announced right away, but the global is not freed yet
+    server
+        .display
+        .handle()
+        .retire_global::<ServerHandler>(output.clone(), Duration::from_secs(3600));
+
+    roundtrip(&mut client, &mut server, &mut client_ddata, &mut ServerHandler).unwrap();
+    assert!(client_ddata.globals.list().is_empty());
+    let info = server.display.handle().backend_handle().global_info(output.clone()).unwrap();
+    assert!(info.disabled);
+
+    // retiring again replaces the deadline, the global is freed on the next dispatch
+    server.display.handle().retire_global::<ServerHandler>(output.clone(), Duration::ZERO);
+    assert!(server.display.handle().backend_handle().global_info(output.clone()).is_ok());
+
+    server.display.dispatch_clients(&mut ServerHandler).unwrap();
+    assert!(server.display.handle().backend_handle().global_info(output).is_err());
+}
+
 #[test]
 #[should_panic]
 fn wrong_version_create_global() {
@@ -87,6 +213,19 @@ fn wrong_version_create_global() {
     server.display.handle().create_global::<ServerHandler, ServerCompositor, _>(42, ());
 }
 
+#[test]
+fn wrong_version_try_create_global() {
+    let server = TestServer::<ServerHandler>::new();
+    let handle = server.display.handle();
+    let err = handle.try_create_global::<ServerHandler, ServerCompositor, _>(42, ()).unwrap_err();
+    assert!(matches!(
+        err,
+        ways::backend::GlobalError::UnsupportedVersion { version: 42, interface }
+            if interface.name == "wl_compositor"
+    ));
+    assert!(handle.try_create_global::<ServerHandler, ServerCompositor, _>(1, ()).is_ok());
+}
+
 #[test]
 fn wrong_global() {
     use wayc::protocol::wl_output::WlOutput;
@@ -102,7 +241,7 @@ fn wrong_global() {
     // instantiate a wrong global, this should kill the client
     // but currently does not fail on native_lib
 
-    registry.bind::<WlOutput, _, _>(1, 1, &client.event_queue.handle(), ());
+    let _global = registry.bind::<WlOutput, _, _>(1, 1, &client.event_queue.handle(), ());
 
     assert!(roundtrip(&mut client, &mut server, &mut client_ddata, &mut ServerHandler).is_err());
 }
@@ -121,7 +260,7 @@ fn wrong_global_version() {
 
     // instantiate a global with wrong version, this should kill the client
 
-    registry.bind::<WlCompositor, _, _>(1, 2, &client.event_queue.handle(), ());
+    let _global = registry.bind::<WlCompositor, _, _>(1, 2, &client.event_queue.handle(), ());
 
     assert!(roundtrip(&mut client, &mut server, &mut client_ddata, &mut ServerHandler).is_err());
 }
@@ -140,7 +279,7 @@ fn invalid_global_version() {
 
     // instantiate a global with version 0, which is invalid this should kill the client
 
-    registry.bind::<WlCompositor, _, _>(1, 0, &client.event_queue.handle(), ());
+    let _global = registry.bind::<WlCompositor, _, _>(1, 0, &client.event_queue.handle(), ());
 
     assert!(roundtrip(&mut client, &mut server, &mut client_ddata, &mut ServerHandler).is_err());
 }
@@ -159,7 +298,7 @@ fn wrong_global_id() {
 
     // instantiate a global with version 0, which is invalid this should kill the client
 
-    registry.bind::<WlCompositor, _, _>(3, 1, &client.event_queue.handle(), ());
+    let _global = registry.bind::<WlCompositor, _, _>(3, 1, &client.event_queue.handle(), ());
 
     assert!(roundtrip(&mut client, &mut server, &mut client_ddata, &mut ServerHandler).is_err());
 }
@@ -196,6 +335,34 @@ fn two_step_binding() {
     roundtrip(&mut client, &mut server, &mut client_ddata, &mut ServerHandler).unwrap();
 }
 
+#[test]
+fn bind_requested_version() {
+    let mut server = TestServer::new();
+    server.display.handle().create_global::<VersionHandler, ServerOutput, _>(3, ());
+    let mut server_ddata = VersionHandler { bound_versions: Vec::new() };
+
+    let (_, mut client) = server.add_client();
+    let mut client_ddata = ClientHandler { globals: globals::GlobalList::new() };
+
+    let registry = client.display.get_registry(&client.event_queue.handle(), ());
+
+    roundtrip(&mut client, &mut server, &mut client_ddata, &mut server_ddata).unwrap();
+
+    // the client binds a lower version than the one of the global
+    let global = &client_ddata.globals.list()[0];
+    assert_eq!(global.version, 3);
+    let _output = registry.bind::<wayc::protocol::wl_output::WlOutput, _, ClientHandler>(
+        global.name,
+        2,
+        &client.event_queue.handle(),
+        (),
+    );
+
+    roundtrip(&mut client, &mut server, &mut client_ddata, &mut server_ddata).unwrap();
+
+    assert_eq!(server_ddata.bound_versions, [(2, 2)]);
+}
+
 struct ServerHandler;
 
 server_ignore_impl!(ServerHandler => [ServerCompositor, ServerShell, ServerOutput]);
@@ -220,3 +387,27 @@ client_ignore_impl!(ClientHandler => [
     wayc::protocol::wl_shell::WlShell,
     wayc::protocol::wl_output::WlOutput
 ]);
+
+struct VersionHandler {
+    // the versions of the new object before and after its initialization
+    bound_versions: Vec<(u32, u32)>,
+}
+
+server_ignore_impl!(VersionHandler => [ServerOutput]);
+
+impl ways::GlobalDispatch<ServerOutput, ()> for VersionHandler {
+    fn bind(
+        state: &mut Self,
+        _: &ways::DisplayHandle,
+        _: &ways::Client,
+        output: ways::New<ServerOutput>,
+        _: &(),
+        data_init: &mut ways::DataInit<'_, Self>,
+    ) {
+        use ways::Resource;
+
+        let requested = output.version();
+        let output = data_init.init(output, ());
+        state.bound_versions.push((requested, output.version()));
+    }
+}
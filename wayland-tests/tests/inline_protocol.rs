@@ -0,0 +1,130 @@
+#[macro_use]
+mod helpers;
+
+use helpers::{globals, roundtrip, wayc, ways, TestServer};
+
+// expands the given scanner macro on the inline protocol
+macro_rules! inline_protocol {
+    ($generate:path) => {
+        $generate!(
+            inline = r#"
+            <protocol name="inline_test">
+                <interface name="test_ping" version="1">
+                    <request name="ping">
+                        <arg name="serial" type="uint"/>
+                    </request>
+                    <event name="pong">
+                        <arg name="serial" type="uint"/>
+                    </event>
+                </interface>
+            </protocol>
+            "#
+        );
+    };
+}
+
+mod protocol {
+    pub mod __interfaces {
+        inline_protocol!(wayland_scanner::generate_interfaces);
+    }
+
+    pub mod client {
+        use super::__interfaces::*;
+        use crate::helpers::wayc as wayland_client;
+
+        inline_protocol!(wayland_scanner::generate_client_code);
+    }
+
+    pub mod server {
+        use super::__interfaces::*;
+        use crate::helpers::ways as wayland_server;
+
+        inline_protocol!(wayland_scanner::generate_server_code);
+    }
+}
+
+use protocol::{client::test_ping as client_ping, server::test_ping as server_ping};
+
+#[test]
+fn inline_protocol_roundtrip() {
+    let mut server = TestServer::new();
+    server.display.handle().create_global::<ServerHandler, server_ping::TestPing, _>(1, ());
+    let mut server_ddata = ServerHandler { pings: Vec::new() };
+
+    let (_, mut client) = server.add_client();
+    let mut client_ddata = ClientHandler { globals: globals::GlobalList::new(), pongs: Vec::new() };
+
+    let registry = client.display.get_registry(&client.event_queue.handle(), ());
+
+    roundtrip(&mut client, &mut server, &mut client_ddata, &mut server_ddata).unwrap();
+
+    let ping = client_ddata
+        .globals
+        .bind::<client_ping::TestPing, _, _>(&client.event_queue.handle(), &registry, 1..2, ())
+        .unwrap();
+    ping.ping(42);
+
+    roundtrip(&mut client, &mut server, &mut client_ddata, &mut server_ddata).unwrap();
+
+    assert_eq!(server_ddata.pings, vec![42]);
+    assert_eq!(client_ddata.pongs, vec![42]);
+}
+
+/*
+ * Server Handler
+ */
+
+struct ServerHandler {
+    pings: Vec<u32>,
+}
+
+impl ways::Dispatch<server_ping::TestPing, ()> for ServerHandler {
+    fn request(
+        state: &mut Self,
+        _: &ways::Client,
+        resource: &server_ping::TestPing,
+        request: server_ping::Request,
+        _: &(),
+        _: &ways::DisplayHandle,
+        _: &mut ways::DataInit<'_, Self>,
+    ) {
+        let server_ping::Request::Ping { serial } = request;
+        state.pings.push(serial);
+        resource.pong(serial);
+    }
+}
+
+server_ignore_global_impl!(ServerHandler => [server_ping::TestPing]);
+
+/*
+ * Client Handler
+ */
+
+struct ClientHandler {
+    globals: globals::GlobalList,
+    pongs: Vec<u32>,
+}
+
+impl AsMut<globals::GlobalList> for ClientHandler {
+    fn as_mut(&mut self) -> &mut globals::GlobalList {
+        &mut self.globals
+    }
+}
+
+wayc::delegate_dispatch!(ClientHandler:
+    [wayc::protocol::wl_registry::WlRegistry: ()] => globals::GlobalList
+);
+
+impl wayc::Dispatch<client_ping::TestPing, ()> for ClientHandler {
+    fn event(
+        state: &mut Self,
+        _: &client_ping::TestPing,
+        event: client_ping::Event,
+        _: &(),
+        _: &wayc::Connection,
+        _: &wayc::QueueHandle<Self>,
+    ) {
+        let client_ping::Event::Pong { serial } = event;
+        state.pongs.push(serial);
+    }
+}
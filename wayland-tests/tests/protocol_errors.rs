@@ -50,13 +50,108 @@ fn client_receive_generic_error() {
     assert_eq!(error.code, 42);
     assert_eq!(error.object_id, 3);
     assert_eq!(error.object_interface, "wl_compositor");
-    // native lib can't give us the message
+    // native lib can't give us the message nor the interface description
     #[cfg(not(feature = "client_system"))]
     {
         assert_eq!(error.message, "I don't like you!");
+        assert_eq!(
+            client.conn.backend().protocol_error_interface().map(|iface| iface.name),
+            Some("wl_compositor")
+        );
     }
 }
 
+#[test]
+fn client_receive_display_error() {
+    let mut server = TestServer::new();
+
+    let (s_client, mut client) = server.add_client();
+
+    let mut client_ddata = ClientHandler::new();
+
+    let _registry = client.display.get_registry(&client.event_queue.handle(), ());
+
+    roundtrip(&mut client, &mut server, &mut client_ddata, &mut ServerHandler).unwrap();
+
+    s_client.post_display_error(&server.display.handle(), 7, "Go away!".into());
+
+    assert!(roundtrip(&mut client, &mut server, &mut client_ddata, &mut ServerHandler).is_err());
+    // the native lib does not report errors on the display object as protocol errors
+    #[cfg(not(feature = "client_system"))]
+    {
+        let error = client.conn.protocol_error().unwrap();
+        assert_eq!(error.code, 7);
+        assert_eq!(error.object_id, 1);
+        assert_eq!(error.object_interface, "wl_display");
+        assert_eq!(error.message, "Go away!");
+    }
+}
+
+#[test]
+fn display_error_with_nul_byte() {
+    let mut server = TestServer::new();
+
+    let (s_client, mut client) = server.add_client();
+
+    let mut client_ddata = ClientHandler::new();
+
+    let _registry = client.display.get_registry(&client.event_queue.handle(), ());
+
+    roundtrip(&mut client, &mut server, &mut client_ddata, &mut ServerHandler).unwrap();
+
+    // the nul byte cannot be sent, it is dropped rather than panicking
+    s_client.post_display_error(&server.display.handle(), 7, "Go\0 away!".into());
+
+    assert!(roundtrip(&mut client, &mut server, &mut client_ddata, &mut ServerHandler).is_err());
+    #[cfg(not(feature = "client_system"))]
+    assert_eq!(client.conn.protocol_error().unwrap().message, "Go away!");
+}
+
+#[test]
+fn client_protocol_kill() {
+    let mut server = TestServer::new();
+    server
+        .display
+        .handle()
+        .create_global::<ServerHandler, ways::protocol::wl_compositor::WlCompositor, _>(1, ());
+
+    let (s_client, mut client) = server.add_client();
+
+    let mut client_ddata = ClientHandler::new();
+
+    let registry = client.display.get_registry(&client.event_queue.handle(), ());
+
+    roundtrip(&mut client, &mut server, &mut client_ddata, &mut ServerHandler).unwrap();
+
+    client_ddata
+        .globals
+        .bind::<wayc::protocol::wl_compositor::WlCompositor, _, _>(
+            &client.event_queue.handle(),
+            &registry,
+            1..2,
+            (),
+        )
+        .unwrap();
+
+    roundtrip(&mut client, &mut server, &mut client_ddata, &mut ServerHandler).unwrap();
+
+    let compositor = s_client
+        .object_from_protocol_id::<ways::protocol::wl_compositor::WlCompositor>(
+            &server.display.handle(),
+            3,
+        )
+        .unwrap();
+    s_client.protocol_kill(&server.display.handle(), &compositor, 12, "Bad compositor use".into());
+
+    assert!(roundtrip(&mut client, &mut server, &mut client_ddata, &mut ServerHandler).is_err());
+    let error = client.conn.protocol_error().unwrap();
+    assert_eq!(error.code, 12);
+    assert_eq!(error.object_id, 3);
+    assert_eq!(error.object_interface, "wl_compositor");
+    #[cfg(not(feature = "client_system"))]
+    assert_eq!(error.message, "Bad compositor use");
+}
+
 struct ClientHandler {
     globals: globals::GlobalList,
 }
@@ -48,6 +48,8 @@ fn client_user_data() {
         let cdata = s_client.get_data::<MyClientData>().unwrap();
         assert!(cdata.has_output.load(Ordering::SeqCst));
         assert!(!cdata.has_compositor.load(Ordering::SeqCst));
+        assert!(std::ptr::eq(cdata, s_client.data::<MyClientData>().unwrap()));
+        assert!(s_client.data::<helpers::DumbClientData>().is_none());
     }
 
     client_ddata
@@ -69,6 +71,27 @@ fn client_user_data() {
     }
 }
 
+#[test]
+fn display_clients() {
+    let mut server = TestServer::<()>::new();
+
+    assert_eq!(server.display.handle().clients().count(), 0);
+
+    let (s_client_1, _client_1) = server.add_client::<()>();
+    let (s_client_2, client_2) = server.add_client::<()>();
+
+    let ids = server.display.handle().clients().map(|c| c.id()).collect::<Vec<_>>();
+    assert_eq!(ids.len(), 2);
+    assert!(ids.contains(&s_client_1.id()));
+    assert!(ids.contains(&s_client_2.id()));
+
+    std::mem::drop(client_2);
+    server.answer(&mut ());
+
+    let ids = server.display.handle().clients().map(|c| c.id()).collect::<Vec<_>>();
+    assert_eq!(ids, vec![s_client_1.id()]);
+}
+
 #[test]
 fn client_credentials() {
     let mut server = TestServer::<()>::new();
@@ -80,6 +103,110 @@ fn client_credentials() {
     assert_credentials(credentials.unwrap());
 }
 
+#[cfg(any(target_os = "linux", target_os = "android"))]
+#[test]
+fn client_security_context() {
+    let mut server = TestServer::<()>::new();
+
+    let (s_client, client) = server.add_client::<()>();
+
+    let handle = server.display.handle().backend_handle();
+    // whether a label is available depends on the security modules of the host
+    if let Some(label) = handle.get_client_security_context(s_client.id()).unwrap() {
+        assert!(!label.is_empty());
+    }
+
+    std::mem::drop(client);
+    server.answer(&mut ());
+
+    let err = handle.get_client_security_context(s_client.id()).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::NotFound);
+}
+
+// libwayland-server cannot stop reading from a client
+#[cfg(not(feature = "server_system"))]
+#[test]
+fn client_paused() {
+    let mut server = TestServer::new();
+    server
+        .display
+        .handle()
+        .create_global::<ServerHandler, ways::protocol::wl_output::WlOutput, _>(1, ());
+    let mut server_ddata = ServerHandler {};
+
+    let (s_client, mut client) = server.add_client();
+    let mut client_ddata = ClientHandler::new();
+
+    server.display.handle().set_client_paused(&s_client, true).unwrap();
+
+    let _registry = client.display.get_registry(&client.event_queue.handle(), ());
+    client.conn.flush().unwrap();
+
+    // the request stays buffered, so no global is advertised
+    server.answer(&mut server_ddata);
+    if let Some(guard) = client.conn.prepare_read() {
+        let _ = guard.read();
+    }
+    client.event_queue.dispatch_pending(&mut client_ddata).unwrap();
+    assert!(client_ddata.globals.list().is_empty());
+
+    server.display.handle().set_client_paused(&s_client, false).unwrap();
+
+    roundtrip(&mut client, &mut server, &mut client_ddata, &mut server_ddata).unwrap();
+    assert_eq!(client_ddata.globals.list().len(), 1);
+    assert_eq!(client_ddata.globals.list()[0].interface, "wl_output");
+}
+
+// libwayland-server always dispatches all clients
+#[cfg(not(feature = "server_system"))]
+#[test]
+fn dispatch_single_client() {
+    let mut server = TestServer::new();
+    server
+        .display
+        .handle()
+        .create_global::<ServerHandler, ways::protocol::wl_output::WlOutput, _>(1, ());
+    let mut server_ddata = ServerHandler {};
+
+    let (s_client_1, mut client_1) = server.add_client();
+    let mut client_ddata_1 = ClientHandler::new();
+    let (_, mut client_2) = server.add_client();
+    let mut client_ddata_2 = ClientHandler::new();
+
+    let _registry_1 = client_1.display.get_registry(&client_1.event_queue.handle(), ());
+    client_1.conn.flush().unwrap();
+    let _registry_2 = client_2.display.get_registry(&client_2.event_queue.handle(), ());
+    client_2.conn.flush().unwrap();
+
+    // only the requests of the first client are dispatched
+    server.display.dispatch_client(&mut server_ddata, &s_client_1).unwrap();
+    server.display.flush_clients().unwrap();
+
+    for (client, ddata) in
+        [(&mut client_1, &mut client_ddata_1), (&mut client_2, &mut client_ddata_2)]
+    {
+        if let Some(guard) = client.conn.prepare_read() {
+            let _ = guard.read();
+        }
+        client.event_queue.dispatch_pending(ddata).unwrap();
+    }
+    assert_eq!(client_ddata_1.globals.list().len(), 1);
+    assert!(client_ddata_2.globals.list().is_empty());
+
+    roundtrip(&mut client_2, &mut server, &mut client_ddata_2, &mut server_ddata).unwrap();
+    assert_eq!(client_ddata_2.globals.list().len(), 1);
+}
+
+#[test]
+fn dispatch_single_client_without_requests() {
+    let mut server = TestServer::<ServerHandler>::new();
+    let mut server_ddata = ServerHandler {};
+
+    let (s_client, _client) = server.add_client::<ClientHandler>();
+
+    assert_eq!(server.display.dispatch_client(&mut server_ddata, &s_client).unwrap(), 0);
+}
+
 #[cfg(any(not(feature = "server_system"), not(target_os = "freebsd")))]
 fn assert_credentials(credentials: ways::backend::Credentials) {
     assert!(credentials.pid != 0);
@@ -40,7 +40,7 @@ fn data_offer() {
         .bind::<ClientDDMgr, _, _>(&client.event_queue.handle(), &registry, 3..4, ())
         .unwrap();
 
-    ddmgr.get_data_device(&seat, &client.event_queue.handle(), ());
+    let _data_device = ddmgr.get_data_device(&seat, &client.event_queue.handle(), ());
 
     roundtrip(&mut client, &mut server, &mut client_ddata, &mut server_ddata).unwrap();
 
@@ -54,6 +54,8 @@ fn data_offer() {
         )
         .unwrap();
     assert_eq!(offer.id().protocol_id(), 0xFF000000);
+    assert!(offer.is_server_created());
+    assert!(!server_dd.is_server_created());
     server_dd.data_offer(&offer);
 
     roundtrip(&mut client, &mut server, &mut client_ddata, &mut server_ddata).unwrap();
@@ -86,7 +88,7 @@ fn server_id_reuse() {
         .bind::<ClientDDMgr, _, _>(&client.event_queue.handle(), &registry, 3..4, ())
         .unwrap();
 
-    ddmgr.get_data_device(&seat, &client.event_queue.handle(), ());
+    let _data_device = ddmgr.get_data_device(&seat, &client.event_queue.handle(), ());
 
     roundtrip(&mut client, &mut server, &mut client_ddata, &mut server_ddata).unwrap();
 
@@ -167,7 +169,7 @@ fn server_created_race() {
         .bind::<ClientDDMgr, _, _>(&client.event_queue.handle(), &registry, 3..4, ())
         .unwrap();
 
-    ddmgr.get_data_device(&seat, &client.event_queue.handle(), ());
+    let _data_device = ddmgr.get_data_device(&seat, &client.event_queue.handle(), ());
 
     roundtrip(&mut client, &mut server, &mut client_ddata, &mut server_ddata).unwrap();
 
@@ -236,7 +238,7 @@ fn creation_destruction_race() {
     roundtrip(&mut client, &mut server, &mut client_ddata, &mut server_ddata).unwrap();
     let s_dd1 = server_ddata.data_device.take().unwrap();
 
-    ddmgr.get_data_device(&seat, &client.event_queue.handle(), ());
+    let _data_device = ddmgr.get_data_device(&seat, &client.event_queue.handle(), ());
     roundtrip(&mut client, &mut server, &mut client_ddata, &mut server_ddata).unwrap();
     let s_dd2 = server_ddata.data_device.take().unwrap();
 
@@ -0,0 +1,111 @@
+#[macro_use]
+mod helpers;
+
+use std::os::unix::io::OwnedFd;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use helpers::{globals, roundtrip, wayc, ways, TestServer};
+
+use ways::backend::{protocol::Message, ClientId, Handle, ObjectData, ObjectId};
+
+use wayc::protocol::wl_output::WlOutput as ClientOutput;
+
+#[test]
+fn global_bind_custom_init() {
+    let mut server = TestServer::new();
+    server
+        .display
+        .handle()
+        .create_global::<ServerHandler, ways::protocol::wl_output::WlOutput, _>(3, ());
+    let raw_data = Arc::new(RawOutputData { released: AtomicBool::new(false) });
+    let mut server_ddata = ServerHandler { raw_data: raw_data.clone() };
+
+    let (_, mut client) = server.add_client();
+    let mut client_ddata = ClientHandler::new();
+
+    let registry = client.display.get_registry(&client.event_queue.handle(), ());
+
+    roundtrip(&mut client, &mut server, &mut client_ddata, &mut server_ddata).unwrap();
+
+    let client_output = client_ddata
+        .globals
+        .bind::<ClientOutput, _, _>(&client.event_queue.handle(), &registry, 3..4, ())
+        .unwrap();
+
+    roundtrip(&mut client, &mut server, &mut client_ddata, &mut server_ddata).unwrap();
+    assert!(!raw_data.released.load(Ordering::SeqCst));
+
+    // the release request is routed to the custom object data
+    client_output.release();
+
+    roundtrip(&mut client, &mut server, &mut client_ddata, &mut server_ddata).unwrap();
+    assert!(raw_data.released.load(Ordering::SeqCst));
+}
+
+struct ClientHandler {
+    globals: globals::GlobalList,
+}
+
+impl ClientHandler {
+    fn new() -> ClientHandler {
+        ClientHandler { globals: Default::default() }
+    }
+}
+
+impl AsMut<globals::GlobalList> for ClientHandler {
+    fn as_mut(&mut self) -> &mut globals::GlobalList {
+        &mut self.globals
+    }
+}
+
+wayc::delegate_dispatch!(ClientHandler:
+    [wayc::protocol::wl_registry::WlRegistry: ()] => globals::GlobalList
+);
+
+client_ignore_impl!(ClientHandler => [ClientOutput]);
+
+struct ServerHandler {
+    raw_data: Arc<RawOutputData>,
+}
+
+impl ways::GlobalDispatch<ways::protocol::wl_output::WlOutput, ()> for ServerHandler {
+    fn bind(
+        state: &mut Self,
+        _handle: &ways::DisplayHandle,
+        _client: &ways::Client,
+        resource: ways::New<ways::protocol::wl_output::WlOutput>,
+        _global_data: &(),
+        data_init: &mut ways::DataInit<'_, Self>,
+    ) {
+        data_init.custom_init(resource, state.raw_data.clone());
+    }
+}
+
+struct RawOutputData {
+    released: AtomicBool,
+}
+
+impl ObjectData<ServerHandler> for RawOutputData {
+    fn request(
+        self: Arc<Self>,
+        _handle: &Handle,
+        _data: &mut ServerHandler,
+        _client_id: ClientId,
+        msg: Message<ObjectId, OwnedFd>,
+    ) -> Option<Arc<dyn ObjectData<ServerHandler>>> {
+        // wl_output.release is the only request of wl_output
+        assert_eq!(msg.opcode, 0);
+        self.released.store(true, Ordering::SeqCst);
+        None
+    }
+
+    fn destroyed(
+        self: Arc<Self>,
+        _handle: &Handle,
+        _data: &mut ServerHandler,
+        _client_id: ClientId,
+        _object_id: ObjectId,
+    ) {
+    }
+}
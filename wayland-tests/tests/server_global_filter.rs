@@ -26,7 +26,7 @@ fn global_filter() {
     let (_, mut client) = server.add_client_with_data(Arc::new(MyClientData { privileged: false }));
     let mut client_ddata = ClientHandler::new();
 
-    client.display.get_registry(&client.event_queue.handle(), ());
+    let _registry = client.display.get_registry(&client.event_queue.handle(), ());
 
     roundtrip(&mut client, &mut server, &mut client_ddata, &mut server_ddata).unwrap();
 
@@ -36,7 +36,7 @@ fn global_filter() {
         server.add_client_with_data(Arc::new(MyClientData { privileged: true }));
     let mut priv_client_ddata = ClientHandler::new();
 
-    priv_client.display.get_registry(&priv_client.event_queue.handle(), ());
+    let _registry = priv_client.display.get_registry(&priv_client.event_queue.handle(), ());
 
     roundtrip(&mut priv_client, &mut server, &mut priv_client_ddata, &mut server_ddata).unwrap();
 
@@ -78,7 +78,7 @@ fn global_filter_try_force() {
     // privileged client can bind it
 
     let priv_registry = priv_client.display.get_registry(&priv_client.event_queue.handle(), ());
-    priv_registry.bind::<wayc::protocol::wl_output::WlOutput, _, _>(
+    let _global = priv_registry.bind::<wayc::protocol::wl_output::WlOutput, _, _>(
         1,
         1,
         &priv_client.event_queue.handle(),
@@ -88,7 +88,7 @@ fn global_filter_try_force() {
 
     // unprivileged client cannot
     let registry = client.display.get_registry(&client.event_queue.handle(), ());
-    registry.bind::<wayc::protocol::wl_output::WlOutput, _, _>(
+    let _global = registry.bind::<wayc::protocol::wl_output::WlOutput, _, _>(
         1,
         1,
         &client.event_queue.handle(),
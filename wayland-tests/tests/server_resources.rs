@@ -51,10 +51,69 @@ fn resource_equals() {
     assert!(server_ddata.outputs.len() == 2);
     assert!(server_ddata.outputs[0] != server_ddata.outputs[1]);
 
+    let info = server_ddata.outputs[0].info().unwrap();
+    assert_eq!(info.id, server_ddata.outputs[0].id().protocol_id());
+    assert_eq!(info.interface.name, "wl_output");
+    assert_eq!(info.version, 3);
+
     let cloned = server_ddata.outputs[0].clone();
     assert!(server_ddata.outputs[0] == cloned);
 
     assert!(server_ddata.outputs[0].id().same_client_as(&server_ddata.outputs[1].id()));
+
+    assert!(wl_output::WlOutput::is_interface_of(&server_ddata.outputs[0].id()));
+    assert!(!wl_compositor::WlCompositor::is_interface_of(&server_ddata.outputs[0].id()));
+
+    // resources and weak handles compare in both directions
+    let weak = server_ddata.outputs[0].downgrade();
+    assert!(server_ddata.outputs[0] == weak);
+    assert!(weak == server_ddata.outputs[0]);
+    assert!(server_ddata.outputs[1] != weak);
+    assert!(weak != server_ddata.outputs[1]);
+}
+
+#[test]
+fn resource_same_client() {
+    let mut server = TestServer::new();
+    server
+        .display
+        .handle()
+        .create_global::<ServerHandler, ways::protocol::wl_output::WlOutput, _>(3, ());
+    let mut server_ddata = ServerHandler { outputs: Vec::new() };
+
+    let (s_client_1, mut client_1) = server.add_client();
+    let mut client_ddata_1 = ClientHandler::new();
+    let (s_client_2, mut client_2) = server.add_client();
+    let mut client_ddata_2 = ClientHandler::new();
+
+    let registry_1 = client_1.display.get_registry(&client_1.event_queue.handle(), ());
+    let registry_2 = client_2.display.get_registry(&client_2.event_queue.handle(), ());
+
+    roundtrip(&mut client_1, &mut server, &mut client_ddata_1, &mut server_ddata).unwrap();
+    roundtrip(&mut client_2, &mut server, &mut client_ddata_2, &mut server_ddata).unwrap();
+
+    // the first client creates two outputs, the second one
+    for _ in 0..2 {
+        client_ddata_1
+            .globals
+            .bind::<ClientOutput, _, _>(&client_1.event_queue.handle(), &registry_1, 3..4, ())
+            .unwrap();
+    }
+    roundtrip(&mut client_1, &mut server, &mut client_ddata_1, &mut server_ddata).unwrap();
+    client_ddata_2
+        .globals
+        .bind::<ClientOutput, _, _>(&client_2.event_queue.handle(), &registry_2, 3..4, ())
+        .unwrap();
+    roundtrip(&mut client_2, &mut server, &mut client_ddata_2, &mut server_ddata).unwrap();
+
+    assert_eq!(server_ddata.outputs.len(), 3);
+    assert!(server_ddata.outputs[0].same_client_as(&server_ddata.outputs[1]));
+    assert!(!server_ddata.outputs[0].same_client_as(&server_ddata.outputs[2]));
+
+    let handle = server.display.handle().backend_handle();
+    assert_eq!(server_ddata.outputs[1].id().client_id(), Some(s_client_1.id()));
+    assert_eq!(handle.owning_client(&server_ddata.outputs[2].id()), Some(s_client_2.id()));
+    assert_eq!(ways::backend::ObjectId::null().client_id(), None);
 }
 
 #[test]
@@ -151,6 +210,66 @@ fn dead_resources() {
     assert!(server.display.handle().get_object_data(server_ddata.outputs[0].id()).is_err());
     assert!(server.display.handle().get_object_data(server_ddata.outputs[1].id()).is_ok());
     assert!(server.display.handle().get_object_data(cloned.id()).is_err());
+    assert!(cloned.info().is_none());
+}
+
+#[test]
+fn resource_map() {
+    let mut server = TestServer::new();
+    server
+        .display
+        .handle()
+        .create_global::<ServerHandler, ways::protocol::wl_output::WlOutput, _>(3, ());
+    let mut server_ddata = ServerHandler { outputs: Vec::new() };
+
+    let (_, mut client) = server.add_client();
+    let mut client_ddata = ClientHandler::new();
+
+    let registry = client.display.get_registry(&client.event_queue.handle(), ());
+
+    roundtrip(&mut client, &mut server, &mut client_ddata, &mut server_ddata).unwrap();
+
+    // create two outputs
+    let client_output_1 = client_ddata
+        .globals
+        .bind::<wayc::protocol::wl_output::WlOutput, _, _>(
+            &client.event_queue.handle(),
+            &registry,
+            3..4,
+            (),
+        )
+        .unwrap();
+    client_ddata
+        .globals
+        .bind::<wayc::protocol::wl_output::WlOutput, _, _>(
+            &client.event_queue.handle(),
+            &registry,
+            3..4,
+            (),
+        )
+        .unwrap();
+
+    roundtrip(&mut client, &mut server, &mut client_ddata, &mut server_ddata).unwrap();
+
+    let mut map = ways::ResourceMap::new();
+    assert_eq!(map.insert(&server_ddata.outputs[0], 1), None);
+    assert_eq!(map.insert(&server_ddata.outputs[1], 2), None);
+    assert_eq!(map.insert(&server_ddata.outputs[1], 3), Some(2));
+    assert_eq!(map.get(&server_ddata.outputs[0]), Some(&1));
+    assert_eq!(map.iter_live().count(), 2);
+
+    client_output_1.release();
+
+    roundtrip(&mut client, &mut server, &mut client_ddata, &mut server_ddata).unwrap();
+
+    assert_eq!(map.get(&server_ddata.outputs[0]), None);
+    assert_eq!(map.get(&server_ddata.outputs[1]), Some(&3));
+    let live = map.iter_live().collect::<Vec<_>>();
+    assert_eq!(live, vec![(server_ddata.outputs[1].clone(), &3)]);
+
+    assert_eq!(map.len(), 2);
+    map.retain_live();
+    assert_eq!(map.len(), 1);
 }
 
 #[test]
@@ -199,6 +318,79 @@ fn get_resource() {
         .is_ok());
 }
 
+#[test]
+fn resource_init_with() {
+    let mut server = TestServer::new();
+    server
+        .display
+        .handle()
+        .create_global::<SelfRefHandler, ways::protocol::wl_output::WlOutput, _>(3, ());
+    let mut server_ddata = SelfRefHandler { outputs: Vec::new() };
+
+    let (_, mut client) = server.add_client();
+    let mut client_ddata = ClientHandler::new();
+
+    let registry = client.display.get_registry(&client.event_queue.handle(), ());
+
+    roundtrip(&mut client, &mut server, &mut client_ddata, &mut server_ddata).unwrap();
+
+    client_ddata
+        .globals
+        .bind::<wayc::protocol::wl_output::WlOutput, _, _>(
+            &client.event_queue.handle(),
+            &registry,
+            3..4,
+            (),
+        )
+        .unwrap();
+
+    roundtrip(&mut client, &mut server, &mut client_ddata, &mut server_ddata).unwrap();
+
+    // the user data refers to its own resource
+    let output = &server_ddata.outputs[0];
+    let data = output.data::<SelfRef>().unwrap();
+    assert_eq!(&data.0.upgrade().unwrap(), output);
+    assert!(data.0.upgrade().unwrap().data::<SelfRef>().is_some());
+}
+
+#[test]
+fn server_request_try_from_message() {
+    use ways::backend::protocol::{Argument, Message};
+    use ways::protocol::wl_surface::{parse_request_raw, Request};
+
+    let request = Request::try_from(Message {
+        sender_id: ways::backend::ObjectId::null(),
+        opcode: 2,
+        args: [Argument::Int(1), Argument::Int(2), Argument::Int(3), Argument::Int(4)]
+            .into_iter()
+            .collect(),
+    })
+    .unwrap();
+    assert!(matches!(request, Request::Damage { x: 1, y: 2, width: 3, height: 4 }));
+
+    let request = parse_request_raw(8, [Argument::Int(2)]).unwrap();
+    assert!(matches!(request, Request::SetBufferScale { scale: 2 }));
+
+    // arguments not matching the signature are rejected
+    assert!(matches!(
+        Request::try_from(Message {
+            sender_id: ways::backend::ObjectId::null(),
+            opcode: 8,
+            args: [Argument::Uint(2)].into_iter().collect(),
+        }),
+        Err(ways::DispatchError::BadMessage { interface: "wl_surface", opcode: 8, .. })
+    ));
+
+    // requests carrying objects cannot be decoded without a display handle
+    assert!(matches!(
+        parse_request_raw(
+            1,
+            [Argument::Object(ways::backend::ObjectId::null()), Argument::Int(0), Argument::Int(0)]
+        ),
+        Err(ways::DispatchError::BadMessage { interface: "wl_surface", opcode: 1, .. })
+    ));
+}
+
 struct ClientHandler {
     globals: globals::GlobalList,
 }
@@ -253,3 +445,40 @@ impl ways::Dispatch<wl_output::WlOutput, UData> for ServerHandler {
     ) {
     }
 }
+
+struct SelfRefHandler {
+    outputs: Vec<wl_output::WlOutput>,
+}
+
+impl ways::GlobalDispatch<wl_output::WlOutput, ()> for SelfRefHandler {
+    fn bind(
+        state: &mut Self,
+        _: &ways::DisplayHandle,
+        _: &ways::Client,
+        output: ways::New<ways::protocol::wl_output::WlOutput>,
+        _: &(),
+        data_init: &mut ways::DataInit<'_, Self>,
+    ) {
+        let output = data_init.init_with(output, |output| {
+            // the user data is not set yet
+            assert!(output.data::<SelfRef>().is_none());
+            SelfRef(output.downgrade())
+        });
+        state.outputs.push(output);
+    }
+}
+
+struct SelfRef(ways::Weak<wl_output::WlOutput>);
+
+impl ways::Dispatch<wl_output::WlOutput, SelfRef> for SelfRefHandler {
+    fn request(
+        _: &mut Self,
+        _: &ways::Client,
+        _: &wl_output::WlOutput,
+        _: wl_output::Request,
+        _: &SelfRef,
+        _: &ways::DisplayHandle,
+        _: &mut ways::DataInit<'_, Self>,
+    ) {
+    }
+}